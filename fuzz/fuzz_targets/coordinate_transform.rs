@@ -0,0 +1,8 @@
+#![no_main]
+
+use geotiff::fuzzing::CoordinateTransformInput;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: CoordinateTransformInput| {
+    let _ = input.parse();
+});
@@ -0,0 +1,8 @@
+#![no_main]
+
+use geotiff::fuzzing::GeoKeyDirectoryInput;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: GeoKeyDirectoryInput| {
+    let _ = input.parse();
+});
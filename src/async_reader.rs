@@ -0,0 +1,522 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use futures::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+use geo_types::{Coord, Polygon, Rect};
+use num_traits::FromPrimitive;
+use tiff::tags::{CompressionMethod, PhotometricInterpretation};
+use tiff::TiffError;
+
+use crate::decoder_ext;
+use crate::error::{GeoTiffError, GeoTiffResult};
+use crate::gdal_metadata::GdalMetadata;
+use crate::geo_key_directory::GeoKeyDirectory;
+use crate::georeference::Gcp;
+use crate::layout::{ArrayOrder, BandFormat, Interleave, RasterDataType, SampleRole};
+use crate::metadata::GeoTiffMetadata;
+use crate::profile::Profile;
+use crate::range_cache::RangeCache;
+use crate::raster_space::RasterSpace;
+use crate::storage::StorageReport;
+use crate::tiff_tags::{DescriptiveTags, GpsPosition, TiffTag};
+use crate::window::Window;
+use crate::{GeoTiff, GeoTiffReader, Overview, ReadOptions};
+
+/// An asynchronous variant of [`GeoTiffReader`], reading from an [`AsyncRead`] + [`AsyncSeek`]
+/// source without blocking the executor.
+///
+/// The bytes needed to decode the metadata and the requested strips or tiles are fetched
+/// asynchronously, while the decoding itself works on the fetched bytes in memory.
+pub struct AsyncGeoTiff<R: AsyncRead + AsyncSeek + Unpin> {
+    pub geo_key_directory: GeoKeyDirectory,
+    pub raster_width: usize,
+    pub raster_height: usize,
+    pub num_samples: usize,
+    pub no_data: Option<f64>,
+    pub gdal_metadata: Option<GdalMetadata>,
+    pub descriptive_tags: DescriptiveTags,
+    source: R,
+    cache: Arc<Mutex<RangeCache>>,
+    reader: GeoTiffReader<RangeReader>,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> AsyncGeoTiff<R> {
+    /// Opens a GeoTIFF from the given source, reading its metadata but no raster data.
+    pub async fn open(source: R) -> GeoTiffResult<Self> {
+        Self::open_with_options(source, &ReadOptions::default()).await
+    }
+
+    /// Opens a GeoTIFF from the given source with the given options, see [`Self::open`].
+    pub async fn open_with_options(mut source: R, options: &ReadOptions) -> GeoTiffResult<Self> {
+        let cache = Arc::new(Mutex::new(RangeCache::default()));
+
+        let reader = retry(&mut source, &cache, || {
+            GeoTiffReader::open_with_options(RangeReader::new(cache.clone()), options)
+        })
+        .await?;
+
+        Ok(Self {
+            geo_key_directory: reader.geo_key_directory.clone(),
+            raster_width: reader.raster_width,
+            raster_height: reader.raster_height,
+            num_samples: reader.num_samples,
+            no_data: reader.no_data,
+            gdal_metadata: reader.gdal_metadata.clone(),
+            descriptive_tags: reader.descriptive_tags.clone(),
+            source,
+            cache,
+            reader,
+        })
+    }
+
+    /// Sets the arrangement of the samples in memory for subsequent reads, regardless of how they
+    /// are stored in the file.
+    pub fn with_interleave(mut self, interleave: Interleave) -> Self {
+        self.reader = self.reader.with_interleave(interleave);
+        self
+    }
+
+    /// Sets the order of the pixels in memory for subsequent reads.
+    pub fn with_order(mut self, order: ArrayOrder) -> Self {
+        self.reader = self.reader.with_order(order);
+        self
+    }
+
+    /// Sets whether the alpha sample is interpreted as a transparency mask in subsequent reads, see
+    /// [`GeoTiffReader::with_alpha_mask`].
+    pub fn with_alpha_mask(mut self, alpha_mask: bool) -> Self {
+        self.reader = self.reader.with_alpha_mask(alpha_mask);
+        self
+    }
+
+    /// Sets the samples (bands) kept by subsequent reads, see [`GeoTiffReader::with_samples`].
+    ///
+    /// Of images with a planar configuration, only the planes holding the samples are fetched.
+    pub fn with_samples(mut self, samples: &[usize]) -> Self {
+        self.reader = self.reader.with_samples(samples);
+        self
+    }
+
+    /// Returns whether the file is a BigTIFF, which uses 64-bit offsets.
+    pub fn is_bigtiff(&self) -> bool {
+        self.reader.is_bigtiff()
+    }
+
+    /// Returns the ground control points of files georeferenced by multiple tie points, see
+    /// [`GeoTiff::gcps`].
+    ///
+    /// [`GeoTiff::gcps`]: crate::GeoTiff::gcps
+    pub fn gcps(&self) -> &[Gcp] {
+        self.reader.gcps()
+    }
+
+    /// Returns the format of the samples of each band, as stored in the file.
+    pub fn band_formats(&self) -> &[BandFormat] {
+        self.reader.band_formats()
+    }
+
+    /// Returns the number of bits of the samples of each band as stored in the file, which may be
+    /// fewer than those of the data type they are decoded to, e.g. 12 bits of samples decoded as
+    /// `U16`.
+    pub fn bits_per_sample(&self) -> Vec<u16> {
+        self.band_formats()
+            .iter()
+            .map(|band_format| band_format.bits_per_sample)
+            .collect()
+    }
+
+    /// Returns the type the raster data is decoded to.
+    pub fn data_type(&self) -> RasterDataType {
+        self.reader.data_type()
+    }
+
+    /// Returns the role of each sample, as given by the ExtraSamples tag.
+    pub fn sample_roles(&self) -> &[SampleRole] {
+        self.reader.sample_roles()
+    }
+
+    /// Returns how the decoded samples are interpreted as colors, see
+    /// [`GeoTiffReader::photometric_interpretation`].
+    pub fn photometric_interpretation(&self) -> PhotometricInterpretation {
+        self.reader.photometric_interpretation()
+    }
+
+    /// Returns the method the raster data is compressed with, see
+    /// [`GeoTiffReader::compression`].
+    pub fn compression(&self) -> CompressionMethod {
+        self.reader.compression()
+    }
+
+    /// Returns the index of the sample holding the opacity of the pixels, if there is one.
+    pub fn alpha_sample(&self) -> Option<usize> {
+        self.reader.alpha_sample()
+    }
+
+    /// Returns the extent of the image in model space.
+    pub fn model_extent(&self) -> Rect {
+        self.reader.model_extent()
+    }
+
+    /// Returns the outline of the image in model space, starting at its top left corner.
+    ///
+    /// Unlike [`Self::model_extent`], which is its bounding rectangle, it follows the edges of
+    /// rasters that are rotated or sheared by their coordinate transform.
+    pub fn footprint(&self) -> Polygon {
+        self.reader.footprint()
+    }
+
+    /// Returns the affine transform from raster to model space as the six coefficients used by
+    /// GDAL, or `None` if there is no coordinate transform or it is not affine, e.g. one given by
+    /// multiple tie points.
+    ///
+    /// The model location of the top left corner of the pixel at column `col` and row `row` is
+    /// `(gt[0] + col * gt[1] + row * gt[2], gt[3] + col * gt[4] + row * gt[5])`, also for rasters
+    /// whose pixels represent points.
+    pub fn geotransform(&self) -> Option<[f64; 6]> {
+        self.reader.geotransform()
+    }
+
+    /// Returns the location of the top left corner of the raster in model space, i.e. the first
+    /// and fourth coefficient of [`Self::geotransform`].
+    pub fn origin(&self) -> Option<Coord> {
+        self.reader.origin()
+    }
+
+    /// Returns the width and height of a pixel in model space, i.e. the second and sixth
+    /// coefficient of [`Self::geotransform`], where the height is negative for rasters with
+    /// north up.
+    ///
+    /// The size is only meaningful if the raster is not rotated, see [`Self::geotransform`].
+    pub fn pixel_size(&self) -> Option<(f64, f64)> {
+        self.reader.pixel_size()
+    }
+
+    /// Returns the width and height of a pixel in model units, which unlike [`Self::pixel_size`]
+    /// are positive and also apply to rotated rasters, or `None` if the raster has no affine
+    /// transform.
+    ///
+    /// See [`GeoTiff::ground_sample_distance`] for the size in metres.
+    pub fn resolution(&self) -> Option<Coord> {
+        self.reader.resolution()
+    }
+
+    /// Returns whether the raster intersects the given rectangle in model space.
+    pub fn intersects(&self, rect: &Rect) -> bool {
+        self.reader.intersects(rect)
+    }
+
+    /// Returns whether the given location in model space lies within the raster.
+    pub fn contains(&self, coord: &Coord) -> bool {
+        self.reader.contains(coord)
+    }
+
+    /// Returns the smallest pixel window covering the intersection of the raster with the given
+    /// rectangle in model space, or `None` if they do not intersect.
+    pub fn intersection_window(&self, rect: &Rect) -> Option<Window> {
+        self.reader.intersection_window(rect)
+    }
+
+    /// Returns the profile of the file, e.g. to create a GeoTIFF with the same properties, see
+    /// [`GeoTiff::from_profile`].
+    pub fn profile(&self) -> Profile {
+        self.reader.profile()
+    }
+
+    /// Returns whether the raster data is stored in tiles rather than strips.
+    pub fn is_tiled(&self) -> bool {
+        self.reader.is_tiled()
+    }
+
+    /// Returns the width and height of the strips or tiles in pixels. Strips span the width of
+    /// the raster, and the last strip or tile in each direction may extend past the raster.
+    pub fn chunk_dimensions(&self) -> (usize, usize) {
+        self.reader.chunk_dimensions()
+    }
+
+    /// Returns the number of strips or tiles, which includes the chunks of every sample for planar
+    /// images.
+    pub fn chunk_count(&self) -> usize {
+        self.reader.chunk_count()
+    }
+
+    /// Expands the given window to the boundaries of the strips or tiles it touches, clipped to the
+    /// raster dimensions.
+    pub fn align_window_to_blocks(&self, window: &Window) -> Window {
+        self.reader.align_window_to_blocks(window)
+    }
+
+    /// Returns the value at the given location for the specified sample.
+    /// The coordinates are in model space.
+    ///
+    /// Only the strip or tile containing the location is fetched and decoded.
+    pub async fn get_value_at<T: FromPrimitive + 'static>(
+        &mut self,
+        coord: &Coord,
+        sample: usize,
+    ) -> GeoTiffResult<Option<T>> {
+        self.reader.sample_in_bounds(sample)?;
+
+        let chunks = self
+            .reader
+            .chunk_position(coord, sample)
+            .map(|(chunk_index, _)| chunk_index);
+        self.with_chunks(chunks, |reader| reader.get_value_at(coord, sample))
+            .await
+    }
+
+    /// Returns the values at the given locations for the specified sample, in the order of the
+    /// locations. The coordinates are in model space.
+    ///
+    /// The strips or tiles containing the locations are fetched together, and each is decoded at
+    /// most once.
+    pub async fn get_values_at<T: FromPrimitive + 'static>(
+        &mut self,
+        coords: &[Coord],
+        sample: usize,
+    ) -> GeoTiffResult<Vec<Option<T>>> {
+        self.reader.sample_in_bounds(sample)?;
+
+        let mut chunks = coords
+            .iter()
+            .filter_map(|coord| self.reader.chunk_position(coord, sample))
+            .map(|(chunk_index, _)| chunk_index)
+            .collect::<Vec<_>>();
+        chunks.sort_unstable();
+        chunks.dedup();
+        self.with_chunks(chunks, |reader| reader.get_values_at(coords, sample))
+            .await
+    }
+
+    /// Reads the given pixel window, fetching and decoding only the strips or tiles intersecting
+    /// it.
+    pub async fn read_region(&mut self, window: &Window) -> GeoTiffResult<GeoTiff> {
+        let chunks = if window.is_empty()
+            || window.x + window.width > self.raster_width
+            || window.y + window.height > self.raster_height
+        {
+            // Reading the window fails without touching any chunk
+            Vec::new()
+        } else {
+            let chunk_layout = self.reader.chunk_layout();
+            chunk_layout
+                .chunks_in_window(window)
+                .filter(|&chunk_index| self.reader.reads_chunk(chunk_index))
+                .collect()
+        };
+
+        self.with_chunks(chunks, |reader| reader.read_region(window))
+            .await
+    }
+
+    /// Returns the reduced-resolution images (overviews) of the image, fetching only their image
+    /// file directories.
+    pub async fn overviews(&mut self) -> GeoTiffResult<Vec<Overview>> {
+        self.with_chunks([], |reader| reader.overviews()).await
+    }
+
+    /// Summarizes the metadata of the image, fetching only the image file directories of the image
+    /// and its overviews, see [`GeoTiffReader::metadata`].
+    pub async fn metadata(&mut self) -> GeoTiffResult<GeoTiffMetadata> {
+        self.with_chunks([], |reader| reader.metadata()).await
+    }
+
+    /// Returns all tags of the image file directory of the image, fetching only the image file
+    /// directory and the values of the tags, see [`GeoTiffReader::tags`].
+    pub async fn tags(&mut self) -> GeoTiffResult<Vec<TiffTag>> {
+        self.with_chunks([], |reader| reader.tags()).await
+    }
+
+    /// Returns the tags of the EXIF directory of the image, see [`GeoTiffReader::exif_tags`].
+    pub async fn exif_tags(&mut self) -> GeoTiffResult<Option<Vec<TiffTag>>> {
+        self.with_chunks([], |reader| reader.exif_tags()).await
+    }
+
+    /// Returns the tags of the GPS directory of the image, see [`GeoTiffReader::gps_tags`].
+    pub async fn gps_tags(&mut self) -> GeoTiffResult<Option<Vec<TiffTag>>> {
+        self.with_chunks([], |reader| reader.gps_tags()).await
+    }
+
+    /// Returns the location where the image was captured, see [`GeoTiffReader::gps_position`].
+    pub async fn gps_position(&mut self) -> GeoTiffResult<Option<GpsPosition>> {
+        self.with_chunks([], |reader| reader.gps_position()).await
+    }
+
+    /// Summarizes the compression and the strips or tiles of the image and its overviews, fetching
+    /// only their image file directories, see [`GeoTiffReader::storage_report`].
+    pub async fn storage_report(&mut self) -> GeoTiffResult<StorageReport> {
+        self.with_chunks([], |reader| reader.storage_report()).await
+    }
+
+    /// Fetches and decodes the whole raster.
+    pub async fn read(mut self) -> GeoTiffResult<GeoTiff> {
+        let window = Window::new(0, 0, self.raster_width, self.raster_height);
+        self.read_region(&window).await
+    }
+
+    /// Fetches the encoded data of the given chunks before running the given operation, and drops
+    /// it afterwards.
+    ///
+    /// As when reading the encoded data of a chunk, fails with [`TiffError::LimitsExceeded`] before
+    /// fetching a chunk exceeding the decoding buffer size.
+    async fn with_chunks<T>(
+        &mut self,
+        chunks: impl IntoIterator<Item = u32>,
+        mut op: impl FnMut(&mut GeoTiffReader<RangeReader>) -> GeoTiffResult<T>,
+    ) -> GeoTiffResult<T> {
+        let requested: Vec<_> = chunks
+            .into_iter()
+            .filter_map(|chunk_index| self.reader.chunk_byte_ranges().get(chunk_index as usize))
+            .cloned()
+            .collect();
+        for range in &requested {
+            let len = range.end.saturating_sub(range.start);
+            decoder_ext::check_buffer_size(usize::try_from(len).ok(), self.reader.limits())?;
+        }
+        let missing: Vec<_> = requested
+            .iter()
+            .filter(|range| !lock(&self.cache).contains(range))
+            .cloned()
+            .collect();
+        trace_event!(
+            chunks = requested.len(),
+            cache_hits = requested.len() - missing.len(),
+            "chunk lookup"
+        );
+        let ranges = coalesce_ranges(missing);
+        for range in &ranges {
+            fetch(&mut self.source, &self.cache, range.clone()).await?;
+        }
+
+        let reader = &mut self.reader;
+        let result = retry(&mut self.source, &self.cache, || op(reader)).await;
+
+        let mut cache = lock(&self.cache);
+        for range in &ranges {
+            cache.remove(range);
+        }
+
+        result
+    }
+}
+
+/// Runs the given operation until it no longer fails because of bytes that have not been fetched.
+async fn retry<R: AsyncRead + AsyncSeek + Unpin, T>(
+    source: &mut R,
+    cache: &Mutex<RangeCache>,
+    mut op: impl FnMut() -> GeoTiffResult<T>,
+) -> GeoTiffResult<T> {
+    loop {
+        match op() {
+            Err(error) => match MissingRange::from_error(&error) {
+                Some(range) => fetch(source, cache, range).await?,
+                None => return Err(error),
+            },
+            result => return result,
+        }
+    }
+}
+
+async fn fetch<R: AsyncRead + AsyncSeek + Unpin>(
+    source: &mut R,
+    cache: &Mutex<RangeCache>,
+    range: Range<u64>,
+) -> GeoTiffResult<()> {
+    trace_span!("fetch", start = range.start, end = range.end);
+    source.seek(SeekFrom::Start(range.start)).await?;
+
+    let mut data = Vec::new();
+    (&mut *source)
+        .take(range.end - range.start)
+        .read_to_end(&mut data)
+        .await?;
+
+    trace_event!(bytes = data.len(), "fetched range");
+    lock(cache).insert(range, data);
+    Ok(())
+}
+
+/// The error raised when reading bytes which are not cached yet, carrying the range to fetch.
+#[derive(Debug)]
+struct MissingRange(pub Range<u64>);
+
+impl fmt::Display for MissingRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Bytes {:?} have not been fetched", self.0)
+    }
+}
+
+impl Error for MissingRange {}
+
+impl MissingRange {
+    /// Returns the range to fetch if the given error was caused by a cache miss.
+    pub fn from_error(error: &GeoTiffError) -> Option<Range<u64>> {
+        let GeoTiffError::Tiff(TiffError::IoError(error)) = error else {
+            return None;
+        };
+
+        error
+            .get_ref()
+            .and_then(|error| error.downcast_ref::<MissingRange>())
+            .map(|missing| missing.0.clone())
+    }
+}
+
+/// A synchronous reader serving bytes from a [`RangeCache`] shared with the code fetching them.
+///
+/// Reading bytes that are not cached fails with a [`MissingRange`] error, after which the caller
+/// is expected to fetch the range and retry the operation.
+#[derive(Debug)]
+struct RangeReader {
+    cache: Arc<Mutex<RangeCache>>,
+    position: u64,
+}
+
+impl RangeReader {
+    pub fn new(cache: Arc<Mutex<RangeCache>>) -> Self {
+        Self { cache, position: 0 }
+    }
+}
+
+impl Read for RangeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some(count) = lock(&self.cache).read_at(self.position, buf) else {
+            return Err(io::Error::other(MissingRange(RangeCache::range_to_fetch(
+                self.position,
+                buf,
+            ))));
+        };
+
+        self.position += count as u64;
+        Ok(count)
+    }
+}
+
+impl Seek for RangeReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = lock(&self.cache).seek(self.position, pos)?;
+        Ok(self.position)
+    }
+}
+
+/// Merges overlapping or adjacent ranges, so that they can be fetched with fewer requests.
+fn coalesce_ranges(mut ranges: Vec<Range<u64>>) -> Vec<Range<u64>> {
+    ranges.sort_by_key(|range| range.start);
+
+    let mut coalesced: Vec<Range<u64>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match coalesced.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => coalesced.push(range),
+        }
+    }
+
+    coalesced
+}
+
+fn lock(cache: &Mutex<RangeCache>) -> MutexGuard<'_, RangeCache> {
+    // The cache is never left in an inconsistent state, so a poisoned lock can be recovered
+    cache.lock().unwrap_or_else(|error| error.into_inner())
+}
@@ -0,0 +1,108 @@
+use num_traits::FromPrimitive;
+
+use crate::layout::{Interleave, RasterDataType};
+use crate::raster_data::BufferLayout;
+use crate::raster_space::RasterSpace;
+use crate::{GeoTiff, GeoTiffResult};
+
+/// A view of a single sample of the raster data of a GeoTIFF, see [`GeoTiff::band`].
+#[derive(Debug, Clone, Copy)]
+pub struct BandView<'a> {
+    geotiff: &'a GeoTiff,
+    sample: usize,
+    layout: BufferLayout,
+}
+
+impl<'a> BandView<'a> {
+    /// Returns the index of the sample.
+    pub fn sample(&self) -> usize {
+        self.sample
+    }
+
+    /// Returns the width and height of the band in pixels.
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.layout.width, self.layout.height)
+    }
+
+    /// Returns the type of the values.
+    pub fn data_type(&self) -> RasterDataType {
+        self.geotiff.data_type()
+    }
+
+    /// Returns the value of the given pixel converted to `T`, or `None` if the pixel lies outside
+    /// the raster.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` cannot represent the value, which never happens for `f64`.
+    pub fn get<T: FromPrimitive + 'static>(&self, col: usize, row: usize) -> Option<T> {
+        (col < self.layout.width && row < self.layout.height).then(|| {
+            self.geotiff
+                .raster_data
+                .get(self.layout.index(col, row, self.sample))
+        })
+    }
+
+    /// Returns an iterator over the values converted to `T`, row by row regardless of how the
+    /// pixels are arranged in memory.
+    ///
+    /// # Panics
+    ///
+    /// The iterator panics if `T` cannot represent a value, which never happens for `f64`.
+    pub fn iter<T: FromPrimitive + 'static>(&self) -> impl Iterator<Item = T> + 'a {
+        let BandView {
+            geotiff,
+            sample,
+            layout,
+        } = *self;
+        (0..layout.height).flat_map(move |row| {
+            (0..layout.width)
+                .map(move |col| geotiff.raster_data.get(layout.index(col, row, sample)))
+        })
+    }
+
+    /// Returns the values as a slice without copying them, in the order the pixels are arranged
+    /// in memory, see [`GeoTiff::order`].
+    ///
+    /// Returns `None` if `T` is not the type of the values or the values of the band are not
+    /// stored contiguously, i.e. if the samples of a multi-band raster are interleaved by pixel,
+    /// see [`GeoTiff::into_layout`].
+    pub fn as_slice<T: 'static>(&self) -> Option<&'a [T]> {
+        if self.layout.interleave == Interleave::Pixel && self.layout.num_samples > 1 {
+            return None;
+        }
+
+        let len = self.layout.width * self.layout.height;
+        let start = self.sample * len;
+        self.geotiff
+            .raster_data
+            .as_slice()
+            .map(|values| &values[start..start + len])
+    }
+}
+
+impl GeoTiff {
+    /// Returns a view of the specified sample, e.g. to process multi-band imagery band by band.
+    ///
+    /// Fails with [`GeoTiffError::SampleOutOfBounds`](crate::GeoTiffError::SampleOutOfBounds) if
+    /// the image does not have the sample.
+    pub fn band(&self, sample: usize) -> GeoTiffResult<BandView<'_>> {
+        self.sample_in_bounds(sample)?;
+
+        Ok(BandView {
+            geotiff: self,
+            sample,
+            layout: self.buffer_layout(),
+        })
+    }
+
+    /// Returns an iterator over views of all samples.
+    pub fn bands(&self) -> impl Iterator<Item = BandView<'_>> {
+        let layout = self.buffer_layout();
+        (0..self.num_samples).map(move |sample| BandView {
+            geotiff: self,
+            sample,
+            layout,
+        })
+    }
+}
@@ -0,0 +1,50 @@
+use crate::layout::RasterDataType;
+use crate::raster_data::RasterData;
+use crate::GeoTiff;
+
+impl GeoTiff {
+    /// Applies the given function to the values of the samples of each pixel, e.g. to compute an
+    /// index like NDVI from two bands, returning a single-band raster of the results.
+    ///
+    /// The function is called with the values of all samples of a pixel in the order of the
+    /// samples. The results are stored as 32-bit floats, or as 64-bit floats if the samples are
+    /// 32-bit or 64-bit integers or 64-bit floats, so that their values are represented exactly.
+    /// Pixels with a sample holding the no data value or NaN and pixels marked invalid by the
+    /// transparency mask are set to NaN without calling the function, which is the no data value
+    /// of the returned raster.
+    pub fn map(&self, f: impl Fn(&[f64]) -> f64) -> GeoTiff {
+        let data_type = match self.data_type() {
+            RasterDataType::U8
+            | RasterDataType::U16
+            | RasterDataType::I8
+            | RasterDataType::I16
+            | RasterDataType::F16
+            | RasterDataType::F32 => RasterDataType::F32,
+            RasterDataType::U32
+            | RasterDataType::U64
+            | RasterDataType::I32
+            | RasterDataType::I64
+            | RasterDataType::F64
+            | RasterDataType::CI16
+            | RasterDataType::CI32
+            | RasterDataType::CF32
+            | RasterDataType::CF64 => RasterDataType::F64,
+        };
+
+        let mut raster_data = RasterData::zeroed(data_type, self.raster_width * self.raster_height);
+        let mut values = vec![0.0; self.num_samples];
+        for row in 0..self.raster_height {
+            for col in 0..self.raster_width {
+                let known = values.iter_mut().enumerate().all(|(sample, value)| {
+                    self.known_value(col as isize, row as isize, sample)
+                        .map(|known| *value = known)
+                        .is_some()
+                });
+                let result = if known { f(&values) } else { f64::NAN };
+                raster_data.set(row * self.raster_width + col, result);
+            }
+        }
+
+        self.derive_band(raster_data, Some(f64::NAN))
+    }
+}
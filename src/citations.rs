@@ -0,0 +1,94 @@
+use crate::geo_key_directory::GeoKeyDirectory;
+
+/// The name of the projected CRS in composite citations.
+const PCS_NAME: &str = "PCS Name";
+/// The name of the geographic CRS in composite citations.
+const GCS_NAME: &str = "GCS Name";
+
+/// The names GDAL and ESRI software store in the citation keys, see
+/// [`GeoKeyDirectory::citations`].
+///
+/// GDAL writes composite citations of `Name = value` pairs separated by pipes, e.g.
+/// `GCS Name = NAD83|Datum = North_American_Datum_1983|Ellipsoid = GRS 1980|Primem = Greenwich||`,
+/// to keep the names of user-defined CRSs, which have no EPSG code to look them up by.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Citations {
+    /// The name of the projected CRS, given by `PCS Name`.
+    pub pcs_name: Option<String>,
+    /// The name of the geographic CRS, given by `GCS Name`.
+    pub gcs_name: Option<String>,
+    /// The name of the map projection, given by `Projection Name`.
+    pub projection_name: Option<String>,
+    /// The name of the geodetic datum, given by `Datum`.
+    pub datum: Option<String>,
+    /// The name of the ellipsoid, given by `Ellipsoid`.
+    pub ellipsoid: Option<String>,
+    /// The name of the prime meridian, given by `Primem`.
+    pub prime_meridian: Option<String>,
+    /// The name of the angular unit, given by `AUnits`.
+    pub angular_units: Option<String>,
+    /// The name of the linear unit, given by `LUnits`.
+    pub linear_units: Option<String>,
+    /// The CRS as ESRI WKT, given by `ESRI PE String`.
+    pub esri_pe_string: Option<String>,
+}
+
+impl GeoKeyDirectory {
+    /// Parses the citation keys into the names they hold, e.g. to show the name of a
+    /// user-defined CRS.
+    ///
+    /// Composite citations are split into their `Name = value` pairs, where unknown names are
+    /// skipped. Plain citations are taken as the name of the CRS they describe: the one of
+    /// `geog_citation` as the name of the geographic CRS, the one of `proj_citation` as the name
+    /// of the projected CRS, and the one of `citation` as the name of the projected or
+    /// geographic CRS, depending on the model type. More specific keys take precedence.
+    pub fn citations(&self) -> Citations {
+        let mut citations = Citations::default();
+        let projected = self.is_projected() != Some(false);
+        for (citation, plain_name) in [
+            (&self.proj_citation, PCS_NAME),
+            (&self.geog_citation, GCS_NAME),
+            (&self.citation, if projected { PCS_NAME } else { GCS_NAME }),
+        ] {
+            let Some(citation) = citation else {
+                continue;
+            };
+
+            if !citation.contains('=') {
+                citations.set(plain_name, citation.trim());
+                continue;
+            }
+            // IMAGINE separates the pairs by line breaks instead of pipes
+            for (name, value) in citation
+                .split(['|', '\n'])
+                .filter_map(|pair| pair.split_once('='))
+            {
+                citations.set(name.trim(), value.trim());
+            }
+        }
+
+        citations
+    }
+}
+
+impl Citations {
+    /// Sets the field with the given name in composite citations to the given value, unless it
+    /// is set already or the value is empty.
+    fn set(&mut self, name: &str, value: &str) {
+        let field = match name {
+            PCS_NAME => &mut self.pcs_name,
+            GCS_NAME => &mut self.gcs_name,
+            "Projection Name" => &mut self.projection_name,
+            "Datum" => &mut self.datum,
+            "Ellipsoid" => &mut self.ellipsoid,
+            "Primem" => &mut self.prime_meridian,
+            "AUnits" => &mut self.angular_units,
+            "LUnits" => &mut self.linear_units,
+            "ESRI PE String" => &mut self.esri_pe_string,
+            _ => return,
+        };
+        if field.is_none() && !value.is_empty() {
+            *field = Some(value.into());
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use geo_types::{Coord, Polygon, Rect};
+
+use crate::error::GeoTiffResult;
+use crate::raster_space::RasterSpace;
+use crate::GeoTiff;
+
+impl GeoTiff {
+    /// Returns the pixels within the given polygon in model space, or `None` if the raster does
+    /// not intersect its bounding rectangle, e.g. to restrict an analysis to an administrative
+    /// boundary.
+    ///
+    /// The raster is cropped to the bounding rectangle of the polygon like [`Self::crop`], and
+    /// pixels whose centers lie outside of the polygon or within one of its holes are set to the
+    /// no data value. If the raster has a transparency mask or no no data value, they are marked
+    /// invalid in the mask as well, which is created if needed.
+    pub fn clip(&self, polygon: &Polygon) -> GeoTiffResult<Option<Self>> {
+        let Some(rect) = bounding_rect(polygon) else {
+            return Ok(None);
+        };
+        let Some(mut clipped) = self.crop(&rect)? else {
+            return Ok(None);
+        };
+
+        let layout = clipped.buffer_layout();
+        if self.no_data.is_none() && clipped.mask.is_none() {
+            clipped.mask = Some(vec![true; clipped.raster_width * clipped.raster_height]);
+        }
+        for row in 0..clipped.raster_height {
+            for col in 0..clipped.raster_width {
+                if polygon_contains(polygon, &clipped.pixel_center(col, row)) {
+                    continue;
+                }
+
+                if let Some(no_data) = self.no_data {
+                    for sample in 0..clipped.num_samples {
+                        clipped
+                            .raster_data
+                            .set(layout.index(col, row, sample), no_data);
+                    }
+                }
+                if let Some(mask) = &mut clipped.mask {
+                    mask[row * clipped.raster_width + col] = false;
+                }
+            }
+        }
+
+        Ok(Some(clipped))
+    }
+}
+
+/// Returns the bounding rectangle of the exterior of the given polygon, or `None` if it is empty.
+pub(crate) fn bounding_rect(polygon: &Polygon) -> Option<Rect> {
+    let mut coords = polygon.exterior().coords();
+    let first = *coords.next()?;
+    let (min, max) = coords.fold((first, first), |(min, max), coord| {
+        (
+            Coord {
+                x: min.x.min(coord.x),
+                y: min.y.min(coord.y),
+            },
+            Coord {
+                x: max.x.max(coord.x),
+                y: max.y.max(coord.y),
+            },
+        )
+    });
+
+    Some(Rect::new(min, max))
+}
+
+/// Returns whether the given location lies within the given polygon and outside of its holes,
+/// using the even-odd rule.
+pub(crate) fn polygon_contains(polygon: &Polygon, coord: &Coord) -> bool {
+    std::iter::once(polygon.exterior())
+        .chain(polygon.interiors())
+        .flat_map(|ring| ring.lines())
+        .filter(|line| {
+            let (start, end) = (line.start, line.end);
+            // Whether the edge crosses the horizontal ray from the location to the right
+            (start.y > coord.y) != (end.y > coord.y)
+                && coord.x < start.x + (coord.y - start.y) / (end.y - start.y) * (end.x - start.x)
+        })
+        .count()
+        % 2
+        == 1
+}
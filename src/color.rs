@@ -0,0 +1,96 @@
+use tiff::tags::PhotometricInterpretation;
+
+use crate::error::{GeoTiffError, GeoTiffResult};
+use crate::layout::{ArrayOrder, Interleave, RasterDataType};
+use crate::raster_data::BufferLayout;
+use crate::reader::select;
+use crate::GeoTiff;
+
+impl GeoTiff {
+    /// Converts the CMYK samples of a GeoTIFF with the CMYK (Separated) photometric
+    /// interpretation to RGB, e.g. for displaying scanned maps.
+    ///
+    /// The samples are converted without a color profile, by scaling the complement of the cyan,
+    /// magenta and yellow samples with the complement of the black sample. The returned raster
+    /// is pixel interleaved and row-major, and keeps any samples following the CMYK ones, e.g.
+    /// alpha. Overviews are converted as well.
+    ///
+    /// Fails with [`GeoTiffError::Format`] if the samples are not CMYK samples of 8 or 16 bits.
+    pub fn cmyk_to_rgb(&self) -> GeoTiffResult<GeoTiff> {
+        let max = match self.data_type() {
+            RasterDataType::U8 => u8::MAX as f64,
+            RasterDataType::U16 => u16::MAX as f64,
+            data_type => {
+                return Err(GeoTiffError::Format(format!(
+                    "CMYK samples of type {data_type:?} cannot be converted to RGB"
+                )))
+            }
+        };
+        if self.photometric_interpretation() != PhotometricInterpretation::CMYK
+            || self.num_samples < 4
+        {
+            return Err(GeoTiffError::Format(format!(
+                "Samples with the {:?} photometric interpretation are not CMYK samples",
+                self.photometric_interpretation()
+            )));
+        }
+
+        // The black sample is dropped, keeping the others in order
+        let samples: Vec<_> = (0..self.num_samples)
+            .filter(|&sample| sample != 3)
+            .collect();
+        let layout = self.buffer_layout();
+        let rgb_layout = BufferLayout {
+            num_samples: samples.len(),
+            interleave: Interleave::Pixel,
+            order: ArrayOrder::RowMajor,
+            ..layout
+        };
+        let mut raster_data = self
+            .raster_data
+            .zeroed_like(self.raster_width * self.raster_height * samples.len());
+        for y in 0..self.raster_height {
+            for x in 0..self.raster_width {
+                let value = |sample| self.raster_data.get::<f64>(layout.index(x, y, sample));
+                let white = max - value(3);
+                for (rgb_sample, &sample) in samples.iter().enumerate() {
+                    let rgb_value = match sample {
+                        0..=2 => (max - value(sample)) * white / max,
+                        _ => value(sample),
+                    };
+                    raster_data.set(rgb_layout.index(x, y, rgb_sample), rgb_value);
+                }
+            }
+        }
+
+        Ok(GeoTiff {
+            geo_key_directory: self.geo_key_directory.clone(),
+            raster_width: self.raster_width,
+            raster_height: self.raster_height,
+            num_samples: samples.len(),
+            no_data: self.no_data,
+            gdal_metadata: self
+                .gdal_metadata
+                .as_ref()
+                .map(|gdal_metadata| gdal_metadata.select_samples(&samples)),
+            descriptive_tags: self.descriptive_tags.clone(),
+            coordinate_transform: self.coordinate_transform.clone(),
+            gcps: self.gcps.clone(),
+            chunk_dimensions: self.chunk_dimensions,
+            interleave: Interleave::Pixel,
+            order: ArrayOrder::RowMajor,
+            band_formats: select(&self.band_formats, &samples),
+            sample_roles: select(&self.sample_roles, &samples),
+            photometric_interpretation: Some(PhotometricInterpretation::RGB),
+            raster_data,
+            corrupt_chunks: self.corrupt_chunks.clone(),
+            mask: self.mask.clone(),
+            bigtiff: self.bigtiff,
+            overviews: self
+                .overviews
+                .iter()
+                .map(GeoTiff::cmyk_to_rgb)
+                .collect::<GeoTiffResult<_>>()?,
+        })
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::{BTreeMap, HashSet};
+
+use geo_types::{Coord, LineString, MultiLineString};
+
+use crate::raster_space::RasterSpace;
+use crate::{GeoTiff, GeoTiffResult};
+
+/// The values at which contour lines are generated, see [`GeoTiff::contours`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContourLevels {
+    /// The multiples of the given interval within the range of the values, e.g. every 10 meters
+    /// of elevation.
+    Interval(f64),
+    /// The given values.
+    Levels(Vec<f64>),
+}
+
+/// A crossing of a contour line with the line between the centers of two neighboring pixels, as
+/// the row and column of the top left one and whether the other one lies to its right.
+type Crossing = (usize, usize, bool);
+
+impl GeoTiff {
+    /// Generates contour lines of the specified sample in model space using marching squares,
+    /// e.g. to extract elevation contours from a DEM, returning the lines of each level in the
+    /// order of the levels.
+    ///
+    /// The lines connect points interpolated linearly between the centers of neighboring pixels.
+    /// They are closed unless they end at the edge of the raster or at a pixel holding the no
+    /// data value or NaN or marked invalid by the transparency mask. Values above the level lie
+    /// to the right of the lines as seen in raster space, e.g. on a map with north up.
+    ///
+    /// Fails with [`GeoTiffError::SampleOutOfBounds`](crate::GeoTiffError::SampleOutOfBounds) if
+    /// the image does not have the sample.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the interval is not positive and finite.
+    pub fn contours(
+        &self,
+        sample: usize,
+        levels: ContourLevels,
+    ) -> GeoTiffResult<Vec<(f64, MultiLineString)>> {
+        self.sample_in_bounds(sample)?;
+
+        let levels = match levels {
+            ContourLevels::Interval(interval) => {
+                assert!(
+                    interval > 0.0 && interval.is_finite(),
+                    "The contour interval {interval} is not positive and finite"
+                );
+                let (minimum, maximum) = self.known_values(sample).fold(
+                    (f64::INFINITY, f64::NEG_INFINITY),
+                    |(minimum, maximum), value| (minimum.min(value), maximum.max(value)),
+                );
+                if minimum > maximum {
+                    Vec::new()
+                } else {
+                    ((minimum / interval).ceil() as i64..=(maximum / interval).floor() as i64)
+                        .map(|multiple| multiple as f64 * interval)
+                        .collect()
+                }
+            }
+            ContourLevels::Levels(levels) => levels,
+        };
+
+        Ok(levels
+            .into_iter()
+            .map(|level| (level, self.contour(sample, level)))
+            .collect())
+    }
+
+    /// Returns the contour lines of the specified sample at the given level.
+    fn contour(&self, sample: usize, level: f64) -> MultiLineString {
+        // The segments of the lines within each square between four pixel centers, directed from
+        // the crossing where values fall below the level to the one where they rise above it
+        let mut segments = Vec::new();
+        for row in 0..self.raster_height.saturating_sub(1) {
+            for col in 0..self.raster_width.saturating_sub(1) {
+                // The corners of the square in clockwise order, each followed by the crossing
+                // between it and the next one
+                let corners = [
+                    (col, row),
+                    (col + 1, row),
+                    (col + 1, row + 1),
+                    (col, row + 1),
+                ]
+                .map(|(col, row)| self.known_value(col as isize, row as isize, sample));
+                let [Some(top_left), Some(top_right), Some(bottom_right), Some(bottom_left)] =
+                    corners
+                else {
+                    continue;
+                };
+                let values = [top_left, top_right, bottom_right, bottom_left];
+                let edges = [
+                    (row, col, true),
+                    (row, col + 1, false),
+                    (row + 1, col, true),
+                    (row, col, false),
+                ];
+
+                // The crossings and whether values fall across them
+                let crossings: Vec<(Crossing, bool)> = (0..4)
+                    .filter_map(|i| {
+                        let (above, next_above) =
+                            (values[i] >= level, values[(i + 1) % 4] >= level);
+                        (above != next_above).then_some((edges[i], above))
+                    })
+                    .collect();
+
+                // At saddle points, the mean decides whether the values above the level are
+                // connected through the center of the square
+                let center_above = values.iter().sum::<f64>() / 4.0 >= level;
+                let count = crossings.len();
+                for (i, &(crossing, falling)) in crossings.iter().enumerate() {
+                    if falling {
+                        let partner = if center_above {
+                            (i + 1) % count
+                        } else {
+                            (i + count - 1) % count
+                        };
+                        segments.push((crossing, crossings[partner].0));
+                    }
+                }
+            }
+        }
+
+        let ends: HashSet<Crossing> = segments.iter().map(|&(_, end)| end).collect();
+        let open: Vec<Crossing> = segments
+            .iter()
+            .map(|&(start, _)| start)
+            .filter(|start| !ends.contains(start))
+            .collect();
+        let mut next: BTreeMap<Crossing, Crossing> = segments.into_iter().collect();
+
+        let trace = |start: Crossing, next: &mut BTreeMap<Crossing, Crossing>| {
+            let mut line = vec![self.crossing_to_model(sample, level, start)];
+            let mut crossing = start;
+            while let Some(end) = next.remove(&crossing) {
+                line.push(self.crossing_to_model(sample, level, end));
+                crossing = end;
+            }
+            LineString::new(line)
+        };
+
+        // Open lines start where no segment ends, the remaining ones are closed
+        let mut lines: Vec<LineString> = open
+            .into_iter()
+            .map(|start| trace(start, &mut next))
+            .collect();
+        while let Some(&start) = next.keys().next() {
+            lines.push(trace(start, &mut next));
+        }
+
+        MultiLineString::new(lines)
+    }
+
+    /// Returns the location of the given crossing in model space.
+    fn crossing_to_model(&self, sample: usize, level: f64, (row, col, right): Crossing) -> Coord {
+        let (other_col, other_row) = if right {
+            (col + 1, row)
+        } else {
+            (col, row + 1)
+        };
+        let layout = self.buffer_layout();
+        let value = self.raster_data.get::<f64>(layout.index(col, row, sample));
+        let other_value = self
+            .raster_data
+            .get::<f64>(layout.index(other_col, other_row, sample));
+        let fraction = (level - value) / (other_value - value);
+
+        let offset = self.raster_offset() + 0.5;
+        self.transform_to_model(&Coord {
+            x: col as f64 + offset + if right { fraction } else { 0.0 },
+            y: row as f64 + offset + if right { 0.0 } else { fraction },
+        })
+    }
+}
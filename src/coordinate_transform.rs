@@ -1,13 +1,14 @@
+use std::fmt;
 #[cfg(feature = "tie-points")]
 use std::rc::Rc;
 
 #[cfg(feature = "tie-points")]
 use geo_index::rtree::OwnedRTree;
 use geo_types::Coord;
-use tiff::{TiffError, TiffFormatError, TiffResult};
 
 #[cfg(feature = "tie-points")]
 use crate::coordinate_transform::tie_points::Face;
+use crate::error::{GeoTiffError, GeoTiffResult};
 
 mod affine_transform;
 mod tie_point_and_pixel_scale;
@@ -18,10 +19,17 @@ const MODEL_TIE_POINT_TAG: &str = "ModelTiePointTag";
 const MODEL_PIXEL_SCALE_TAG: &str = "ModelPixelScaleTag";
 const MODEL_TRANSFORMATION_TAG: &str = "ModelTransformationTag";
 
+/// The values of the tags defining a [`CoordinateTransform`].
+pub(super) struct TransformTagData {
+    pub pixel_scale: Option<Vec<f64>>,
+    pub tie_points: Option<Vec<f64>>,
+    pub model_transformation: Option<Vec<f64>>,
+}
+
 /// Defines the transformation between raster space and model space.
 ///
 /// Ref: https://docs.ogc.org/is/19-008r4/19-008r4.html#_raster_to_model_coordinate_transformation_requirements
-#[derive(Debug)]
+#[derive(Clone)]
 pub enum CoordinateTransform {
     AffineTransform {
         transform: [f64; 6],
@@ -41,18 +49,46 @@ pub enum CoordinateTransform {
     },
 }
 
+/// Leaves out the inverse transform and summarizes the meshes of tie points by their sizes.
+impl fmt::Debug for CoordinateTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoordinateTransform::AffineTransform { transform, .. } => f
+                .debug_struct("AffineTransform")
+                .field("transform", transform)
+                .finish(),
+            CoordinateTransform::TiePointAndPixelScale {
+                raster_point,
+                model_point,
+                pixel_scale,
+            } => f
+                .debug_struct("TiePointAndPixelScale")
+                .field("raster_point", raster_point)
+                .field("model_point", model_point)
+                .field("pixel_scale", pixel_scale)
+                .finish(),
+            #[cfg(feature = "tie-points")]
+            CoordinateTransform::TiePoints { raster_mesh, .. } => f
+                .debug_struct("TiePoints")
+                .field("faces", &raster_mesh.len())
+                .finish(),
+        }
+    }
+}
+
 impl CoordinateTransform {
     pub(super) fn from_tag_data(
         pixel_scale_data: Option<Vec<f64>>,
         model_tie_points_data: Option<Vec<f64>>,
         model_transformation_data: Option<Vec<f64>>,
-    ) -> TiffResult<Self> {
+    ) -> GeoTiffResult<Self> {
+        trace_span!("coordinate_transform");
         let pixel_scale = pixel_scale_data
             .map(|data| {
                 <[f64; 3]>::try_from(data).map_err(|_| {
-                    TiffError::FormatError(TiffFormatError::Format(format!(
+                    GeoTiffError::Format(format!(
                         "Number values in {MODEL_PIXEL_SCALE_TAG} must be equal to 3"
-                    )))
+                    ))
                 })
             })
             .transpose()?;
@@ -60,15 +96,15 @@ impl CoordinateTransform {
             .map(|data| {
                 let len = data.len();
                 if len == 0 {
-                    return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+                    return Err(GeoTiffError::Format(format!(
                         "Number of values in {MODEL_TIE_POINT_TAG} must be greater than 0"
-                    ))));
+                    )));
                 }
 
                 if len % 6 != 0 {
-                    return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+                    return Err(GeoTiffError::Format(format!(
                         "Number of values in {MODEL_TIE_POINT_TAG} must be divisible by 6"
-                    ))));
+                    )));
                 }
 
                 Ok(data)
@@ -77,38 +113,38 @@ impl CoordinateTransform {
         let transformation_matrix = model_transformation_data
             .map(|data| {
                 <[f64; 16]>::try_from(data).map_err(|_| {
-                    TiffError::FormatError(TiffFormatError::Format(format!(
+                    GeoTiffError::Format(format!(
                         "Number of values in {MODEL_TRANSFORMATION_TAG} must be equal to 16"
-                    )))
+                    ))
                 })
             })
             .transpose()?;
 
         if let Some(transformation_matrix) = transformation_matrix {
             if pixel_scale.is_some() {
-                return Err(TiffError::FormatError(TiffFormatError::Format(
+                return Err(GeoTiffError::Format(
                     format!("{MODEL_PIXEL_SCALE_TAG} must not be specified when {MODEL_TRANSFORMATION_TAG} is present"),
-                )));
+                ));
             }
             if tie_points.is_some() {
-                return Err(TiffError::FormatError(TiffFormatError::Format(
+                return Err(GeoTiffError::Format(
                     format!("{MODEL_TIE_POINT_TAG} must not be specified when {MODEL_TRANSFORMATION_TAG} is present"),
-                )));
+                ));
             }
 
             Self::from_transformation_matrix(transformation_matrix)
         } else {
             let Some(tie_points) = tie_points else {
-                return Err(TiffError::FormatError(TiffFormatError::Format(
+                return Err(GeoTiffError::Format(
                     format!("{MODEL_TIE_POINT_TAG} must be present when {MODEL_TRANSFORMATION_TAG} is missing"),
-                )));
+                ));
             };
 
             if tie_points.len() == 6 {
                 let Some(pixel_scale) = pixel_scale else {
-                    return Err(TiffError::FormatError(TiffFormatError::Format(
+                    return Err(GeoTiffError::Format(
                         format!("{MODEL_PIXEL_SCALE_TAG} must be specified when {MODEL_TIE_POINT_TAG} contains 6 values"),
-                    )));
+                    ));
                 };
 
                 Self::from_tie_point_and_pixel_scale(&tie_points, &pixel_scale)
@@ -119,14 +155,69 @@ impl CoordinateTransform {
                 }
                 #[cfg(not(feature = "tie-points"))]
                 {
-                    Err(TiffError::FormatError(TiffFormatError::Format(
+                    Err(GeoTiffError::Transform(
                         "Transformation by tie points is not supported".into(),
-                    )))
+                    ))
                 }
             }
         }
     }
 
+    /// Encodes the transform as the data of the ModelPixelScaleTag, ModelTiepointTag and
+    /// ModelTransformationTag.
+    pub(super) fn to_tag_data(&self) -> TransformTagData {
+        match self {
+            CoordinateTransform::AffineTransform { transform, .. } => TransformTagData {
+                pixel_scale: None,
+                tie_points: None,
+                model_transformation: Some(vec![
+                    transform[0],
+                    transform[1],
+                    0.0,
+                    transform[2],
+                    transform[3],
+                    transform[4],
+                    0.0,
+                    transform[5],
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    1.0,
+                ]),
+            },
+            CoordinateTransform::TiePointAndPixelScale {
+                raster_point,
+                model_point,
+                pixel_scale,
+            } => TransformTagData {
+                pixel_scale: Some(vec![pixel_scale.x, pixel_scale.y, 0.0]),
+                tie_points: Some(vec![
+                    raster_point.x,
+                    raster_point.y,
+                    0.0,
+                    model_point.x,
+                    model_point.y,
+                    0.0,
+                ]),
+                model_transformation: None,
+            },
+            #[cfg(feature = "tie-points")]
+            CoordinateTransform::TiePoints {
+                raster_mesh,
+                model_mesh,
+                ..
+            } => TransformTagData {
+                pixel_scale: None,
+                tie_points: Some(Self::tie_points(raster_mesh, model_mesh)),
+                model_transformation: None,
+            },
+        }
+    }
+
     pub fn transform_to_model(&self, coord: &Coord) -> Coord {
         match self {
             CoordinateTransform::AffineTransform { transform, .. } => {
@@ -176,4 +267,67 @@ impl CoordinateTransform {
             } => Self::transform_by_tie_points(model_index, model_mesh, raster_mesh, coord),
         }
     }
+
+    /// Returns the coefficients `[a, b, c, d, e, f]` of the transform to model space
+    /// `x' = a * x + b * y + c` and `y' = d * x + e * y + f`, or `None` if it is not affine.
+    pub(super) fn affine_coefficients(&self) -> Option<[f64; 6]> {
+        match self {
+            CoordinateTransform::AffineTransform { transform, .. } => Some(*transform),
+            CoordinateTransform::TiePointAndPixelScale {
+                raster_point,
+                model_point,
+                pixel_scale,
+            } => Some([
+                pixel_scale.x,
+                0.0,
+                model_point.x - raster_point.x * pixel_scale.x,
+                0.0,
+                -pixel_scale.y,
+                model_point.y + raster_point.y * pixel_scale.y,
+            ]),
+            #[cfg(feature = "tie-points")]
+            CoordinateTransform::TiePoints { .. } => None,
+        }
+    }
+
+    /// Returns the transform of a raster whose pixel grid is derived from the one of this
+    /// transform: a location `c` in the raster space of the derived raster corresponds to the
+    /// location `c * scale + origin` in the raster space of this transform.
+    ///
+    /// This is used for sub-rasters (`scale` of 1) as well as for reduced-resolution rasters.
+    pub(super) fn derive_raster_grid(&self, origin: &Coord, scale: &Coord) -> GeoTiffResult<Self> {
+        match self {
+            CoordinateTransform::AffineTransform { transform, .. } => {
+                Self::derive_affine_transform(transform, origin, scale)
+            }
+            CoordinateTransform::TiePointAndPixelScale {
+                raster_point,
+                model_point,
+                pixel_scale,
+            } => Ok(CoordinateTransform::TiePointAndPixelScale {
+                raster_point: Coord {
+                    x: (raster_point.x - origin.x) / scale.x,
+                    y: (raster_point.y - origin.y) / scale.y,
+                },
+                model_point: *model_point,
+                pixel_scale: Coord {
+                    x: pixel_scale.x * scale.x,
+                    y: pixel_scale.y * scale.y,
+                },
+            }),
+            #[cfg(feature = "tie-points")]
+            CoordinateTransform::TiePoints {
+                raster_mesh,
+                model_mesh,
+                model_index,
+                ..
+            } => Ok(Self::derive_tie_points(
+                raster_mesh,
+                model_mesh,
+                model_index,
+                origin,
+                scale,
+            )),
+        }
+    }
 }
@@ -1,24 +1,33 @@
 use geo_types::Coord;
-use tiff::{TiffError, TiffFormatError, TiffResult};
 
 use crate::coordinate_transform::CoordinateTransform;
+use crate::error::{GeoTiffError, GeoTiffResult};
 
 impl CoordinateTransform {
-    pub fn from_transformation_matrix(transformation_matrix: [f64; 16]) -> TiffResult<Self> {
-        let transform = [
+    pub fn from_transformation_matrix(transformation_matrix: [f64; 16]) -> GeoTiffResult<Self> {
+        Self::from_affine_transform([
             transformation_matrix[0],
             transformation_matrix[1],
             transformation_matrix[3],
             transformation_matrix[4],
             transformation_matrix[5],
             transformation_matrix[7],
-        ];
+        ])
+    }
+
+    /// Returns the affine transform with the given GDAL geotransform, whose pixel grid is offset
+    /// by the given distance depending on the raster type.
+    pub(crate) fn from_geotransform(geotransform: [f64; 6], offset: f64) -> GeoTiffResult<Self> {
+        let [x, a, b, y, d, e] = geotransform;
+        Self::from_affine_transform([a, b, x - (a + b) * offset, d, e, y - (d + e) * offset])
+    }
 
+    pub(super) fn from_affine_transform(transform: [f64; 6]) -> GeoTiffResult<Self> {
         let det = transform[0] * transform[4] - transform[1] * transform[3];
         if det.abs() < 0.000000000000001 {
-            return Err(TiffError::FormatError(TiffFormatError::Format(
+            return Err(GeoTiffError::Transform(
                 "Provided transformation matrix is not invertible".into(),
-            )));
+            ));
         }
 
         let inverse_transform = [
@@ -42,4 +51,20 @@ impl CoordinateTransform {
             y: coord.x * transform[3] + coord.y * transform[4] + transform[5],
         }
     }
+
+    pub(super) fn derive_affine_transform(
+        transform: &[f64; 6],
+        origin: &Coord,
+        scale: &Coord,
+    ) -> GeoTiffResult<Self> {
+        let origin = Self::transform_by_affine_transform(transform, origin);
+        Self::from_affine_transform([
+            transform[0] * scale.x,
+            transform[1] * scale.y,
+            origin.x,
+            transform[3] * scale.x,
+            transform[4] * scale.y,
+            origin.y,
+        ])
+    }
 }
@@ -1,13 +1,13 @@
 use geo_types::Coord;
-use tiff::TiffResult;
 
 use crate::coordinate_transform::CoordinateTransform;
+use crate::error::GeoTiffResult;
 
 impl CoordinateTransform {
     pub(super) fn from_tie_point_and_pixel_scale(
         tie_points: &[f64],
         pixel_scale: &[f64],
-    ) -> TiffResult<Self> {
+    ) -> GeoTiffResult<Self> {
         Ok(CoordinateTransform::TiePointAndPixelScale {
             raster_point: Coord {
                 x: tie_points[0],
@@ -5,12 +5,12 @@ use delaunator::{Point, Triangulation};
 use geo_index::rtree::sort::STRSort;
 use geo_index::rtree::{OwnedRTree, RTreeBuilder, RTreeIndex};
 use geo_types::Coord;
-use tiff::TiffResult;
 
 use crate::coordinate_transform::CoordinateTransform;
+use crate::error::{GeoTiffError, GeoTiffResult};
 
 impl CoordinateTransform {
-    pub(super) fn from_tie_points(tie_points: &[f64]) -> TiffResult<CoordinateTransform> {
+    pub(super) fn from_tie_points(tie_points: &[f64]) -> GeoTiffResult<CoordinateTransform> {
         let capacity = tie_points.iter().len() / 6;
         let mut raster_points = Vec::with_capacity(capacity);
         let mut model_points = Vec::with_capacity(capacity);
@@ -27,6 +27,11 @@ impl CoordinateTransform {
         }
 
         let triangulation = delaunator::triangulate(&raster_points);
+        if triangulation.triangles.is_empty() {
+            return Err(GeoTiffError::Transform(
+                "At least three tie points that do not lie on a line are required".into(),
+            ));
+        }
         let raster_mesh = Rc::new(Self::build_faces(raster_points, &triangulation));
         let model_mesh = Rc::new(Self::build_faces(model_points, &triangulation));
         let raster_index = Self::build_index(&raster_mesh);
@@ -179,6 +184,58 @@ impl CoordinateTransform {
         builder.finish::<STRSort>()
     }
 
+    pub(super) fn derive_tie_points(
+        raster_mesh: &[Face],
+        model_mesh: &Rc<Vec<Face>>,
+        model_index: &OwnedRTree<f64>,
+        origin: &Coord,
+        scale: &Coord,
+    ) -> Self {
+        let raster_mesh = Rc::new(
+            raster_mesh
+                .iter()
+                .map(|face| face.derive(origin, scale))
+                .collect::<Vec<_>>(),
+        );
+        let raster_index = Self::build_index(&raster_mesh);
+
+        Self::TiePoints {
+            raster_mesh,
+            raster_index,
+            model_mesh: model_mesh.clone(),
+            model_index: model_index.clone(),
+        }
+    }
+
+    /// Returns the tie points spanning the given meshes, as stored in the ModelTiepointTag.
+    pub(super) fn tie_points(raster_mesh: &[Face], model_mesh: &[Face]) -> Vec<f64> {
+        let mut tie_points: Vec<f64> = Vec::new();
+        for (raster_face, model_face) in raster_mesh.iter().zip(model_mesh) {
+            for (raster_point, model_point) in raster_face
+                .support_points
+                .iter()
+                .zip(&model_face.support_points)
+            {
+                // Each point is shared by several faces
+                let is_known = tie_points
+                    .chunks(6)
+                    .any(|chunk| chunk[0] == raster_point.x && chunk[1] == raster_point.y);
+                if !is_known {
+                    tie_points.extend([
+                        raster_point.x,
+                        raster_point.y,
+                        0.0,
+                        model_point.x,
+                        model_point.y,
+                        0.0,
+                    ]);
+                }
+            }
+        }
+
+        tie_points
+    }
+
     pub(super) fn transform_by_tie_points(
         source_index: &OwnedRTree<f64>,
         source_mesh: &Rc<Vec<Face>>,
@@ -195,7 +252,7 @@ impl CoordinateTransform {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Face {
     boundary: Option<Boundary>,
     support_points: [Coord; 3],
@@ -246,6 +303,41 @@ impl Face {
         }
     }
 
+    /// Maps the face into a raster space where `c * scale + origin` corresponds to `c` in the
+    /// current raster space.
+    fn derive(&self, origin: &Coord, scale: &Coord) -> Self {
+        let map_coord = |c: &Coord| Coord {
+            x: (c.x - origin.x) / scale.x,
+            y: (c.y - origin.y) / scale.y,
+        };
+        let map_direction = |d: &Coord| {
+            Coord {
+                x: d.x / scale.x,
+                y: d.y / scale.y,
+            }
+            .normalize()
+        };
+        let map_coords = |coords: &[Coord]| coords.iter().map(map_coord).collect();
+
+        Face {
+            boundary: self.boundary.as_ref().map(|boundary| match boundary {
+                Boundary::Open {
+                    coords,
+                    from_direction,
+                    to_direction,
+                } => Boundary::Open {
+                    coords: map_coords(coords),
+                    from_direction: map_direction(from_direction),
+                    to_direction: map_direction(to_direction),
+                },
+                Boundary::Closed { coords } => Boundary::Closed {
+                    coords: map_coords(coords),
+                },
+            }),
+            support_points: self.support_points.map(|c| map_coord(&c)),
+        }
+    }
+
     fn compute_envelope(&self) -> (f64, f64, f64, f64) {
         let Some(boundary) = &self.boundary else {
             return (f64::MIN, f64::MIN, f64::MAX, f64::MAX);
@@ -298,7 +390,7 @@ impl Face {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum Boundary {
     Open {
         coords: Vec<Coord>,
@@ -0,0 +1,99 @@
+use geo_types::Rect;
+
+use crate::error::GeoTiffResult;
+use crate::raster_data::{all_samples, BufferLayout};
+use crate::raster_space::RasterSpace;
+use crate::reader::CorruptChunk;
+use crate::window::Window;
+use crate::GeoTiff;
+
+impl GeoTiff {
+    /// Returns the pixels intersecting the given rectangle in model space, or `None` if the
+    /// raster does not intersect it.
+    ///
+    /// The coordinate transform of the returned GeoTIFF is adjusted so that the pixels keep their
+    /// location in model space. Pixels partially covered by the rectangle are included.
+    pub fn crop(&self, rect: &Rect) -> GeoTiffResult<Option<Self>> {
+        self.compute_intersection_window(rect)
+            .map(|window| self.subset(&window))
+            .transpose()
+    }
+
+    /// Returns the pixels in the given window, which has to lie within the raster.
+    pub(crate) fn subset(&self, window: &Window) -> GeoTiffResult<Self> {
+        let layout = self.buffer_layout();
+        let subset_layout = BufferLayout {
+            width: window.width,
+            height: window.height,
+            ..layout
+        };
+        let mut raster_data = self
+            .raster_data
+            .zeroed_like(window.width * window.height * self.num_samples);
+        raster_data.copy_block(
+            subset_layout,
+            (0, 0),
+            &self.raster_data,
+            layout,
+            window,
+            &all_samples(self.num_samples),
+        )?;
+
+        let mask = self.mask.as_ref().map(|mask| {
+            (window.y..window.y + window.height)
+                .flat_map(|row| {
+                    let start = row * self.raster_width + window.x;
+                    mask[start..start + window.width].iter().copied()
+                })
+                .collect()
+        });
+
+        // Corrupt chunks are clipped to the window and located relative to it
+        let corrupt_chunks = self
+            .corrupt_chunks
+            .iter()
+            .filter_map(|chunk| {
+                let x = chunk.window.x.max(window.x);
+                let y = chunk.window.y.max(window.y);
+                let x_end = (chunk.window.x + chunk.window.width).min(window.x + window.width);
+                let y_end = (chunk.window.y + chunk.window.height).min(window.y + window.height);
+                (x < x_end && y < y_end).then(|| CorruptChunk {
+                    window: Window::new(x - window.x, y - window.y, x_end - x, y_end - y),
+                    ..chunk.clone()
+                })
+            })
+            .collect();
+
+        let (chunk_width, chunk_height) = self.chunk_dimensions;
+
+        Ok(Self {
+            geo_key_directory: self.geo_key_directory.clone(),
+            raster_width: window.width,
+            raster_height: window.height,
+            num_samples: self.num_samples,
+            no_data: self.no_data,
+            gdal_metadata: self.gdal_metadata.clone(),
+            descriptive_tags: self.descriptive_tags.clone(),
+            coordinate_transform: self.derive_coordinate_transform(
+                window,
+                window.width,
+                window.height,
+            )?,
+            gcps: self.derive_gcps(window, window.width, window.height),
+            chunk_dimensions: (
+                chunk_width.min(window.width),
+                chunk_height.min(window.height),
+            ),
+            interleave: self.interleave,
+            order: self.order,
+            band_formats: self.band_formats.clone(),
+            sample_roles: self.sample_roles.clone(),
+            photometric_interpretation: self.photometric_interpretation,
+            raster_data,
+            corrupt_chunks,
+            mask,
+            bigtiff: self.bigtiff,
+            overviews: Vec::new(),
+        })
+    }
+}
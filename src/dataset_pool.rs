@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::error::GeoTiffResult;
+#[cfg(feature = "http")]
+use crate::http_reader::HttpRangeReader;
+use crate::reader::GeoTiffReader;
+
+/// A reader shared by the users of a [`DatasetPool`].
+pub type SharedReader<R> = Arc<Mutex<GeoTiffReader<R>>>;
+
+type Opener<R> = Box<dyn Fn(&str) -> GeoTiffResult<R> + Send + Sync>;
+
+/// A cache of open [`GeoTiffReader`]s keyed by path or URL, for applications reading from a
+/// bounded set of files many times, e.g. tile servers.
+///
+/// Opening a reader parses the header and the image file directories, which can dominate the time
+/// of small reads. The pool keeps up to a maximum number of readers open, evicting the least
+/// recently used one when full, and optionally reopens readers after a time to live so that
+/// changes to the files are picked up.
+pub struct DatasetPool<R: Read + Seek> {
+    open: Opener<R>,
+    max_entries: usize,
+    ttl: Option<Duration>,
+    entries: Mutex<HashMap<String, Entry<R>>>,
+}
+
+struct Entry<R: Read + Seek> {
+    reader: SharedReader<R>,
+    opened: Instant,
+    last_used: Instant,
+}
+
+impl<R: Read + Seek> DatasetPool<R> {
+    /// Creates a pool holding up to `max_entries` readers, opening the source of each key with the
+    /// given function.
+    pub fn new(
+        max_entries: usize,
+        open: impl Fn(&str) -> GeoTiffResult<R> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            open: Box::new(open),
+            max_entries,
+            ttl: None,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the time after which a reader is reopened instead of being taken from the pool.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Returns the reader for the given key, opening it if it is not in the pool or has expired.
+    ///
+    /// The source is opened without holding a lock on the pool, so that slow sources do not block
+    /// requests for other keys.
+    pub fn get(&self, key: &str) -> GeoTiffResult<SharedReader<R>> {
+        let now = Instant::now();
+        {
+            let mut entries = self.entries();
+            self.remove_expired(&mut entries, now);
+            if let Some(entry) = entries.get_mut(key) {
+                entry.last_used = now;
+                return Ok(entry.reader.clone());
+            }
+        }
+
+        let reader = Arc::new(Mutex::new(GeoTiffReader::open((self.open)(key)?)?));
+
+        let mut entries = self.entries();
+        if let Some(entry) = entries.get_mut(key) {
+            // Another thread opened the same key in the meantime
+            entry.last_used = now;
+            return Ok(entry.reader.clone());
+        }
+        if self.max_entries == 0 {
+            return Ok(reader);
+        }
+        while entries.len() >= self.max_entries {
+            let Some(least_recently_used) = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            entries.remove(&least_recently_used);
+        }
+        entries.insert(
+            key.to_string(),
+            Entry {
+                reader: reader.clone(),
+                opened: now,
+                last_used: now,
+            },
+        );
+
+        Ok(reader)
+    }
+
+    /// Removes the reader for the given key from the pool, returning whether it was present.
+    ///
+    /// Users still holding the reader can keep using it.
+    pub fn remove(&self, key: &str) -> bool {
+        self.entries().remove(key).is_some()
+    }
+
+    /// Removes all readers from the pool.
+    pub fn clear(&self) {
+        self.entries().clear();
+    }
+
+    /// Returns the number of readers in the pool, including expired ones that have not been
+    /// removed yet.
+    pub fn len(&self) -> usize {
+        self.entries().len()
+    }
+
+    /// Returns whether the pool holds no readers.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn entries(&self) -> MutexGuard<'_, HashMap<String, Entry<R>>> {
+        // The map is consistent after every operation, so it can be used after a panic
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn remove_expired(&self, entries: &mut HashMap<String, Entry<R>>, now: Instant) {
+        if let Some(ttl) = self.ttl {
+            entries.retain(|_, entry| now.duration_since(entry.opened) < ttl);
+        }
+    }
+}
+
+impl DatasetPool<File> {
+    /// Creates a pool of readers for the files at the paths used as keys.
+    pub fn for_files(max_entries: usize) -> Self {
+        Self::new(max_entries, |path| Ok(File::open(path)?))
+    }
+}
+
+#[cfg(feature = "http")]
+impl DatasetPool<HttpRangeReader> {
+    /// Creates a pool of readers for the remote files at the URLs used as keys, see
+    /// [`HttpRangeReader`].
+    pub fn for_urls(max_entries: usize) -> Self {
+        Self::new(max_entries, |url| Ok(HttpRangeReader::new(url)))
+    }
+}
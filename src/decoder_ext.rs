@@ -1,29 +1,228 @@
-use std::io::{Read, Seek};
+use std::io::{self, Cursor, Read, Seek};
+use std::ops::Range;
 
-use tiff::decoder::Decoder;
-use tiff::tags::Tag;
-use tiff::TiffResult;
+use geo_types::Coord;
+use num_complex::Complex;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use tiff::decoder::{ChunkType, Decoder, DecodingResult, Limits};
+use tiff::encoder::TiffEncoder;
+use tiff::tags::{
+    CompressionMethod, PhotometricInterpretation, PlanarConfiguration, Predictor, SampleFormat, Tag,
+};
+use tiff::{TiffError, TiffFormatError, TiffUnsupportedError};
+#[cfg(feature = "lzma")]
+use xz2::read::XzDecoder;
 
 use crate::coordinate_transform::CoordinateTransform;
+use crate::error::{GeoTiffError, GeoTiffResult, TiffFeature, Unsupported};
+use crate::gdal_metadata::{GdalMetadata, GDAL_METADATA_TAG};
 use crate::geo_key_directory::GeoKeyDirectory;
+use crate::georeference::Gcp;
+use crate::layout::{
+    ArrayOrder, BandFormat, Interleave, RasterDataType, SampleRole, COMPLEX_IEEEFP_SAMPLE_FORMAT,
+    COMPLEX_INT_SAMPLE_FORMAT,
+};
+use crate::raster_data::{BufferLayout, RasterData};
+use crate::reader::CorruptChunk;
+use crate::tiff_tags::DescriptiveTags;
+use crate::window::Window;
+
+/// Bit of the NewSubfileType tag marking a reduced-resolution version of another image.
+const REDUCED_RESOLUTION_IMAGE: u32 = 1;
+/// Bit of the NewSubfileType tag marking a transparency mask for another image.
+const MASK_IMAGE: u32 = 4;
+/// The version number in the header of BigTIFF files, which is 42 for classic TIFF files.
+const BIGTIFF_VERSION: u16 = 43;
+/// The compression method of LZMA-compressed images, which the decoder does not support.
+const LZMA_COMPRESSION: u16 = 34925;
+/// The coefficients for computing the luma from RGB.
+const YCBCR_COEFFICIENTS_TAG: u16 = 529;
+/// The chroma subsampling factors of YCbCr images.
+const YCBCR_SUBSAMPLING_TAG: u16 = 530;
+/// The headroom and footroom of the luma and chroma samples.
+const REFERENCE_BLACK_WHITE_TAG: u16 = 532;
+/// The number of tiles decoded concurrently per thread before they are copied into the raster.
+#[cfg(feature = "rayon")]
+const TILES_PER_THREAD: usize = 4;
+/// The compression methods whose encoded data can be decoded without further tags.
+#[cfg(feature = "rayon")]
+const STANDALONE_COMPRESSIONS: [CompressionMethod; 5] = [
+    CompressionMethod::None,
+    CompressionMethod::LZW,
+    CompressionMethod::Deflate,
+    CompressionMethod::OldDeflate,
+    CompressionMethod::PackBits,
+];
+
+/// The organization of an image into strips or tiles.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct ChunkLayout {
+    pub chunk_width: usize,
+    pub chunk_height: usize,
+    pub chunks_across: usize,
+    pub chunks_down: usize,
+    /// The number of planes, which is the number of samples for planar images and 1 otherwise.
+    pub num_planes: usize,
+}
+
+impl ChunkLayout {
+    /// Returns the index of the chunk at the given position in the chunk grid.
+    pub fn chunk_index(&self, chunk_x: usize, chunk_y: usize, plane: usize) -> u32 {
+        ((plane * self.chunks_down + chunk_y) * self.chunks_across + chunk_x) as u32
+    }
+
+    /// Returns the indices of the chunks intersecting the given non-empty window.
+    pub fn chunks_in_window(&self, window: &Window) -> impl Iterator<Item = u32> + '_ {
+        let chunks_x =
+            window.x / self.chunk_width..=(window.x + window.width - 1) / self.chunk_width;
+        let chunks_y =
+            window.y / self.chunk_height..=(window.y + window.height - 1) / self.chunk_height;
+
+        (0..self.num_planes).flat_map(move |plane| {
+            let chunks_x = chunks_x.clone();
+            chunks_y.clone().flat_map(move |chunk_y| {
+                chunks_x
+                    .clone()
+                    .map(move |chunk_x| self.chunk_index(chunk_x, chunk_y, plane))
+            })
+        })
+    }
+}
+
+/// The tags needed to decode the encoded data of a strip or tile on its own, see
+/// [`decode_standalone_chunk`].
+pub(super) struct ChunkEncoding {
+    pub bits_per_sample: Vec<u16>,
+    pub sample_formats: Vec<u16>,
+    pub compression: u16,
+    pub predictor: u16,
+    pub photometric_interpretation: PhotometricInterpretation,
+}
+
+/// The location of the encoded data of the strips or tiles of an image and the formats of its
+/// bands, read once per image rather than for every chunk, and the limits on the buffers holding
+/// it.
+pub(super) struct ImageChunks {
+    pub byte_ranges: Vec<Range<u64>>,
+    pub band_formats: Vec<BandFormat>,
+    pub limits: Limits,
+}
 
 pub(super) trait DecoderExt {
-    fn coordinate_transform(&mut self) -> TiffResult<Option<CoordinateTransform>>;
+    fn coordinate_transform(&mut self, strict: bool) -> GeoTiffResult<Option<CoordinateTransform>>;
+
+    fn gcps(&mut self) -> GeoTiffResult<Vec<Gcp>>;
+
+    fn geo_key_directory(&mut self, strict: bool) -> GeoTiffResult<GeoKeyDirectory>;
+
+    fn num_samples(&mut self) -> GeoTiffResult<usize>;
+
+    fn band_formats(&mut self) -> GeoTiffResult<Vec<BandFormat>>;
+
+    fn sample_roles(&mut self) -> GeoTiffResult<Vec<SampleRole>>;
+
+    fn photometric_interpretation(&mut self) -> GeoTiffResult<PhotometricInterpretation>;
+
+    fn compression_method(&mut self) -> GeoTiffResult<CompressionMethod>;
+
+    fn chunk_layout(&mut self) -> GeoTiffResult<ChunkLayout>;
+
+    #[cfg(feature = "rayon")]
+    fn tile_encoding(&mut self, chunks: &ImageChunks) -> GeoTiffResult<Option<ChunkEncoding>>;
+
+    fn no_data(&mut self) -> GeoTiffResult<Option<f64>>;
+
+    fn gdal_metadata(&mut self) -> GeoTiffResult<Option<GdalMetadata>>;
+
+    fn descriptive_tags(&mut self) -> GeoTiffResult<DescriptiveTags>;
+
+    fn subfile_images(&mut self) -> GeoTiffResult<Vec<(usize, u32)>>;
+
+    fn overview_images(&mut self) -> GeoTiffResult<Vec<usize>>;
+
+    fn mask_image(&mut self, image: usize) -> GeoTiffResult<Option<usize>>;
+
+    fn read_mask(&mut self, window: &Window, chunks: &ImageChunks) -> GeoTiffResult<Vec<bool>>;
+
+    fn is_bigtiff(&mut self) -> GeoTiffResult<bool>;
+
+    fn unsupported_features(&mut self) -> GeoTiffResult<Vec<Unsupported>>;
+
+    fn chunk_byte_ranges(&mut self) -> GeoTiffResult<Vec<Range<u64>>>;
+
+    fn image_chunks(&mut self, limits: &Limits) -> GeoTiffResult<ImageChunks>;
+
+    fn read_bytes(&mut self, byte_range: &Range<u64>, limits: &Limits) -> GeoTiffResult<Vec<u8>>;
 
-    fn geo_key_directory(&mut self) -> TiffResult<GeoKeyDirectory>;
+    fn verify_chunk_bytes(
+        &mut self,
+        chunk_index: u32,
+        byte_range: Option<&Range<u64>>,
+    ) -> GeoTiffResult<Option<String>>;
+
+    fn decode_chunk(&mut self, chunk_index: u32, chunks: &ImageChunks)
+        -> GeoTiffResult<RasterData>;
+
+    #[cfg(feature = "lzma")]
+    fn decode_lzma_chunk(
+        &mut self,
+        chunk_index: u32,
+        chunks: &ImageChunks,
+    ) -> GeoTiffResult<DecodingResult>;
+
+    fn decode_packed_chunk(
+        &mut self,
+        chunk_index: u32,
+        chunks: &ImageChunks,
+    ) -> GeoTiffResult<DecodingResult>;
+
+    fn decode_complex_chunk(
+        &mut self,
+        chunk_index: u32,
+        chunks: &ImageChunks,
+    ) -> GeoTiffResult<RasterData>;
+
+    fn read_encoded_chunk(
+        &mut self,
+        chunk_index: u32,
+        chunks: &ImageChunks,
+    ) -> GeoTiffResult<Vec<u8>>;
+
+    fn read_standalone_chunk(
+        &mut self,
+        chunk_index: u32,
+        chunks: &ImageChunks,
+    ) -> GeoTiffResult<(Vec<u8>, u16)>;
+
+    fn read_verified_chunk(
+        &mut self,
+        chunk_index: u32,
+        chunks: &ImageChunks,
+    ) -> GeoTiffResult<Result<RasterData, CorruptChunk>>;
+
+    fn read_window(
+        &mut self,
+        window: &Window,
+        interleave: Interleave,
+        order: ArrayOrder,
+        samples: &[usize],
+        verify_chunks: bool,
+        chunks: &ImageChunks,
+    ) -> GeoTiffResult<(RasterData, Vec<CorruptChunk>)>;
 }
 
 impl<R: Read + Seek> DecoderExt for Decoder<R> {
-    fn coordinate_transform(&mut self) -> TiffResult<Option<CoordinateTransform>> {
-        let pixel_scale_data = self
+    fn coordinate_transform(&mut self, strict: bool) -> GeoTiffResult<Option<CoordinateTransform>> {
+        let mut pixel_scale_data = self
             .find_tag(Tag::ModelPixelScaleTag)?
             .map(|value| value.into_f64_vec())
             .transpose()?;
-        let tie_points_data = self
+        let mut tie_points_data = self
             .find_tag(Tag::ModelTiepointTag)?
             .map(|value| value.into_f64_vec())
             .transpose()?;
-        let model_transformation_data = self
+        let mut model_transformation_data = self
             .find_tag(Tag::ModelTransformationTag)?
             .map(|value| value.into_f64_vec())
             .transpose()?;
@@ -34,6 +233,31 @@ impl<R: Read + Seek> DecoderExt for Decoder<R> {
         {
             return Ok(None);
         }
+        // Outside of strict mode, a tie point with a pixel scale takes precedence over a
+        // transformation matrix given as well, as in GDAL
+        if !strict
+            && model_transformation_data.is_some()
+            && (pixel_scale_data.is_some() || tie_points_data.is_some())
+        {
+            if pixel_scale_data.is_some()
+                && tie_points_data.as_ref().is_some_and(|data| data.len() == 6)
+            {
+                model_transformation_data = None;
+            } else {
+                pixel_scale_data = None;
+                tie_points_data = None;
+            }
+        }
+        // Files georeferenced by ground control points are read without a transform, exposing
+        // the points instead
+        #[cfg(not(feature = "tie-points"))]
+        if model_transformation_data.is_none()
+            && tie_points_data
+                .as_ref()
+                .is_some_and(|data| data.len() > 6 && data.len() % 6 == 0)
+        {
+            return Ok(None);
+        }
 
         Ok(Some(CoordinateTransform::from_tag_data(
             pixel_scale_data,
@@ -42,7 +266,35 @@ impl<R: Read + Seek> DecoderExt for Decoder<R> {
         )?))
     }
 
-    fn geo_key_directory(&mut self) -> TiffResult<GeoKeyDirectory> {
+    fn gcps(&mut self) -> GeoTiffResult<Vec<Gcp>> {
+        if self.find_tag(Tag::ModelTransformationTag)?.is_some() {
+            return Ok(Vec::new());
+        }
+        let tie_points = self
+            .find_tag(Tag::ModelTiepointTag)?
+            .map(|value| value.into_f64_vec())
+            .transpose()?
+            .unwrap_or_default();
+        if tie_points.len() <= 6 || tie_points.len() % 6 != 0 {
+            return Ok(Vec::new());
+        }
+
+        Ok(tie_points
+            .chunks_exact(6)
+            .map(|tie_point| Gcp {
+                pixel: Coord {
+                    x: tie_point[0],
+                    y: tie_point[1],
+                },
+                model: Coord {
+                    x: tie_point[3],
+                    y: tie_point[4],
+                },
+            })
+            .collect())
+    }
+
+    fn geo_key_directory(&mut self, strict: bool) -> GeoTiffResult<GeoKeyDirectory> {
         let Some(directory_data) = self
             .find_tag(Tag::GeoKeyDirectoryTag)?
             .map(|v| v.into_u16_vec())
@@ -63,6 +315,1294 @@ impl<R: Read + Seek> DecoderExt for Decoder<R> {
             .transpose()?
             .unwrap_or_else(|| String::with_capacity(0));
 
-        GeoKeyDirectory::from_tag_data(directory_data, double_params_data, ascii_params_data)
+        GeoKeyDirectory::from_tag_data(
+            directory_data,
+            double_params_data,
+            ascii_params_data,
+            strict,
+        )
+    }
+
+    fn num_samples(&mut self) -> GeoTiffResult<usize> {
+        Ok(match self.find_tag(Tag::SamplesPerPixel)? {
+            None => 1,
+            Some(value) => value.into_u16()? as usize,
+        })
+    }
+
+    /// Returns the format of the samples of each band. A single value of the SampleFormat or
+    /// BitsPerSample tag applies to all bands.
+    fn band_formats(&mut self) -> GeoTiffResult<Vec<BandFormat>> {
+        let num_samples = self.num_samples()?.max(1);
+        let sample_formats = self
+            .find_tag_unsigned_vec::<u16>(Tag::SampleFormat)?
+            .unwrap_or_else(|| vec![1]);
+        let bits_per_sample = self
+            .find_tag_unsigned_vec::<u16>(Tag::BitsPerSample)?
+            .unwrap_or_else(|| vec![1]);
+
+        let signed_byte = self
+            .gdal_metadata()?
+            .is_some_and(|gdal_metadata| gdal_metadata.is_signed_byte());
+
+        let value_of_band =
+            |values: &[u16], band: usize| values.get(band).or(values.last()).copied().unwrap_or(1);
+        Ok((0..num_samples)
+            .map(|band| {
+                let sample_format =
+                    SampleFormat::from_u16_exhaustive(value_of_band(&sample_formats, band));
+                let bits_per_sample = value_of_band(&bits_per_sample, band);
+                BandFormat {
+                    sample_format: match sample_format {
+                        SampleFormat::Uint if signed_byte && bits_per_sample == 8 => {
+                            SampleFormat::Int
+                        }
+                        sample_format => sample_format,
+                    },
+                    bits_per_sample,
+                }
+            })
+            .collect())
+    }
+
+    /// Returns the role of each sample. The extra samples listed by the ExtraSamples tag follow
+    /// the color or data samples.
+    fn sample_roles(&mut self) -> GeoTiffResult<Vec<SampleRole>> {
+        let num_samples = self.num_samples()?;
+        let extra_samples = self
+            .find_tag_unsigned_vec::<u16>(Tag::ExtraSamples)?
+            .unwrap_or_default();
+        let num_extra_samples = extra_samples.len().min(num_samples);
+
+        let mut sample_roles = vec![SampleRole::Data; num_samples - num_extra_samples];
+        sample_roles.extend(
+            extra_samples[..num_extra_samples]
+                .iter()
+                .map(|value| SampleRole::from_extra_sample(*value)),
+        );
+        Ok(sample_roles)
+    }
+
+    /// Returns the photometric interpretation of the decoded samples, which differs from the one
+    /// of the file for images the decoder converts.
+    fn photometric_interpretation(&mut self) -> GeoTiffResult<PhotometricInterpretation> {
+        Ok(
+            match self
+                .find_tag_unsigned::<u16>(Tag::PhotometricInterpretation)?
+                .and_then(PhotometricInterpretation::from_u16)
+            {
+                // The decoder inverts the samples, and YCbCr samples are converted to RGB
+                Some(PhotometricInterpretation::WhiteIsZero) | None => {
+                    PhotometricInterpretation::BlackIsZero
+                }
+                Some(PhotometricInterpretation::YCbCr) => PhotometricInterpretation::RGB,
+                Some(photometric_interpretation) => photometric_interpretation,
+            },
+        )
     }
+
+    fn compression_method(&mut self) -> GeoTiffResult<CompressionMethod> {
+        Ok(CompressionMethod::from_u16_exhaustive(
+            self.find_tag_unsigned::<u16>(Tag::Compression)?
+                .unwrap_or(CompressionMethod::None.to_u16()),
+        ))
+    }
+
+    fn chunk_layout(&mut self) -> GeoTiffResult<ChunkLayout> {
+        let (raster_width, raster_height) = self.dimensions()?;
+        let num_planes = match self
+            .find_tag_unsigned(Tag::PlanarConfiguration)?
+            .and_then(PlanarConfiguration::from_u16)
+        {
+            Some(PlanarConfiguration::Planar) => self.num_samples()?,
+            _ => 1,
+        };
+
+        let (chunk_width, chunk_height) = self.chunk_dimensions();
+        let (chunk_width, chunk_height) = (chunk_width as usize, chunk_height as usize);
+        if chunk_width == 0 || chunk_height == 0 {
+            return Err(
+                TiffError::FormatError(TiffFormatError::InconsistentSizesEncountered).into(),
+            );
+        }
+
+        Ok(ChunkLayout {
+            chunk_width,
+            chunk_height,
+            chunks_across: (raster_width as usize).div_ceil(chunk_width),
+            chunks_down: (raster_height as usize).div_ceil(chunk_height),
+            num_planes,
+        })
+    }
+
+    /// Returns the encoding of the tiles of the image if they can be decoded independently of
+    /// the decoder, i.e. concurrently, to the same values as the decoder yields.
+    #[cfg(feature = "rayon")]
+    fn tile_encoding(&mut self, chunks: &ImageChunks) -> GeoTiffResult<Option<ChunkEncoding>> {
+        if self.get_chunk_type() != ChunkType::Tile {
+            return Ok(None);
+        }
+
+        let compression = self
+            .find_tag_unsigned::<u16>(Tag::Compression)?
+            .unwrap_or(CompressionMethod::None.to_u16());
+        let photometric_interpretation = self
+            .find_tag_unsigned::<u16>(Tag::PhotometricInterpretation)?
+            .and_then(PhotometricInterpretation::from_u16);
+        let band_format = chunks.band_formats[0];
+        // The decoder converts the samples from the byte order of the file, which starts with
+        // "II" for little endian
+        self.goto_offset(0)?;
+        let byte_order_independent = self.read_byte()? == b'I' || band_format.bits_per_sample <= 8;
+        // Samples that are not whole bytes are unpacked and complex samples are split when
+        // decoded by the decoder instead
+        if !band_format.bits_per_sample.is_multiple_of(8) || band_format.is_complex() {
+            return Ok(None);
+        }
+        let photometric_interpretation = match photometric_interpretation {
+            Some(PhotometricInterpretation::WhiteIsZero) => PhotometricInterpretation::WhiteIsZero,
+            Some(PhotometricInterpretation::BlackIsZero | PhotometricInterpretation::RGB) => {
+                PhotometricInterpretation::BlackIsZero
+            }
+            _ => return Ok(None),
+        };
+        if !STANDALONE_COMPRESSIONS.contains(&CompressionMethod::from_u16_exhaustive(compression))
+            || !byte_order_independent
+            || chunks
+                .band_formats
+                .iter()
+                .any(|format| *format != band_format)
+        {
+            return Ok(None);
+        }
+
+        let samples_per_plane = self.num_samples()? / self.chunk_layout()?.num_planes;
+        Ok(Some(ChunkEncoding {
+            bits_per_sample: vec![band_format.bits_per_sample; samples_per_plane],
+            sample_formats: vec![band_format.sample_format.to_u16(); samples_per_plane],
+            compression,
+            predictor: self
+                .find_tag_unsigned::<u16>(Tag::Predictor)?
+                .unwrap_or(Predictor::None.to_u16()),
+            photometric_interpretation,
+        }))
+    }
+
+    fn no_data(&mut self) -> GeoTiffResult<Option<f64>> {
+        let Some(no_data) = self
+            .find_tag(Tag::GdalNodata)?
+            .map(|value| value.into_string())
+            .transpose()?
+        else {
+            return Ok(None);
+        };
+
+        let no_data = no_data.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+        no_data
+            .parse()
+            .map(Some)
+            .map_err(|_| GeoTiffError::Format(format!("Invalid no data value: {no_data}")))
+    }
+
+    fn gdal_metadata(&mut self) -> GeoTiffResult<Option<GdalMetadata>> {
+        self.find_tag(Tag::Unknown(GDAL_METADATA_TAG))?
+            .map(|value| GdalMetadata::from_xml(&value.into_string()?))
+            .transpose()
+    }
+
+    fn descriptive_tags(&mut self) -> GeoTiffResult<DescriptiveTags> {
+        let mut string = |tag| -> GeoTiffResult<Option<String>> {
+            Ok(self
+                .find_tag(tag)?
+                .map(|value| value.into_string())
+                .transpose()?)
+        };
+
+        Ok(DescriptiveTags {
+            image_description: string(Tag::ImageDescription)?,
+            date_time: string(Tag::DateTime)?,
+            software: string(Tag::Software)?,
+            artist: string(Tag::Artist)?,
+            copyright: string(Tag::Copyright)?,
+            host_computer: string(Tag::HostComputer)?,
+        })
+    }
+
+    /// Returns whether the file is a BigTIFF, as given by the version number in its header.
+    fn is_bigtiff(&mut self) -> GeoTiffResult<bool> {
+        self.goto_offset(2)?;
+        Ok(self.read_short()? == BIGTIFF_VERSION)
+    }
+
+    /// Returns the indices of the images holding reduced-resolution versions of the first image,
+    /// leaving the decoder positioned at the first image.
+    fn overview_images(&mut self) -> GeoTiffResult<Vec<usize>> {
+        Ok(self
+            .subfile_images()?
+            .into_iter()
+            .filter(|(_, subfile_type)| {
+                subfile_type & REDUCED_RESOLUTION_IMAGE != 0 && subfile_type & MASK_IMAGE == 0
+            })
+            .map(|(index, _)| index)
+            .collect())
+    }
+
+    /// Returns the indices and NewSubfileType values of the images following the first image,
+    /// leaving the decoder positioned at the first image.
+    fn subfile_images(&mut self) -> GeoTiffResult<Vec<(usize, u32)>> {
+        self.seek_to_image(0)?;
+
+        let mut subfile_images = Vec::new();
+        let mut index = 0;
+        while self.more_images() {
+            self.next_image()?;
+            index += 1;
+
+            let subfile_type = self
+                .find_tag_unsigned::<u32>(Tag::NewSubfileType)?
+                .unwrap_or(0);
+            subfile_images.push((index, subfile_type));
+        }
+
+        self.seek_to_image(0)?;
+        Ok(subfile_images)
+    }
+
+    /// Returns the index of the image holding the transparency mask of the given image, i.e. a
+    /// mask image of the same dimensions which is reduced-resolution if the given image is an
+    /// overview. The decoder is left positioned at the given image.
+    fn mask_image(&mut self, image: usize) -> GeoTiffResult<Option<usize>> {
+        let reduced_resolution = image != 0;
+        let candidates: Vec<_> = self
+            .subfile_images()?
+            .into_iter()
+            .filter(|(_, subfile_type)| {
+                subfile_type & MASK_IMAGE != 0
+                    && (subfile_type & REDUCED_RESOLUTION_IMAGE != 0) == reduced_resolution
+            })
+            .map(|(index, _)| index)
+            .collect();
+
+        self.seek_to_image(image)?;
+        let dimensions = self.dimensions()?;
+        let mut mask_image = None;
+        for candidate in candidates {
+            self.seek_to_image(candidate)?;
+            if self.dimensions()? == dimensions {
+                mask_image = Some(candidate);
+                break;
+            }
+        }
+
+        self.seek_to_image(image)?;
+        Ok(mask_image)
+    }
+
+    /// Decodes the given window of the current image as a transparency mask, returning whether
+    /// each pixel is valid in row-major order.
+    ///
+    /// Masks hold a single sample of 1 bit, as written by GDAL, or 8 bits, where any non-zero
+    /// value marks a valid pixel.
+    fn read_mask(&mut self, window: &Window, chunks: &ImageChunks) -> GeoTiffResult<Vec<bool>> {
+        let bits_per_sample = self
+            .find_tag_unsigned::<u16>(Tag::BitsPerSample)?
+            .unwrap_or(1);
+        if bits_per_sample != 1 && bits_per_sample != 8 {
+            return Err(GeoTiffError::Unsupported(Unsupported::new(
+                TiffFeature::BitsPerSample,
+                bits_per_sample,
+            )));
+        }
+
+        let compression = self
+            .find_tag_unsigned::<u16>(Tag::Compression)?
+            .unwrap_or(CompressionMethod::None.to_u16());
+        let predictor = self
+            .find_tag_unsigned::<u16>(Tag::Predictor)?
+            .unwrap_or(Predictor::None.to_u16());
+
+        let chunk_layout = self.chunk_layout()?;
+        let tile_size = (self.get_chunk_type() == ChunkType::Tile).then(|| self.chunk_dimensions());
+        let mut mask = vec![false; window.width * window.height];
+        for chunk_index in chunk_layout.chunks_in_window(window) {
+            let (data_width, data_height) = self.chunk_data_dimensions(chunk_index);
+            let Some(byte_range) = chunks.byte_ranges.get(chunk_index as usize) else {
+                return Err(GeoTiffError::Format(format!(
+                    "Missing offset or byte count of mask chunk {chunk_index}"
+                )));
+            };
+            let encoded = self.read_bytes(byte_range, &chunks.limits)?;
+            // Tiles at the right and bottom edges are encoded padded to the full tile size
+            let (decoded_width, decoded_height) = tile_size.unwrap_or((data_width, data_height));
+            let data = decode_mask_chunk(
+                encoded,
+                (decoded_width, decoded_height),
+                tile_size,
+                bits_per_sample,
+                compression,
+                predictor,
+            )?;
+
+            let (data_width, data_height) = (data_width as usize, data_height as usize);
+            // Rows of 1-bit samples are padded to whole bytes
+            let row_len = match bits_per_sample {
+                1 => (decoded_width as usize).div_ceil(8),
+                _ => decoded_width as usize,
+            };
+            let chunk_x =
+                chunk_index as usize % chunk_layout.chunks_across * chunk_layout.chunk_width;
+            let chunk_y =
+                chunk_index as usize / chunk_layout.chunks_across * chunk_layout.chunk_height;
+
+            for y in window.y.max(chunk_y)..(window.y + window.height).min(chunk_y + data_height) {
+                let row = &data[(y - chunk_y) * row_len..];
+                for x in window.x.max(chunk_x)..(window.x + window.width).min(chunk_x + data_width)
+                {
+                    let x_in_chunk = x - chunk_x;
+                    mask[(y - window.y) * window.width + x - window.x] = match bits_per_sample {
+                        1 => row[x_in_chunk / 8] & (0x80 >> (x_in_chunk % 8)) != 0,
+                        _ => row[x_in_chunk] != 0,
+                    };
+                }
+            }
+        }
+
+        Ok(mask)
+    }
+
+    /// Returns the features of the current image that prevent it from being decoded, judged by its
+    /// tags alone.
+    fn unsupported_features(&mut self) -> GeoTiffResult<Vec<Unsupported>> {
+        let mut unsupported = Vec::new();
+
+        let compression = self
+            .find_tag_unsigned::<u16>(Tag::Compression)?
+            .unwrap_or(1);
+        let compression_supported = match CompressionMethod::from_u16(compression) {
+            Some(
+                CompressionMethod::None
+                | CompressionMethod::LZW
+                | CompressionMethod::Deflate
+                | CompressionMethod::OldDeflate
+                | CompressionMethod::PackBits,
+            ) => true,
+            // JPEG, ZSTD and LZMA decoding is optional
+            Some(CompressionMethod::ModernJPEG) => cfg!(feature = "jpeg"),
+            Some(CompressionMethod::ZSTD) => cfg!(feature = "zstd"),
+            None if compression == LZMA_COMPRESSION => cfg!(feature = "lzma"),
+            _ => false,
+        };
+        if !compression_supported {
+            unsupported.push(Unsupported::new(TiffFeature::Compression, compression));
+        }
+
+        let num_samples = self.num_samples()?;
+        let photometric_interpretation = self
+            .find_tag_unsigned::<u16>(Tag::PhotometricInterpretation)?
+            .unwrap_or(1);
+        // The decoder reads any number of BlackIsZero samples as multiband, but inverts only
+        // single WhiteIsZero samples
+        let samples_supported =
+            match PhotometricInterpretation::from_u16(photometric_interpretation) {
+                Some(PhotometricInterpretation::BlackIsZero) => true,
+                Some(PhotometricInterpretation::WhiteIsZero) => num_samples == 1,
+                Some(PhotometricInterpretation::RGB) => matches!(num_samples, 3 | 4),
+                Some(PhotometricInterpretation::CMYK) => matches!(num_samples, 4 | 5),
+                Some(PhotometricInterpretation::YCbCr) => num_samples == 3,
+                _ => {
+                    unsupported.push(Unsupported::new(
+                        TiffFeature::PhotometricInterpretation,
+                        photometric_interpretation,
+                    ));
+                    true
+                }
+            };
+        if !samples_supported {
+            unsupported.push(Unsupported::new(
+                TiffFeature::SamplesPerPixel,
+                num_samples as u16,
+            ));
+        }
+
+        let band_formats = self.band_formats()?;
+        let BandFormat {
+            sample_format,
+            bits_per_sample: bits,
+        } = band_formats[0];
+        if let Some(other) = band_formats
+            .iter()
+            .find(|format| format.sample_format != sample_format)
+        {
+            unsupported.push(Unsupported::new(
+                TiffFeature::SampleFormat,
+                other.sample_format.to_u16(),
+            ));
+        } else if !matches!(
+            sample_format,
+            SampleFormat::Uint
+                | SampleFormat::Int
+                | SampleFormat::IEEEFP
+                | SampleFormat::Unknown(COMPLEX_INT_SAMPLE_FORMAT | COMPLEX_IEEEFP_SAMPLE_FORMAT)
+        ) {
+            unsupported.push(Unsupported::new(
+                TiffFeature::SampleFormat,
+                sample_format.to_u16(),
+            ));
+        }
+        let is_float = sample_format == SampleFormat::IEEEFP;
+        let is_complex = band_formats[0].is_complex();
+
+        if let Some(other) = band_formats
+            .iter()
+            .find(|format| format.bits_per_sample != bits)
+        {
+            unsupported.push(Unsupported::new(
+                TiffFeature::BitsPerSample,
+                other.bits_per_sample,
+            ));
+        } else if !match (is_float, bits) {
+            _ if is_complex => band_formats[0].data_type().is_some(),
+            (true, bits) => bits == 16 || bits == 32 || bits == 64,
+            (false, 8 | 16 | 32 | 64) => true,
+            (false, 9..=15) => {
+                sample_format == SampleFormat::Uint
+                    && compression != CompressionMethod::ModernJPEG.to_u16()
+            }
+            (false, bits) => bits < 8 && num_samples == 1,
+        } {
+            unsupported.push(Unsupported::new(TiffFeature::BitsPerSample, bits));
+        }
+
+        if photometric_interpretation == PhotometricInterpretation::YCbCr.to_u16() {
+            if bits != 8 {
+                unsupported.push(Unsupported::new(TiffFeature::BitsPerSample, bits));
+            }
+            // The JPEG decoder upsamples the chroma itself, otherwise it is left subsampled
+            let subsampling = self
+                .find_tag(Tag::Unknown(YCBCR_SUBSAMPLING_TAG))?
+                .map(|value| value.into_u16_vec())
+                .transpose()?
+                .unwrap_or(vec![2, 2]);
+            if compression != CompressionMethod::ModernJPEG.to_u16() && subsampling != [1, 1] {
+                unsupported.push(Unsupported::new(
+                    TiffFeature::PhotometricInterpretation,
+                    photometric_interpretation,
+                ));
+            }
+        }
+
+        // Complex samples are decoded as little endian, in which the differences of big-endian
+        // samples are not reversed correctly
+        self.goto_offset(0)?;
+        let big_endian = self.read_byte()? == b'M';
+        let predictor = self.find_tag_unsigned::<u16>(Tag::Predictor)?.unwrap_or(1);
+        let predictor_supported = match Predictor::from_u16(predictor) {
+            Some(Predictor::None) => true,
+            Some(Predictor::Horizontal) => {
+                !is_float
+                    && (8..=64).contains(&bits)
+                    && bits % 8 == 0
+                    && !(is_complex && big_endian)
+            }
+            Some(Predictor::FloatingPoint) => is_float,
+            _ => false,
+        };
+        if !predictor_supported {
+            unsupported.push(Unsupported::new(TiffFeature::Predictor, predictor));
+        }
+
+        let planar_configuration = self
+            .find_tag_unsigned::<u16>(Tag::PlanarConfiguration)?
+            .unwrap_or(1);
+        if PlanarConfiguration::from_u16(planar_configuration).is_none() {
+            unsupported.push(Unsupported::new(
+                TiffFeature::PlanarConfiguration,
+                planar_configuration,
+            ));
+        }
+
+        Ok(unsupported)
+    }
+
+    /// Returns the location of the encoded data of each strip or tile in the file.
+    fn chunk_byte_ranges(&mut self) -> GeoTiffResult<Vec<Range<u64>>> {
+        let (offsets, byte_counts) = match self.find_tag(Tag::TileOffsets)? {
+            Some(offsets) => (offsets, self.get_tag(Tag::TileByteCounts)?),
+            None => (
+                self.get_tag(Tag::StripOffsets)?,
+                self.get_tag(Tag::StripByteCounts)?,
+            ),
+        };
+
+        Ok(offsets
+            .into_u64_vec()?
+            .into_iter()
+            .zip(byte_counts.into_u64_vec()?)
+            .map(|(offset, byte_count)| offset..offset + byte_count)
+            .collect())
+    }
+
+    fn image_chunks(&mut self, limits: &Limits) -> GeoTiffResult<ImageChunks> {
+        Ok(ImageChunks {
+            byte_ranges: self.chunk_byte_ranges()?,
+            band_formats: self.band_formats()?,
+            limits: limits.clone(),
+        })
+    }
+
+    /// Reads the given bytes of the file, failing with [`TiffError::LimitsExceeded`] instead of
+    /// allocating a buffer exceeding the decoding buffer size, e.g. for corrupt byte counts.
+    fn read_bytes(&mut self, byte_range: &Range<u64>, limits: &Limits) -> GeoTiffResult<Vec<u8>> {
+        let len = byte_range.end.saturating_sub(byte_range.start);
+        check_buffer_size(usize::try_from(len).ok(), limits)?;
+        let mut bytes = vec![0; len as usize];
+        self.goto_offset_u64(byte_range.start)?;
+        self.inner().read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Checks that the encoded data of the given chunk lies within the file and, for uncompressed
+    /// images, is large enough to hold the chunk, returning the reason if not.
+    fn verify_chunk_bytes(
+        &mut self,
+        chunk_index: u32,
+        byte_range: Option<&Range<u64>>,
+    ) -> GeoTiffResult<Option<String>> {
+        let Some(byte_range) = byte_range else {
+            return Ok(Some("missing offset or byte count".into()));
+        };
+        if byte_range.is_empty() {
+            return Ok(Some("empty byte count".into()));
+        }
+
+        let compression = self
+            .find_tag_unsigned::<u16>(Tag::Compression)?
+            .unwrap_or(1);
+        if compression == CompressionMethod::None.to_u16() {
+            let bits_per_sample = self
+                .find_tag_unsigned_vec::<u16>(Tag::BitsPerSample)?
+                .unwrap_or_else(|| vec![1]);
+            let chunk_layout = self.chunk_layout()?;
+            let samples_per_pixel = self.num_samples()? / chunk_layout.num_planes;
+            let (data_width, data_height) = self.chunk_data_dimensions(chunk_index);
+            let row_bits = data_width as u64
+                * samples_per_pixel as u64
+                * bits_per_sample.first().copied().unwrap_or(1) as u64;
+            let expected = row_bits.div_ceil(8) * data_height as u64;
+            let actual = byte_range.end - byte_range.start;
+            if actual < expected {
+                return Ok(Some(format!(
+                    "byte count {actual} is smaller than the uncompressed size {expected}"
+                )));
+            }
+        }
+
+        // Reading the last byte fails if the data is truncated
+        match self
+            .goto_offset_u64(byte_range.end - 1)
+            .and_then(|_| self.read_byte())
+        {
+            Ok(_) => Ok(None),
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => Ok(Some(format!(
+                "data at {byte_range:?} exceeds the end of the file"
+            ))),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Decodes a strip or tile, unpacking samples of fewer than 8 bits to a byte each and
+    /// converting YCbCr samples to RGB.
+    ///
+    /// The decoder leaves the samples of YCbCr images, which JPEG-compressed images usually are,
+    /// in the YCbCr color space.
+    fn decode_chunk(
+        &mut self,
+        chunk_index: u32,
+        chunks: &ImageChunks,
+    ) -> GeoTiffResult<RasterData> {
+        let band_format = chunks.band_formats[0];
+        if band_format.is_complex() {
+            return self.decode_complex_chunk(chunk_index, chunks);
+        }
+        let bits_per_sample = band_format.bits_per_sample;
+        let mut chunk = match self.find_tag_unsigned::<u16>(Tag::Compression)? {
+            // The decoder only supports samples of fewer than 8 bits or of whole bytes
+            _ if bits_per_sample > 8 && !bits_per_sample.is_multiple_of(8) => {
+                self.decode_packed_chunk(chunk_index, chunks)?
+            }
+            #[cfg(feature = "lzma")]
+            Some(LZMA_COMPRESSION) => self.decode_lzma_chunk(chunk_index, chunks)?,
+            _ => self.read_chunk(chunk_index)?,
+        };
+        if let (DecodingResult::U8(packed), 1..=7) = (&chunk, bits_per_sample) {
+            let (data_width, _) = self.chunk_data_dimensions(chunk_index);
+            let samples_per_plane = self.num_samples()? / self.chunk_layout()?.num_planes;
+            let row_len = data_width as usize * samples_per_plane;
+            chunk = DecodingResult::U8(
+                unpack_samples(packed, bits_per_sample as usize, row_len)
+                    .map(|sample| sample as u8)
+                    .collect(),
+            );
+        }
+        let photometric_interpretation =
+            self.find_tag_unsigned::<u16>(Tag::PhotometricInterpretation)?;
+        if photometric_interpretation != Some(PhotometricInterpretation::YCbCr.to_u16()) {
+            return Ok(chunk.into());
+        }
+
+        let coefficients = match self.find_tag(Tag::Unknown(YCBCR_COEFFICIENTS_TAG))? {
+            Some(value) => value.into_f64_vec()?,
+            None => vec![0.299, 0.587, 0.114],
+        };
+        let reference = match self.find_tag(Tag::Unknown(REFERENCE_BLACK_WHITE_TAG))? {
+            Some(value) => value.into_f64_vec()?,
+            None => vec![0.0, 255.0, 128.0, 255.0, 128.0, 255.0],
+        };
+        let (
+            DecodingResult::U8(data),
+            [luma_red, luma_green, luma_blue],
+            [y_black, y_white, cb_black, cb_white, cr_black, cr_white],
+        ) = (&mut chunk, &coefficients[..], &reference[..])
+        else {
+            return Err(GeoTiffError::Unsupported(Unsupported::new(
+                TiffFeature::PhotometricInterpretation,
+                PhotometricInterpretation::YCbCr.to_u16(),
+            )));
+        };
+
+        for pixel in data.chunks_exact_mut(3) {
+            let y = (pixel[0] as f64 - y_black) * 255.0 / (y_white - y_black);
+            let cb = (pixel[1] as f64 - cb_black) * 127.0 / (cb_white - cb_black);
+            let cr = (pixel[2] as f64 - cr_black) * 127.0 / (cr_white - cr_black);
+            let red = cr * (2.0 - 2.0 * luma_red) + y;
+            let blue = cb * (2.0 - 2.0 * luma_blue) + y;
+            let green = (y - luma_blue * blue - luma_red * red) / luma_green;
+            for (sample, value) in pixel.iter_mut().zip([red, green, blue]) {
+                *sample = value.round().clamp(0.0, 255.0) as u8;
+            }
+        }
+
+        Ok(chunk.into())
+    }
+
+    /// Decodes a strip or tile of an LZMA-compressed image, which the decoder does not support,
+    /// by decompressing its data and decoding that on its own.
+    #[cfg(feature = "lzma")]
+    fn decode_lzma_chunk(
+        &mut self,
+        chunk_index: u32,
+        chunks: &ImageChunks,
+    ) -> GeoTiffResult<DecodingResult> {
+        let (mut decompressed, _) = self.read_standalone_chunk(chunk_index, chunks)?;
+        let samples_per_plane = self.num_samples()? / self.chunk_layout()?.num_planes;
+        let band_format = chunks.band_formats[0];
+        let bits_per_sample = band_format.bits_per_sample as usize;
+
+        // The chunk is decoded as little endian, so big-endian samples are swapped unless the
+        // floating point predictor arranged their bytes independently of the byte order
+        let predictor = self
+            .find_tag_unsigned::<u16>(Tag::Predictor)?
+            .unwrap_or(Predictor::None.to_u16());
+        self.goto_offset(0)?;
+        if self.read_byte()? == b'M'
+            && bits_per_sample > 8
+            && predictor != Predictor::FloatingPoint.to_u16()
+        {
+            for sample in decompressed.chunks_exact_mut(bits_per_sample / 8) {
+                sample.reverse();
+            }
+        }
+
+        let photometric_interpretation = match self
+            .find_tag_unsigned::<u16>(Tag::PhotometricInterpretation)?
+            .and_then(PhotometricInterpretation::from_u16)
+        {
+            Some(PhotometricInterpretation::WhiteIsZero) => PhotometricInterpretation::WhiteIsZero,
+            _ => PhotometricInterpretation::BlackIsZero,
+        };
+        let encoding = ChunkEncoding {
+            bits_per_sample: vec![band_format.bits_per_sample; samples_per_plane],
+            sample_formats: vec![band_format.sample_format.to_u16(); samples_per_plane],
+            compression: CompressionMethod::None.to_u16(),
+            predictor,
+            photometric_interpretation,
+        };
+        let tile_size = (self.get_chunk_type() == ChunkType::Tile).then(|| self.chunk_dimensions());
+        decode_standalone_chunk(
+            decompressed,
+            self.chunk_data_dimensions(chunk_index),
+            tile_size,
+            &encoding,
+        )
+    }
+
+    fn decode_packed_chunk(
+        &mut self,
+        chunk_index: u32,
+        chunks: &ImageChunks,
+    ) -> GeoTiffResult<DecodingResult> {
+        let (encoded, compression) = self.read_standalone_chunk(chunk_index, chunks)?;
+
+        // Tiles are padded to their full size, while the last strip only holds the remaining rows
+        let (width, height) = match self.get_chunk_type() {
+            ChunkType::Tile => self.chunk_dimensions(),
+            ChunkType::Strip => self.chunk_data_dimensions(chunk_index),
+        };
+        let samples_per_plane = self.num_samples()? / self.chunk_layout()?.num_planes;
+        let bits_per_sample = chunks.band_formats[0].bits_per_sample as usize;
+        let row_len = width as usize * samples_per_plane;
+        let row_size = (row_len * bits_per_sample).div_ceil(8);
+
+        // The packed rows are decoded as rows of bytes, which are then unpacked
+        let encoding = ChunkEncoding {
+            bits_per_sample: vec![8],
+            sample_formats: vec![SampleFormat::Uint.to_u16()],
+            compression,
+            predictor: Predictor::None.to_u16(),
+            photometric_interpretation: PhotometricInterpretation::BlackIsZero,
+        };
+        let DecodingResult::U8(packed) =
+            decode_standalone_chunk(encoded, (row_size as u32, height), None, &encoding)?
+        else {
+            unreachable!("bytes are decoded to U8");
+        };
+        let (data_width, data_height) = self.chunk_data_dimensions(chunk_index);
+        let data_row_len = data_width as usize * samples_per_plane;
+        let samples = unpack_samples(&packed, bits_per_sample, row_len)
+            .enumerate()
+            .filter(|(i, _)| i % row_len < data_row_len && i / row_len < data_height as usize)
+            .map(|(_, sample)| sample as u16)
+            .collect();
+        Ok(DecodingResult::U16(samples))
+    }
+
+    fn decode_complex_chunk(
+        &mut self,
+        chunk_index: u32,
+        chunks: &ImageChunks,
+    ) -> GeoTiffResult<RasterData> {
+        let (encoded, compression) = self.read_standalone_chunk(chunk_index, chunks)?;
+        let samples_per_plane = self.num_samples()? / self.chunk_layout()?.num_planes;
+        let band_format = chunks.band_formats[0];
+
+        // Each sample is decoded as an unsigned integer of its size, or as two of 64 bits if it is
+        // larger, whose bytes are split into the real and imaginary part
+        let (bits_per_sample, num_parts) = match band_format.bits_per_sample {
+            128 => (64, 2),
+            bits_per_sample => (bits_per_sample, 1),
+        };
+        let encoding = ChunkEncoding {
+            bits_per_sample: vec![bits_per_sample; samples_per_plane * num_parts],
+            sample_formats: vec![SampleFormat::Uint.to_u16(); samples_per_plane * num_parts],
+            compression,
+            predictor: self
+                .find_tag_unsigned::<u16>(Tag::Predictor)?
+                .unwrap_or(Predictor::None.to_u16()),
+            photometric_interpretation: PhotometricInterpretation::BlackIsZero,
+        };
+        let tile_size = (self.get_chunk_type() == ChunkType::Tile).then(|| self.chunk_dimensions());
+        let data_dimensions = self.chunk_data_dimensions(chunk_index);
+        let bytes: Vec<u8> =
+            match decode_standalone_chunk(encoded, data_dimensions, tile_size, &encoding)? {
+                DecodingResult::U32(values) => {
+                    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+                }
+                DecodingResult::U64(values) => {
+                    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+                }
+                _ => unreachable!("complex samples are decoded as 32-bit or 64-bit integers"),
+            };
+
+        // The samples were decoded as little endian, so the bytes are in the order of the file
+        self.goto_offset(0)?;
+        let big_endian = self.read_byte()? == b'M';
+        macro_rules! complex_values {
+            ($part: ty) => {{
+                const SIZE: usize = size_of::<$part>();
+                let part = |bytes: &[u8]| {
+                    let bytes = bytes.try_into().expect("a part has the size of its type");
+                    match big_endian {
+                        true => <$part>::from_be_bytes(bytes),
+                        false => <$part>::from_le_bytes(bytes),
+                    }
+                };
+                bytes
+                    .chunks_exact(2 * SIZE)
+                    .map(|value| Complex::new(part(&value[..SIZE]), part(&value[SIZE..])))
+                    .collect()
+            }};
+        }
+
+        match band_format.data_type() {
+            Some(RasterDataType::CI16) => Ok(RasterData::CI16(complex_values!(i16))),
+            Some(RasterDataType::CI32) => Ok(RasterData::CI32(complex_values!(i32))),
+            Some(RasterDataType::CF32) => Ok(RasterData::CF32(complex_values!(f32))),
+            Some(RasterDataType::CF64) => Ok(RasterData::CF64(complex_values!(f64))),
+            _ => Err(TiffError::UnsupportedError(TiffUnsupportedError::UnsupportedDataType).into()),
+        }
+    }
+
+    fn read_encoded_chunk(
+        &mut self,
+        chunk_index: u32,
+        chunks: &ImageChunks,
+    ) -> GeoTiffResult<Vec<u8>> {
+        let Some(byte_range) = chunks.byte_ranges.get(chunk_index as usize) else {
+            return Err(GeoTiffError::Format(format!(
+                "Missing offset or byte count of chunk {chunk_index}"
+            )));
+        };
+        self.read_bytes(byte_range, &chunks.limits)
+    }
+
+    /// Reads the encoded data of a strip or tile to decode it on its own, together with its
+    /// compression. LZMA-compressed data, which the decoder does not support, is decompressed
+    /// instead.
+    fn read_standalone_chunk(
+        &mut self,
+        chunk_index: u32,
+        chunks: &ImageChunks,
+    ) -> GeoTiffResult<(Vec<u8>, u16)> {
+        let encoded = self.read_encoded_chunk(chunk_index, chunks)?;
+        let compression = self
+            .find_tag_unsigned::<u16>(Tag::Compression)?
+            .unwrap_or(CompressionMethod::None.to_u16());
+
+        #[cfg(feature = "lzma")]
+        if compression == LZMA_COMPRESSION {
+            // Tiles are padded to their full size, while the last strip only holds the remaining
+            // rows
+            let (width, height) = match self.get_chunk_type() {
+                ChunkType::Tile => self.chunk_dimensions(),
+                ChunkType::Strip => self.chunk_data_dimensions(chunk_index),
+            };
+            let samples_per_plane = self.num_samples()? / self.chunk_layout()?.num_planes;
+            let bits_per_sample = chunks.band_formats[0].bits_per_sample as usize;
+            let row_size = (width as usize * samples_per_plane * bits_per_sample).div_ceil(8);
+            let mut decompressed = Vec::with_capacity(row_size * height as usize);
+            XzDecoder::new(&encoded[..])
+                .take((row_size * height as usize) as u64)
+                .read_to_end(&mut decompressed)?;
+            return Ok((decompressed, CompressionMethod::None.to_u16()));
+        }
+        Ok((encoded, compression))
+    }
+
+    /// Decodes the given chunk after verifying its encoded data, returning the reason as a
+    /// [`CorruptChunk`] if either fails.
+    fn read_verified_chunk(
+        &mut self,
+        chunk_index: u32,
+        chunks: &ImageChunks,
+    ) -> GeoTiffResult<Result<RasterData, CorruptChunk>> {
+        let byte_range = chunks.byte_ranges.get(chunk_index as usize);
+        let reason = match self.verify_chunk_bytes(chunk_index, byte_range)? {
+            Some(reason) => reason,
+            None => {
+                trace_span!("decode_chunk", chunk_index);
+                match self.decode_chunk(chunk_index, chunks) {
+                    Ok(chunk) => return Ok(Ok(chunk)),
+                    Err(
+                        error @ GeoTiffError::Tiff(
+                            TiffError::IoError(_) | TiffError::FormatError(_),
+                        ),
+                    ) => error.to_string(),
+                    Err(error) => return Err(error),
+                }
+            }
+        };
+
+        let chunk_layout = self.chunk_layout()?;
+        let chunk_in_plane =
+            chunk_index as usize % (chunk_layout.chunks_across * chunk_layout.chunks_down);
+        let (data_width, data_height) = self.chunk_data_dimensions(chunk_index);
+        Ok(Err(CorruptChunk {
+            index: chunk_index,
+            window: Window::new(
+                chunk_in_plane % chunk_layout.chunks_across * chunk_layout.chunk_width,
+                chunk_in_plane / chunk_layout.chunks_across * chunk_layout.chunk_height,
+                data_width as usize,
+                data_height as usize,
+            ),
+            reason,
+        }))
+    }
+
+    /// Reads the given samples of the given window, arranging the samples and pixels in memory as
+    /// requested regardless of the planar configuration of the image.
+    ///
+    /// The samples are kept in the given order. Of planar images, only the planes holding them
+    /// are decoded.
+    ///
+    /// If `verify_chunks` is set, the encoded data of each chunk is checked before decoding it,
+    /// and chunks which are invalid or fail to decode are filled with the no data value (or left
+    /// zeroed) and reported instead of failing the read.
+    fn read_window(
+        &mut self,
+        window: &Window,
+        interleave: Interleave,
+        order: ArrayOrder,
+        samples: &[usize],
+        verify_chunks: bool,
+        chunks: &ImageChunks,
+    ) -> GeoTiffResult<(RasterData, Vec<CorruptChunk>)> {
+        let (raster_width, raster_height) = self.dimensions()?;
+        if window.is_empty()
+            || window.x + window.width > raster_width as usize
+            || window.y + window.height > raster_height as usize
+        {
+            return Err(GeoTiffError::Format(format!(
+                "Window {window:?} is empty or exceeds the raster dimensions {raster_width}x{raster_height}"
+            )));
+        }
+
+        let num_samples = self.num_samples()?;
+        if let Some(&sample) = samples.iter().find(|&&sample| sample >= num_samples) {
+            return Err(GeoTiffError::SampleOutOfBounds {
+                sample,
+                num_samples,
+            });
+        }
+        let chunk_layout = self.chunk_layout()?;
+        let ChunkLayout {
+            chunk_width,
+            chunk_height,
+            num_planes,
+            ..
+        } = chunk_layout;
+
+        let layout = BufferLayout {
+            width: window.width,
+            height: window.height,
+            num_samples: samples.len(),
+            interleave,
+            order,
+        };
+        // The decoder uses the format of the first band for all of them
+        let band_format = chunks.band_formats[0];
+        let data_type = band_format.data_type().ok_or(TiffError::UnsupportedError(
+            TiffUnsupportedError::UnsupportedDataType,
+        ))?;
+        let mut buffer = WindowBuffer {
+            data: RasterData::zeroed(data_type, window.width * window.height * samples.len()),
+            layout,
+            window: *window,
+            chunk_layout,
+            band_format,
+            samples_per_chunk: num_samples / num_planes,
+            samples: samples.to_vec(),
+        };
+
+        let mut chunk_indices = Vec::new();
+        for chunk_y in window.y / chunk_height..=(window.y + window.height - 1) / chunk_height {
+            for chunk_x in window.x / chunk_width..=(window.x + window.width - 1) / chunk_width {
+                for plane in (0..num_planes).filter(|&plane| buffer.reads_plane(plane)) {
+                    chunk_indices.push(chunk_layout.chunk_index(chunk_x, chunk_y, plane));
+                }
+            }
+        }
+
+        // Tiles are read in batches, whose encoded data is decoded concurrently
+        #[cfg(feature = "rayon")]
+        if let Some(encoding) = self.tile_encoding(chunks)?.filter(|_| !verify_chunks) {
+            let tile_dimensions = self.chunk_dimensions();
+            let batch_size = TILES_PER_THREAD * rayon::current_num_threads();
+            for batch in chunk_indices.chunks(batch_size) {
+                // Tiles without encoded data are left to the decoder
+                let mut encoded_tiles = Vec::with_capacity(batch.len());
+                for &chunk_index in batch {
+                    encoded_tiles.push(match chunks.byte_ranges.get(chunk_index as usize) {
+                        Some(byte_range) if !byte_range.is_empty() => {
+                            Some(self.read_bytes(byte_range, &chunks.limits)?)
+                        }
+                        _ => None,
+                    });
+                }
+
+                let decoded_tiles = batch
+                    .par_iter()
+                    .zip(encoded_tiles)
+                    .map(|(&chunk_index, encoded)| {
+                        trace_span!("decode_chunk", chunk_index);
+                        encoded
+                            .map(|encoded| {
+                                decode_standalone_chunk(encoded, tile_dimensions, None, &encoding)
+                                    .map(RasterData::from)
+                            })
+                            .transpose()
+                    })
+                    .collect::<GeoTiffResult<Vec<_>>>()?;
+
+                for (&chunk_index, decoded) in batch.iter().zip(decoded_tiles) {
+                    // Tiles decoded on their own are padded to the full tile size
+                    let (chunk, dimensions) = match decoded {
+                        Some(chunk) => (chunk, tile_dimensions),
+                        None => (
+                            self.decode_chunk(chunk_index, chunks)?,
+                            self.chunk_data_dimensions(chunk_index),
+                        ),
+                    };
+                    buffer.insert(chunk_index, dimensions, chunk)?;
+                }
+            }
+
+            return Ok((buffer.data, Vec::new()));
+        }
+
+        let no_data = match verify_chunks {
+            true => self.no_data()?,
+            false => None,
+        };
+        let mut corrupt_chunks = Vec::new();
+
+        for chunk_index in chunk_indices {
+            let chunk = if verify_chunks {
+                match self.read_verified_chunk(chunk_index, chunks)? {
+                    Ok(chunk) => chunk,
+                    Err(corrupt_chunk) => {
+                        corrupt_chunks.push(corrupt_chunk);
+                        if let Some(no_data) = no_data {
+                            buffer.fill(chunk_index, no_data);
+                        }
+                        continue;
+                    }
+                }
+            } else {
+                trace_span!("decode_chunk", chunk_index);
+                self.decode_chunk(chunk_index, chunks)?
+            };
+            buffer.insert(chunk_index, self.chunk_data_dimensions(chunk_index), chunk)?;
+        }
+
+        Ok((buffer.data, corrupt_chunks))
+    }
+}
+
+/// The buffer a window is read into, see [`DecoderExt::read_window`].
+struct WindowBuffer {
+    data: RasterData,
+    layout: BufferLayout,
+    window: Window,
+    chunk_layout: ChunkLayout,
+    band_format: BandFormat,
+    /// The number of samples per pixel in each chunk.
+    samples_per_chunk: usize,
+    /// The samples of the image to keep, in the order they are arranged in the buffer.
+    samples: Vec<usize>,
+}
+
+impl WindowBuffer {
+    /// Returns whether any of the samples to keep lie in the given plane.
+    fn reads_plane(&self, plane: usize) -> bool {
+        !self.sample_pairs(plane).is_empty()
+    }
+
+    /// Returns the pairs of samples in the chunks of the given plane and samples in the buffer
+    /// they are copied to.
+    fn sample_pairs(&self, plane: usize) -> Vec<(usize, usize)> {
+        self.samples
+            .iter()
+            .enumerate()
+            .filter_map(|(dst_sample, &sample)| {
+                let (sample_plane, src_sample) = (
+                    sample / self.samples_per_chunk,
+                    sample % self.samples_per_chunk,
+                );
+                (sample_plane == plane).then_some((src_sample, dst_sample))
+            })
+            .collect()
+    }
+
+    /// Returns the intersection of the window and the given chunk in raster space, together with
+    /// the origin of the chunk and its plane.
+    fn intersection(&self, chunk_index: u32) -> (Window, (usize, usize), usize) {
+        let ChunkLayout {
+            chunk_width,
+            chunk_height,
+            chunks_across,
+            chunks_down,
+            ..
+        } = self.chunk_layout;
+        let chunks_per_plane = chunks_across * chunks_down;
+        let chunk_in_plane = chunk_index as usize % chunks_per_plane;
+        let chunk_x = chunk_in_plane % chunks_across * chunk_width;
+        let chunk_y = chunk_in_plane / chunks_across * chunk_height;
+
+        let window = &self.window;
+        let x = window.x.max(chunk_x);
+        let y = window.y.max(chunk_y);
+        let x_end = (window.x + window.width).min(chunk_x + chunk_width);
+        let y_end = (window.y + window.height).min(chunk_y + chunk_height);
+        (
+            Window::new(x, y, x_end - x, y_end - y),
+            (chunk_x, chunk_y),
+            chunk_index as usize / chunks_per_plane,
+        )
+    }
+
+    /// Copies the part of the given decoded chunk within the window, where the chunk holds rows
+    /// of the given dimensions.
+    fn insert(
+        &mut self,
+        chunk_index: u32,
+        (width, height): (u32, u32),
+        chunk: RasterData,
+    ) -> GeoTiffResult<()> {
+        let data_type = self.data.data_type();
+        let chunk = chunk.reinterpret(data_type);
+        if chunk.data_type() != data_type {
+            return Err(GeoTiffError::Format(format!(
+                "Decoded {:?} values, but the format of the samples is {:?}",
+                chunk.data_type(),
+                self.band_format
+            )));
+        }
+
+        let (intersection, (chunk_x, chunk_y), plane) = self.intersection(chunk_index);
+        self.data.copy_block(
+            self.layout,
+            (
+                intersection.x - self.window.x,
+                intersection.y - self.window.y,
+            ),
+            &chunk,
+            BufferLayout {
+                width: width as usize,
+                height: height as usize,
+                num_samples: self.samples_per_chunk,
+                interleave: Interleave::Pixel,
+                order: ArrayOrder::RowMajor,
+            },
+            &Window::new(
+                intersection.x - chunk_x,
+                intersection.y - chunk_y,
+                intersection.width,
+                intersection.height,
+            ),
+            &self.sample_pairs(plane),
+        )
+    }
+
+    /// Fills the part of the given chunk within the window with the given value.
+    fn fill(&mut self, chunk_index: u32, value: f64) {
+        let (intersection, _, plane) = self.intersection(chunk_index);
+        let samples: Vec<_> = self
+            .sample_pairs(plane)
+            .into_iter()
+            .map(|(_, dst_sample)| dst_sample)
+            .collect();
+        self.data.fill_block(
+            self.layout,
+            &Window::new(
+                intersection.x - self.window.x,
+                intersection.y - self.window.y,
+                intersection.width,
+                intersection.height,
+            ),
+            &samples,
+            value,
+        );
+    }
+}
+
+/// Decodes the encoded data of a strip or tile of the given dimensions on its own, by wrapping it
+/// in a single-strip TIFF with the given encoding and decoding that instead.
+///
+/// If a tile size is given, the data is wrapped in a single-tile TIFF instead, so a padded tile
+/// is decoded to the given dimensions.
+fn decode_standalone_chunk(
+    encoded: Vec<u8>,
+    (width, height): (u32, u32),
+    tile_size: Option<(u32, u32)>,
+    encoding: &ChunkEncoding,
+) -> GeoTiffResult<DecodingResult> {
+    let mut tiff = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut tiff)?;
+    let mut directory = encoder.image_directory()?;
+    let offset = directory.write_data(&encoded[..])?;
+    directory.write_tag(Tag::ImageWidth, width)?;
+    directory.write_tag(Tag::ImageLength, height)?;
+    directory.write_tag(Tag::BitsPerSample, &encoding.bits_per_sample[..])?;
+    directory.write_tag(Tag::SampleFormat, &encoding.sample_formats[..])?;
+    directory.write_tag(Tag::SamplesPerPixel, encoding.bits_per_sample.len() as u16)?;
+    directory.write_tag(Tag::Compression, encoding.compression)?;
+    directory.write_tag(Tag::Predictor, encoding.predictor)?;
+    directory.write_tag(
+        Tag::PhotometricInterpretation,
+        encoding.photometric_interpretation.to_u16(),
+    )?;
+    match tile_size {
+        Some((tile_width, tile_height)) => {
+            directory.write_tag(Tag::TileWidth, tile_width)?;
+            directory.write_tag(Tag::TileLength, tile_height)?;
+            directory.write_tag(Tag::TileOffsets, offset as u32)?;
+            directory.write_tag(Tag::TileByteCounts, encoded.len() as u32)?;
+        }
+        None => {
+            directory.write_tag(Tag::StripOffsets, offset as u32)?;
+            directory.write_tag(Tag::RowsPerStrip, height)?;
+            directory.write_tag(Tag::StripByteCounts, encoded.len() as u32)?;
+        }
+    }
+    directory.finish()?;
+
+    tiff.set_position(0);
+    Ok(Decoder::new(tiff)?.read_chunk(0)?)
+}
+
+/// Unpacks samples of fewer than 16 bits, given the number of samples per row, each of which
+/// starts at a byte boundary.
+fn unpack_samples(
+    packed: &[u8],
+    bits_per_sample: usize,
+    row_len: usize,
+) -> impl Iterator<Item = u32> + '_ {
+    let row_size = (row_len * bits_per_sample).div_ceil(8);
+    let mask = (1 << bits_per_sample) - 1;
+    packed.chunks_exact(row_size).flat_map(move |row| {
+        (0..row_len).map(move |i| {
+            // Samples may span up to three bytes unless the number of bits is a power of two
+            let bit = i * bits_per_sample;
+            let byte = |offset| row.get(bit / 8 + offset).copied().unwrap_or(0);
+            let bytes = u32::from_be_bytes([byte(0), byte(1), byte(2), 0]);
+            (bytes >> (32 - bits_per_sample - bit % 8)) & mask
+        })
+    })
+}
+
+/// Decodes the encoded data of a mask chunk, see [`decode_standalone_chunk`].
+///
+/// The decoder rejects images with the transparency mask photometric interpretation that GDAL
+/// writes, so the data is decoded on its own as grayscale instead.
+fn decode_mask_chunk(
+    encoded: Vec<u8>,
+    dimensions: (u32, u32),
+    tile_size: Option<(u32, u32)>,
+    bits_per_sample: u16,
+    compression: u16,
+    predictor: u16,
+) -> GeoTiffResult<Vec<u8>> {
+    let encoding = ChunkEncoding {
+        bits_per_sample: vec![bits_per_sample],
+        sample_formats: vec![SampleFormat::Uint.to_u16()],
+        compression,
+        predictor,
+        photometric_interpretation: PhotometricInterpretation::BlackIsZero,
+    };
+    match decode_standalone_chunk(encoded, dimensions, tile_size, &encoding)? {
+        DecodingResult::U8(data) => Ok(data),
+        _ => Err(GeoTiffError::Format(
+            "Mask images must hold unsigned integers".into(),
+        )),
+    }
+}
+
+/// Fails with [`TiffError::LimitsExceeded`] if a buffer of the given size in bytes, or `None` if
+/// the size overflows, exceeds the decoding buffer size of the given limits.
+pub(super) fn check_buffer_size(size: Option<usize>, limits: &Limits) -> GeoTiffResult<()> {
+    if size.is_none_or(|size| size > limits.decoding_buffer_size) {
+        return Err(TiffError::LimitsExceeded.into());
+    }
+
+    Ok(())
 }
@@ -0,0 +1,68 @@
+use geo_types::Coord;
+
+use crate::epsg::WGS_84;
+use crate::error::{GeoTiffError, GeoTiffResult};
+use crate::interpolation::Interpolation;
+use crate::GeoTiff;
+
+impl GeoTiff {
+    /// Returns the elevation in metres at the given longitude and latitude in degrees on WGS 84,
+    /// or `None` if the location lies outside the DEM or its elevation is unknown.
+    ///
+    /// The location is reprojected into the coordinate reference system of the model space and
+    /// the elevation is interpolated bilinearly from the first sample, see [`Self::sample`].
+    /// Elevations stored with a GDAL scale and offset are unpacked, and converted from the
+    /// vertical unit given by the GeoKeys or else by the GDAL unit type, defaulting to metres.
+    ///
+    /// Fails with [`GeoTiffError::Crs`] if the vertical unit is not a known linear unit.
+    pub fn elevation_at(&self, lon: f64, lat: f64) -> GeoTiffResult<Option<f64>> {
+        let factor = self.vertical_unit_factor()?;
+        let coord = self.to_model_crs(&Coord { x: lon, y: lat }, WGS_84)?;
+        let Some(value) = self.sample(&coord, 0, Interpolation::Bilinear)? else {
+            return Ok(None);
+        };
+
+        let (scale, offset) = match &self.gdal_metadata {
+            Some(gdal_metadata) => (
+                gdal_metadata.scale(0).unwrap_or(1.0),
+                gdal_metadata.offset(0).unwrap_or(0.0),
+            ),
+            None => (1.0, 0.0),
+        };
+
+        Ok(Some((value * scale + offset) * factor))
+    }
+
+    /// Returns the size of the vertical unit in metres.
+    fn vertical_unit_factor(&self) -> GeoTiffResult<f64> {
+        let directory = &self.geo_key_directory;
+        if directory.vertical_units.is_some() {
+            return directory
+                .linear_unit(directory.vertical_units, None)
+                .map(|unit| unit.factor)
+                .ok_or_else(|| {
+                    GeoTiffError::Crs(format!(
+                        "The vertical unit {:?} is not a known linear unit.",
+                        directory.vertical_units
+                    ))
+                });
+        }
+
+        let unit_type = self
+            .gdal_metadata
+            .as_ref()
+            .and_then(|gdal_metadata| gdal_metadata.unit_type(0))
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase();
+        match unit_type.as_str() {
+            "" | "m" | "metre" | "meter" | "metres" | "meters" => Ok(1.0),
+            "ft" | "foot" | "feet" => Ok(0.3048),
+            "us-ft" | "ftus" | "us survey foot" => Ok(1200.0 / 3937.0),
+            "km" | "kilometre" | "kilometer" => Ok(1000.0),
+            _ => Err(GeoTiffError::Crs(format!(
+                "The vertical unit {unit_type:?} is not a known linear unit."
+            ))),
+        }
+    }
+}
@@ -0,0 +1,368 @@
+#[cfg(feature = "epsg")]
+use crate::geo_key_directory::CoordinateTransformationMethod;
+
+/// A coordinate reference system of the EPSG registry.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct EpsgCrs {
+    pub code: u16,
+    pub name: String,
+    pub kind: CrsKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum CrsKind {
+    /// A geographic CRS with coordinates in degrees.
+    Geographic,
+    /// A projected CRS with coordinates in metres, based on the given geographic CRS.
+    Projected { geographic: u16 },
+}
+
+/// EPSG code of the unit degree.
+pub(crate) const DEGREE: u16 = 9102;
+/// EPSG code of the unit metre.
+pub(crate) const METRE: u16 = 9001;
+/// EPSG code of WGS 84 with coordinates in degrees.
+pub(crate) const WGS_84: u16 = 4326;
+/// EPSG code of the web mercator projection of slippy maps.
+pub(crate) const WEB_MERCATOR: u16 = 3857;
+
+/// Geographic CRSs of the subset of the EPSG registry known to this crate, which covers coordinate
+/// reference systems commonly used for GeoTIFFs.
+const GEOGRAPHIC: &[(u16, &str)] = &[
+    (4149, "CH1903"),
+    (4150, "CH1903+"),
+    (4171, "RGF93 v1"),
+    (4258, "ETRS89"),
+    (4267, "NAD27"),
+    (4269, "NAD83"),
+    (4277, "OSGB36"),
+    (4283, "GDA94"),
+    (4312, "MGI"),
+    (4326, "WGS 84"),
+];
+
+/// Projected CRSs of the subset, together with their geographic CRS.
+const PROJECTED: &[(u16, &str, u16)] = &[
+    (2056, "CH1903+ / LV95", 4150),
+    (2154, "RGF93 v1 / Lambert-93", 4171),
+    (3035, "ETRS89-extended / LAEA Europe", 4258),
+    (3395, "WGS 84 / World Mercator", 4326),
+    (3857, "WGS 84 / Pseudo-Mercator", 4326),
+    (21781, "CH1903 / LV03", 4149),
+    (27700, "OSGB36 / British National Grid", 4277),
+    (31287, "MGI / Austria Lambert", 4312),
+];
+
+/// Ranges of codes of UTM zones, given by the first code and zone, the last zone, the hemisphere
+/// and the geographic CRS.
+const UTM_ZONES: &[(u16, u16, u16, char, u16)] = &[
+    (25828, 28, 38, 'N', 4258),
+    (26901, 1, 23, 'N', 4269),
+    (32601, 1, 60, 'N', 4326),
+    (32701, 1, 60, 'S', 4326),
+];
+
+/// Returns the coordinate reference system with the given EPSG code, or `None` if it is not part
+/// of the subset known to this crate.
+pub(crate) fn lookup(code: u16) -> Option<EpsgCrs> {
+    if let Some((_, name)) = GEOGRAPHIC.iter().find(|(c, _)| *c == code) {
+        return Some(EpsgCrs {
+            code,
+            name: name.to_string(),
+            kind: CrsKind::Geographic,
+        });
+    }
+
+    if let Some((_, name, geographic)) = PROJECTED.iter().find(|(c, ..)| *c == code) {
+        return Some(EpsgCrs {
+            code,
+            name: name.to_string(),
+            kind: CrsKind::Projected {
+                geographic: *geographic,
+            },
+        });
+    }
+
+    UTM_ZONES
+        .iter()
+        .find(|(first_code, first_zone, last_zone, ..)| {
+            (*first_code..=first_code + (last_zone - first_zone)).contains(&code)
+        })
+        .map(
+            |(first_code, first_zone, _, hemisphere, geographic)| EpsgCrs {
+                code,
+                name: format!(
+                    "{} / UTM zone {}{hemisphere}",
+                    geographic_name(*geographic),
+                    code - first_code + first_zone
+                ),
+                kind: CrsKind::Projected {
+                    geographic: *geographic,
+                },
+            },
+        )
+}
+
+/// Returns the name of the given geographic CRS of the table.
+pub(crate) fn geographic_name(code: u16) -> &'static str {
+    GEOGRAPHIC
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, name)| *name)
+        .unwrap_or_default()
+}
+
+/// EPSG code of the Greenwich prime meridian.
+#[cfg(feature = "epsg")]
+pub(crate) const GREENWICH: u16 = 8901;
+
+/// The datum and ellipsoid of a geographic CRS, with the ellipsoid given by its EPSG code, semi-major
+/// axis in metres and inverse flattening.
+#[cfg(feature = "epsg")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct GeodeticParameters {
+    pub datum: u16,
+    pub ellipsoid: u16,
+    pub semi_major_axis: f64,
+    pub inv_flattening: f64,
+}
+
+/// Datums and ellipsoids of the geographic CRSs of the subset.
+#[cfg(feature = "epsg")]
+const GEODETIC_PARAMETERS: &[(u16, u16, u16)] = &[
+    (4149, 6149, 7004),
+    (4150, 6150, 7004),
+    (4171, 6171, 7019),
+    (4258, 6258, 7019),
+    (4267, 6267, 7008),
+    (4269, 6269, 7019),
+    (4277, 6277, 7001),
+    (4283, 6283, 7019),
+    (4312, 6312, 7004),
+    (4326, 6326, 7030),
+];
+
+/// Datums of the geographic CRSs of the subset.
+const DATUMS: &[(u16, &str)] = &[
+    (6149, "CH1903"),
+    (6150, "CH1903+"),
+    (6171, "Reseau Geodesique Francais 1993 v1"),
+    (6258, "European Terrestrial Reference System 1989"),
+    (6267, "North American Datum 1927"),
+    (6269, "North American Datum 1983"),
+    (6277, "Ordnance Survey of Great Britain 1936"),
+    (6283, "Geocentric Datum of Australia 1994"),
+    (6312, "Militar-Geographische Institut"),
+    (6326, "World Geodetic System 1984"),
+];
+
+/// Ellipsoids used by the datums, given by their name, semi-major axis and inverse flattening.
+const ELLIPSOIDS: &[(u16, &str, f64, f64)] = &[
+    (7001, "Airy 1830", 6377563.396, 299.3249646),
+    (7004, "Bessel 1841", 6377397.155, 299.1528128),
+    (7008, "Clarke 1866", 6378206.4, 294.978_698_213_898),
+    (7019, "GRS 1980", 6378137.0, 298.257222101),
+    (7030, "WGS 84", 6378137.0, 298.257223563),
+];
+
+/// The map projection of a projected CRS, given by the EPSG code of the conversion and its method.
+#[cfg(feature = "epsg")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Projection {
+    pub conversion: u16,
+    pub method: ProjectionMethod,
+}
+
+/// The method of a map projection with its parameters, angles given in degrees and distances in
+/// metres.
+#[cfg(feature = "epsg")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ProjectionMethod {
+    TransverseMercator(NaturalOrigin),
+    Mercator(NaturalOrigin),
+    LambertConicConformal2Sp {
+        std_parallel1: f64,
+        std_parallel2: f64,
+        false_origin_lat: f64,
+        false_origin_long: f64,
+        false_origin_easting: f64,
+        false_origin_northing: f64,
+    },
+    LambertAzimuthalEqualArea {
+        center_lat: f64,
+        center_long: f64,
+        false_easting: f64,
+        false_northing: f64,
+    },
+    ObliqueMercator {
+        center_lat: f64,
+        center_long: f64,
+        azimuth: f64,
+        scale_at_center: f64,
+        false_easting: f64,
+        false_northing: f64,
+    },
+    /// The spherical Mercator projection of Web Mercator, which has no GeoTIFF coordinate
+    /// transformation code.
+    PseudoMercator,
+}
+
+#[cfg(feature = "epsg")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct NaturalOrigin {
+    pub lat: f64,
+    pub long: f64,
+    pub scale: f64,
+    pub false_easting: f64,
+    pub false_northing: f64,
+}
+
+#[cfg(feature = "epsg")]
+impl ProjectionMethod {
+    /// Returns the method given by the ProjCoordTransGeoKey for this method.
+    pub fn coordinate_transformation_method(&self) -> Option<CoordinateTransformationMethod> {
+        match self {
+            Self::TransverseMercator(_) => Some(CoordinateTransformationMethod::TransverseMercator),
+            Self::ObliqueMercator { .. } => Some(CoordinateTransformationMethod::ObliqueMercator),
+            Self::Mercator(_) => Some(CoordinateTransformationMethod::Mercator),
+            Self::LambertConicConformal2Sp { .. } => {
+                Some(CoordinateTransformationMethod::LambertConformalConic2Sp)
+            }
+            Self::LambertAzimuthalEqualArea { .. } => {
+                Some(CoordinateTransformationMethod::LambertAzimuthalEqualArea)
+            }
+            Self::PseudoMercator => None,
+        }
+    }
+}
+
+/// Returns the datum and ellipsoid of the given geographic CRS, or `None` if it is not part of the
+/// subset known to this crate.
+#[cfg(feature = "epsg")]
+pub(crate) fn geodetic_parameters(geographic: u16) -> Option<GeodeticParameters> {
+    let (_, datum, ellipsoid) = GEODETIC_PARAMETERS
+        .iter()
+        .find(|(code, ..)| *code == geographic)?;
+    let (_, _, semi_major_axis, inv_flattening) =
+        ELLIPSOIDS.iter().find(|(code, ..)| code == ellipsoid)?;
+
+    Some(GeodeticParameters {
+        datum: *datum,
+        ellipsoid: *ellipsoid,
+        semi_major_axis: *semi_major_axis,
+        inv_flattening: *inv_flattening,
+    })
+}
+
+/// Returns the map projection of the given projected CRS, or `None` if it is not part of the subset
+/// known to this crate.
+#[cfg(feature = "epsg")]
+pub(crate) fn projection(projected: u16) -> Option<Projection> {
+    use ProjectionMethod::*;
+
+    let swiss_oblique_mercator = |false_easting, false_northing| ObliqueMercator {
+        center_lat: 46.952_405_555_555_6,
+        center_long: 7.439_583_333_333_33,
+        azimuth: 90.0,
+        scale_at_center: 1.0,
+        false_easting,
+        false_northing,
+    };
+
+    let (conversion, method) = match projected {
+        2056 => (19950, swiss_oblique_mercator(2600000.0, 1200000.0)),
+        2154 => (
+            18085,
+            LambertConicConformal2Sp {
+                std_parallel1: 49.0,
+                std_parallel2: 44.0,
+                false_origin_lat: 46.5,
+                false_origin_long: 3.0,
+                false_origin_easting: 700000.0,
+                false_origin_northing: 6600000.0,
+            },
+        ),
+        3035 => (
+            19986,
+            LambertAzimuthalEqualArea {
+                center_lat: 52.0,
+                center_long: 10.0,
+                false_easting: 4321000.0,
+                false_northing: 3210000.0,
+            },
+        ),
+        3395 => (
+            19883,
+            Mercator(NaturalOrigin {
+                lat: 0.0,
+                long: 0.0,
+                scale: 1.0,
+                false_easting: 0.0,
+                false_northing: 0.0,
+            }),
+        ),
+        3857 => (3856, PseudoMercator),
+        21781 => (19922, swiss_oblique_mercator(600000.0, 200000.0)),
+        27700 => (
+            19916,
+            TransverseMercator(NaturalOrigin {
+                lat: 49.0,
+                long: -2.0,
+                scale: 0.999_601_271_7,
+                false_easting: 400000.0,
+                false_northing: -100000.0,
+            }),
+        ),
+        31287 => (
+            19947,
+            LambertConicConformal2Sp {
+                std_parallel1: 49.0,
+                std_parallel2: 46.0,
+                false_origin_lat: 47.5,
+                false_origin_long: 13.333_333_333_333_3,
+                false_origin_easting: 400000.0,
+                false_origin_northing: 400000.0,
+            },
+        ),
+        _ => {
+            // UTM zones, whose conversion codes are 16000 plus the zone in the north and 16100
+            // plus the zone in the south
+            let (first_code, first_zone, _, hemisphere, _) =
+                UTM_ZONES
+                    .iter()
+                    .find(|(first_code, first_zone, last_zone, ..)| {
+                        (*first_code..=first_code + (last_zone - first_zone)).contains(&projected)
+                    })?;
+            let zone = projected - first_code + first_zone;
+            let south = *hemisphere == 'S';
+            (
+                if south { 16100 } else { 16000 } + zone,
+                TransverseMercator(NaturalOrigin {
+                    lat: 0.0,
+                    long: zone as f64 * 6.0 - 183.0,
+                    scale: 0.9996,
+                    false_easting: 500000.0,
+                    false_northing: if south { 10000000.0 } else { 0.0 },
+                }),
+            )
+        }
+    };
+
+    Some(Projection { conversion, method })
+}
+
+/// Returns the name of the given datum, or `None` if it is not part of the subset.
+pub(crate) fn datum_name(code: u16) -> Option<&'static str> {
+    DATUMS
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, name)| *name)
+}
+
+/// Returns the name, semi-major axis and inverse flattening of the given ellipsoid, or `None` if
+/// it is not part of the subset.
+pub(crate) fn ellipsoid(code: u16) -> Option<(&'static str, f64, f64)> {
+    ELLIPSOIDS.iter().find(|(c, ..)| *c == code).map(
+        |(_, name, semi_major_axis, inv_flattening)| (*name, *semi_major_axis, *inv_flattening),
+    )
+}
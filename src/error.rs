@@ -0,0 +1,192 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use tiff::{TiffError, TiffUnsupportedError};
+
+/// Errors that can occur when reading or writing GeoTIFFs.
+#[derive(Debug)]
+pub enum GeoTiffError {
+    /// An error of the underlying TIFF decoder or encoder.
+    Tiff(TiffError),
+    /// The file uses a TIFF feature that cannot be decoded.
+    Unsupported(Unsupported),
+    /// The GeoTIFF tags or data are malformed.
+    Format(String),
+    /// The GeoKeys do not define a valid coordinate reference system.
+    Crs(String),
+    /// The transformation between raster space and model space is invalid, e.g. not invertible.
+    Transform(String),
+    /// A sample (band) was requested that the image does not have.
+    SampleOutOfBounds { sample: usize, num_samples: usize },
+    /// A value cannot be represented by the requested type, e.g. a negative value as `u8`.
+    ValueConversion {
+        value_type: &'static str,
+        requested_type: &'static str,
+    },
+    /// PROJ failed to create a transformation between coordinate reference systems or to
+    /// reproject coordinates.
+    #[cfg(feature = "proj")]
+    Proj(Box<dyn Error + Send + Sync>),
+    /// GDAL failed to read or create a dataset when converting between GeoTIFFs and datasets.
+    #[cfg(feature = "gdal")]
+    Gdal(Box<dyn Error + Send + Sync>),
+}
+
+pub type GeoTiffResult<T> = Result<T, GeoTiffError>;
+
+impl fmt::Display for GeoTiffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeoTiffError::Tiff(error) => error.fmt(f),
+            GeoTiffError::Unsupported(unsupported) => unsupported.fmt(f),
+            GeoTiffError::Format(message) => write!(f, "Format error: {message}"),
+            GeoTiffError::Crs(message) => write!(f, "CRS error: {message}"),
+            GeoTiffError::Transform(message) => write!(f, "Transform error: {message}"),
+            GeoTiffError::SampleOutOfBounds {
+                sample,
+                num_samples,
+            } => write!(
+                f,
+                "Sample out of bounds: the number of samples is {num_samples} but the sample is {sample}"
+            ),
+            GeoTiffError::ValueConversion {
+                value_type,
+                requested_type,
+            } => write!(f, "Cannot represent {value_type} as {requested_type}"),
+            #[cfg(feature = "proj")]
+            GeoTiffError::Proj(error) => write!(f, "PROJ error: {error}"),
+            #[cfg(feature = "gdal")]
+            GeoTiffError::Gdal(error) => write!(f, "GDAL error: {error}"),
+        }
+    }
+}
+
+impl Error for GeoTiffError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GeoTiffError::Tiff(error) => Some(error),
+            GeoTiffError::Unsupported(_)
+            | GeoTiffError::Format(_)
+            | GeoTiffError::Crs(_)
+            | GeoTiffError::Transform(_)
+            | GeoTiffError::SampleOutOfBounds { .. }
+            | GeoTiffError::ValueConversion { .. } => None,
+            #[cfg(feature = "proj")]
+            GeoTiffError::Proj(error) => Some(error.as_ref()),
+            #[cfg(feature = "gdal")]
+            GeoTiffError::Gdal(error) => Some(error.as_ref()),
+        }
+    }
+}
+
+impl From<TiffError> for GeoTiffError {
+    fn from(error: TiffError) -> Self {
+        match &error {
+            TiffError::UnsupportedError(unsupported) => Unsupported::from_tiff(unsupported)
+                .map(GeoTiffError::Unsupported)
+                .unwrap_or(GeoTiffError::Tiff(error)),
+            _ => GeoTiffError::Tiff(error),
+        }
+    }
+}
+
+impl From<io::Error> for GeoTiffError {
+    fn from(error: io::Error) -> Self {
+        GeoTiffError::Tiff(TiffError::IoError(error))
+    }
+}
+
+#[cfg(feature = "proj")]
+impl From<proj::ProjCreateError> for GeoTiffError {
+    fn from(error: proj::ProjCreateError) -> Self {
+        GeoTiffError::Proj(Box::new(error))
+    }
+}
+
+#[cfg(feature = "proj")]
+impl From<proj::ProjError> for GeoTiffError {
+    fn from(error: proj::ProjError) -> Self {
+        GeoTiffError::Proj(Box::new(error))
+    }
+}
+
+#[cfg(feature = "gdal")]
+impl From<gdal::errors::GdalError> for GeoTiffError {
+    fn from(error: gdal::errors::GdalError) -> Self {
+        GeoTiffError::Gdal(Box::new(error))
+    }
+}
+
+/// A TIFF feature used by a file that cannot be decoded, together with the value of the tag
+/// selecting it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Unsupported {
+    pub feature: TiffFeature,
+    pub value: u16,
+}
+
+/// TIFF features whose support depends on the value of the tag selecting them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TiffFeature {
+    Compression,
+    PhotometricInterpretation,
+    SamplesPerPixel,
+    BitsPerSample,
+    SampleFormat,
+    Predictor,
+    PlanarConfiguration,
+}
+
+impl fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unsupported {:?}: {}", self.feature, self.value)
+    }
+}
+
+impl Unsupported {
+    pub(crate) fn new(feature: TiffFeature, value: u16) -> Self {
+        Self { feature, value }
+    }
+
+    fn from_tiff(error: &TiffUnsupportedError) -> Option<Self> {
+        Some(match error {
+            TiffUnsupportedError::HorizontalPredictor(_) => Self::new(TiffFeature::Predictor, 2),
+            TiffUnsupportedError::FloatingPointPredictor(_) => Self::new(TiffFeature::Predictor, 3),
+            TiffUnsupportedError::InconsistentBitsPerSample(bits_per_sample) => {
+                let first = bits_per_sample.first()?;
+                let value = bits_per_sample.iter().find(|bits| *bits != first)?;
+                Self::new(TiffFeature::BitsPerSample, *value as u16)
+            }
+            TiffUnsupportedError::InterpretationWithBits(photometric_interpretation, _)
+            | TiffUnsupportedError::UnsupportedInterpretation(photometric_interpretation) => {
+                Self::new(
+                    TiffFeature::PhotometricInterpretation,
+                    photometric_interpretation.to_u16(),
+                )
+            }
+            TiffUnsupportedError::UnsupportedCompressionMethod(compression_method) => {
+                Self::new(TiffFeature::Compression, compression_method.to_u16())
+            }
+            TiffUnsupportedError::UnsupportedSampleFormat(sample_formats) => {
+                // Either a format is unsupported or the formats differ between samples
+                let first = sample_formats.first()?;
+                let value = sample_formats
+                    .iter()
+                    .find(|format| *format != first)
+                    .unwrap_or(first);
+                Self::new(TiffFeature::SampleFormat, value.to_u16())
+            }
+            TiffUnsupportedError::UnsupportedSampleDepth(bits)
+            | TiffUnsupportedError::UnsupportedBitsPerChannel(bits) => {
+                Self::new(TiffFeature::BitsPerSample, *bits as u16)
+            }
+            TiffUnsupportedError::UnsupportedPlanarConfig(planar_configuration) => Self::new(
+                TiffFeature::PlanarConfiguration,
+                (*planar_configuration)?.to_u16(),
+            ),
+            _ => return None,
+        })
+    }
+}
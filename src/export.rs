@@ -0,0 +1,258 @@
+use std::io::{self, Write};
+
+#[cfg(feature = "arrow")]
+use arrow_array::{ArrayRef, RecordBatch};
+#[cfg(feature = "arrow")]
+use arrow_schema::ArrowError;
+#[cfg(feature = "ndarray")]
+use num_traits::FromPrimitive;
+
+#[cfg(feature = "arrow")]
+use crate::error::GeoTiffResult;
+#[cfg(feature = "arrow")]
+use crate::raster_data::RasterData;
+use crate::raster_space::RasterSpace;
+use crate::window::Window;
+use crate::GeoTiff;
+
+impl GeoTiff {
+    /// Writes the pixels inside the given window as CSV records.
+    ///
+    /// Each record holds the location of the pixel center in model space followed by the values
+    /// of all samples, i.e. `x,y,sample_0,sample_1,...`. A header line naming the columns is
+    /// written first.
+    pub fn write_csv<W: Write>(&self, mut writer: W, window: &Window) -> io::Result<()> {
+        self.check_window(window)?;
+
+        write!(writer, "x,y")?;
+        for sample in 0..self.num_samples {
+            write!(writer, ",sample_{sample}")?;
+        }
+        writeln!(writer)?;
+
+        let layout = self.buffer_layout();
+        for row in window.y..window.y + window.height {
+            for col in window.x..window.x + window.width {
+                let coord = self.pixel_center(col, row);
+                write!(writer, "{},{}", coord.x, coord.y)?;
+
+                for sample in 0..self.num_samples {
+                    write!(writer, ",")?;
+                    self.raster_data
+                        .write_value(layout.index(col, row, sample), &mut writer)?;
+                }
+                writeln!(writer)?;
+            }
+        }
+
+        writer.flush()
+    }
+
+    /// Returns the raster data as an array with the dimensions (bands, rows, columns), converting
+    /// the values to `T`.
+    #[cfg(feature = "ndarray")]
+    pub fn to_array3<T: FromPrimitive + 'static>(&self) -> ndarray::Array3<T> {
+        let layout = self.buffer_layout();
+        ndarray::Array3::from_shape_fn(
+            (self.num_samples, self.raster_height, self.raster_width),
+            |(sample, row, col)| self.raster_data.get(layout.index(col, row, sample)),
+        )
+    }
+
+    /// Returns the raster data of a single-band image as an array with the dimensions (rows,
+    /// columns), converting the values to `T`, or `None` if the image has multiple bands.
+    #[cfg(feature = "ndarray")]
+    pub fn to_array2<T: FromPrimitive + 'static>(&self) -> Option<ndarray::Array2<T>> {
+        if self.num_samples != 1 {
+            return None;
+        }
+
+        let layout = self.buffer_layout();
+        Some(ndarray::Array2::from_shape_fn(
+            (self.raster_height, self.raster_width),
+            |(row, col)| self.raster_data.get(layout.index(col, row, 0)),
+        ))
+    }
+
+    /// Writes the pixels inside the given window as Parquet records.
+    ///
+    /// The schema mirrors [`GeoTiff::write_csv`]: the columns `x` and `y` hold the location of the
+    /// pixel center in model space, followed by one column `sample_<n>` per sample. All values
+    /// are stored as doubles.
+    #[cfg(feature = "parquet")]
+    pub fn write_parquet<W: Write + Send>(
+        &self,
+        writer: W,
+        window: &Window,
+    ) -> parquet::errors::Result<()> {
+        use std::sync::Arc;
+
+        use parquet::data_type::DoubleType;
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::SerializedFileWriter;
+        use parquet::schema::parser::parse_message_type;
+
+        self.check_window(window)?;
+
+        let mut message_type = "message raster { REQUIRED DOUBLE x; REQUIRED DOUBLE y;".to_string();
+        for sample in 0..self.num_samples {
+            message_type += &format!(" REQUIRED DOUBLE sample_{sample};");
+        }
+        message_type += " }";
+
+        let schema = Arc::new(parse_message_type(&message_type)?);
+        let properties = Arc::new(WriterProperties::builder().build());
+        let mut file_writer = SerializedFileWriter::new(writer, schema, properties)?;
+        let mut row_group_writer = file_writer.next_row_group()?;
+
+        let layout = self.buffer_layout();
+        let pixels = window.pixels().collect::<Vec<_>>();
+        let coords = pixels
+            .iter()
+            .map(|(col, row)| self.pixel_center(*col, *row))
+            .collect::<Vec<_>>();
+
+        let mut column = 0;
+        while let Some(mut column_writer) = row_group_writer.next_column()? {
+            let values = match column {
+                0 => coords.iter().map(|coord| coord.x).collect::<Vec<_>>(),
+                1 => coords.iter().map(|coord| coord.y).collect(),
+                _ => pixels
+                    .iter()
+                    .map(|(col, row)| {
+                        let index = layout.index(*col, *row, column - 2);
+                        self.raster_data.get::<f64>(index)
+                    })
+                    .collect(),
+            };
+            column_writer
+                .typed::<DoubleType>()
+                .write_batch(&values, None, None)?;
+            column_writer.close()?;
+            column += 1;
+        }
+
+        row_group_writer.close()?;
+        file_writer.close()?;
+        Ok(())
+    }
+
+    /// Returns the values of the specified sample row by row as an Arrow array of the type of the
+    /// values, where no-data and NaN values are null.
+    ///
+    /// Returns `None` if the values are complex, which Arrow has no type for. Fails with
+    /// [`GeoTiffError::SampleOutOfBounds`](crate::GeoTiffError::SampleOutOfBounds) if the image
+    /// does not have the sample.
+    #[cfg(feature = "arrow")]
+    pub fn to_arrow_array(&self, sample: usize) -> GeoTiffResult<Option<ArrayRef>> {
+        self.sample_in_bounds(sample)?;
+        Ok(self.arrow_column(
+            sample,
+            &Window::new(0, 0, self.raster_width, self.raster_height),
+        ))
+    }
+
+    /// Returns the pixels inside the given window as an Arrow record batch, e.g. to query them
+    /// with DataFusion or Polars.
+    ///
+    /// The schema mirrors [`GeoTiff::write_parquet`], except that the sample columns keep the type
+    /// of the values and are null for no-data and NaN values. Fails if the window exceeds the
+    /// raster dimensions or the values are complex.
+    #[cfg(feature = "arrow")]
+    pub fn to_arrow_record_batch(&self, window: &Window) -> Result<RecordBatch, ArrowError> {
+        use std::sync::Arc;
+
+        use arrow_array::Float64Array;
+
+        self.check_window(window)?;
+
+        let coords = window
+            .pixels()
+            .map(|(col, row)| self.pixel_center(col, row))
+            .collect::<Vec<_>>();
+        let x = Float64Array::from_iter_values(coords.iter().map(|coord| coord.x));
+        let y = Float64Array::from_iter_values(coords.iter().map(|coord| coord.y));
+
+        let mut columns = vec![
+            ("x".to_string(), Arc::new(x) as ArrayRef, false),
+            ("y".to_string(), Arc::new(y) as ArrayRef, false),
+        ];
+        for sample in 0..self.num_samples {
+            let column = self.arrow_column(sample, window).ok_or_else(|| {
+                ArrowError::NotYetImplemented(format!(
+                    "Arrow arrays of {:?} values",
+                    self.data_type()
+                ))
+            })?;
+            columns.push((format!("sample_{sample}"), column, true));
+        }
+
+        RecordBatch::try_from_iter_with_nullable(columns)
+    }
+
+    /// Returns the values of the given sample inside the window as an Arrow array, or `None` if
+    /// the values are complex.
+    #[cfg(feature = "arrow")]
+    fn arrow_column(&self, sample: usize, window: &Window) -> Option<ArrayRef> {
+        use std::sync::Arc;
+
+        use arrow_array::types::*;
+        use arrow_array::PrimitiveArray;
+
+        let layout = self.buffer_layout();
+        let indices = window
+            .pixels()
+            .map(|(col, row)| layout.index(col, row, sample));
+
+        macro_rules! to_arrow {
+            ($($variant: ident => $arrow_type: ty),*) => {
+                match &self.raster_data {
+                    $(RasterData::$variant(values) => {
+                        let array = indices
+                            .map(|index| {
+                                let value = self.raster_data.get::<f64>(index);
+                                (!value.is_nan() && Some(value) != self.no_data)
+                                    .then_some(values[index])
+                            })
+                            .collect::<PrimitiveArray<$arrow_type>>();
+                        Some(Arc::new(array) as ArrayRef)
+                    })*
+                    RasterData::CI16(_)
+                    | RasterData::CI32(_)
+                    | RasterData::CF32(_)
+                    | RasterData::CF64(_) => None,
+                }
+            };
+        }
+
+        to_arrow!(
+            U8 => UInt8Type,
+            U16 => UInt16Type,
+            U32 => UInt32Type,
+            U64 => UInt64Type,
+            F16 => Float16Type,
+            F32 => Float32Type,
+            F64 => Float64Type,
+            I8 => Int8Type,
+            I16 => Int16Type,
+            I32 => Int32Type,
+            I64 => Int64Type
+        )
+    }
+
+    fn check_window(&self, window: &Window) -> io::Result<()> {
+        if window.x + window.width > self.raster_width
+            || window.y + window.height > self.raster_height
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Window {window:?} exceeds the raster dimensions {}x{}",
+                    self.raster_width, self.raster_height
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use crate::error::GeoTiffResult;
+use crate::raster_data::RasterData;
+use crate::GeoTiff;
+
+impl GeoTiff {
+    /// Returns a hash of the content of the GeoTIFF, combining a checksum of each band with the
+    /// coordinate transform, the GeoKey directory and the data type of the samples.
+    ///
+    /// The hash does not depend on how the data is stored, e.g. the compression, the strip or tile
+    /// size or the memory layout, so it can be used to detect duplicate datasets. It is stable
+    /// across platforms and versions of this crate.
+    pub fn content_fingerprint(&self) -> GeoTiffResult<u64> {
+        let mut hasher = Fnv1a::default();
+
+        let (bits_per_sample, sample_format) = self.raster_data.data_type().sample_type();
+        hasher.write(&bits_per_sample.to_le_bytes());
+        hasher.write(&sample_format.to_u16().to_le_bytes());
+        hasher.write(&(self.raster_width as u64).to_le_bytes());
+        hasher.write(&(self.raster_height as u64).to_le_bytes());
+        hasher.write(&(self.num_samples as u64).to_le_bytes());
+
+        let layout = self.buffer_layout();
+        for sample in 0..self.num_samples {
+            let mut band_hasher = Fnv1a::default();
+            macro_rules! hash_band {
+                ($($variant: ident),*; $($complex_variant: ident),*) => {
+                    match &self.raster_data {
+                        $(RasterData::$variant(data) => {
+                            for y in 0..self.raster_height {
+                                for x in 0..self.raster_width {
+                                    band_hasher
+                                        .write(&data[layout.index(x, y, sample)].to_le_bytes());
+                                }
+                            }
+                        })*
+                        $(RasterData::$complex_variant(data) => {
+                            for y in 0..self.raster_height {
+                                for x in 0..self.raster_width {
+                                    let value = data[layout.index(x, y, sample)];
+                                    band_hasher.write(&value.re.to_le_bytes());
+                                    band_hasher.write(&value.im.to_le_bytes());
+                                }
+                            }
+                        })*
+                    }
+                };
+            }
+            hash_band!(U8, U16, U32, U64, F16, F32, F64, I8, I16, I32, I64; CI16, CI32, CF32, CF64);
+            hasher.write(&band_hasher.finish().to_le_bytes());
+        }
+
+        if let Some(coordinate_transform) = &self.coordinate_transform {
+            let tag_data = coordinate_transform.to_tag_data();
+            for values in [
+                tag_data.pixel_scale,
+                tag_data.tie_points,
+                tag_data.model_transformation,
+            ] {
+                let values = values.unwrap_or_default();
+                hasher.write(&(values.len() as u64).to_le_bytes());
+                for value in values {
+                    hasher.write(&value.to_le_bytes());
+                }
+            }
+        }
+
+        let (directory, double_params, ascii_params) = self.geo_key_directory.to_tag_data()?;
+        for key in directory {
+            hasher.write(&key.to_le_bytes());
+        }
+        for value in double_params {
+            hasher.write(&value.to_le_bytes());
+        }
+        hasher.write(ascii_params.as_bytes());
+
+        Ok(hasher.finish())
+    }
+}
+
+/// The 64-bit FNV-1a hash function, which unlike the hashers of the standard library is
+/// guaranteed to be stable.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
@@ -0,0 +1,61 @@
+//! Entry points to the parsers of GeoTIFF metadata for fuzz targets, enabled by the `fuzzing`
+//! feature.
+//!
+//! The inputs implement [`Arbitrary`], so that fuzzers generate structured tag data instead of
+//! whole TIFF files, most of which would be rejected by the TIFF decoder before reaching the
+//! GeoTIFF parsers.
+use arbitrary::Arbitrary;
+use geo_types::Coord;
+
+use crate::coordinate_transform::CoordinateTransform;
+use crate::error::GeoTiffResult;
+use crate::geo_key_directory::GeoKeyDirectory;
+
+/// The values of the GeoKeyDirectoryTag, GeoDoubleParamsTag and GeoAsciiParamsTag.
+#[derive(Debug, Clone, Arbitrary)]
+pub struct GeoKeyDirectoryInput {
+    pub directory_data: Vec<u16>,
+    pub double_params_data: Vec<f64>,
+    pub ascii_params_data: String,
+}
+
+impl GeoKeyDirectoryInput {
+    /// Parses the directory in strict mode and encodes it again if successful.
+    pub fn parse(self) -> GeoTiffResult<GeoKeyDirectory> {
+        let directory = GeoKeyDirectory::from_tag_data(
+            self.directory_data,
+            self.double_params_data,
+            self.ascii_params_data,
+            true,
+        )?;
+        directory.to_tag_data()?;
+        Ok(directory)
+    }
+}
+
+/// The values of the ModelPixelScaleTag, ModelTiepointTag and ModelTransformationTag, together with
+/// a coordinate to transform.
+#[derive(Debug, Clone, Arbitrary)]
+pub struct CoordinateTransformInput {
+    pub pixel_scale_data: Option<Vec<f64>>,
+    pub tie_points_data: Option<Vec<f64>>,
+    pub model_transformation_data: Option<Vec<f64>>,
+    pub coord: (f64, f64),
+}
+
+impl CoordinateTransformInput {
+    /// Parses the transform and, if successful, transforms the coordinate from raster to model
+    /// space and back, returning both results.
+    pub fn parse(self) -> GeoTiffResult<(Coord, Coord)> {
+        let transform = CoordinateTransform::from_tag_data(
+            self.pixel_scale_data,
+            self.tie_points_data,
+            self.model_transformation_data,
+        )?;
+        transform.to_tag_data();
+
+        let (x, y) = self.coord;
+        let model = transform.transform_to_model(&Coord { x, y });
+        Ok((model, transform.transform_to_raster(&model)))
+    }
+}
@@ -0,0 +1,145 @@
+use gdal::raster::{Buffer, GdalDataType, GdalType};
+use gdal::{Dataset, DriverManager};
+use num_traits::FromPrimitive;
+
+use crate::band::BandView;
+use crate::error::{GeoTiffError, GeoTiffResult};
+use crate::geo_key_directory::{GeoKeyDirectory, GeoKeyDirectoryBuilder};
+use crate::layout::RasterDataType;
+use crate::profile::Profile;
+use crate::raster_data::RasterData;
+use crate::GeoTiff;
+
+/// Reads all bands of a GDAL dataset, e.g. one opened with `gdal::Dataset::open`.
+///
+/// Bands of types that are not available in all GDAL versions, such as 64-bit integers, are read
+/// as `F64`. The no-data value of the first band applies to all bands, and the coordinate
+/// reference system is taken from its EPSG code if it is one known to
+/// [`GeoKeyDirectoryBuilder::epsg`].
+impl TryFrom<&Dataset> for GeoTiff {
+    type Error = GeoTiffError;
+
+    fn try_from(dataset: &Dataset) -> GeoTiffResult<Self> {
+        let (width, height) = dataset.raster_size();
+        let num_samples = dataset.raster_count();
+        let first_band = dataset.rasterband(1)?;
+
+        let raster_data = match first_band.band_type() {
+            GdalDataType::UInt8 => RasterData::U8(read_pixels(dataset)?),
+            GdalDataType::UInt16 => RasterData::U16(read_pixels(dataset)?),
+            GdalDataType::UInt32 => RasterData::U32(read_pixels(dataset)?),
+            GdalDataType::Int16 => RasterData::I16(read_pixels(dataset)?),
+            GdalDataType::Int32 => RasterData::I32(read_pixels(dataset)?),
+            GdalDataType::Float32 => RasterData::F32(read_pixels(dataset)?),
+            _ => RasterData::F64(read_pixels(dataset)?),
+        };
+
+        let geo_key_directory = dataset
+            .spatial_ref()
+            .and_then(|spatial_ref| spatial_ref.auth_code())
+            .ok()
+            .and_then(|code| u16::try_from(code).ok())
+            .and_then(GeoKeyDirectoryBuilder::from_epsg)
+            .map_or_else(GeoKeyDirectory::default, GeoKeyDirectoryBuilder::build);
+
+        let profile = Profile {
+            geotransform: dataset.geo_transform().ok(),
+            geo_key_directory,
+            no_data: first_band.no_data_value(),
+            ..Profile::new(width, height, num_samples, raster_data.data_type())
+        };
+        GeoTiff::from_profile(&profile, raster_data)
+    }
+}
+
+/// Creates an in-memory GDAL dataset holding the raster data, the geotransform, the no-data value
+/// and the coordinate reference system as WKT.
+///
+/// Values of types that are not available in all GDAL versions are converted to the closest
+/// type, i.e. `I8` to `Int16`, `F16` to `Float32` and 64-bit integers to `Float64`. Fails with
+/// [`GeoTiffError::Format`] for complex values.
+impl TryFrom<&GeoTiff> for Dataset {
+    type Error = GeoTiffError;
+
+    fn try_from(geotiff: &GeoTiff) -> GeoTiffResult<Self> {
+        let mut dataset = match geotiff.data_type() {
+            RasterDataType::U8 => create_dataset::<u8>(geotiff)?,
+            RasterDataType::U16 => create_dataset::<u16>(geotiff)?,
+            RasterDataType::U32 => create_dataset::<u32>(geotiff)?,
+            RasterDataType::I8 | RasterDataType::I16 => create_dataset::<i16>(geotiff)?,
+            RasterDataType::I32 => create_dataset::<i32>(geotiff)?,
+            RasterDataType::F16 | RasterDataType::F32 => create_dataset::<f32>(geotiff)?,
+            RasterDataType::U64 | RasterDataType::I64 | RasterDataType::F64 => {
+                create_dataset::<f64>(geotiff)?
+            }
+            data_type @ (RasterDataType::CI16
+            | RasterDataType::CI32
+            | RasterDataType::CF32
+            | RasterDataType::CF64) => {
+                return Err(GeoTiffError::Format(format!(
+                    "Complex values of type {data_type:?} cannot be converted to a GDAL dataset"
+                )))
+            }
+        };
+
+        if let Some(geotransform) = geotiff.geotransform() {
+            dataset.set_geo_transform(&geotransform)?;
+        }
+        if let Some(wkt) = geotiff.geo_key_directory.to_wkt() {
+            dataset.set_projection(&wkt)?;
+        }
+        for band in dataset.rasterbands() {
+            band?.set_no_data_value(geotiff.no_data)?;
+        }
+
+        Ok(dataset)
+    }
+}
+
+/// Copies the values of a band into a GDAL buffer, e.g. to write them to a band of a dataset
+/// with `gdal::raster::RasterBand::write`.
+///
+/// # Panics
+///
+/// Panics if `T` cannot represent a value, which never happens for `f64`.
+impl<T: GdalType + FromPrimitive + 'static> From<BandView<'_>> for Buffer<T> {
+    fn from(band: BandView<'_>) -> Self {
+        Buffer::new(band.dimensions(), band.iter().collect())
+    }
+}
+
+/// Reads the bands of the dataset as pixel interleaved values.
+fn read_pixels<T: GdalType + Copy>(dataset: &Dataset) -> gdal::errors::Result<Vec<T>> {
+    let bands = dataset
+        .rasterbands()
+        .map(|band| band?.read_band_as::<T>())
+        .collect::<gdal::errors::Result<Vec<_>>>()?;
+
+    let (width, height) = dataset.raster_size();
+    Ok((0..width * height)
+        .flat_map(|pixel| bands.iter().map(move |band| band.data()[pixel]))
+        .collect())
+}
+
+/// Creates an in-memory dataset with bands of type `T` holding the raster data.
+fn create_dataset<T: GdalType + Copy + FromPrimitive + 'static>(
+    geotiff: &GeoTiff,
+) -> GeoTiffResult<Dataset> {
+    let driver = DriverManager::get_driver_by_name("MEM")?;
+    let dataset = driver.create_with_band_type::<T, _>(
+        "",
+        geotiff.raster_width,
+        geotiff.raster_height,
+        geotiff.num_samples,
+    )?;
+
+    for band in geotiff.bands() {
+        let mut buffer = Buffer::<T>::from(band);
+        let size = buffer.shape();
+        dataset
+            .rasterband(band.sample() + 1)?
+            .write((0, 0), size, &mut buffer)?;
+    }
+
+    Ok(dataset)
+}
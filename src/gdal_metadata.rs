@@ -0,0 +1,292 @@
+use std::fmt::Write;
+
+use crate::error::{GeoTiffError, GeoTiffResult};
+
+/// The GDAL_METADATA tag, holding the GDAL metadata as XML.
+pub(crate) const GDAL_METADATA_TAG: u16 = 42112;
+
+/// The metadata GDAL stores as XML in the GDAL_METADATA TIFF tag, such as band descriptions,
+/// scale and offset, units and statistics.
+///
+/// Ref: https://gdal.org/drivers/raster/gtiff.html#metadata
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GdalMetadata {
+    pub items: Vec<GdalMetadataItem>,
+}
+
+/// A single `<Item>` of the GDAL metadata.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GdalMetadataItem {
+    pub name: String,
+    pub value: String,
+    /// The index of the sample (band) the item applies to, or `None` for the whole dataset.
+    pub sample: Option<usize>,
+    /// The role of the item, e.g. `description`, `scale`, `offset` or `unittype`.
+    pub role: Option<String>,
+    /// The metadata domain, or `None` for the default domain.
+    pub domain: Option<String>,
+}
+
+/// The statistics GDAL computed for a sample (band).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BandStatistics {
+    pub minimum: Option<f64>,
+    pub maximum: Option<f64>,
+    pub mean: Option<f64>,
+    pub std_dev: Option<f64>,
+}
+
+impl GdalMetadata {
+    /// Returns the value of the item with the given name in the default domain, either for the
+    /// given sample or for the whole dataset if `sample` is `None`.
+    ///
+    /// Names are compared case-insensitively, as GDAL does.
+    pub fn get(&self, name: &str, sample: Option<usize>) -> Option<&str> {
+        self.items
+            .iter()
+            .find(|item| {
+                item.domain.is_none()
+                    && item.sample == sample
+                    && item.name.eq_ignore_ascii_case(name)
+            })
+            .map(|item| item.value.as_str())
+    }
+
+    /// Returns the description of the given sample.
+    pub fn description(&self, sample: usize) -> Option<&str> {
+        self.get_with_role("description", sample)
+            .or_else(|| self.get("DESCRIPTION", Some(sample)))
+    }
+
+    /// Returns the scale to apply to the values of the given sample.
+    pub fn scale(&self, sample: usize) -> Option<f64> {
+        self.get_with_role("scale", sample)
+            .or_else(|| self.get("SCALE", Some(sample)))
+            .and_then(parse_f64)
+    }
+
+    /// Returns the offset to add to the scaled values of the given sample.
+    pub fn offset(&self, sample: usize) -> Option<f64> {
+        self.get_with_role("offset", sample)
+            .or_else(|| self.get("OFFSET", Some(sample)))
+            .and_then(parse_f64)
+    }
+
+    /// Returns the unit of the values of the given sample.
+    pub fn unit_type(&self, sample: usize) -> Option<&str> {
+        self.get_with_role("unittype", sample)
+            .or_else(|| self.get("UNITTYPE", Some(sample)))
+    }
+
+    /// Returns the statistics of the given sample, or `None` if none have been stored.
+    pub fn statistics(&self, sample: usize) -> Option<BandStatistics> {
+        let statistic = |name| self.get(name, Some(sample)).and_then(parse_f64);
+        let statistics = BandStatistics {
+            minimum: statistic("STATISTICS_MINIMUM"),
+            maximum: statistic("STATISTICS_MAXIMUM"),
+            mean: statistic("STATISTICS_MEAN"),
+            std_dev: statistic("STATISTICS_STDDEV"),
+        };
+
+        (statistics != BandStatistics::default()).then_some(statistics)
+    }
+
+    /// Returns whether GDAL flagged the 8-bit unsigned samples as signed bytes, a legacy convention
+    /// for storing signed 8-bit data that predates GDAL's support for the `Int8` data type.
+    pub fn is_signed_byte(&self) -> bool {
+        self.items.iter().any(|item| {
+            matches!(item.domain.as_deref(), None | Some("IMAGE_STRUCTURE"))
+                && item.name.eq_ignore_ascii_case("PIXELTYPE")
+                && item.value.trim().eq_ignore_ascii_case("SIGNEDBYTE")
+        })
+    }
+
+    fn get_with_role(&self, role: &str, sample: usize) -> Option<&str> {
+        self.items
+            .iter()
+            .find(|item| {
+                item.domain.is_none()
+                    && item.sample == Some(sample)
+                    && item.role.as_deref() == Some(role)
+            })
+            .map(|item| item.value.as_str())
+    }
+
+    /// Parses the content of the GDAL_METADATA tag.
+    /// Returns the metadata of the given samples, renumbering the items of each sample to its
+    /// position among them.
+    pub(crate) fn select_samples(&self, samples: &[usize]) -> Self {
+        let items = self
+            .items
+            .iter()
+            .flat_map(|item| {
+                let renumbered: Vec<_> = match item.sample {
+                    None => vec![item.clone()],
+                    Some(sample) => (0..samples.len())
+                        .filter(|&i| samples[i] == sample)
+                        .map(|i| GdalMetadataItem {
+                            sample: Some(i),
+                            ..item.clone()
+                        })
+                        .collect(),
+                };
+                renumbered
+            })
+            .collect();
+
+        Self { items }
+    }
+
+    pub(crate) fn from_xml(xml: &str) -> GeoTiffResult<Self> {
+        let mut items = Vec::new();
+        let mut rest = xml;
+
+        while let Some(start) = rest.find("<Item") {
+            rest = &rest[start + "<Item".len()..];
+            let Some(tag_end) = rest.find('>') else {
+                return Err(invalid_metadata("unterminated <Item> tag"));
+            };
+            let (attributes, self_closing) = match rest[..tag_end].strip_suffix('/') {
+                Some(attributes) => (attributes, true),
+                None => (&rest[..tag_end], false),
+            };
+            let mut item = parse_attributes(attributes)?;
+            rest = &rest[tag_end + 1..];
+
+            if !self_closing {
+                let Some(content_end) = rest.find("</Item>") else {
+                    return Err(invalid_metadata("missing </Item>"));
+                };
+                item.value = unescape(&rest[..content_end])?;
+                rest = &rest[content_end + "</Item>".len()..];
+            }
+
+            items.push(item);
+        }
+
+        Ok(Self { items })
+    }
+
+    /// Serializes the metadata as the content of the GDAL_METADATA tag.
+    ///
+    /// Non-ASCII characters are written as character references, since TIFF ASCII tags cannot hold
+    /// them.
+    pub(crate) fn to_xml(&self) -> String {
+        let mut xml = String::from("<GDALMetadata>\n");
+        for item in &self.items {
+            let _ = write!(xml, "  <Item name=\"{}\"", escape(&item.name));
+            if let Some(sample) = item.sample {
+                let _ = write!(xml, " sample=\"{sample}\"");
+            }
+            if let Some(role) = &item.role {
+                let _ = write!(xml, " role=\"{}\"", escape(role));
+            }
+            if let Some(domain) = &item.domain {
+                let _ = write!(xml, " domain=\"{}\"", escape(domain));
+            }
+            let _ = writeln!(xml, ">{}</Item>", escape(&item.value));
+        }
+        xml.push_str("</GDALMetadata>");
+
+        xml
+    }
+}
+
+fn parse_attributes(mut attributes: &str) -> GeoTiffResult<GdalMetadataItem> {
+    let mut item = GdalMetadataItem::default();
+
+    loop {
+        attributes = attributes.trim_start();
+        if attributes.is_empty() {
+            return Ok(item);
+        }
+
+        let (name, rest) = attributes
+            .split_once('=')
+            .ok_or_else(|| invalid_metadata("attribute without value"))?;
+        let rest = rest.trim_start();
+        let quote = rest
+            .chars()
+            .next()
+            .filter(|quote| *quote == '"' || *quote == '\'')
+            .ok_or_else(|| invalid_metadata("unquoted attribute value"))?;
+        let (value, rest) = rest[1..]
+            .split_once(quote)
+            .ok_or_else(|| invalid_metadata("unterminated attribute value"))?;
+        let value = unescape(value)?;
+        attributes = rest;
+
+        match name.trim() {
+            "name" => item.name = value,
+            "sample" => {
+                item.sample = Some(
+                    value
+                        .trim()
+                        .parse()
+                        .map_err(|_| invalid_metadata("invalid sample index"))?,
+                )
+            }
+            "role" => item.role = Some(value),
+            "domain" if !value.is_empty() => item.domain = Some(value),
+            _ => {}
+        }
+    }
+}
+
+fn unescape(text: &str) -> GeoTiffResult<String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(';')
+            .ok_or_else(|| invalid_metadata("unterminated entity"))?;
+        let entity = &rest[start + 1..start + end];
+        let c = match entity {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32)
+                .ok_or_else(|| invalid_metadata("unknown entity"))?,
+        };
+        unescaped.push(c);
+        rest = &rest[start + end + 1..];
+    }
+    unescaped.push_str(rest);
+
+    Ok(unescaped)
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c if c.is_ascii() && c != '\0' => escaped.push(c),
+            c => {
+                let _ = write!(escaped, "&#{};", c as u32);
+            }
+        }
+    }
+
+    escaped
+}
+
+fn parse_f64(value: &str) -> Option<f64> {
+    value.trim().parse().ok()
+}
+
+fn invalid_metadata(reason: &str) -> GeoTiffError {
+    GeoTiffError::Format(format!("Invalid GDAL metadata: {reason}"))
+}
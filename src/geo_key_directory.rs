@@ -1,12 +1,24 @@
+use std::collections::HashMap;
+use std::fmt;
+
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use tiff::tags::Tag;
-use tiff::{TiffError, TiffFormatError, TiffResult};
+
+use crate::epsg::{self, CrsKind};
+use crate::error::{GeoTiffError, GeoTiffResult};
+
+/// The value of the GTModelTypeGeoKey for projected coordinate reference systems.
+pub(crate) const MODEL_TYPE_PROJECTED: u16 = 1;
+/// The value of the GTModelTypeGeoKey for geographic coordinate reference systems.
+pub(crate) const MODEL_TYPE_GEOGRAPHIC: u16 = 2;
+/// The value of code keys marking a user-defined code, whose definition follows in other keys.
+pub(crate) const USER_DEFINED: u16 = 32767;
 
 /// The GeoKeyDirectoryTag Requirements Class specifies the requirements for
 /// implementing the reserved GeoKeyDirectoryTag TIFF tag.
 ///
 /// Ref: https://docs.ogc.org/is/19-008r4/19-008r4.html#_requirements_class_geokeydirectorytag
-#[derive(Debug, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub struct GeoKeyDirectory {
     pub key_directory_version: u16,
     pub key_revision: u16,
@@ -56,19 +68,28 @@ pub struct GeoKeyDirectory {
     pub vertical_citation: Option<String>,
     pub vertical_datum: Option<u16>,
     pub vertical_units: Option<u16>,
+    /// The keys whose IDs are not known to this crate, e.g. vendor-specific or newer ones, which
+    /// are kept so that they are written back. Keys with known IDs are ignored.
+    pub unknown_keys: HashMap<u16, GeoKeyValue>,
 }
 
 impl GeoKeyDirectory {
+    /// Parses the data of the GeoKeyDirectoryTag, GeoDoubleParamsTag and GeoAsciiParamsTag.
+    ///
+    /// In strict mode, directories with a version other than 1.1, a number of keys not matching
+    /// the length of the data, keys not sorted by ID, and ASCII values not terminated by a pipe
+    /// at the end of their count are rejected. Otherwise, the keys present are read.
     pub(crate) fn from_tag_data(
         directory_data: Vec<u16>,
         double_params_data: Vec<f64>,
         ascii_params_data: String,
-    ) -> TiffResult<Self> {
+        strict: bool,
+    ) -> GeoTiffResult<Self> {
         let mut directory = Self::default();
         if directory_data.len() < 4 {
-            return Err(TiffError::FormatError(TiffFormatError::Format(
+            return Err(GeoTiffError::Format(
                 "Unexpected length of directory data: must be at least 4.".into(),
-            )));
+            ));
         }
 
         directory.key_directory_version = directory_data[0];
@@ -76,22 +97,64 @@ impl GeoKeyDirectory {
         directory.minor_revision = directory_data[2];
         let number_of_keys = directory_data[3] as usize;
 
-        if directory_data.len() - 4 != 4 * number_of_keys {
-            return Err(TiffError::FormatError(TiffFormatError::Format(
-                "Unexpected length of directory data: number of keys does not match length of directory data.".into())
+        if strict && (directory.key_directory_version != 1 || directory.key_revision != 1) {
+            return Err(GeoTiffError::Format(format!(
+                "Unsupported GeoKey directory version {}.{}: must be 1.1.",
+                directory.key_directory_version, directory.key_revision
+            )));
+        }
+        if strict && directory_data.len() - 4 != 4 * number_of_keys {
+            return Err(GeoTiffError::Format(
+                "Unexpected length of directory data: number of keys does not match length of directory data.".into(),
             ));
         }
 
+        let mut previous_key_id = None;
         for [key_id, tiff_tag_location, count, value_or_offset] in directory_data[4..]
             .chunks(4)
+            .take(number_of_keys)
             .filter_map(|c| <&[u16; 4]>::try_from(c).ok())
         {
-            let key_tag = GeoKeyDirectoryTag::try_from(*key_id).map_err(|_| {
-                TiffError::FormatError(TiffFormatError::Format(format!(
-                    "Unknown GeoKeyDirectoryTag: {key_id}"
-                )))
-            })?;
+            if strict && previous_key_id.is_some_and(|previous| previous >= *key_id) {
+                return Err(GeoTiffError::Format(format!(
+                    "Key `{key_id}` is not sorted by ID."
+                )));
+            }
+            previous_key_id = Some(*key_id);
+
             let location_tag = Tag::from_u16(*tiff_tag_location);
+            let Ok(key_tag) = GeoKeyDirectoryTag::try_from(*key_id) else {
+                let value = match location_tag {
+                    None => GeoKeyValue::Short(Self::get_short(
+                        key_id,
+                        location_tag,
+                        *count,
+                        *value_or_offset,
+                    )?),
+                    Some(Tag::GeoDoubleParamsTag) => GeoKeyValue::Double(Self::get_double(
+                        &double_params_data,
+                        key_id,
+                        location_tag,
+                        *count,
+                        *value_or_offset,
+                    )?),
+                    Some(Tag::GeoAsciiParamsTag) => GeoKeyValue::Ascii(Self::get_string(
+                        &ascii_params_data,
+                        strict,
+                        key_id,
+                        location_tag,
+                        *count,
+                        *value_or_offset,
+                    )?),
+                    Some(_) => {
+                        return Err(GeoTiffError::Format(format!(
+                            "Key `{key_id}` did not have a SHORT, DOUBLE or ASCII value type."
+                        )))
+                    }
+                };
+                directory.unknown_keys.insert(*key_id, value);
+                continue;
+            };
 
             match key_tag {
                 GeoKeyDirectoryTag::ModelType => {
@@ -103,14 +166,13 @@ impl GeoKeyDirectory {
                         Self::get_short(key_tag, location_tag, *count, *value_or_offset)?;
                     directory.raster_type =
                         Some(RasterType::try_from(raster_type).map_err(|_| {
-                            TiffError::FormatError(TiffFormatError::Format(format!(
-                                "Unknown raster type: {raster_type}"
-                            )))
+                            GeoTiffError::Format(format!("Unknown raster type: {raster_type}"))
                         })?)
                 }
                 GeoKeyDirectoryTag::Citation => {
                     directory.citation = Self::get_string(
                         &ascii_params_data,
+                        strict,
                         key_tag,
                         location_tag,
                         *count,
@@ -125,6 +187,7 @@ impl GeoKeyDirectory {
                 GeoKeyDirectoryTag::GeogCitation => {
                     directory.geog_citation = Self::get_string(
                         &ascii_params_data,
+                        strict,
                         key_tag,
                         location_tag,
                         *count,
@@ -223,6 +286,7 @@ impl GeoKeyDirectory {
                 GeoKeyDirectoryTag::ProjCitation => {
                     directory.proj_citation = Self::get_string(
                         &ascii_params_data,
+                        strict,
                         key_tag,
                         location_tag,
                         *count,
@@ -439,6 +503,7 @@ impl GeoKeyDirectory {
                 GeoKeyDirectoryTag::VerticalCitation => {
                     directory.vertical_citation = Self::get_string(
                         &ascii_params_data,
+                        strict,
                         key_tag,
                         location_tag,
                         *count,
@@ -461,22 +526,22 @@ impl GeoKeyDirectory {
     }
 
     fn get_short(
-        key_tag: GeoKeyDirectoryTag,
+        key_tag: impl fmt::Debug,
         location_tag: Option<Tag>,
         count: u16,
         offset: u16,
-    ) -> TiffResult<u16> {
+    ) -> GeoTiffResult<u16> {
         // Check that TIFFTagLocation == 0 so value is of SHORT type
         if location_tag.is_some() {
-            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+            return Err(GeoTiffError::Format(format!(
                 "Key `{key_tag:?}` did not have the expected SHORT value type."
-            ))));
+            )));
         }
 
         if count != 1 {
-            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+            return Err(GeoTiffError::Format(format!(
                 "Unexpected count: expected 1, got {count}."
-            ))));
+            )));
         }
 
         Ok(offset)
@@ -484,64 +549,739 @@ impl GeoKeyDirectory {
 
     fn get_double(
         data: &[f64],
-        key_tag: GeoKeyDirectoryTag,
+        key_tag: impl fmt::Debug,
         location_tag: Option<Tag>,
         count: u16,
         offset: u16,
-    ) -> TiffResult<f64> {
+    ) -> GeoTiffResult<f64> {
         if location_tag != Some(Tag::GeoDoubleParamsTag) {
-            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+            return Err(GeoTiffError::Format(format!(
                 "Key `{key_tag:?}` did not have the expected DOUBLE value type."
-            ))));
+            )));
         }
 
         if count != 1 {
-            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+            return Err(GeoTiffError::Format(format!(
                 "Unexpected count: expected 1, got {count}."
-            ))));
+            )));
         }
 
         match data.get(offset as usize) {
-            None => Err(TiffError::FormatError(TiffFormatError::Format(format!(
+            None => Err(GeoTiffError::Format(format!(
                 "Offset out of bounds: the length is {} but the offset is {offset}",
                 data.len()
-            )))),
+            ))),
             Some(value) => Ok(*value),
         }
     }
 
     fn get_string(
         data: &str,
-        key_tag: GeoKeyDirectoryTag,
+        strict: bool,
+        key_tag: impl fmt::Debug,
         location_tag: Option<Tag>,
         count: u16,
         offset: u16,
-    ) -> TiffResult<String> {
+    ) -> GeoTiffResult<String> {
         let len = data.len();
 
         if location_tag != Some(Tag::GeoAsciiParamsTag) {
-            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+            return Err(GeoTiffError::Format(format!(
                 "Key `{key_tag:?}` did not have the expected ASCII value type."
-            ))));
+            )));
         }
 
         let start = offset as usize;
         if start >= len {
-            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+            return Err(GeoTiffError::Format(format!(
                 "Start offset out of bounds: the length is {} but the offset is {offset}.",
                 len
-            ))));
+            )));
         }
 
-        let end = (offset + count - 1) as usize;
-        if end >= len {
-            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
-                "End offset out of bounds: the length is {} but the offset is {offset}.",
-                len
-            ))));
+        // The count includes the terminating pipe, which may be missing at the end of the data
+        // when it was written as or replaced by a NUL terminator
+        let end = (start + count as usize).min(len);
+        let Some(value) = data.get(start..end) else {
+            return Err(GeoTiffError::Format(format!(
+                "Key `{key_tag:?}` does not hold a valid ASCII value."
+            )));
+        };
+        if strict {
+            return match value.strip_suffix('|') {
+                Some(value) if end == start + count as usize => Ok(value.into()),
+                _ => Err(GeoTiffError::Format(format!(
+                    "Key `{key_tag:?}` does not hold an ASCII value terminated by a pipe."
+                ))),
+            };
+        }
+
+        Ok(value.strip_suffix(['|', '\0']).unwrap_or(value).into())
+    }
+
+    /// Returns the method of the map projection given by `proj_coord_trans`, or `None` if the key
+    /// is missing or holds an unknown code.
+    pub fn coordinate_transformation_method(&self) -> Option<CoordinateTransformationMethod> {
+        self.proj_coord_trans
+            .and_then(|code| CoordinateTransformationMethod::try_from(code).ok())
+    }
+
+    /// Returns whether the model space is projected, or `None` if it is neither projected nor
+    /// geographic, e.g. geocentric.
+    ///
+    /// Directories without a model type are taken to be projected if they have a projected CRS.
+    pub(crate) fn is_projected(&self) -> Option<bool> {
+        match self.model_type {
+            Some(MODEL_TYPE_PROJECTED) => Some(true),
+            Some(MODEL_TYPE_GEOGRAPHIC) => Some(false),
+            Some(_) => None,
+            None => Some(self.projected_type.is_some()),
         }
+    }
+
+    /// Returns the EPSG code of the projected or geographic CRS of the model space, or `None` if
+    /// it is missing or user-defined.
+    pub(crate) fn epsg_code(&self) -> Option<u16> {
+        let code = match self.is_projected()? {
+            true => self.projected_type,
+            false => self.geographic_type,
+        };
 
-        Ok(data[start..end].into())
+        code.filter(|&code| code != 0 && code != USER_DEFINED)
+    }
+
+    /// Returns the keys present in the directory with their IDs and values, sorted by ID,
+    /// including unknown keys.
+    pub fn keys(&self) -> impl Iterator<Item = (u16, GeoKeyValue)> {
+        self.entries().into_iter()
+    }
+
+    /// Lists the keys present in the directory by ID, sorted by ID.
+    fn entries(&self) -> Vec<(u16, GeoKeyValue)> {
+        let mut entries = GeoKeyEntries::default();
+
+        entries.short(GeoKeyDirectoryTag::ModelType, self.model_type);
+        entries.short(
+            GeoKeyDirectoryTag::RasterType,
+            self.raster_type.map(Into::into),
+        );
+        entries.string(GeoKeyDirectoryTag::Citation, &self.citation);
+        entries.short(GeoKeyDirectoryTag::GeographicType, self.geographic_type);
+        entries.string(GeoKeyDirectoryTag::GeogCitation, &self.geog_citation);
+        entries.short(
+            GeoKeyDirectoryTag::GeogGeodeticDatum,
+            self.geog_geodetic_datum,
+        );
+        entries.short(
+            GeoKeyDirectoryTag::GeogPrimeMeridian,
+            self.geog_prime_meridian,
+        );
+        entries.short(GeoKeyDirectoryTag::GeogLinearUnits, self.geog_linear_units);
+        entries.double(
+            GeoKeyDirectoryTag::GeogLinearUnitSize,
+            self.geog_linear_unit_size,
+        );
+        entries.short(
+            GeoKeyDirectoryTag::GeogAngularUnits,
+            self.geog_angular_units,
+        );
+        entries.double(
+            GeoKeyDirectoryTag::GeogAngularUnitSize,
+            self.geog_angular_unit_size,
+        );
+        entries.short(GeoKeyDirectoryTag::GeogEllipsoid, self.geog_ellipsoid);
+        entries.double(
+            GeoKeyDirectoryTag::GeogSemiMajorAxis,
+            self.geog_semi_major_axis,
+        );
+        entries.double(
+            GeoKeyDirectoryTag::GeogSemiMinorAxis,
+            self.geog_semi_minor_axis,
+        );
+        entries.double(
+            GeoKeyDirectoryTag::GeogInvFlattening,
+            self.geog_inv_flattening,
+        );
+        entries.short(
+            GeoKeyDirectoryTag::GeogAzimuthUnits,
+            self.geog_azimuth_units,
+        );
+        entries.double(
+            GeoKeyDirectoryTag::GeogPrimeMeridianLong,
+            self.geog_prime_meridian_long,
+        );
+        entries.short(GeoKeyDirectoryTag::ProjectedType, self.projected_type);
+        entries.string(GeoKeyDirectoryTag::ProjCitation, &self.proj_citation);
+        entries.short(GeoKeyDirectoryTag::Projection, self.projection);
+        entries.short(GeoKeyDirectoryTag::ProjCoordTrans, self.proj_coord_trans);
+        entries.short(GeoKeyDirectoryTag::ProjLinearUnits, self.proj_linear_units);
+        entries.double(
+            GeoKeyDirectoryTag::ProjLinearUnitSize,
+            self.proj_linear_unit_size,
+        );
+        entries.double(
+            GeoKeyDirectoryTag::ProjStdParallel1,
+            self.proj_std_parallel1,
+        );
+        entries.double(
+            GeoKeyDirectoryTag::ProjStdParallel2,
+            self.proj_std_parallel2,
+        );
+        entries.double(
+            GeoKeyDirectoryTag::ProjNatOriginLong,
+            self.proj_nat_origin_long,
+        );
+        entries.double(
+            GeoKeyDirectoryTag::ProjNatOriginLat,
+            self.proj_nat_origin_lat,
+        );
+        entries.double(
+            GeoKeyDirectoryTag::ProjFalseEasting,
+            self.proj_false_easting,
+        );
+        entries.double(
+            GeoKeyDirectoryTag::ProjFalseNorthing,
+            self.proj_false_northing,
+        );
+        entries.double(
+            GeoKeyDirectoryTag::ProjFalseOriginLong,
+            self.proj_false_origin_long,
+        );
+        entries.double(
+            GeoKeyDirectoryTag::ProjFalseOriginLat,
+            self.proj_false_origin_lat,
+        );
+        entries.double(
+            GeoKeyDirectoryTag::ProjFalseOriginEasting,
+            self.proj_false_origin_easting,
+        );
+        entries.double(
+            GeoKeyDirectoryTag::ProjFalseOriginNorthing,
+            self.proj_false_origin_northing,
+        );
+        entries.double(GeoKeyDirectoryTag::ProjCenterLong, self.proj_center_long);
+        entries.double(GeoKeyDirectoryTag::ProjCenterLat, self.proj_center_lat);
+        entries.double(
+            GeoKeyDirectoryTag::ProjCenterEasting,
+            self.proj_center_easting,
+        );
+        entries.double(
+            GeoKeyDirectoryTag::ProjCenterNorthing,
+            self.proj_center_northing,
+        );
+        entries.double(
+            GeoKeyDirectoryTag::ProjScaleAtNatOrigin,
+            self.proj_scale_at_nat_origin,
+        );
+        entries.double(
+            GeoKeyDirectoryTag::ProjScaleAtCenter,
+            self.proj_scale_at_center,
+        );
+        entries.double(
+            GeoKeyDirectoryTag::ProjAzimuthAngle,
+            self.proj_azimuth_angle,
+        );
+        entries.double(
+            GeoKeyDirectoryTag::ProjStraightVertPoleLong,
+            self.proj_straight_vert_pole_long,
+        );
+        entries.short(GeoKeyDirectoryTag::Vertical, self.vertical);
+        entries.string(
+            GeoKeyDirectoryTag::VerticalCitation,
+            &self.vertical_citation,
+        );
+        entries.short(GeoKeyDirectoryTag::VerticalDatum, self.vertical_datum);
+        entries.short(GeoKeyDirectoryTag::VerticalUnits, self.vertical_units);
+
+        let mut entries = entries.0;
+        entries.extend(
+            self.unknown_keys
+                .iter()
+                .filter(|(key_id, _)| GeoKeyDirectoryTag::try_from(**key_id).is_err())
+                .map(|(key_id, value)| (*key_id, value.clone())),
+        );
+        entries.sort_by_key(|(key_id, _)| *key_id);
+
+        entries
+    }
+
+    /// Encodes the directory as the data of the GeoKeyDirectoryTag, GeoDoubleParamsTag and
+    /// GeoAsciiParamsTag, with the keys sorted by ID.
+    ///
+    /// ASCII values are terminated by a pipe, which is included in their count, as required by the
+    /// specification. The NUL terminating the GeoAsciiParamsTag is added by the TIFF encoder.
+    pub(crate) fn to_tag_data(&self) -> GeoTiffResult<(Vec<u16>, Vec<f64>, String)> {
+        let mut encoder = GeoKeyEncoder::default();
+        for (key_id, value) in self.entries() {
+            match value {
+                GeoKeyValue::Short(value) => encoder.short(key_id, value),
+                GeoKeyValue::Double(value) => encoder.double(key_id, value),
+                GeoKeyValue::Ascii(value) => encoder.string(key_id, &value)?,
+            }
+        }
+
+        encoder.finish(
+            self.key_directory_version,
+            self.key_revision,
+            self.minor_revision,
+        )
+    }
+}
+
+impl GeoKeyDirectory {
+    /// Returns a builder for a directory, e.g.
+    /// `GeoKeyDirectory::builder().projected_type(3857).try_build()`.
+    pub fn builder() -> GeoKeyDirectoryBuilder {
+        GeoKeyDirectoryBuilder::new()
+    }
+}
+
+#[cfg(feature = "epsg")]
+impl GeoKeyDirectory {
+    /// Returns a copy of the directory with the keys implied by the EPSG codes in
+    /// `geographic_type` and `projected_type` filled in: the datum, ellipsoid, prime meridian,
+    /// units, and the map projection with its parameters.
+    ///
+    /// Keys already present are kept, so that consumers get a complete description of the
+    /// coordinate reference system whether the file carries only the code or the full set of
+    /// keys. Only the subset of the EPSG registry known to [`GeoKeyDirectoryBuilder::epsg`] is
+    /// resolved, other codes leave the directory unchanged.
+    pub fn resolve_epsg(&self) -> Self {
+        let mut directory = self.clone();
+
+        if let Some(crs) = directory.projected_type.and_then(epsg::lookup) {
+            if let CrsKind::Projected { geographic } = crs.kind {
+                directory.geographic_type.get_or_insert(geographic);
+                directory.proj_linear_units.get_or_insert(epsg::METRE);
+            }
+            if let Some(projection) = epsg::projection(crs.code) {
+                directory.resolve_projection(projection);
+            }
+        }
+
+        if let Some(parameters) = directory
+            .geographic_type
+            .and_then(epsg::geodetic_parameters)
+        {
+            directory
+                .geog_geodetic_datum
+                .get_or_insert(parameters.datum);
+            directory.geog_ellipsoid.get_or_insert(parameters.ellipsoid);
+            directory
+                .geog_semi_major_axis
+                .get_or_insert(parameters.semi_major_axis);
+            directory
+                .geog_inv_flattening
+                .get_or_insert(parameters.inv_flattening);
+            directory.geog_semi_minor_axis.get_or_insert(
+                parameters.semi_major_axis * (1.0 - 1.0 / parameters.inv_flattening),
+            );
+            directory.geog_prime_meridian.get_or_insert(epsg::GREENWICH);
+            directory.geog_prime_meridian_long.get_or_insert(0.0);
+            directory.geog_angular_units.get_or_insert(epsg::DEGREE);
+        }
+
+        directory
+    }
+
+    fn resolve_projection(&mut self, projection: epsg::Projection) {
+        use epsg::ProjectionMethod::*;
+
+        self.projection.get_or_insert(projection.conversion);
+        if let Some(method) = projection.method.coordinate_transformation_method() {
+            self.proj_coord_trans.get_or_insert(method.into());
+        }
+
+        match projection.method {
+            TransverseMercator(origin) | Mercator(origin) => {
+                self.proj_nat_origin_lat.get_or_insert(origin.lat);
+                self.proj_nat_origin_long.get_or_insert(origin.long);
+                self.proj_scale_at_nat_origin.get_or_insert(origin.scale);
+                self.proj_false_easting.get_or_insert(origin.false_easting);
+                self.proj_false_northing
+                    .get_or_insert(origin.false_northing);
+            }
+            LambertConicConformal2Sp {
+                std_parallel1,
+                std_parallel2,
+                false_origin_lat,
+                false_origin_long,
+                false_origin_easting,
+                false_origin_northing,
+            } => {
+                self.proj_std_parallel1.get_or_insert(std_parallel1);
+                self.proj_std_parallel2.get_or_insert(std_parallel2);
+                self.proj_false_origin_lat.get_or_insert(false_origin_lat);
+                self.proj_false_origin_long.get_or_insert(false_origin_long);
+                self.proj_false_origin_easting
+                    .get_or_insert(false_origin_easting);
+                self.proj_false_origin_northing
+                    .get_or_insert(false_origin_northing);
+            }
+            LambertAzimuthalEqualArea {
+                center_lat,
+                center_long,
+                false_easting,
+                false_northing,
+            } => {
+                self.proj_center_lat.get_or_insert(center_lat);
+                self.proj_center_long.get_or_insert(center_long);
+                self.proj_false_easting.get_or_insert(false_easting);
+                self.proj_false_northing.get_or_insert(false_northing);
+            }
+            ObliqueMercator {
+                center_lat,
+                center_long,
+                azimuth,
+                scale_at_center,
+                false_easting,
+                false_northing,
+            } => {
+                self.proj_center_lat.get_or_insert(center_lat);
+                self.proj_center_long.get_or_insert(center_long);
+                self.proj_azimuth_angle.get_or_insert(azimuth);
+                self.proj_scale_at_center.get_or_insert(scale_at_center);
+                self.proj_false_easting.get_or_insert(false_easting);
+                self.proj_false_northing.get_or_insert(false_northing);
+            }
+            PseudoMercator => {}
+        }
+    }
+}
+
+/// The value of a GeoKey.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeoKeyValue {
+    Short(u16),
+    Double(f64),
+    Ascii(String),
+}
+
+impl fmt::Display for GeoKeyValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeoKeyValue::Short(value) => value.fmt(f),
+            GeoKeyValue::Double(value) => value.fmt(f),
+            GeoKeyValue::Ascii(value) => write!(f, "{value:?}"),
+        }
+    }
+}
+
+/// Collects the keys present in a directory by ID in the order they are added.
+#[derive(Default)]
+struct GeoKeyEntries(Vec<(u16, GeoKeyValue)>);
+
+impl GeoKeyEntries {
+    fn short(&mut self, key_tag: GeoKeyDirectoryTag, value: Option<u16>) {
+        if let Some(value) = value {
+            self.0.push((key_tag.into(), GeoKeyValue::Short(value)));
+        }
+    }
+
+    fn double(&mut self, key_tag: GeoKeyDirectoryTag, value: Option<f64>) {
+        if let Some(value) = value {
+            self.0.push((key_tag.into(), GeoKeyValue::Double(value)));
+        }
+    }
+
+    fn string(&mut self, key_tag: GeoKeyDirectoryTag, value: &Option<String>) {
+        if let Some(value) = value {
+            self.0
+                .push((key_tag.into(), GeoKeyValue::Ascii(value.clone())));
+        }
+    }
+}
+
+/// Collects the entries of a GeoKeyDirectoryTag and the values stored in the parameter tags.
+#[derive(Default)]
+struct GeoKeyEncoder {
+    keys: Vec<[u16; 4]>,
+    double_params: Vec<f64>,
+    ascii_params: String,
+}
+
+impl GeoKeyEncoder {
+    fn short(&mut self, key_id: u16, value: u16) {
+        self.keys.push([key_id, 0, 1, value]);
+    }
+
+    fn double(&mut self, key_id: u16, value: f64) {
+        self.keys.push([
+            key_id,
+            Tag::GeoDoubleParamsTag.to_u16(),
+            1,
+            self.double_params.len() as u16,
+        ]);
+        self.double_params.push(value);
+    }
+
+    fn string(&mut self, key_id: u16, value: &str) -> GeoTiffResult<()> {
+        if !value.is_ascii() || value.contains('\0') {
+            return Err(GeoTiffError::Format(format!(
+                "Value of key `{}` must consist of ASCII characters other than NUL.",
+                key_name(key_id)
+            )));
+        }
+
+        let offset = self.ascii_params.len();
+        self.ascii_params.push_str(value);
+        self.ascii_params.push('|');
+        self.keys.push([
+            key_id,
+            Tag::GeoAsciiParamsTag.to_u16(),
+            (value.len() + 1) as u16,
+            offset as u16,
+        ]);
+
+        Ok(())
+    }
+
+    fn finish(
+        self,
+        key_directory_version: u16,
+        key_revision: u16,
+        minor_revision: u16,
+    ) -> GeoTiffResult<(Vec<u16>, Vec<f64>, String)> {
+        if self.ascii_params.len() > u16::MAX as usize {
+            return Err(GeoTiffError::Format(
+                "ASCII values of the GeoKeyDirectory exceed the maximum length.".into(),
+            ));
+        }
+
+        let mut directory_data = vec![
+            key_directory_version,
+            key_revision,
+            minor_revision,
+            self.keys.len() as u16,
+        ];
+        directory_data.extend(self.keys.into_iter().flatten());
+
+        Ok((directory_data, self.double_params, self.ascii_params))
+    }
+}
+
+/// A builder for GeoKey directories, e.g. to write a GeoTIFF in a given coordinate reference
+/// system, see [`GeoKeyDirectory::builder`].
+#[derive(Debug, Clone, Default)]
+pub struct GeoKeyDirectoryBuilder {
+    directory: GeoKeyDirectory,
+}
+
+/// Defines a builder method per key, setting its value.
+macro_rules! setters {
+    ($($name: ident: $type: tt),* $(,)?) => {
+        $(setters!(@setter $name, $type);)*
+    };
+    (@setter $name: ident, String) => {
+        #[doc = concat!("Sets the `", stringify!($name), "` key.")]
+        pub fn $name(mut self, $name: impl Into<String>) -> Self {
+            self.directory.$name = Some($name.into());
+            self
+        }
+    };
+    (@setter $name: ident, $type: ty) => {
+        #[doc = concat!("Sets the `", stringify!($name), "` key.")]
+        pub fn $name(mut self, $name: $type) -> Self {
+            self.directory.$name = Some($name);
+            self
+        }
+    };
+}
+
+impl GeoKeyDirectoryBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts from an existing directory, e.g. to replace its coordinate reference system.
+    pub fn from_directory(directory: GeoKeyDirectory) -> Self {
+        Self { directory }
+    }
+
+    /// Starts from a directory describing the coordinate reference system with the given EPSG
+    /// code, see [`GeoKeyDirectoryBuilder::epsg`].
+    pub fn from_epsg(code: u16) -> Option<Self> {
+        Self::new().epsg(code)
+    }
+
+    /// Sets the model type, coordinate reference system, units and citation keys for the
+    /// coordinate reference system with the given EPSG code, replacing any geographic or
+    /// projected keys set before.
+    ///
+    /// Only a subset of the EPSG registry is known, covering common geographic systems, Web
+    /// Mercator, UTM zones and a few national grids. Returns `None` for other codes.
+    pub fn epsg(mut self, code: u16) -> Option<Self> {
+        let crs = epsg::lookup(code)?;
+
+        let directory = &mut self.directory;
+        *directory = GeoKeyDirectory {
+            key_directory_version: directory.key_directory_version,
+            key_revision: directory.key_revision,
+            minor_revision: directory.minor_revision,
+            raster_type: directory.raster_type,
+            vertical: directory.vertical,
+            vertical_citation: directory.vertical_citation.take(),
+            vertical_datum: directory.vertical_datum,
+            vertical_units: directory.vertical_units,
+            ..GeoKeyDirectory::default()
+        };
+
+        match crs.kind {
+            CrsKind::Geographic => {
+                directory.model_type = Some(MODEL_TYPE_GEOGRAPHIC);
+                directory.geographic_type = Some(crs.code);
+                directory.geog_citation = Some(crs.name);
+                directory.geog_angular_units = Some(epsg::DEGREE);
+            }
+            CrsKind::Projected { geographic } => {
+                directory.model_type = Some(MODEL_TYPE_PROJECTED);
+                directory.projected_type = Some(crs.code);
+                directory.citation = Some(crs.name.clone());
+                directory.proj_citation = Some(crs.name);
+                directory.proj_linear_units = Some(epsg::METRE);
+                directory.geog_citation = Some(epsg::geographic_name(geographic).into());
+                directory.geog_angular_units = Some(epsg::DEGREE);
+            }
+        }
+
+        Some(self)
+    }
+
+    setters! {
+        model_type: u16,
+        raster_type: RasterType,
+        citation: String,
+        geographic_type: u16,
+        geog_citation: String,
+        geog_geodetic_datum: u16,
+        geog_prime_meridian: u16,
+        geog_linear_units: u16,
+        geog_linear_unit_size: f64,
+        geog_angular_units: u16,
+        geog_angular_unit_size: f64,
+        geog_ellipsoid: u16,
+        geog_semi_major_axis: f64,
+        geog_semi_minor_axis: f64,
+        geog_inv_flattening: f64,
+        geog_azimuth_units: u16,
+        geog_prime_meridian_long: f64,
+        projected_type: u16,
+        proj_citation: String,
+        projection: u16,
+        proj_coord_trans: u16,
+        proj_linear_units: u16,
+        proj_linear_unit_size: f64,
+        proj_std_parallel1: f64,
+        proj_std_parallel2: f64,
+        proj_nat_origin_long: f64,
+        proj_nat_origin_lat: f64,
+        proj_false_easting: f64,
+        proj_false_northing: f64,
+        proj_false_origin_long: f64,
+        proj_false_origin_lat: f64,
+        proj_false_origin_easting: f64,
+        proj_false_origin_northing: f64,
+        proj_center_long: f64,
+        proj_center_lat: f64,
+        proj_center_easting: f64,
+        proj_center_northing: f64,
+        proj_scale_at_nat_origin: f64,
+        proj_scale_at_center: f64,
+        proj_azimuth_angle: f64,
+        proj_straight_vert_pole_long: f64,
+        vertical: u16,
+        vertical_citation: String,
+        vertical_datum: u16,
+        vertical_units: u16,
+    }
+
+    /// Sets the `proj_coord_trans` key to the code of the given method.
+    pub fn coordinate_transformation_method(
+        mut self,
+        method: CoordinateTransformationMethod,
+    ) -> Self {
+        self.directory.proj_coord_trans = Some(method.into());
+        self
+    }
+
+    /// Returns the directory without checking the combination of keys, see
+    /// [`Self::try_build`].
+    pub fn build(self) -> GeoKeyDirectory {
+        self.directory
+    }
+
+    /// Returns the directory, setting the model type implied by a projected or geographic type if
+    /// it is missing, or fails if the keys contradict each other or a user-defined code lacks the
+    /// keys defining it.
+    pub fn try_build(self) -> GeoTiffResult<GeoKeyDirectory> {
+        let mut directory = self.directory;
+        if directory.model_type.is_none() {
+            if directory.projected_type.is_some() {
+                directory.model_type = Some(MODEL_TYPE_PROJECTED);
+            } else if directory.geographic_type.is_some() {
+                directory.model_type = Some(MODEL_TYPE_GEOGRAPHIC);
+            }
+        }
+
+        let invalid = |reason: &str| Err(GeoTiffError::Crs(format!("Invalid GeoKeys: {reason}")));
+        let has_projection = directory.projection.is_some() || directory.proj_coord_trans.is_some();
+        match directory.model_type {
+            Some(MODEL_TYPE_GEOGRAPHIC) if directory.projected_type.is_some() || has_projection => {
+                return invalid("a geographic model type has projected keys");
+            }
+            Some(MODEL_TYPE_PROJECTED) if directory.projected_type.is_none() && !has_projection => {
+                return invalid("a projected model type lacks the projected type or projection");
+            }
+            _ => {}
+        }
+        if directory.projected_type == Some(USER_DEFINED) && !has_projection {
+            return invalid("a user-defined projected type lacks the projection");
+        }
+        if directory.geographic_type == Some(USER_DEFINED)
+            && directory.geog_geodetic_datum.is_none()
+            && directory.geog_ellipsoid.is_none()
+            && directory.geog_semi_major_axis.is_none()
+        {
+            return invalid("a user-defined geographic type lacks the datum or ellipsoid");
+        }
+        for (name, units, unit_size) in [
+            (
+                "linear",
+                directory.geog_linear_units,
+                directory.geog_linear_unit_size,
+            ),
+            (
+                "angular",
+                directory.geog_angular_units,
+                directory.geog_angular_unit_size,
+            ),
+            (
+                "projected linear",
+                directory.proj_linear_units,
+                directory.proj_linear_unit_size,
+            ),
+        ] {
+            match (units, unit_size) {
+                (Some(USER_DEFINED), None) => {
+                    return invalid(&format!("user-defined {name} units lack the unit size"))
+                }
+                (Some(units), Some(_)) if units != USER_DEFINED => {
+                    return invalid(&format!(
+                        "{name} units {units} are not user-defined but have a unit size"
+                    ))
+                }
+                _ => {}
+            }
+        }
+
+        Ok(directory)
+    }
+
+    /// Validates the directory like [`Self::try_build`] and encodes it as the data of the
+    /// GeoKeyDirectoryTag, GeoDoubleParamsTag and GeoAsciiParamsTag, e.g. to write them with
+    /// another TIFF encoder.
+    pub fn to_tag_data(self) -> GeoTiffResult<(Vec<u16>, Vec<f64>, String)> {
+        self.try_build()?.to_tag_data()
     }
 }
 
@@ -600,14 +1340,60 @@ impl Default for GeoKeyDirectory {
             vertical_citation: None,
             vertical_datum: None,
             vertical_units: None,
+            unknown_keys: HashMap::new(),
         }
     }
 }
 
+/// Lists the version and the keys present, sorted by ID, leaving out absent keys.
+impl fmt::Debug for GeoKeyDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("GeoKeyDirectory");
+        debug
+            .field("key_directory_version", &self.key_directory_version)
+            .field("key_revision", &self.key_revision)
+            .field("minor_revision", &self.minor_revision);
+        for (key_id, value) in self.entries() {
+            debug.field(&key_name(key_id), &value);
+        }
+        debug.finish()
+    }
+}
+
+/// Writes the version followed by one line per key present, sorted by ID, e.g.
+///
+/// ```text
+/// GeoKeyDirectory 1.1.1
+/// ModelType (1024): 2
+/// GeogCitation (2049): "WGS 84"
+/// ```
+impl fmt::Display for GeoKeyDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "GeoKeyDirectory {}.{}.{}",
+            self.key_directory_version, self.key_revision, self.minor_revision
+        )?;
+        for (key_id, value) in self.entries() {
+            write!(f, "\n{} ({key_id}): {value}", key_name(key_id))?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the name of the key with the given ID, e.g. `GeogCitation`, or `GeoKey` followed by
+/// the ID if the key is unknown.
+fn key_name(key_id: u16) -> String {
+    match GeoKeyDirectoryTag::try_from(key_id) {
+        Ok(key_tag) => format!("{key_tag:?}"),
+        Err(_) => format!("GeoKey{key_id}"),
+    }
+}
+
 /// GeoTIFF key names and IDs.
 ///
 /// Ref: https://docs.ogc.org/is/19-008r4/19-008r4.html#_summary_of_geokey_ids_and_names
-#[derive(Debug, TryFromPrimitive, IntoPrimitive)]
+#[derive(Debug, Clone, Copy, TryFromPrimitive, IntoPrimitive)]
 #[repr(u16)]
 enum GeoKeyDirectoryTag {
     // GeoTIFF configuration keys
@@ -675,3 +1461,40 @@ pub enum RasterType {
     RasterPixelIsPoint = 2,
     UserDefined = 32767,
 }
+
+/// The method of the map projection of a user-defined projected CRS, as given by the
+/// ProjCoordTransGeoKey, see [`GeoKeyDirectory::coordinate_transformation_method`].
+///
+/// Ref: https://docs.ogc.org/is/19-008r4/19-008r4.html#_requirements_class_projcoordtransgeokey
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, TryFromPrimitive, IntoPrimitive)]
+#[repr(u16)]
+pub enum CoordinateTransformationMethod {
+    TransverseMercator = 1,
+    TransverseMercatorModifiedAlaska = 2,
+    ObliqueMercator = 3,
+    ObliqueMercatorLaborde = 4,
+    ObliqueMercatorRosenmund = 5,
+    ObliqueMercatorSpherical = 6,
+    Mercator = 7,
+    LambertConformalConic2Sp = 8,
+    LambertConformalConic1Sp = 9,
+    LambertAzimuthalEqualArea = 10,
+    AlbersEqualArea = 11,
+    AzimuthalEquidistant = 12,
+    EquidistantConic = 13,
+    Stereographic = 14,
+    PolarStereographic = 15,
+    ObliqueStereographic = 16,
+    Equirectangular = 17,
+    CassiniSoldner = 18,
+    Gnomonic = 19,
+    MillerCylindrical = 20,
+    Orthographic = 21,
+    Polyconic = 22,
+    Robinson = 23,
+    Sinusoidal = 24,
+    VanDerGrinten = 25,
+    NewZealandMapGrid = 26,
+    TransverseMercatorSouthOriented = 27,
+    UserDefined = 32767,
+}
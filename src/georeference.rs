@@ -0,0 +1,288 @@
+use geo_types::Coord;
+
+use crate::raster_space::RasterSpace;
+use crate::GeoTiff;
+
+/// The mean radius of the earth in metres, used for geographic CRSs without an ellipsoid.
+const EARTH_RADIUS: f64 = 6371008.8;
+
+/// The largest plausible absolute value of projected coordinates in metres, about the
+/// circumference of the earth.
+const MAX_PROJECTED_COORDINATE: f64 = 4.0e7;
+
+/// A ground control point tying a location in raster space to a location in model space, as
+/// given by one of multiple tie points of the ModelTiepointTag.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gcp {
+    pub pixel: Coord,
+    pub model: Coord,
+}
+
+/// A location of the raster checked by a [`GeoreferenceReport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReferenceLocation {
+    UpperLeft,
+    UpperRight,
+    LowerLeft,
+    LowerRight,
+    Center,
+}
+
+/// A location of the raster in raster space, model space and, if known, geographic coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReferencePoint {
+    pub location: ReferenceLocation,
+    pub raster: Coord,
+    pub model: Coord,
+    /// The longitude and latitude in degrees on WGS 84.
+    pub lon_lat: Option<Coord>,
+}
+
+/// A finding of a [`GeoreferenceReport`] hinting at a missing or wrong georeference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum GeoreferenceIssue {
+    /// The file has no coordinate transform, so model space equals raster space.
+    MissingTransform,
+    /// The GeoKeys do not define the coordinate reference system.
+    MissingCrs,
+    /// The pixels have a size of zero or one that is not finite.
+    DegeneratePixelSize,
+    /// The point lies outside the valid range of coordinates of the coordinate reference system.
+    OutsideCrsBounds(ReferenceLocation),
+    /// The point could not be reprojected to WGS 84.
+    ReprojectionFailed(ReferenceLocation),
+    /// The CRS is projected but all points lie within the range of longitudes and latitudes,
+    /// hinting at geographic coordinates.
+    ProjectedLooksGeographic,
+}
+
+/// A sanity check of the georeference of a GeoTIFF, see [`GeoTiff::georeference_report`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoreferenceReport {
+    /// The four corners of the raster and its center.
+    pub points: Vec<ReferencePoint>,
+    /// The width and height of the pixel at the center of the raster in metres, if the units of
+    /// the coordinate reference system are known.
+    pub pixel_size: Option<(f64, f64)>,
+    pub issues: Vec<GeoreferenceIssue>,
+}
+
+impl GeoreferenceReport {
+    /// Returns whether no issues were found.
+    pub fn is_plausible(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl GeoTiff {
+    /// Checks whether the GeoTIFF is georeferenced plausibly, e.g. before ingesting it.
+    ///
+    /// The report lists the corners and the center of the raster in model space, the size of the
+    /// pixels in metres and the issues found. With the `proj` feature, the points are reprojected
+    /// to WGS 84, otherwise their longitudes and latitudes are only known for geographic CRSs in
+    /// degrees, ignoring differences between datums.
+    pub fn georeference_report(&self) -> GeoreferenceReport {
+        let directory = &self.geo_key_directory;
+        let mut issues = Vec::new();
+        if self.coordinate_transform.is_none() {
+            issues.push(GeoreferenceIssue::MissingTransform);
+        }
+        let projected = self.is_projected();
+        if projected.is_none() {
+            issues.push(GeoreferenceIssue::MissingCrs);
+        }
+
+        let (width, height) = (self.raster_width as f64, self.raster_height as f64);
+        let offset = self.raster_offset();
+        let mut points: Vec<_> = [
+            (ReferenceLocation::UpperLeft, 0.0, 0.0),
+            (ReferenceLocation::UpperRight, width, 0.0),
+            (ReferenceLocation::LowerLeft, 0.0, height),
+            (ReferenceLocation::LowerRight, width, height),
+            (ReferenceLocation::Center, width / 2.0, height / 2.0),
+        ]
+        .into_iter()
+        .map(|(location, x, y)| {
+            let raster = Coord {
+                x: x + offset,
+                y: y + offset,
+            };
+            ReferencePoint {
+                location,
+                raster,
+                model: self.transform_to_model(&raster),
+                lon_lat: None,
+            }
+        })
+        .collect();
+
+        self.locate_points(&mut points, projected, &mut issues);
+
+        let in_degrees = directory
+            .angular_unit()
+            .is_some_and(|unit| unit.name == "degree");
+        for point in &points {
+            let in_bounds = point.lon_lat.as_ref().is_none_or(is_lon_lat)
+                && match projected {
+                    Some(true) => {
+                        point.model.x.abs() <= MAX_PROJECTED_COORDINATE
+                            && point.model.y.abs() <= MAX_PROJECTED_COORDINATE
+                    }
+                    Some(false) if in_degrees => is_lon_lat(&point.model),
+                    _ => true,
+                };
+            if !in_bounds {
+                issues.push(GeoreferenceIssue::OutsideCrsBounds(point.location));
+            }
+        }
+        if projected == Some(true)
+            && self.coordinate_transform.is_some()
+            && points.iter().all(|point| is_lon_lat(&point.model))
+        {
+            issues.push(GeoreferenceIssue::ProjectedLooksGeographic);
+        }
+
+        let pixel_size = projected.and_then(|projected| self.pixel_size_in_metres(projected));
+        if let Some((pixel_width, pixel_height)) = pixel_size {
+            if !(pixel_width.is_finite() && pixel_height.is_finite())
+                || pixel_width == 0.0
+                || pixel_height == 0.0
+            {
+                issues.push(GeoreferenceIssue::DegeneratePixelSize);
+            }
+        }
+
+        GeoreferenceReport {
+            points,
+            pixel_size,
+            issues,
+        }
+    }
+
+    /// Sets the longitudes and latitudes of the points by reprojecting them to WGS 84.
+    #[cfg(feature = "proj")]
+    fn locate_points(
+        &self,
+        points: &mut [ReferencePoint],
+        projected: Option<bool>,
+        issues: &mut Vec<GeoreferenceIssue>,
+    ) {
+        if projected.is_none() {
+            return;
+        }
+        let Ok(proj) = self
+            .model_crs()
+            .and_then(|crs| Ok(proj::Proj::new_known_crs(&crs, "EPSG:4326", None)?))
+        else {
+            issues.extend(
+                points
+                    .iter()
+                    .map(|point| GeoreferenceIssue::ReprojectionFailed(point.location)),
+            );
+            return;
+        };
+
+        for point in points {
+            match proj.convert(point.model) {
+                Ok(lon_lat) => point.lon_lat = Some(lon_lat),
+                Err(_) => issues.push(GeoreferenceIssue::ReprojectionFailed(point.location)),
+            }
+        }
+    }
+
+    /// Sets the longitudes and latitudes of the points for geographic CRSs in degrees.
+    #[cfg(not(feature = "proj"))]
+    fn locate_points(
+        &self,
+        points: &mut [ReferencePoint],
+        projected: Option<bool>,
+        _issues: &mut Vec<GeoreferenceIssue>,
+    ) {
+        let in_degrees = self
+            .geo_key_directory
+            .angular_unit()
+            .is_some_and(|unit| unit.name == "degree");
+        if projected == Some(false) && in_degrees {
+            for point in points {
+                point.lon_lat = Some(point.model);
+            }
+        }
+    }
+
+    /// Returns the ground sample distance, i.e. the width and height of a pixel at the center of
+    /// the raster in metres, or `None` if the raster is not georeferenced or the units of its CRS
+    /// are unknown.
+    ///
+    /// The size of the pixels is converted from the linear units of projected CRSs, and from the
+    /// angular units of geographic CRSs on a sphere with the radius of the ellipsoid.
+    pub fn ground_sample_distance(&self) -> Option<Coord> {
+        self.coordinate_transform.as_ref()?;
+        let (x, y) = self.pixel_size_in_metres(self.is_projected()?)?;
+        Some(Coord { x, y })
+    }
+
+    /// Returns whether the CRS is projected rather than geographic, or `None` if it is neither or
+    /// not given.
+    fn is_projected(&self) -> Option<bool> {
+        let directory = &self.geo_key_directory;
+        directory.is_projected().filter(|_| {
+            directory.model_type.is_some()
+                || directory.projected_type.is_some()
+                || directory.geographic_type.is_some()
+        })
+    }
+
+    /// Returns the distances in metres between the center of the raster and its neighbours to the
+    /// right and below.
+    fn pixel_size_in_metres(&self, projected: bool) -> Option<(f64, f64)> {
+        let directory = &self.geo_key_directory;
+        let center = Coord {
+            x: self.raster_width as f64 / 2.0,
+            y: self.raster_height as f64 / 2.0,
+        };
+        let origin = self.transform_to_model(&center);
+        let right = self.transform_to_model(&Coord {
+            x: center.x + 1.0,
+            y: center.y,
+        });
+        let below = self.transform_to_model(&Coord {
+            x: center.x,
+            y: center.y + 1.0,
+        });
+
+        if projected {
+            let factor = directory
+                .linear_unit(directory.proj_linear_units, directory.proj_linear_unit_size)?
+                .factor;
+            return Some((
+                distance(&origin, &right) * factor,
+                distance(&origin, &below) * factor,
+            ));
+        }
+
+        Some((
+            self.geographic_distance(&origin, &right)?,
+            self.geographic_distance(&origin, &below)?,
+        ))
+    }
+
+    /// Returns the distance in metres between the given nearby locations in a geographic CRS.
+    pub(crate) fn geographic_distance(&self, from: &Coord, to: &Coord) -> Option<f64> {
+        // Distances on a sphere with the radius of the ellipsoid, which is accurate enough for
+        // the size of a pixel
+        let directory = &self.geo_key_directory;
+        let factor = directory.angular_unit()?.factor;
+        let radius = directory.geog_semi_major_axis.unwrap_or(EARTH_RADIUS);
+        let cos_lat = (from.y * factor).cos();
+        Some(radius * factor * ((to.x - from.x) * cos_lat).hypot(to.y - from.y))
+    }
+}
+
+fn distance(from: &Coord, to: &Coord) -> f64 {
+    (to.x - from.x).hypot(to.y - from.y)
+}
+
+fn is_lon_lat(coord: &Coord) -> bool {
+    (-180.0..=360.0).contains(&coord.x) && (-90.0..=90.0).contains(&coord.y)
+}
@@ -0,0 +1,114 @@
+use std::ops::Range;
+
+use crate::raster_space::RasterSpace;
+use crate::{GeoTiff, GeoTiffResult};
+
+/// The frequencies of the values of a sample in equally wide bins, see [`GeoTiff::histogram`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    /// The lower bound of the first bin.
+    pub minimum: f64,
+    /// The upper bound of the last bin, which is included in the last bin.
+    pub maximum: f64,
+    /// The number of values in each bin.
+    pub counts: Vec<u64>,
+    /// The number of values outside the bins.
+    pub outside: u64,
+}
+
+impl Histogram {
+    /// Returns the width of each bin.
+    pub fn bin_width(&self) -> f64 {
+        (self.maximum - self.minimum) / self.counts.len() as f64
+    }
+
+    /// Returns the range of values counted by the given bin.
+    pub fn bin_range(&self, bin: usize) -> Range<f64> {
+        let bin_width = self.bin_width();
+        self.minimum + bin as f64 * bin_width..self.minimum + (bin + 1) as f64 * bin_width
+    }
+
+    /// Returns the number of values in all bins.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    fn bin(&self, value: f64) -> Option<usize> {
+        if !(self.minimum..=self.maximum).contains(&value) {
+            return None;
+        }
+        let bin_width = self.bin_width();
+        if bin_width == 0.0 {
+            return Some(0);
+        }
+
+        Some((((value - self.minimum) / bin_width) as usize).min(self.counts.len() - 1))
+    }
+}
+
+impl GeoTiff {
+    /// Counts the values of the specified sample in the given number of equally wide bins
+    /// spanning the given range of values, e.g. for contrast stretching or classification.
+    ///
+    /// Without a range, the bins span the minimum to the maximum value, or are empty bins at zero
+    /// if there are no values. Pixels holding the no data value or NaN and pixels marked invalid
+    /// by the transparency mask are not counted.
+    ///
+    /// Fails with [`GeoTiffError::SampleOutOfBounds`](crate::GeoTiffError::SampleOutOfBounds) if
+    /// the image does not have the sample.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of bins is zero.
+    pub fn histogram(
+        &self,
+        sample: usize,
+        bins: usize,
+        range: Option<(f64, f64)>,
+    ) -> GeoTiffResult<Histogram> {
+        assert!(bins > 0, "The number of bins is zero");
+        self.sample_in_bounds(sample)?;
+
+        let (minimum, maximum) = range.unwrap_or_else(|| {
+            let (minimum, maximum) = self.known_values(sample).fold(
+                (f64::INFINITY, f64::NEG_INFINITY),
+                |(minimum, maximum), value| (minimum.min(value), maximum.max(value)),
+            );
+            if minimum > maximum {
+                (0.0, 0.0)
+            } else {
+                (minimum, maximum)
+            }
+        });
+
+        let mut histogram = Histogram {
+            minimum,
+            maximum,
+            counts: vec![0; bins],
+            outside: 0,
+        };
+        for value in self.known_values(sample) {
+            match histogram.bin(value) {
+                Some(bin) => histogram.counts[bin] += 1,
+                None => histogram.outside += 1,
+            }
+        }
+
+        Ok(histogram)
+    }
+
+    /// Returns the values of the specified sample, skipping pixels holding the no data value or
+    /// NaN and pixels marked invalid by the transparency mask.
+    pub(crate) fn known_values(&self, sample: usize) -> impl Iterator<Item = f64> + '_ {
+        let layout = self.buffer_layout();
+        (0..self.raster_width * self.raster_height).filter_map(move |pixel| {
+            if self.mask.as_ref().is_some_and(|mask| !mask[pixel]) {
+                return None;
+            }
+
+            let (col, row) = (pixel % self.raster_width, pixel / self.raster_width);
+            let value = self.raster_data.get::<f64>(layout.index(col, row, sample));
+            (!value.is_nan() && Some(value) != self.no_data).then_some(value)
+        })
+    }
+}
@@ -0,0 +1,106 @@
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+
+use crate::range_cache::RangeCache;
+
+/// A reader fetching byte ranges of a remote file over HTTP(S) using range requests.
+///
+/// Combined with a [`GeoTiffReader`](crate::GeoTiffReader), only the header, the image file
+/// directories and the strips or tiles actually touched by a query are downloaded, which makes it
+/// possible to sample Cloud Optimized GeoTIFFs hosted on a web server. Fetched bytes are kept in
+/// memory, so that they are requested only once.
+pub struct HttpRangeReader {
+    agent: ureq::Agent,
+    url: String,
+    cache: RangeCache,
+    position: u64,
+}
+
+impl HttpRangeReader {
+    /// Creates a reader for the file at the given URL. No request is made until the first read.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self::with_agent(ureq::Agent::new(), url)
+    }
+
+    /// Creates a reader for the file at the given URL, sending requests with the given agent,
+    /// e.g. to configure timeouts, proxies or TLS.
+    pub fn with_agent(agent: ureq::Agent, url: impl Into<String>) -> Self {
+        Self {
+            agent,
+            url: url.into(),
+            cache: RangeCache::default(),
+            position: 0,
+        }
+    }
+
+    fn fetch(&mut self, range: Range<u64>) -> io::Result<()> {
+        trace_span!("fetch", start = range.start, end = range.end);
+        let response = match self
+            .agent
+            .get(&self.url)
+            .set("Range", &format!("bytes={}-{}", range.start, range.end - 1))
+            .call()
+        {
+            Ok(response) => response,
+            // The range starts beyond the end of the file
+            Err(ureq::Error::Status(416, _)) => {
+                self.cache.insert(range, Vec::new());
+                return Ok(());
+            }
+            Err(error) => return Err(io::Error::other(error)),
+        };
+
+        let mut data = Vec::new();
+        match response.status() {
+            206 => {
+                response
+                    .into_reader()
+                    .take(range.end - range.start)
+                    .read_to_end(&mut data)?;
+                trace_event!(bytes = data.len(), "fetched range");
+                self.cache.insert(range, data);
+            }
+            // The server does not support range requests and sent the whole file
+            200 => {
+                response.into_reader().read_to_end(&mut data)?;
+                trace_event!(bytes = data.len(), "fetched whole file");
+                self.cache.insert(0..u64::MAX, data);
+            }
+            status => {
+                return Err(io::Error::other(format!(
+                    "Unexpected HTTP status {status} fetching bytes {range:?} of {}",
+                    self.url
+                )))
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Read for HttpRangeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = match self.cache.read_at(self.position, buf) {
+            Some(count) => {
+                trace_event!(position = self.position, cache_hit = true, "read");
+                count
+            }
+            None => {
+                trace_event!(position = self.position, cache_hit = false, "read");
+                self.fetch(RangeCache::range_to_fetch(self.position, buf))?;
+                // The fetched range starts at the current position or marks the end of the file
+                self.cache.read_at(self.position, buf).unwrap_or(0)
+            }
+        };
+
+        self.position += count as u64;
+        Ok(count)
+    }
+}
+
+impl Seek for HttpRangeReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = self.cache.seek(self.position, pos)?;
+        Ok(self.position)
+    }
+}
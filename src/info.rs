@@ -0,0 +1,254 @@
+use std::fmt::Write;
+
+use crate::gdal_metadata::GdalMetadata;
+use crate::geo_key_directory::GeoKeyDirectory;
+use crate::layout::RasterDataType;
+use crate::metadata::GeoTiffMetadata;
+use crate::tiff_tags::DescriptiveTags;
+use crate::GeoTiff;
+
+impl GeoTiff {
+    /// Returns a JSON document summarizing the raster in the spirit of `gdalinfo -json`, e.g. to
+    /// populate a catalog or to inspect a file while debugging.
+    ///
+    /// The document holds the `size`, the `coordinateSystem` as WKT, the `geoTransform` and the
+    /// `cornerCoordinates` derived from it, the `metadata` of the dataset, i.e. the descriptive
+    /// TIFF tags and the GDAL metadata, and the `bands` with their type, no-data value and GDAL
+    /// metadata, as well as the sizes of the overviews built by [`GeoTiff::build_overviews`].
+    /// Members that are unknown are omitted.
+    ///
+    /// See [`GeoTiffMetadata::info_json`] to list the overviews stored in a file instead.
+    pub fn info_json(&self) -> String {
+        Info {
+            width: self.raster_width,
+            height: self.raster_height,
+            num_samples: self.num_samples,
+            data_type: self.data_type(),
+            geo_key_directory: &self.geo_key_directory,
+            geotransform: self.geotransform(),
+            no_data: self.no_data,
+            descriptive_tags: &self.descriptive_tags,
+            gdal_metadata: self.gdal_metadata.as_ref(),
+            overviews: self
+                .overviews()
+                .iter()
+                .map(|overview| (overview.raster_width, overview.raster_height))
+                .collect(),
+        }
+        .to_json()
+    }
+}
+
+impl GeoTiffMetadata {
+    /// Returns a JSON document summarizing the file in the spirit of `gdalinfo -json`, see
+    /// [`GeoTiff::info_json`], where the bands list the `overviews` stored in the file.
+    pub fn info_json(&self) -> String {
+        Info {
+            width: self.width,
+            height: self.height,
+            num_samples: self.num_samples,
+            data_type: self.data_type,
+            geo_key_directory: &self.geo_key_directory,
+            geotransform: self.geotransform,
+            no_data: self.no_data,
+            descriptive_tags: &self.descriptive_tags,
+            gdal_metadata: None,
+            overviews: self
+                .overviews
+                .iter()
+                .map(|overview| (overview.width, overview.height))
+                .collect(),
+        }
+        .to_json()
+    }
+}
+
+/// The properties summarized by `info_json`.
+struct Info<'a> {
+    width: usize,
+    height: usize,
+    num_samples: usize,
+    data_type: RasterDataType,
+    geo_key_directory: &'a GeoKeyDirectory,
+    geotransform: Option<[f64; 6]>,
+    no_data: Option<f64>,
+    descriptive_tags: &'a DescriptiveTags,
+    gdal_metadata: Option<&'a GdalMetadata>,
+    /// The width and height of each overview.
+    overviews: Vec<(usize, usize)>,
+}
+
+impl Info<'_> {
+    fn to_json(&self) -> String {
+        let mut members = vec![
+            ("driverShortName", string("GTiff")),
+            ("size", array([self.width, self.height])),
+        ];
+
+        if let Some(wkt) = self.geo_key_directory.to_wkt() {
+            members.push(("coordinateSystem", object([("wkt", string(&wkt))])));
+        }
+
+        if let Some(geotransform) = self.geotransform {
+            members.push(("geoTransform", array(geotransform.map(number))));
+
+            let [x, a, b, y, d, e] = geotransform;
+            let corner = |col: f64, row: f64| {
+                array([number(x + a * col + b * row), number(y + d * col + e * row)])
+            };
+            let (width, height) = (self.width as f64, self.height as f64);
+            members.push((
+                "cornerCoordinates",
+                object([
+                    ("upperLeft", corner(0.0, 0.0)),
+                    ("lowerLeft", corner(0.0, height)),
+                    ("lowerRight", corner(width, height)),
+                    ("upperRight", corner(width, 0.0)),
+                    ("center", corner(width / 2.0, height / 2.0)),
+                ]),
+            ));
+        }
+
+        let mut metadata = self
+            .descriptive_tags
+            .tags()
+            .map(|(tag, value)| {
+                let name = format!("TIFFTAG_{tag:?}").to_uppercase();
+                (name, string(value))
+            })
+            .collect::<Vec<_>>();
+        metadata.extend(self.gdal_items(None));
+        if !metadata.is_empty() {
+            members.push(("metadata", object([("", object(metadata))])));
+        }
+
+        let bands = (0..self.num_samples).map(|sample| self.band_json(sample));
+        members.push(("bands", array(bands)));
+
+        object(members)
+    }
+
+    fn band_json(&self, sample: usize) -> String {
+        let mut members = vec![
+            ("band", (sample + 1).to_string()),
+            ("type", string(gdal_type_name(self.data_type))),
+        ];
+
+        if let Some(gdal_metadata) = self.gdal_metadata {
+            if let Some(description) = gdal_metadata.description(sample) {
+                members.push(("description", string(description)));
+            }
+            if let Some(scale) = gdal_metadata.scale(sample) {
+                members.push(("scale", number(scale)));
+            }
+            if let Some(offset) = gdal_metadata.offset(sample) {
+                members.push(("offset", number(offset)));
+            }
+            if let Some(unit_type) = gdal_metadata.unit_type(sample) {
+                members.push(("unit", string(unit_type)));
+            }
+        }
+
+        if let Some(no_data) = self.no_data {
+            members.push(("noDataValue", number(no_data)));
+        }
+
+        if !self.overviews.is_empty() {
+            let overviews = self
+                .overviews
+                .iter()
+                .map(|&(width, height)| object([("size", array([width, height]))]));
+            members.push(("overviews", array(overviews)));
+        }
+
+        let metadata = self.gdal_items(Some(sample)).collect::<Vec<_>>();
+        if !metadata.is_empty() {
+            members.push(("metadata", object([("", object(metadata))])));
+        }
+
+        object(members)
+    }
+
+    /// Returns the GDAL metadata items of the default domain without a role for the given sample
+    /// or the whole dataset, as the roles are summarized separately.
+    fn gdal_items(&self, sample: Option<usize>) -> impl Iterator<Item = (String, String)> + '_ {
+        self.gdal_metadata
+            .into_iter()
+            .flat_map(|gdal_metadata| &gdal_metadata.items)
+            .filter(move |item| {
+                item.sample == sample && item.role.is_none() && item.domain.is_none()
+            })
+            .map(|item| (item.name.clone(), string(&item.value)))
+    }
+}
+
+/// Returns the name GDAL uses for the data type.
+fn gdal_type_name(data_type: RasterDataType) -> &'static str {
+    match data_type {
+        RasterDataType::U8 => "Byte",
+        RasterDataType::U16 => "UInt16",
+        RasterDataType::U32 => "UInt32",
+        RasterDataType::U64 => "UInt64",
+        RasterDataType::F16 => "Float16",
+        RasterDataType::F32 => "Float32",
+        RasterDataType::F64 => "Float64",
+        RasterDataType::I8 => "Int8",
+        RasterDataType::I16 => "Int16",
+        RasterDataType::I32 => "Int32",
+        RasterDataType::I64 => "Int64",
+        RasterDataType::CI16 => "CInt16",
+        RasterDataType::CI32 => "CInt32",
+        RasterDataType::CF32 => "CFloat32",
+        RasterDataType::CF64 => "CFloat64",
+    }
+}
+
+/// Returns a JSON object with the given members, which are already encoded as JSON.
+fn object<K: AsRef<str>>(members: impl IntoIterator<Item = (K, String)>) -> String {
+    let members = members
+        .into_iter()
+        .map(|(name, value)| format!("{}:{value}", string(name.as_ref())))
+        .collect::<Vec<_>>();
+    format!("{{{}}}", members.join(","))
+}
+
+/// Returns a JSON array with the given elements, which are encoded as JSON if they are not
+/// already.
+fn array<T: ToString>(elements: impl IntoIterator<Item = T>) -> String {
+    let elements = elements
+        .into_iter()
+        .map(|element| element.to_string())
+        .collect::<Vec<_>>();
+    format!("[{}]", elements.join(","))
+}
+
+/// Returns the number as JSON, writing non-finite numbers as strings like GDAL does.
+fn number(value: f64) -> String {
+    match value {
+        _ if value.is_nan() => string("NaN"),
+        f64::INFINITY => string("Infinity"),
+        f64::NEG_INFINITY => string("-Infinity"),
+        _ => value.to_string(),
+    }
+}
+
+/// Returns the string as JSON, escaping quotes, backslashes and control characters.
+fn string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for char in value.chars() {
+        match char {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            _ if char.is_control() => {
+                let _ = write!(json, "\\u{:04x}", char as u32);
+            }
+            _ => json.push(char),
+        }
+    }
+    json.push('"');
+    json
+}
@@ -0,0 +1,187 @@
+use geo_types::{Coord, LineString};
+
+use crate::raster_space::RasterSpace;
+use crate::{GeoTiff, GeoTiffResult};
+
+/// The method used to estimate values between pixel centers, see [`GeoTiff::sample`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Interpolation {
+    /// The value of the pixel containing the location.
+    #[default]
+    Nearest,
+    /// The linear interpolation between the centers of the four nearest pixels.
+    Bilinear,
+    /// The cubic convolution of the sixteen nearest pixels, with the Catmull-Rom kernel.
+    Bicubic,
+}
+
+impl GeoTiff {
+    /// Returns the value at the given location for the specified sample, estimated with the given
+    /// interpolation method, or `None` if the location lies outside the raster or its value is
+    /// unknown. The coordinates are in model space.
+    ///
+    /// Pixels holding the no data value or NaN and pixels marked invalid by the transparency mask
+    /// are unknown. If any of the pixels used for interpolation is unknown, the value of the pixel
+    /// containing the location is returned instead. Near the edges, the pixels at the edge are
+    /// repeated.
+    ///
+    /// Fails with [`GeoTiffError::SampleOutOfBounds`](crate::GeoTiffError::SampleOutOfBounds) if
+    /// the image does not have the sample.
+    pub fn sample(
+        &self,
+        coord: &Coord,
+        sample: usize,
+        interpolation: Interpolation,
+    ) -> GeoTiffResult<Option<f64>> {
+        self.sample_in_bounds(sample)?;
+        Ok(self.interpolate(coord, sample, interpolation))
+    }
+
+    /// Returns the value at the given location for the specified sample, which is in bounds, see
+    /// [`Self::sample`].
+    fn interpolate(
+        &self,
+        coord: &Coord,
+        sample: usize,
+        interpolation: Interpolation,
+    ) -> Option<f64> {
+        let (col, row) = self.pixel_at(coord)?;
+        let nearest = self.known_value(col as isize, row as isize, sample)?;
+
+        // The location relative to the center of the pixel at the top left of the neighbourhood
+        let mut raster = self.transform_to_raster(coord);
+        let offset = self.raster_offset() + 0.5;
+        raster.x -= offset;
+        raster.y -= offset;
+        let (x, y) = (raster.x.floor(), raster.y.floor());
+        let (tx, ty) = (raster.x - x, raster.y - y);
+        let (x, y) = (x as isize, y as isize);
+
+        let interpolated = match interpolation {
+            Interpolation::Nearest => return Some(nearest),
+            Interpolation::Bilinear => self.convolve(x, y, 0..2, sample, |dx, dy| {
+                linear_weight(tx, dx) * linear_weight(ty, dy)
+            }),
+            Interpolation::Bicubic => self.convolve(x, y, -1..3, sample, |dx, dy| {
+                cubic_weight(tx - dx as f64) * cubic_weight(ty - dy as f64)
+            }),
+        };
+
+        Some(interpolated.unwrap_or(nearest))
+    }
+
+    /// Returns the values along the given line for the specified sample, e.g. an elevation
+    /// profile along a route, as pairs of the distance from the start of the line and the value
+    /// estimated with the given interpolation method, see [`Self::sample`].
+    ///
+    /// The line is sampled at the given spacing and at its end. The coordinates and distances are
+    /// in model space.
+    ///
+    /// Fails with [`GeoTiffError::SampleOutOfBounds`](crate::GeoTiffError::SampleOutOfBounds) if
+    /// the image does not have the sample.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the spacing is not positive and finite.
+    pub fn sample_along(
+        &self,
+        line: &LineString,
+        spacing: f64,
+        sample: usize,
+        interpolation: Interpolation,
+    ) -> GeoTiffResult<Vec<(f64, Option<f64>)>> {
+        assert!(
+            spacing > 0.0 && spacing.is_finite(),
+            "Spacing {spacing} is not positive and finite"
+        );
+        self.sample_in_bounds(sample)?;
+
+        let mut profile = Vec::new();
+        let mut sample_at = |distance: f64, coord: Coord| {
+            profile.push((distance, self.interpolate(&coord, sample, interpolation)));
+        };
+
+        // The distance from the start of the line to the start of the current segment
+        let mut start = 0.0;
+        // The index of the next point to sample, multiplied by the spacing
+        let mut next = 0usize;
+        for segment in line.lines() {
+            let length = segment.dx().hypot(segment.dy());
+            while (next as f64) * spacing < start + length {
+                let distance = next as f64 * spacing;
+                let t = (distance - start) / length;
+                sample_at(distance, segment.start + segment.delta() * t);
+                next += 1;
+            }
+            start += length;
+        }
+        if let Some(&end) = line.0.last() {
+            sample_at(start, end);
+        }
+
+        Ok(profile)
+    }
+
+    /// Returns the sum of the values of the pixels around the given one, weighted by the given
+    /// function of their offsets, or `None` if any of them is unknown.
+    pub(crate) fn convolve(
+        &self,
+        x: isize,
+        y: isize,
+        offsets: std::ops::Range<isize>,
+        sample: usize,
+        weight: impl Fn(isize, isize) -> f64,
+    ) -> Option<f64> {
+        let mut sum = 0.0;
+        for dy in offsets.clone() {
+            for dx in offsets.clone() {
+                sum += weight(dx, dy) * self.known_value(x + dx, y + dy, sample)?;
+            }
+        }
+
+        Some(sum)
+    }
+
+    /// Returns the value of the given pixel, clamped to the raster, or `None` if it is unknown.
+    pub(crate) fn known_value(&self, col: isize, row: isize, sample: usize) -> Option<f64> {
+        let col = col.clamp(0, self.raster_width as isize - 1) as usize;
+        let row = row.clamp(0, self.raster_height as isize - 1) as usize;
+        if self
+            .mask
+            .as_ref()
+            .is_some_and(|mask| !mask[row * self.raster_width + col])
+        {
+            return None;
+        }
+
+        let value = self
+            .raster_data
+            .get::<f64>(self.buffer_layout().index(col, row, sample));
+        if value.is_nan() || Some(value) == self.no_data {
+            return None;
+        }
+
+        Some(value)
+    }
+}
+
+/// Returns the weight of the pixel at the given offset (0 or 1) for the given fraction.
+pub(crate) fn linear_weight(fraction: f64, offset: isize) -> f64 {
+    if offset == 0 {
+        1.0 - fraction
+    } else {
+        fraction
+    }
+}
+
+/// Returns the Catmull-Rom weight of a pixel at the given distance.
+fn cubic_weight(distance: f64) -> f64 {
+    let distance = distance.abs();
+    if distance < 1.0 {
+        (1.5 * distance - 2.5) * distance * distance + 1.0
+    } else if distance < 2.0 {
+        ((-0.5 * distance + 2.5) * distance - 4.0) * distance + 2.0
+    } else {
+        0.0
+    }
+}
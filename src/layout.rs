@@ -0,0 +1,163 @@
+use tiff::tags::SampleFormat;
+
+/// The values of the SampleFormat tag for complex integers and floating point numbers, whose real
+/// and imaginary parts each take half of the bits of a sample.
+pub(crate) const COMPLEX_INT_SAMPLE_FORMAT: u16 = 5;
+pub(crate) const COMPLEX_IEEEFP_SAMPLE_FORMAT: u16 = 6;
+
+/// The arrangement of the samples of multi-band raster data in memory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Interleave {
+    /// Band interleaved by pixel (BIP): the samples of each pixel are stored next to each other.
+    #[default]
+    Pixel,
+    /// Band sequential (BSQ): each band is stored as a contiguous image.
+    Band,
+}
+
+/// The order of the pixels of raster data in memory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ArrayOrder {
+    /// The pixels of each row are stored next to each other (C order).
+    #[default]
+    RowMajor,
+    /// The pixels of each column are stored next to each other (Fortran order).
+    ColumnMajor,
+}
+
+/// The type of the values raster data is decoded to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RasterDataType {
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+    F16,
+    F32,
+    F64,
+    /// Complex numbers with 16-bit integer parts, as stored by SAR products.
+    CI16,
+    /// Complex numbers with 32-bit integer parts.
+    CI32,
+    /// Complex numbers with 32-bit floating point parts, as stored by interferometric SAR
+    /// products.
+    CF32,
+    /// Complex numbers with 64-bit floating point parts.
+    CF64,
+}
+
+impl RasterDataType {
+    /// Returns the number of bits per sample and the sample format of values of this type.
+    pub fn sample_type(&self) -> (u16, SampleFormat) {
+        match self {
+            RasterDataType::U8 => (8, SampleFormat::Uint),
+            RasterDataType::U16 => (16, SampleFormat::Uint),
+            RasterDataType::U32 => (32, SampleFormat::Uint),
+            RasterDataType::U64 => (64, SampleFormat::Uint),
+            RasterDataType::I8 => (8, SampleFormat::Int),
+            RasterDataType::I16 => (16, SampleFormat::Int),
+            RasterDataType::I32 => (32, SampleFormat::Int),
+            RasterDataType::I64 => (64, SampleFormat::Int),
+            RasterDataType::F16 => (16, SampleFormat::IEEEFP),
+            RasterDataType::F32 => (32, SampleFormat::IEEEFP),
+            RasterDataType::F64 => (64, SampleFormat::IEEEFP),
+            RasterDataType::CI16 => (32, SampleFormat::Unknown(COMPLEX_INT_SAMPLE_FORMAT)),
+            RasterDataType::CI32 => (64, SampleFormat::Unknown(COMPLEX_INT_SAMPLE_FORMAT)),
+            RasterDataType::CF32 => (64, SampleFormat::Unknown(COMPLEX_IEEEFP_SAMPLE_FORMAT)),
+            RasterDataType::CF64 => (128, SampleFormat::Unknown(COMPLEX_IEEEFP_SAMPLE_FORMAT)),
+        }
+    }
+}
+
+/// The meaning of a sample (band), as given by the ExtraSamples tag.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SampleRole {
+    /// A color or data band, including extra samples of unspecified meaning.
+    #[default]
+    Data,
+    /// The opacity of the pixel, by which the color samples have been premultiplied.
+    AssociatedAlpha,
+    /// The opacity of the pixel, independent of the color samples.
+    UnassociatedAlpha,
+}
+
+impl SampleRole {
+    /// The values of the ExtraSamples tag.
+    const UNSPECIFIED: u16 = 0;
+    const ASSOCIATED_ALPHA: u16 = 1;
+    const UNASSOCIATED_ALPHA: u16 = 2;
+
+    /// Returns the role of an extra sample with the given value of the ExtraSamples tag, treating
+    /// unknown values as unspecified.
+    pub(crate) fn from_extra_sample(value: u16) -> Self {
+        match value {
+            Self::ASSOCIATED_ALPHA => SampleRole::AssociatedAlpha,
+            Self::UNASSOCIATED_ALPHA => SampleRole::UnassociatedAlpha,
+            _ => SampleRole::Data,
+        }
+    }
+
+    /// Returns the value of the ExtraSamples tag for an extra sample with this role.
+    pub(crate) fn to_extra_sample(self) -> u16 {
+        match self {
+            SampleRole::Data => Self::UNSPECIFIED,
+            SampleRole::AssociatedAlpha => Self::ASSOCIATED_ALPHA,
+            SampleRole::UnassociatedAlpha => Self::UNASSOCIATED_ALPHA,
+        }
+    }
+
+    /// Returns whether the sample holds the opacity of the pixel.
+    pub fn is_alpha(&self) -> bool {
+        matches!(
+            self,
+            SampleRole::AssociatedAlpha | SampleRole::UnassociatedAlpha
+        )
+    }
+}
+
+/// The format of the samples of a band, as given by the SampleFormat and BitsPerSample tags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BandFormat {
+    pub sample_format: SampleFormat,
+    pub bits_per_sample: u16,
+}
+
+impl BandFormat {
+    /// Returns the type the samples are decoded to, or `None` if they cannot be decoded.
+    ///
+    /// Integers with fewer bits than the returned type are widened, e.g. 1-bit or 4-bit samples are
+    /// decoded as `U8` and 12-bit samples as `U16`.
+    pub fn data_type(&self) -> Option<RasterDataType> {
+        Some(match (self.sample_format, self.bits_per_sample) {
+            (SampleFormat::Uint, 1..=8) => RasterDataType::U8,
+            (SampleFormat::Uint, 9..=16) => RasterDataType::U16,
+            (SampleFormat::Uint, 17..=32) => RasterDataType::U32,
+            (SampleFormat::Uint, 33..=64) => RasterDataType::U64,
+            (SampleFormat::Int, 1..=8) => RasterDataType::I8,
+            (SampleFormat::Int, 9..=16) => RasterDataType::I16,
+            (SampleFormat::Int, 17..=32) => RasterDataType::I32,
+            (SampleFormat::Int, 33..=64) => RasterDataType::I64,
+            (SampleFormat::IEEEFP, 16) => RasterDataType::F16,
+            (SampleFormat::IEEEFP, 32) => RasterDataType::F32,
+            (SampleFormat::IEEEFP, 64) => RasterDataType::F64,
+            (SampleFormat::Unknown(COMPLEX_INT_SAMPLE_FORMAT), 32) => RasterDataType::CI16,
+            (SampleFormat::Unknown(COMPLEX_INT_SAMPLE_FORMAT), 64) => RasterDataType::CI32,
+            (SampleFormat::Unknown(COMPLEX_IEEEFP_SAMPLE_FORMAT), 64) => RasterDataType::CF32,
+            (SampleFormat::Unknown(COMPLEX_IEEEFP_SAMPLE_FORMAT), 128) => RasterDataType::CF64,
+            _ => return None,
+        })
+    }
+
+    /// Returns whether the samples are complex numbers, whose sample formats are not known to
+    /// [`SampleFormat`] and are given as [`SampleFormat::Unknown`] instead.
+    pub fn is_complex(&self) -> bool {
+        matches!(
+            self.sample_format,
+            SampleFormat::Unknown(COMPLEX_INT_SAMPLE_FORMAT | COMPLEX_IEEEFP_SAMPLE_FORMAT)
+        )
+    }
+}
@@ -1,176 +1,630 @@
 //! A [GeoTIFF](https://www.ogc.org/standard/geotiff) library for Rust
-use std::any::type_name;
+use std::fmt;
 use std::io::{Read, Seek};
 
-use geo_types::{Coord, Rect};
+use geo_types::{Coord, Polygon, Rect};
 use num_traits::FromPrimitive;
-use tiff::decoder::{Decoder, DecodingResult};
-use tiff::tags::Tag;
-use tiff::TiffResult;
+use tiff::tags::PhotometricInterpretation;
 
+#[cfg(feature = "async")]
+pub use crate::async_reader::*;
+pub use crate::band::*;
+pub use crate::citations::*;
+pub use crate::contours::*;
+pub use crate::dataset_pool::*;
+pub use crate::error::*;
+pub use crate::gdal_metadata::*;
 pub use crate::geo_key_directory::*;
+pub use crate::georeference::*;
+pub use crate::histogram::*;
+#[cfg(feature = "http")]
+pub use crate::http_reader::*;
+pub use crate::interpolation::*;
+pub use crate::layout::*;
+pub use crate::metadata::*;
+pub use crate::profile::*;
+pub use crate::raster_data::RasterData;
+pub use crate::raster_source::*;
+pub use crate::reader::*;
+pub use crate::resample::*;
+pub use crate::storage::*;
+pub use crate::terrain::*;
+pub use crate::tiff_tags::*;
+pub use crate::tile::*;
+pub use crate::window::*;
+pub use crate::writer::*;
+pub use crate::zonal::*;
 
 use crate::coordinate_transform::*;
-use crate::decoder_ext::*;
 use crate::raster_data::*;
+use crate::raster_space::*;
 
+#[macro_use]
+mod trace;
+
+#[cfg(feature = "async")]
+mod async_reader;
+mod band;
+mod band_math;
+mod citations;
+mod clip;
+mod color;
+mod contours;
 mod coordinate_transform;
+mod crop;
+mod dataset_pool;
 mod decoder_ext;
+#[cfg(feature = "proj")]
+mod dem;
+mod epsg;
+mod error;
+mod export;
+mod fingerprint;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
+#[cfg(feature = "gdal")]
+mod gdal_interop;
+mod gdal_metadata;
 mod geo_key_directory;
+mod georeference;
+mod histogram;
+#[cfg(feature = "http")]
+mod http_reader;
+mod info;
+mod interpolation;
+mod layout;
+mod metadata;
+mod polygonize;
+mod profile;
+#[cfg(any(feature = "async", feature = "http"))]
+mod range_cache;
 mod raster_data;
-
-macro_rules! unwrap_primitive_type {
-    ($result: expr, $actual: ty, $expected: ty) => {
-        $result
-            .ok_or_else(|| {
-                format!(
-                    "Cannot represent {} as {}",
-                    type_name::<$actual>(),
-                    type_name::<$expected>()
-                )
-            })
-            .unwrap()
-    };
-}
+mod raster_source;
+mod raster_space;
+mod reader;
+#[cfg(feature = "proj")]
+mod reproject;
+mod resample;
+#[cfg(feature = "stac")]
+mod stac;
+mod storage;
+mod terrain;
+mod tiff_tags;
+mod tile;
+mod warp;
+mod window;
+mod wkt;
+mod writer;
+mod zonal;
 
 /// The basic GeoTIFF struct. This includes any metadata as well as the actual raster data.
 ///
 /// The raster data has a size of raster_width * raster_height * num_samples
-#[derive(Debug)]
 pub struct GeoTiff {
     pub geo_key_directory: GeoKeyDirectory,
     pub raster_width: usize,
     pub raster_height: usize,
     pub num_samples: usize,
+    pub no_data: Option<f64>,
+    pub gdal_metadata: Option<GdalMetadata>,
+    pub descriptive_tags: DescriptiveTags,
     coordinate_transform: Option<CoordinateTransform>,
+    gcps: Vec<Gcp>,
+    chunk_dimensions: (usize, usize),
+    interleave: Interleave,
+    order: ArrayOrder,
+    band_formats: Vec<BandFormat>,
+    sample_roles: Vec<SampleRole>,
+    /// The photometric interpretation read from a file or given by a color conversion, or `None`
+    /// if it is inferred from the samples.
+    photometric_interpretation: Option<PhotometricInterpretation>,
     raster_data: RasterData,
+    corrupt_chunks: Vec<CorruptChunk>,
+    mask: Option<Vec<bool>>,
+    bigtiff: bool,
+    overviews: Vec<GeoTiff>,
 }
 
 impl GeoTiff {
+    /// Opens a GeoTIFF from the given source, reading its metadata but no raster data, e.g. to
+    /// index many files without decoding their pixels.
+    ///
+    /// The returned reader gives access to the dimensions, the data type, the GeoKeys and the
+    /// coordinate transform, and decodes the raster with [`GeoTiffReader::read`] if needed.
+    pub fn open<R: Read + Seek>(reader: R) -> GeoTiffResult<GeoTiffReader<R>> {
+        GeoTiffReader::open(reader)
+    }
+
+    /// Reads the metadata of a GeoTIFF from the given source without decoding any raster data,
+    /// see [`GeoTiffReader::metadata`].
+    pub fn read_metadata<R: Read + Seek>(reader: R) -> GeoTiffResult<GeoTiffMetadata> {
+        GeoTiffReader::open(reader)?.metadata()
+    }
+
     /// Reads a GeoTIFF from the given source.
-    pub fn read<R: Read + Seek>(reader: R) -> TiffResult<Self> {
-        let mut decoder = Decoder::new(reader)?;
-
-        let geo_key_directory = decoder.geo_key_directory()?;
-        let coordinate_transform = decoder.coordinate_transform()?;
-
-        let (raster_width, raster_height) = decoder
-            .dimensions()
-            .map(|(width, height)| (width as usize, height as usize))?;
-        let num_samples = match decoder.find_tag(Tag::SamplesPerPixel)? {
-            None => 1,
-            Some(value) => value.into_u16()? as usize,
-        };
+    pub fn read<R: Read + Seek>(reader: R) -> GeoTiffResult<Self> {
+        GeoTiffReader::open(reader)?.read()
+    }
 
-        let raster_data = match decoder.read_image()? {
-            DecodingResult::U8(data) => RasterData::U8(data),
-            DecodingResult::U16(data) => RasterData::U16(data),
-            DecodingResult::U32(data) => RasterData::U32(data),
-            DecodingResult::U64(data) => RasterData::U64(data),
-            DecodingResult::F32(data) => RasterData::F32(data),
-            DecodingResult::F64(data) => RasterData::F64(data),
-            DecodingResult::I8(data) => RasterData::I8(data),
-            DecodingResult::I16(data) => RasterData::I16(data),
-            DecodingResult::I32(data) => RasterData::I32(data),
-            DecodingResult::I64(data) => RasterData::I64(data),
-        };
+    /// Reads a GeoTIFF from the given source with the given options, e.g. to reject files
+    /// violating the specification.
+    pub fn read_with_options<R: Read + Seek>(
+        reader: R,
+        options: &ReadOptions,
+    ) -> GeoTiffResult<Self> {
+        GeoTiffReader::open_with_options(reader, options)?.read()
+    }
 
-        Ok(Self {
-            geo_key_directory,
-            raster_width,
-            raster_height,
-            num_samples,
-            coordinate_transform,
-            raster_data,
-        })
+    /// Reads the given pixel window of a GeoTIFF from the given source.
+    ///
+    /// Only the strips or tiles intersecting the window are decoded. The coordinate transform of
+    /// the returned GeoTIFF is adjusted so that the window keeps its location in model space.
+    pub fn read_region<R: Read + Seek>(reader: R, window: &Window) -> GeoTiffResult<Self> {
+        GeoTiffReader::open(reader)?.read_region(window)
+    }
+
+    /// Reads the reduced-resolution image (overview) at the given level from the given source,
+    /// where level 0 is the first overview following the full-resolution image.
+    ///
+    /// This allows using Cloud Optimized GeoTIFFs at coarser resolutions without decoding the
+    /// full-resolution image.
+    pub fn read_overview<R: Read + Seek>(reader: R, level: usize) -> GeoTiffResult<Self> {
+        GeoTiffReader::open(reader)?.read_overview(level)
     }
 
     /// Returns the extent of the image in model space.
     pub fn model_extent(&self) -> Rect {
-        let offset = self.raster_offset();
-        let lower = Coord {
-            x: offset,
-            y: offset,
-        };
-        let upper = Coord {
-            x: self.raster_width as f64 + offset,
-            y: self.raster_height as f64 + offset,
-        };
+        self.compute_model_extent()
+    }
 
-        if let Some(coordinate_transform) = &self.coordinate_transform {
-            Rect::new(
-                coordinate_transform.transform_to_model(&lower),
-                coordinate_transform.transform_to_model(&upper),
-            )
-        } else {
-            Rect::new(lower, upper)
-        }
+    /// Returns the outline of the image in model space, starting at its top left corner.
+    ///
+    /// Unlike [`Self::model_extent`], which is its bounding rectangle, it follows the edges of
+    /// rasters that are rotated or sheared by their coordinate transform.
+    pub fn footprint(&self) -> Polygon {
+        self.compute_footprint()
+    }
+
+    /// Returns the affine transform from raster to model space as the six coefficients used by
+    /// GDAL, or `None` if there is no coordinate transform or it is not affine, e.g. one given by
+    /// multiple tie points.
+    ///
+    /// The model location of the top left corner of the pixel at column `col` and row `row` is
+    /// `(gt[0] + col * gt[1] + row * gt[2], gt[3] + col * gt[4] + row * gt[5])`, also for rasters
+    /// whose pixels represent points.
+    pub fn geotransform(&self) -> Option<[f64; 6]> {
+        self.compute_geotransform()
+    }
+
+    /// Returns the location of the top left corner of the raster in model space, i.e. the first
+    /// and fourth coefficient of [`Self::geotransform`].
+    pub fn origin(&self) -> Option<Coord> {
+        self.compute_geotransform().map(|geotransform| Coord {
+            x: geotransform[0],
+            y: geotransform[3],
+        })
+    }
+
+    /// Returns the width and height of a pixel in model space, i.e. the second and sixth
+    /// coefficient of [`Self::geotransform`], where the height is negative for rasters with
+    /// north up.
+    ///
+    /// The size is only meaningful if the raster is not rotated, see [`Self::geotransform`].
+    pub fn pixel_size(&self) -> Option<(f64, f64)> {
+        self.compute_geotransform()
+            .map(|geotransform| (geotransform[1], geotransform[5]))
+    }
+
+    /// Returns the width and height of a pixel in model units, which unlike [`Self::pixel_size`]
+    /// are positive and also apply to rotated rasters, or `None` if the raster has no affine
+    /// transform.
+    ///
+    /// See [`Self::ground_sample_distance`] for the size in metres.
+    pub fn resolution(&self) -> Option<Coord> {
+        self.compute_resolution()
+    }
+
+    /// Returns whether the raster intersects the given rectangle in model space.
+    pub fn intersects(&self, rect: &Rect) -> bool {
+        self.compute_intersection_window(rect).is_some()
+    }
+
+    /// Returns whether the given location in model space lies within the raster.
+    pub fn contains(&self, coord: &Coord) -> bool {
+        self.pixel_at(coord).is_some()
+    }
+
+    /// Returns the smallest pixel window covering the intersection of the raster with the given
+    /// rectangle in model space, or `None` if they do not intersect.
+    pub fn intersection_window(&self, rect: &Rect) -> Option<Window> {
+        self.compute_intersection_window(rect)
+    }
+
+    /// Returns the width and height in pixels of the strips or tiles of the file the raster was
+    /// read from, clipped to the raster dimensions.
+    pub fn chunk_dimensions(&self) -> (usize, usize) {
+        self.chunk_dimensions
+    }
+
+    /// Expands the given window to the boundaries of the strips or tiles it touches, clipped to the
+    /// raster dimensions.
+    ///
+    /// Reading an aligned window avoids decoding chunks that are only partially needed.
+    pub fn align_window_to_blocks(&self, window: &Window) -> Window {
+        let (chunk_width, chunk_height) = self.chunk_dimensions;
+        window.align_to(
+            chunk_width,
+            chunk_height,
+            self.raster_width,
+            self.raster_height,
+        )
     }
 
     /// Returns the value at the given location for the specified sample.
     /// The coordinates are in model space.
+    ///
+    /// Panics if the image does not have the sample or `T` cannot represent the value, see
+    /// [`Self::try_get_value_at`].
     pub fn get_value_at<T: FromPrimitive + 'static>(
         &self,
         coord: &Coord,
         sample: usize,
     ) -> Option<T> {
-        let index = self.compute_index(coord, sample)?;
-
-        Some(match &self.raster_data {
-            RasterData::U8(data) => unwrap_primitive_type!(T::from_u8(data[index]), u8, T),
-            RasterData::U16(data) => unwrap_primitive_type!(T::from_u16(data[index]), u16, T),
-            RasterData::U32(data) => unwrap_primitive_type!(T::from_u32(data[index]), u32, T),
-            RasterData::U64(data) => unwrap_primitive_type!(T::from_u64(data[index]), u64, T),
-            RasterData::F32(data) => unwrap_primitive_type!(T::from_f32(data[index]), f32, T),
-            RasterData::F64(data) => unwrap_primitive_type!(T::from_f64(data[index]), f64, T),
-            RasterData::I8(data) => unwrap_primitive_type!(T::from_i8(data[index]), i8, T),
-            RasterData::I16(data) => unwrap_primitive_type!(T::from_i16(data[index]), i16, T),
-            RasterData::I32(data) => unwrap_primitive_type!(T::from_i32(data[index]), i32, T),
-            RasterData::I64(data) => unwrap_primitive_type!(T::from_i64(data[index]), i64, T),
-        })
+        self.try_get_value_at(coord, sample)
+            .unwrap_or_else(|error| panic!("{error}"))
     }
 
-    fn compute_index(&self, coord: &Coord, sample: usize) -> Option<usize> {
-        let GeoTiff {
-            raster_width,
-            raster_height,
-            num_samples,
-            coordinate_transform,
-            ..
-        } = self;
+    /// Returns the value at the given location for the specified sample, or `None` if the location
+    /// lies outside the raster. The coordinates are in model space.
+    ///
+    /// Fails with [`GeoTiffError::SampleOutOfBounds`] if the image does not have the sample, e.g.
+    /// for band indices supplied by users, and with [`GeoTiffError::ValueConversion`] if `T`
+    /// cannot represent the value.
+    pub fn try_get_value_at<T: FromPrimitive + 'static>(
+        &self,
+        coord: &Coord,
+        sample: usize,
+    ) -> GeoTiffResult<Option<T>> {
+        self.sample_in_bounds(sample)?;
 
-        if &sample >= num_samples {
-            panic!(
-                "sample out of bounds: the number of samples is {} but the sample is {}",
-                num_samples, sample
-            )
+        let Some((col, row)) = self.pixel_at(coord) else {
+            return Ok(None);
+        };
+        let index = self.buffer_layout().index(col, row, sample);
+
+        self.raster_data.try_get(index).map(Some)
+    }
+
+    /// Returns the value at the given location for the specified sample, or `None` if the image
+    /// does not have the sample, the location lies outside the raster, the pixel is marked invalid
+    /// by the transparency mask or `T` cannot represent the value. The coordinates are in model
+    /// space.
+    pub fn get_masked_value_at<T: FromPrimitive + 'static>(
+        &self,
+        coord: &Coord,
+        sample: usize,
+    ) -> Option<T> {
+        self.sample_in_bounds(sample).ok()?;
+
+        let (col, row) = self.pixel_at(coord)?;
+        if let Some(mask) = &self.mask {
+            if !mask[row * self.raster_width + col] {
+                return None;
+            }
         }
+        let index = self.buffer_layout().index(col, row, sample);
 
-        let mut coord = match coordinate_transform {
-            None => *coord,
-            Some(transform) => transform.transform_to_raster(coord),
-        };
+        self.raster_data.try_get(index).ok()
+    }
+
+    /// Returns the transparency mask of the image, holding whether each pixel is valid in
+    /// row-major order, or `None` if the file has no internal mask.
+    ///
+    /// Internal masks are stored as separate images, e.g. by GDAL with `GDAL_TIFF_INTERNAL_MASK`.
+    /// If the image was read with [`GeoTiffReader::with_alpha_mask`], fully transparent pixels
+    /// are marked invalid as well.
+    pub fn mask(&self) -> Option<&[bool]> {
+        self.mask.as_deref()
+    }
+
+    /// Returns an iterator over the valid pixels of the specified sample, yielding the location
+    /// of each pixel in model space together with its value, strip by strip or tile by tile and
+    /// in row-major order within each.
+    ///
+    /// Pixels holding the no data value or NaN and pixels marked invalid by the transparency mask
+    /// are skipped, as are whole strips or tiles without valid pixels in the mask. To skip strips
+    /// or tiles without encoded data before decoding them, see [`GeoTiffReader::valid_pixels`].
+    ///
+    /// Fails with [`GeoTiffError::SampleOutOfBounds`] if the image does not have the sample, while
+    /// the iterator yields [`GeoTiffError::ValueConversion`] for values the requested type cannot
+    /// represent.
+    pub fn valid_pixels<T: FromPrimitive + 'static>(
+        &self,
+        sample: usize,
+    ) -> GeoTiffResult<impl Iterator<Item = GeoTiffResult<(Coord, T)>> + '_> {
+        self.sample_in_bounds(sample)?;
+
+        let layout = self.buffer_layout();
+        let (chunk_width, chunk_height) = self.chunk_dimensions;
+        let windows = (0..self.raster_height)
+            .step_by(chunk_height.max(1))
+            .flat_map(move |y| {
+                (0..self.raster_width)
+                    .step_by(chunk_width.max(1))
+                    .map(move |x| {
+                        Window::new(
+                            x,
+                            y,
+                            chunk_width.min(self.raster_width - x),
+                            chunk_height.min(self.raster_height - y),
+                        )
+                    })
+            });
 
-        // See https://docs.ogc.org/is/19-008r4/19-008r4.html#_raster_space for reference
-        let raster_offset = self.raster_offset();
-        coord.x -= raster_offset;
-        coord.y -= raster_offset;
-
-        if coord.x < 0.0
-            || coord.x >= *raster_width as f64
-            || coord.y < 0.0
-            || coord.y >= *raster_height as f64
-        {
-            return None;
+        Ok(windows
+            .filter(move |window| {
+                self.mask.as_ref().is_none_or(|mask| {
+                    (window.y..window.y + window.height).any(|row| {
+                        let start = row * self.raster_width + window.x;
+                        mask[start..start + window.width].contains(&true)
+                    })
+                })
+            })
+            .flat_map(|window| {
+                (window.y..window.y + window.height).flat_map(move |row| {
+                    (window.x..window.x + window.width).map(move |col| (col, row))
+                })
+            })
+            .filter_map(move |(col, row)| {
+                if self
+                    .mask
+                    .as_ref()
+                    .is_some_and(|mask| !mask[row * self.raster_width + col])
+                {
+                    return None;
+                }
+
+                let index = layout.index(col, row, sample);
+                let value = match self.raster_data.try_get::<f64>(index) {
+                    Ok(value) => value,
+                    Err(error) => return Some(Err(error)),
+                };
+                if value.is_nan() || Some(value) == self.no_data {
+                    return None;
+                }
+
+                Some(
+                    self.raster_data
+                        .try_get(index)
+                        .map(|value| (self.pixel_center(col, row), value)),
+                )
+            }))
+    }
+
+    /// Returns the ground control points of files georeferenced by multiple tie points, e.g. to
+    /// run a custom georeferencing, or an empty slice otherwise.
+    ///
+    /// They are available regardless of the `tie-points` feature, without which such files are
+    /// read without a coordinate transform.
+    pub fn gcps(&self) -> &[Gcp] {
+        &self.gcps
+    }
+
+    /// Returns the format of the samples of each band, as stored in the file.
+    pub fn band_formats(&self) -> &[BandFormat] {
+        &self.band_formats
+    }
+
+    /// Returns the number of bits of the samples of each band as stored in the file, which may be
+    /// fewer than those of the data type they are decoded to, e.g. 12 bits of samples decoded as
+    /// `U16`.
+    pub fn bits_per_sample(&self) -> Vec<u16> {
+        self.band_formats()
+            .iter()
+            .map(|band_format| band_format.bits_per_sample)
+            .collect()
+    }
+
+    /// Returns the role of each sample, as given by the ExtraSamples tag.
+    pub fn sample_roles(&self) -> &[SampleRole] {
+        &self.sample_roles
+    }
+
+    /// Returns how the samples are interpreted as colors, e.g. as RGB or CMYK, see
+    /// [`GeoTiffReader::photometric_interpretation`].
+    ///
+    /// Rasters created from raster data, e.g. with [`Self::from_profile`], are interpreted as RGB
+    /// if they have three or four samples of at most 16 bits and as grayscale otherwise. CMYK
+    /// samples can be converted with [`Self::cmyk_to_rgb`].
+    pub fn photometric_interpretation(&self) -> PhotometricInterpretation {
+        let (bits_per_sample, _) = self.raster_data.data_type().sample_type();
+        self.photometric_interpretation
+            .unwrap_or(match self.num_samples {
+                3 | 4 if bits_per_sample <= 16 => PhotometricInterpretation::RGB,
+                _ => PhotometricInterpretation::BlackIsZero,
+            })
+    }
+
+    /// Returns the index of the sample holding the opacity of the pixels, if there is one.
+    ///
+    /// To treat fully transparent pixels as invalid, read with
+    /// [`GeoTiffReader::with_alpha_mask`].
+    pub fn alpha_sample(&self) -> Option<usize> {
+        reader::alpha_sample(&self.sample_roles)
+    }
+
+    /// Returns the type of the values of the raster data.
+    pub fn data_type(&self) -> RasterDataType {
+        self.raster_data.data_type()
+    }
+
+    /// Returns the strips or tiles that could not be decoded when reading with chunk verification
+    /// enabled, see [`GeoTiffReader::with_chunk_verification`].
+    pub fn corrupt_chunks(&self) -> &[CorruptChunk] {
+        &self.corrupt_chunks
+    }
+
+    /// Returns whether the GeoTIFF was read from a BigTIFF file, which uses 64-bit offsets.
+    pub fn is_bigtiff(&self) -> bool {
+        self.bigtiff
+    }
+
+    /// Returns the arrangement of the samples in memory.
+    pub fn interleave(&self) -> Interleave {
+        self.interleave
+    }
+
+    /// Returns the description of the image given by the ImageDescription tag, see
+    /// [`DescriptiveTags::image_description`].
+    pub fn image_description(&self) -> Option<&str> {
+        self.descriptive_tags.image_description.as_deref()
+    }
+
+    /// Returns the order of the pixels in memory.
+    pub fn order(&self) -> ArrayOrder {
+        self.order
+    }
+
+    /// Rearranges the samples and pixels in memory into the given layout.
+    pub fn into_layout(self, interleave: Interleave, order: ArrayOrder) -> GeoTiffResult<Self> {
+        if interleave == self.interleave && order == self.order {
+            return Ok(self);
         }
 
-        Some((coord.y as usize * raster_width + coord.x as usize) * num_samples + sample)
+        let layout = self.buffer_layout();
+        let raster_data = self
+            .raster_data
+            .rearrange(layout, layout.with(interleave, order))?;
+
+        Ok(Self {
+            interleave,
+            order,
+            raster_data,
+            ..self
+        })
+    }
+
+    /// Returns all values converted to `T`, in the order they are arranged in memory, or fails
+    /// with [`GeoTiffError::ValueConversion`] if `T` cannot represent one of them.
+    pub fn values<T: FromPrimitive + 'static>(&self) -> GeoTiffResult<Vec<T>> {
+        (0..self.raster_data.len())
+            .map(|index| self.raster_data.try_get(index))
+            .collect()
+    }
+
+    /// Returns all values converted to `T`, in the order they are arranged in memory, or fails
+    /// with [`GeoTiffError::ValueConversion`] if `T` cannot represent one of them.
+    ///
+    /// If `T` is the type of the values, see [`Self::data_type`], the decoded buffer is returned
+    /// without copying it.
+    pub fn into_vec<T: FromPrimitive + 'static>(self) -> GeoTiffResult<Vec<T>> {
+        self.raster_data.into_vec()
     }
 
-    fn raster_offset(&self) -> f64 {
-        match self.geo_key_directory.raster_type {
-            Some(RasterType::RasterPixelIsPoint) => -0.5,
-            _ => 0.0,
+    /// Returns the decoded values in the type of the samples, in the order they are arranged in
+    /// memory.
+    pub fn into_raster_data(self) -> RasterData {
+        self.raster_data
+    }
+
+    fn buffer_layout(&self) -> BufferLayout {
+        BufferLayout {
+            width: self.raster_width,
+            height: self.raster_height,
+            num_samples: self.num_samples,
+            interleave: self.interleave,
+            order: self.order,
+        }
+    }
+
+    /// Returns a single-band raster with the given row-major data covering the same pixels.
+    fn derive_band(&self, raster_data: RasterData, no_data: Option<f64>) -> GeoTiff {
+        let (bits_per_sample, sample_format) = raster_data.data_type().sample_type();
+
+        GeoTiff {
+            geo_key_directory: self.geo_key_directory.clone(),
+            raster_width: self.raster_width,
+            raster_height: self.raster_height,
+            num_samples: 1,
+            no_data,
+            gdal_metadata: None,
+            descriptive_tags: DescriptiveTags::default(),
+            coordinate_transform: self.coordinate_transform.clone(),
+            gcps: self.gcps.clone(),
+            chunk_dimensions: self.chunk_dimensions,
+            interleave: Interleave::Pixel,
+            order: ArrayOrder::RowMajor,
+            band_formats: vec![BandFormat {
+                sample_format,
+                bits_per_sample,
+            }],
+            sample_roles: vec![SampleRole::Data],
+            photometric_interpretation: None,
+            raster_data,
+            corrupt_chunks: Vec::new(),
+            mask: None,
+            bigtiff: self.bigtiff,
+            overviews: Vec::new(),
         }
     }
 }
+
+/// Summarizes the ground control points, raster data, corrupt chunks and mask instead of listing
+/// their contents.
+impl fmt::Debug for GeoTiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GeoTiff")
+            .field("geo_key_directory", &self.geo_key_directory)
+            .field("raster_width", &self.raster_width)
+            .field("raster_height", &self.raster_height)
+            .field("num_samples", &self.num_samples)
+            .field("no_data", &self.no_data)
+            .field("gdal_metadata", &self.gdal_metadata)
+            .field("descriptive_tags", &self.descriptive_tags)
+            .field("coordinate_transform", &self.coordinate_transform)
+            .field("gcps", &self.gcps.len())
+            .field("chunk_dimensions", &self.chunk_dimensions)
+            .field("interleave", &self.interleave)
+            .field("order", &self.order)
+            .field("band_formats", &self.band_formats)
+            .field("sample_roles", &self.sample_roles)
+            .field(
+                "photometric_interpretation",
+                &self.photometric_interpretation,
+            )
+            .field("raster_data", &self.raster_data)
+            .field("corrupt_chunks", &self.corrupt_chunks.len())
+            .field(
+                "masked_pixels",
+                &self
+                    .mask
+                    .as_ref()
+                    .map(|mask| mask.iter().filter(|valid| !**valid).count()),
+            )
+            .field("bigtiff", &self.bigtiff)
+            .field("overviews", &self.overviews.len())
+            .finish()
+    }
+}
+
+impl RasterSpace for GeoTiff {
+    fn raster_dimensions(&self) -> (usize, usize) {
+        (self.raster_width, self.raster_height)
+    }
+
+    fn raster_type(&self) -> Option<RasterType> {
+        self.geo_key_directory.raster_type
+    }
+
+    fn coordinate_transform(&self) -> Option<&CoordinateTransform> {
+        self.coordinate_transform.as_ref()
+    }
+
+    fn num_samples(&self) -> usize {
+        self.num_samples
+    }
+
+    fn gcps(&self) -> &[Gcp] {
+        &self.gcps
+    }
+}
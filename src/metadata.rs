@@ -0,0 +1,61 @@
+use geo_types::Rect;
+use tiff::tags::{CompressionMethod, PhotometricInterpretation};
+
+use crate::geo_key_directory::GeoKeyDirectory;
+use crate::georeference::Gcp;
+use crate::layout::{BandFormat, RasterDataType};
+use crate::reader::Overview;
+use crate::tiff_tags::DescriptiveTags;
+
+/// The metadata of a GeoTIFF, read without decoding any raster data, e.g. to summarize a file
+/// like `gdalinfo`, see [`GeoTiffReader::metadata`](crate::GeoTiffReader::metadata).
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoTiffMetadata {
+    pub width: usize,
+    pub height: usize,
+    pub num_samples: usize,
+    /// The type the raster data is decoded to.
+    pub data_type: RasterDataType,
+    /// The format of the samples of each band, as given by the SampleFormat and BitsPerSample
+    /// tags, e.g. to tell unsigned reflectances from signed elevations.
+    pub band_formats: Vec<BandFormat>,
+    /// How the decoded samples are interpreted as colors, see
+    /// [`GeoTiffReader::photometric_interpretation`](crate::GeoTiffReader::photometric_interpretation).
+    pub photometric_interpretation: PhotometricInterpretation,
+    pub compression: CompressionMethod,
+    /// Whether the raster data is stored in tiles rather than strips.
+    pub tiled: bool,
+    /// The dimensions of the strips or tiles in pixels.
+    pub chunk_dimensions: (usize, usize),
+    /// Whether the samples are stored in separate planes rather than interleaved.
+    pub planar: bool,
+    pub bigtiff: bool,
+    pub geo_key_directory: GeoKeyDirectory,
+    /// The affine transform from raster to model space as the six coefficients used by GDAL, see
+    /// [`GeoTiff::geotransform`](crate::GeoTiff::geotransform).
+    pub geotransform: Option<[f64; 6]>,
+    /// The ground control points of files georeferenced by multiple tie points.
+    pub gcps: Vec<Gcp>,
+    pub model_extent: Rect,
+    pub no_data: Option<f64>,
+    pub descriptive_tags: DescriptiveTags,
+    pub overviews: Vec<Overview>,
+}
+
+impl GeoTiffMetadata {
+    /// Returns the number of bits of the samples of each band as stored in the file, which may be
+    /// fewer than those of the data type they are decoded to, e.g. 12 bits of samples decoded as
+    /// `U16`.
+    pub fn bits_per_sample(&self) -> Vec<u16> {
+        self.band_formats
+            .iter()
+            .map(|band_format| band_format.bits_per_sample)
+            .collect()
+    }
+
+    /// Returns the description of the image given by the ImageDescription tag, see
+    /// [`DescriptiveTags::image_description`].
+    pub fn image_description(&self) -> Option<&str> {
+        self.descriptive_tags.image_description.as_deref()
+    }
+}
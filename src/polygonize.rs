@@ -0,0 +1,192 @@
+use std::collections::{BTreeMap, HashMap};
+
+use geo_types::{Coord, LineString, MultiPolygon, Polygon};
+
+use crate::raster_space::RasterSpace;
+use crate::{GeoTiff, GeoTiffResult};
+
+/// A vertex of the pixel grid, as row and column, so that the topmost vertices come first.
+type Vertex = (usize, usize);
+
+/// A step along a pixel edge, as column and row offset.
+type Direction = (isize, isize);
+
+impl GeoTiff {
+    /// Converts the specified sample to polygons in model space, e.g. to turn a classification
+    /// raster into vector features, returning one multi-polygon per distinct value, ordered by
+    /// value.
+    ///
+    /// Each polygon covers a region of pixels holding the same value that are connected by their
+    /// edges, so pixels only touching at a corner belong to separate polygons. Pixels holding the
+    /// no data value or NaN and pixels marked invalid by the transparency mask are not covered.
+    ///
+    /// Fails with [`GeoTiffError::SampleOutOfBounds`](crate::GeoTiffError::SampleOutOfBounds) if
+    /// the image does not have the sample.
+    pub fn polygonize(&self, sample: usize) -> GeoTiffResult<Vec<(f64, MultiPolygon)>> {
+        self.sample_in_bounds(sample)?;
+
+        let layout = self.buffer_layout();
+        let values: Vec<Option<f64>> = (0..self.raster_width * self.raster_height)
+            .map(|pixel| {
+                if self.mask.as_ref().is_some_and(|mask| !mask[pixel]) {
+                    return None;
+                }
+
+                let (col, row) = (pixel % self.raster_width, pixel / self.raster_width);
+                let value = self.raster_data.get::<f64>(layout.index(col, row, sample));
+                // Adding zero turns negative zero into zero, so both share a polygon
+                (!value.is_nan() && Some(value) != self.no_data).then_some(value + 0.0)
+            })
+            .collect();
+
+        let mut visited = vec![false; values.len()];
+        let mut polygons: HashMap<u64, (f64, Vec<Polygon>)> = HashMap::new();
+        for start in 0..values.len() {
+            let Some(value) = values[start] else {
+                continue;
+            };
+            if visited[start] {
+                continue;
+            }
+
+            // Collect the region of connected pixels holding the value by flood filling
+            visited[start] = true;
+            let mut region = vec![start];
+            let mut pending = vec![start];
+            while let Some(pixel) = pending.pop() {
+                for neighbor in self.neighbors(pixel).into_iter().flatten() {
+                    if !visited[neighbor] && values[neighbor] == Some(value) {
+                        visited[neighbor] = true;
+                        region.push(neighbor);
+                        pending.push(neighbor);
+                    }
+                }
+            }
+
+            polygons
+                .entry(value.to_bits())
+                .or_insert_with(|| (value, Vec::new()))
+                .1
+                .push(self.trace_region(&region, |pixel| values[pixel] == Some(value)));
+        }
+
+        let mut polygons: Vec<_> = polygons
+            .into_values()
+            .map(|(value, polygons)| (value, MultiPolygon::new(polygons)))
+            .collect();
+        polygons.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        Ok(polygons)
+    }
+
+    /// Returns the pixels above, to the right of, below and to the left of the given one, if
+    /// they lie within the raster.
+    fn neighbors(&self, pixel: usize) -> [Option<usize>; 4] {
+        let (col, row) = (pixel % self.raster_width, pixel / self.raster_width);
+        [
+            (row > 0).then(|| pixel - self.raster_width),
+            (col + 1 < self.raster_width).then(|| pixel + 1),
+            (row + 1 < self.raster_height).then(|| pixel + self.raster_width),
+            (col > 0).then(|| pixel - 1),
+        ]
+    }
+
+    /// Returns the polygon outlining the given region of connected pixels, where the given
+    /// function tells whether a pixel belongs to the region.
+    fn trace_region(&self, region: &[usize], contains: impl Fn(usize) -> bool) -> Polygon {
+        // The edges between the region and other pixels, directed so that the region lies to
+        // their right in raster space
+        let mut edges: BTreeMap<Vertex, Vec<Direction>> = BTreeMap::new();
+        for &pixel in region {
+            let (col, row) = (pixel % self.raster_width, pixel / self.raster_width);
+            let sides = [
+                ((row, col), (1, 0)),
+                ((row, col + 1), (0, 1)),
+                ((row + 1, col + 1), (-1, 0)),
+                ((row + 1, col), (0, -1)),
+            ];
+            for (neighbor, (vertex, direction)) in self.neighbors(pixel).into_iter().zip(sides) {
+                if !neighbor.is_some_and(&contains) {
+                    edges.entry(vertex).or_default().push(direction);
+                }
+            }
+        }
+
+        let mut exterior = None;
+        let mut holes = Vec::new();
+        while let Some((&start, directions)) = edges.iter().next() {
+            let first = directions[0];
+            let (mut vertex, mut direction) = (start, first);
+            let mut ring = vec![start];
+            loop {
+                remove_edge(&mut edges, vertex, direction);
+                vertex = (
+                    vertex.0.wrapping_add_signed(direction.1),
+                    vertex.1.wrapping_add_signed(direction.0),
+                );
+
+                // Where the region touches itself at a corner, turn away from the pixel so that
+                // each ring outlines a single connected region of other pixels
+                let (dx, dy) = direction;
+                let next = [(dy, -dx), (dx, dy), (-dy, dx)]
+                    .into_iter()
+                    .find(|&candidate| {
+                        (vertex == start && candidate == first)
+                            || edges
+                                .get(&vertex)
+                                .is_some_and(|directions| directions.contains(&candidate))
+                    })
+                    .expect("Pixel edges form closed rings");
+                if vertex == start && next == first {
+                    break;
+                }
+                if next != direction {
+                    ring.push(vertex);
+                }
+                direction = next;
+            }
+
+            // The exterior of the region turns clockwise in raster space, i.e. has a positive
+            // area, while its holes turn the other way
+            let area: isize = ring
+                .iter()
+                .zip(ring.iter().cycle().skip(1))
+                .map(|(a, b)| a.1 as isize * b.0 as isize - b.1 as isize * a.0 as isize)
+                .sum();
+            let ring = self.ring_to_model(&ring);
+            if area > 0 {
+                exterior = Some(ring);
+            } else {
+                holes.push(ring);
+            }
+        }
+
+        Polygon::new(
+            exterior.expect("Connected regions have a single exterior"),
+            holes,
+        )
+    }
+
+    /// Returns the ring of the given pixel grid vertices in model space.
+    fn ring_to_model(&self, ring: &[Vertex]) -> LineString {
+        let offset = self.raster_offset();
+        ring.iter()
+            .map(|&(row, col)| {
+                self.transform_to_model(&Coord {
+                    x: col as f64 + offset,
+                    y: row as f64 + offset,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Removes the edge starting at the given vertex in the given direction.
+fn remove_edge(edges: &mut BTreeMap<Vertex, Vec<Direction>>, vertex: Vertex, direction: Direction) {
+    if let Some(directions) = edges.get_mut(&vertex) {
+        directions.retain(|&other| other != direction);
+        if directions.is_empty() {
+            edges.remove(&vertex);
+        }
+    }
+}
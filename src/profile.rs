@@ -0,0 +1,156 @@
+use crate::coordinate_transform::CoordinateTransform;
+use crate::error::{GeoTiffError, GeoTiffResult};
+use crate::geo_key_directory::{GeoKeyDirectory, RasterType};
+use crate::layout::{ArrayOrder, BandFormat, Interleave, RasterDataType, SampleRole};
+use crate::metadata::GeoTiffMetadata;
+use crate::raster_data::RasterData;
+use crate::tiff_tags::DescriptiveTags;
+use crate::writer::{Compression, WriteOptions};
+use crate::GeoTiff;
+
+/// The properties needed to create a GeoTIFF, like the profile of a rasterio dataset.
+///
+/// A profile is read from an existing file with
+/// [`GeoTiffReader::profile`](crate::GeoTiffReader::profile), adjusted as needed, and used to
+/// create a GeoTIFF from new raster data with [`GeoTiff::from_profile`], which is then written
+/// with [`Profile::write_options`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub width: usize,
+    pub height: usize,
+    /// The number of samples (bands), the `count` of rasterio.
+    pub num_samples: usize,
+    pub data_type: RasterDataType,
+    /// The affine transform from raster to model space as the six coefficients used by GDAL, see
+    /// [`GeoTiff::geotransform`].
+    pub geotransform: Option<[f64; 6]>,
+    /// The GeoKeys defining the coordinate reference system, the `crs` of rasterio.
+    pub geo_key_directory: GeoKeyDirectory,
+    pub no_data: Option<f64>,
+    /// The compression of the raster data, where files read with a compression that cannot be
+    /// written, e.g. JPEG, have [`Compression::None`].
+    pub compression: Compression,
+    /// The width and height of the tiles, or `None` for raster data stored in strips.
+    pub tile_size: Option<(usize, usize)>,
+}
+
+impl Profile {
+    /// Returns the profile of an uncompressed raster without georeferencing and no-data value,
+    /// stored in strips.
+    pub fn new(width: usize, height: usize, num_samples: usize, data_type: RasterDataType) -> Self {
+        Self {
+            width,
+            height,
+            num_samples,
+            data_type,
+            geotransform: None,
+            geo_key_directory: GeoKeyDirectory::default(),
+            no_data: None,
+            compression: Compression::None,
+            tile_size: None,
+        }
+    }
+
+    /// Returns the options for writing a GeoTIFF with the compression and tiling of the profile.
+    pub fn write_options(&self) -> WriteOptions {
+        let options = WriteOptions::default().with_compression(self.compression);
+        match self.tile_size {
+            Some((width, height)) => options.with_tile_size(width, height),
+            None => options,
+        }
+    }
+}
+
+impl GeoTiffMetadata {
+    /// Returns the profile of the file, e.g. to create a GeoTIFF with the same properties.
+    pub fn profile(&self) -> Profile {
+        Profile {
+            width: self.width,
+            height: self.height,
+            num_samples: self.num_samples,
+            data_type: self.data_type,
+            geotransform: self.geotransform,
+            geo_key_directory: self.geo_key_directory.clone(),
+            no_data: self.no_data,
+            compression: Compression::from_method(self.compression).unwrap_or_default(),
+            tile_size: self.tiled.then_some(self.chunk_dimensions),
+        }
+    }
+}
+
+impl GeoTiff {
+    /// Creates a GeoTIFF with the given properties from the given pixel interleaved, row-major
+    /// raster data, see [`Profile`].
+    ///
+    /// Fails with [`GeoTiffError::Format`] if the type or the number of values do not match the
+    /// profile and with [`GeoTiffError::Transform`] if the geotransform is not invertible.
+    pub fn from_profile(profile: &Profile, raster_data: RasterData) -> GeoTiffResult<Self> {
+        let data_type = raster_data.data_type();
+        if data_type != profile.data_type {
+            return Err(GeoTiffError::Format(format!(
+                "The raster data of type {data_type:?} does not match the profile of type {:?}",
+                profile.data_type
+            )));
+        }
+        let len = profile.width * profile.height * profile.num_samples;
+        if raster_data.len() != len {
+            return Err(GeoTiffError::Format(format!(
+                "The raster data has {} values but the profile {len}",
+                raster_data.len()
+            )));
+        }
+
+        Self::from_profile_unchecked(profile, raster_data)
+    }
+
+    /// Creates a GeoTIFF with the given properties from the given raster data without checking
+    /// its type and length, e.g. to write the tags of raster data written block by block.
+    pub(crate) fn from_profile_unchecked(
+        profile: &Profile,
+        raster_data: RasterData,
+    ) -> GeoTiffResult<Self> {
+        let offset = match profile.geo_key_directory.raster_type {
+            Some(RasterType::RasterPixelIsPoint) => -0.5,
+            _ => 0.0,
+        };
+        let coordinate_transform = profile
+            .geotransform
+            .map(|geotransform| CoordinateTransform::from_geotransform(geotransform, offset))
+            .transpose()?;
+
+        let (bits_per_sample, sample_format) = profile.data_type.sample_type();
+        let chunk_dimensions = match profile.tile_size {
+            Some((width, height)) => (width.min(profile.width), height.min(profile.height)),
+            None => (profile.width, profile.height),
+        };
+
+        Ok(GeoTiff {
+            geo_key_directory: profile.geo_key_directory.clone(),
+            raster_width: profile.width,
+            raster_height: profile.height,
+            num_samples: profile.num_samples,
+            no_data: profile.no_data,
+            gdal_metadata: None,
+            descriptive_tags: DescriptiveTags::default(),
+            coordinate_transform,
+            gcps: Vec::new(),
+            chunk_dimensions,
+            interleave: Interleave::Pixel,
+            order: ArrayOrder::RowMajor,
+            band_formats: vec![
+                BandFormat {
+                    sample_format,
+                    bits_per_sample,
+                };
+                profile.num_samples
+            ],
+            sample_roles: vec![SampleRole::Data; profile.num_samples],
+            photometric_interpretation: None,
+            raster_data,
+            corrupt_chunks: Vec::new(),
+            mask: None,
+            bigtiff: false,
+            overviews: Vec::new(),
+        })
+    }
+}
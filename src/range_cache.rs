@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+use std::io::{self, SeekFrom};
+use std::ops::Range;
+
+/// The minimum number of bytes fetched on a cache miss, so that the small reads done while parsing
+/// the image file directories do not each require a separate request.
+pub(crate) const MIN_FETCH_SIZE: u64 = 16 * 1024;
+
+/// The byte ranges of a file that have been fetched so far.
+#[derive(Debug, Default)]
+pub(crate) struct RangeCache {
+    segments: BTreeMap<u64, Vec<u8>>,
+    /// The length of the file, which is known once a fetch reached its end.
+    len: Option<u64>,
+}
+
+impl RangeCache {
+    /// Stores the bytes fetched for the given range. Receiving fewer bytes than requested marks
+    /// the end of the file.
+    pub fn insert(&mut self, range: Range<u64>, data: Vec<u8>) {
+        if (data.len() as u64) < range.end - range.start {
+            self.len = Some(range.start + data.len() as u64);
+        }
+        self.segments.insert(range.start, data);
+    }
+
+    /// Drops the segment fetched for the given range.
+    #[cfg(feature = "async")]
+    pub fn remove(&mut self, range: &Range<u64>) {
+        self.segments.remove(&range.start);
+    }
+
+    /// Returns whether the given range can be read without fetching.
+    #[cfg(feature = "async")]
+    pub fn contains(&self, range: &Range<u64>) -> bool {
+        if matches!(self.len, Some(len) if range.start >= len) {
+            return true;
+        }
+
+        self.segments
+            .range(..=range.start)
+            .next_back()
+            .is_some_and(|(start, data)| {
+                start + data.len() as u64 >= range.end
+                    || matches!(self.len, Some(len) if start + data.len() as u64 == len)
+            })
+    }
+
+    /// Copies the cached bytes at the given position, returning `None` if they have not been
+    /// fetched yet.
+    pub fn read_at(&self, position: u64, buf: &mut [u8]) -> Option<usize> {
+        if matches!(self.len, Some(len) if position >= len) {
+            return Some(0);
+        }
+
+        let (start, data) = self.segments.range(..=position).next_back()?;
+        let offset = (position - start) as usize;
+        if offset >= data.len() {
+            return None;
+        }
+
+        let count = buf.len().min(data.len() - offset);
+        buf[..count].copy_from_slice(&data[offset..offset + count]);
+        Some(count)
+    }
+
+    /// Returns the range to fetch when reading into the given buffer at the given position fails.
+    pub fn range_to_fetch(position: u64, buf: &[u8]) -> Range<u64> {
+        position..position + (buf.len() as u64).max(MIN_FETCH_SIZE)
+    }
+
+    /// Resolves the given seek relative to the current position.
+    pub fn seek(&self, position: u64, pos: SeekFrom) -> io::Result<u64> {
+        match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => position.checked_add_signed(offset),
+            SeekFrom::End(offset) => {
+                let Some(len) = self.len else {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        "Seeking relative to the end of a file of unknown length",
+                    ));
+                };
+                len.checked_add_signed(offset)
+            }
+        }
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid seek position"))
+    }
+}
@@ -1,17 +1,46 @@
+use std::any::{type_name, Any};
 use std::fmt;
 use std::fmt::{Debug, Formatter};
+use std::io::{self, Write};
+use std::ops::Range;
 
-pub(super) enum RasterData {
+use half::f16;
+use num_complex::Complex;
+use num_traits::{FromPrimitive, ToPrimitive};
+use tiff::decoder::DecodingResult;
+use tiff::tags::SampleFormat;
+
+use crate::error::{GeoTiffError, GeoTiffResult};
+use crate::layout::{ArrayOrder, Interleave, RasterDataType, COMPLEX_IEEEFP_SAMPLE_FORMAT};
+use crate::window::Window;
+
+macro_rules! convert_primitive_type {
+    ($result: expr, $actual: ty, $expected: ty) => {
+        $result.ok_or(GeoTiffError::ValueConversion {
+            value_type: type_name::<$actual>(),
+            requested_type: type_name::<$expected>(),
+        })
+    };
+}
+
+/// The decoded values of a raster in the type of its samples, see
+/// [`GeoTiff::into_raster_data`](crate::GeoTiff::into_raster_data).
+pub enum RasterData {
     U8(Vec<u8>),
     U16(Vec<u16>),
     U32(Vec<u32>),
     U64(Vec<u64>),
+    F16(Vec<f16>),
     F32(Vec<f32>),
     F64(Vec<f64>),
     I8(Vec<i8>),
     I16(Vec<i16>),
     I32(Vec<i32>),
     I64(Vec<i64>),
+    CI16(Vec<Complex<i16>>),
+    CI32(Vec<Complex<i32>>),
+    CF32(Vec<Complex<f32>>),
+    CF64(Vec<Complex<f64>>),
 }
 
 impl Debug for RasterData {
@@ -23,31 +52,526 @@ impl Debug for RasterData {
                 RasterData::U16(_) => "u16",
                 RasterData::U32(_) => "u32",
                 RasterData::U64(_) => "u64",
+                RasterData::F16(_) => "f16",
                 RasterData::F32(_) => "f32",
                 RasterData::F64(_) => "f64",
                 RasterData::I8(_) => "i8",
                 RasterData::I16(_) => "i16",
                 RasterData::I32(_) => "i32",
                 RasterData::I64(_) => "i64",
+                RasterData::CI16(_) => "complex i16",
+                RasterData::CI32(_) => "complex i32",
+                RasterData::CF32(_) => "complex f32",
+                RasterData::CF64(_) => "complex f64",
             },
             self.len()
         ))
     }
 }
 
+impl From<DecodingResult> for RasterData {
+    fn from(result: DecodingResult) -> Self {
+        match result {
+            DecodingResult::U8(data) => RasterData::U8(data),
+            DecodingResult::U16(data) => RasterData::U16(data),
+            DecodingResult::U32(data) => RasterData::U32(data),
+            DecodingResult::U64(data) => RasterData::U64(data),
+            DecodingResult::F16(data) => RasterData::F16(data),
+            DecodingResult::F32(data) => RasterData::F32(data),
+            DecodingResult::F64(data) => RasterData::F64(data),
+            DecodingResult::I8(data) => RasterData::I8(data),
+            DecodingResult::I16(data) => RasterData::I16(data),
+            DecodingResult::I32(data) => RasterData::I32(data),
+            DecodingResult::I64(data) => RasterData::I64(data),
+        }
+    }
+}
+
+/// The shape of a pixel buffer.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct BufferLayout {
+    pub width: usize,
+    pub height: usize,
+    pub num_samples: usize,
+    pub interleave: Interleave,
+    pub order: ArrayOrder,
+}
+
+impl BufferLayout {
+    /// Returns the index of the given sample of the given pixel.
+    pub fn index(&self, x: usize, y: usize, sample: usize) -> usize {
+        match (self.interleave, self.order) {
+            (Interleave::Pixel, ArrayOrder::RowMajor) => {
+                (y * self.width + x) * self.num_samples + sample
+            }
+            (Interleave::Pixel, ArrayOrder::ColumnMajor) => {
+                (x * self.height + y) * self.num_samples + sample
+            }
+            (Interleave::Band, ArrayOrder::RowMajor) => (sample * self.height + y) * self.width + x,
+            (Interleave::Band, ArrayOrder::ColumnMajor) => {
+                (sample * self.width + x) * self.height + y
+            }
+        }
+    }
+
+    /// Returns the distance between the values of a sample of horizontally adjacent pixels.
+    fn pixel_stride(&self) -> usize {
+        match (self.interleave, self.order) {
+            (Interleave::Pixel, ArrayOrder::RowMajor) => self.num_samples,
+            (Interleave::Pixel, ArrayOrder::ColumnMajor) => self.height * self.num_samples,
+            (Interleave::Band, ArrayOrder::RowMajor) => 1,
+            (Interleave::Band, ArrayOrder::ColumnMajor) => self.height,
+        }
+    }
+
+    /// Returns whether the samples of each row are stored contiguously, pixel by pixel.
+    fn has_interleaved_rows(&self) -> bool {
+        self.order == ArrayOrder::RowMajor
+            && (self.interleave == Interleave::Pixel || self.num_samples == 1)
+    }
+
+    /// Returns the same layout with the given arrangement of the samples and pixels.
+    pub fn with(self, interleave: Interleave, order: ArrayOrder) -> Self {
+        Self {
+            interleave,
+            order,
+            ..self
+        }
+    }
+}
+
 impl RasterData {
-    fn len(&self) -> usize {
+    /// Returns the value at the given index, converted to `T`, or fails with
+    /// [`GeoTiffError::ValueConversion`] if `T` cannot represent it.
+    pub(super) fn try_get<T: FromPrimitive + 'static>(&self, index: usize) -> GeoTiffResult<T> {
+        match self {
+            RasterData::U8(data) => convert_primitive_type!(T::from_u8(data[index]), u8, T),
+            RasterData::U16(data) => convert_primitive_type!(T::from_u16(data[index]), u16, T),
+            RasterData::U32(data) => convert_primitive_type!(T::from_u32(data[index]), u32, T),
+            RasterData::U64(data) => convert_primitive_type!(T::from_u64(data[index]), u64, T),
+            RasterData::F16(data) => {
+                convert_primitive_type!(T::from_f32(data[index].to_f32()), f16, T)
+            }
+            RasterData::F32(data) => convert_primitive_type!(T::from_f32(data[index]), f32, T),
+            RasterData::F64(data) => convert_primitive_type!(T::from_f64(data[index]), f64, T),
+            RasterData::I8(data) => convert_primitive_type!(T::from_i8(data[index]), i8, T),
+            RasterData::I16(data) => convert_primitive_type!(T::from_i16(data[index]), i16, T),
+            RasterData::I32(data) => convert_primitive_type!(T::from_i32(data[index]), i32, T),
+            RasterData::I64(data) => convert_primitive_type!(T::from_i64(data[index]), i64, T),
+            RasterData::CI16(data) => {
+                convert_primitive_type!(data[index].to_f64().and_then(T::from_f64), Complex<i16>, T)
+            }
+            RasterData::CI32(data) => {
+                convert_primitive_type!(data[index].to_f64().and_then(T::from_f64), Complex<i32>, T)
+            }
+            RasterData::CF32(data) => {
+                convert_primitive_type!(data[index].to_f32().and_then(T::from_f32), Complex<f32>, T)
+            }
+            RasterData::CF64(data) => {
+                convert_primitive_type!(data[index].to_f64().and_then(T::from_f64), Complex<f64>, T)
+            }
+        }
+    }
+
+    /// Returns the value at the given index, converted to `T`.
+    ///
+    /// Panics if `T` cannot represent the value, which for `f64` only happens for complex values
+    /// with an imaginary part.
+    pub(super) fn get<T: FromPrimitive + 'static>(&self, index: usize) -> T {
+        self.try_get(index)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Returns the data as the given type if it only differs in the interpretation of the bits,
+    /// i.e. unsigned bytes that are flagged as signed ones, and unchanged otherwise.
+    pub(super) fn reinterpret(self, data_type: RasterDataType) -> Self {
+        match (self, data_type) {
+            (RasterData::U8(data), RasterDataType::I8) => {
+                RasterData::I8(data.into_iter().map(|value| value as i8).collect())
+            }
+            (data, _) => data,
+        }
+    }
+
+    /// Writes the textual representation of the value at the given index.
+    pub(super) fn write_value<W: Write>(&self, index: usize, writer: &mut W) -> io::Result<()> {
+        match self {
+            RasterData::U8(data) => write!(writer, "{}", data[index]),
+            RasterData::U16(data) => write!(writer, "{}", data[index]),
+            RasterData::U32(data) => write!(writer, "{}", data[index]),
+            RasterData::U64(data) => write!(writer, "{}", data[index]),
+            RasterData::F16(data) => write!(writer, "{}", data[index]),
+            RasterData::F32(data) => write!(writer, "{}", data[index]),
+            RasterData::F64(data) => write!(writer, "{}", data[index]),
+            RasterData::I8(data) => write!(writer, "{}", data[index]),
+            RasterData::I16(data) => write!(writer, "{}", data[index]),
+            RasterData::I32(data) => write!(writer, "{}", data[index]),
+            RasterData::I64(data) => write!(writer, "{}", data[index]),
+            RasterData::CI16(data) => write!(writer, "{}", data[index]),
+            RasterData::CI32(data) => write!(writer, "{}", data[index]),
+            RasterData::CF32(data) => write!(writer, "{}", data[index]),
+            RasterData::CF64(data) => write!(writer, "{}", data[index]),
+        }
+    }
+
+    /// Returns the type of the values.
+    pub fn data_type(&self) -> RasterDataType {
+        match self {
+            RasterData::U8(_) => RasterDataType::U8,
+            RasterData::U16(_) => RasterDataType::U16,
+            RasterData::U32(_) => RasterDataType::U32,
+            RasterData::U64(_) => RasterDataType::U64,
+            RasterData::F16(_) => RasterDataType::F16,
+            RasterData::F32(_) => RasterDataType::F32,
+            RasterData::F64(_) => RasterDataType::F64,
+            RasterData::I8(_) => RasterDataType::I8,
+            RasterData::I16(_) => RasterDataType::I16,
+            RasterData::I32(_) => RasterDataType::I32,
+            RasterData::I64(_) => RasterDataType::I64,
+            RasterData::CI16(_) => RasterDataType::CI16,
+            RasterData::CI32(_) => RasterDataType::CI32,
+            RasterData::CF32(_) => RasterDataType::CF32,
+            RasterData::CF64(_) => RasterDataType::CF64,
+        }
+    }
+
+    /// Returns the values in the given range as bytes in native byte order, the byte order TIFF
+    /// files are written in.
+    pub(super) fn to_ne_bytes(&self, range: Range<usize>) -> Vec<u8> {
+        // The real part of complex values precedes the imaginary part
+        macro_rules! to_ne_bytes {
+            ($($variant: ident),*; $($complex_variant: ident),*) => {
+                match self {
+                    $(RasterData::$variant(data) => data[range]
+                        .iter()
+                        .flat_map(|value| value.to_ne_bytes())
+                        .collect(),)*
+                    $(RasterData::$complex_variant(data) => data[range]
+                        .iter()
+                        .flat_map(|value| [value.re.to_ne_bytes(), value.im.to_ne_bytes()])
+                        .flatten()
+                        .collect(),)*
+                }
+            };
+        }
+
+        to_ne_bytes!(U8, U16, U32, U64, F16, F32, F64, I8, I16, I32, I64; CI16, CI32, CF32, CF64)
+    }
+
+    /// Creates a zero-initialized buffer of the given type and length.
+    pub(super) fn zeroed(data_type: RasterDataType, len: usize) -> Self {
+        match data_type {
+            RasterDataType::U8 => RasterData::U8(vec![0; len]),
+            RasterDataType::U16 => RasterData::U16(vec![0; len]),
+            RasterDataType::U32 => RasterData::U32(vec![0; len]),
+            RasterDataType::U64 => RasterData::U64(vec![0; len]),
+            RasterDataType::F16 => RasterData::F16(vec![f16::ZERO; len]),
+            RasterDataType::F32 => RasterData::F32(vec![0.0; len]),
+            RasterDataType::F64 => RasterData::F64(vec![0.0; len]),
+            RasterDataType::I8 => RasterData::I8(vec![0; len]),
+            RasterDataType::I16 => RasterData::I16(vec![0; len]),
+            RasterDataType::I32 => RasterData::I32(vec![0; len]),
+            RasterDataType::I64 => RasterData::I64(vec![0; len]),
+            RasterDataType::CI16 => RasterData::CI16(vec![Complex::new(0, 0); len]),
+            RasterDataType::CI32 => RasterData::CI32(vec![Complex::new(0, 0); len]),
+            RasterDataType::CF32 => RasterData::CF32(vec![Complex::new(0.0, 0.0); len]),
+            RasterDataType::CF64 => RasterData::CF64(vec![Complex::new(0.0, 0.0); len]),
+        }
+    }
+
+    /// Creates a zero-initialized buffer of the same type with the given length.
+    pub(super) fn zeroed_like(&self, len: usize) -> Self {
+        match self {
+            RasterData::U8(_) => RasterData::U8(vec![0; len]),
+            RasterData::U16(_) => RasterData::U16(vec![0; len]),
+            RasterData::U32(_) => RasterData::U32(vec![0; len]),
+            RasterData::U64(_) => RasterData::U64(vec![0; len]),
+            RasterData::F16(_) => RasterData::F16(vec![f16::ZERO; len]),
+            RasterData::F32(_) => RasterData::F32(vec![0.0; len]),
+            RasterData::F64(_) => RasterData::F64(vec![0.0; len]),
+            RasterData::I8(_) => RasterData::I8(vec![0; len]),
+            RasterData::I16(_) => RasterData::I16(vec![0; len]),
+            RasterData::I32(_) => RasterData::I32(vec![0; len]),
+            RasterData::I64(_) => RasterData::I64(vec![0; len]),
+            RasterData::CI16(_) => RasterData::CI16(vec![Complex::new(0, 0); len]),
+            RasterData::CI32(_) => RasterData::CI32(vec![Complex::new(0, 0); len]),
+            RasterData::CF32(_) => RasterData::CF32(vec![Complex::new(0.0, 0.0); len]),
+            RasterData::CF64(_) => RasterData::CF64(vec![Complex::new(0.0, 0.0); len]),
+        }
+    }
+
+    /// Copies the pixels inside `src_window` of `src` into this buffer, placing the upper left
+    /// pixel at `dst_origin`.
+    ///
+    /// Only the given pairs of source and destination samples are copied, e.g. to place the single
+    /// sample of a plane of a planar image or to keep only some of the samples.
+    pub(super) fn copy_block(
+        &mut self,
+        layout: BufferLayout,
+        dst_origin: (usize, usize),
+        src: &RasterData,
+        src_layout: BufferLayout,
+        src_window: &Window,
+        samples: &[(usize, usize)],
+    ) -> GeoTiffResult<()> {
+        macro_rules! copy {
+            ($($variant: ident),*) => {
+                match (self, src) {
+                    $((RasterData::$variant(dst), RasterData::$variant(src)) => {
+                        copy_block(dst, layout, dst_origin, src, src_layout, src_window, samples);
+                        Ok(())
+                    })*
+                    _ => Err(GeoTiffError::Format(
+                        "Inconsistent data types between chunks".into(),
+                    )),
+                }
+            };
+        }
+
+        copy!(U8, U16, U32, U64, F16, F32, F64, I8, I16, I32, I64, CI16, CI32, CF32, CF64)
+    }
+
+    /// Sets the given samples of the pixels inside `window` to `value`, or leaves them untouched if
+    /// the value cannot be represented by the type of the data.
+    pub(super) fn fill_block(
+        &mut self,
+        layout: BufferLayout,
+        window: &Window,
+        samples: &[usize],
+        value: f64,
+    ) {
+        macro_rules! fill {
+            ($($variant: ident),*) => {
+                match self {
+                    $(RasterData::$variant(data) => {
+                        if let Some(value) = FromPrimitive::from_f64(value) {
+                            for y in window.y..window.y + window.height {
+                                for x in window.x..window.x + window.width {
+                                    for &sample in samples {
+                                        data[layout.index(x, y, sample)] = value;
+                                    }
+                                }
+                            }
+                        }
+                    })*
+                }
+            };
+        }
+
+        fill!(U8, U16, U32, U64, F16, F32, F64, I8, I16, I32, I64, CI16, CI32, CF32, CF64)
+    }
+
+    /// Sets the value at the given index, rounded for integer types, or leaves it untouched if the
+    /// value cannot be represented by the type of the data.
+    pub(super) fn set(&mut self, index: usize, value: f64) {
+        let value = match self.data_type().sample_type() {
+            (_, SampleFormat::IEEEFP | SampleFormat::Unknown(COMPLEX_IEEEFP_SAMPLE_FORMAT)) => {
+                value
+            }
+            _ => value.round(),
+        };
+
+        macro_rules! set {
+            ($($variant: ident),*) => {
+                match self {
+                    $(RasterData::$variant(data) => {
+                        if let Some(value) = FromPrimitive::from_f64(value) {
+                            data[index] = value;
+                        }
+                    })*
+                }
+            };
+        }
+
+        set!(U8, U16, U32, U64, F16, F32, F64, I8, I16, I32, I64, CI16, CI32, CF32, CF64)
+    }
+
+    /// Returns a copy of the data rearranged from the given layout into another one.
+    pub(super) fn rearrange(&self, from: BufferLayout, to: BufferLayout) -> GeoTiffResult<Self> {
+        let mut data = self.zeroed_like(self.len());
+        data.copy_block(
+            to,
+            (0, 0),
+            self,
+            from,
+            &Window::new(0, 0, from.width, from.height),
+            &all_samples(from.num_samples),
+        )?;
+        Ok(data)
+    }
+
+    /// Returns the number of values.
+    pub fn len(&self) -> usize {
         match self {
             RasterData::U8(data) => data.len(),
             RasterData::U16(data) => data.len(),
             RasterData::U32(data) => data.len(),
             RasterData::U64(data) => data.len(),
+            RasterData::F16(data) => data.len(),
             RasterData::F32(data) => data.len(),
             RasterData::F64(data) => data.len(),
             RasterData::I8(data) => data.len(),
             RasterData::I16(data) => data.len(),
             RasterData::I32(data) => data.len(),
             RasterData::I64(data) => data.len(),
+            RasterData::CI16(data) => data.len(),
+            RasterData::CI32(data) => data.len(),
+            RasterData::CF32(data) => data.len(),
+            RasterData::CF64(data) => data.len(),
+        }
+    }
+
+    /// Returns whether there are no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the values converted to `T`, or fails with [`GeoTiffError::ValueConversion`] if `T`
+    /// cannot represent one of them. If `T` is the type of the values, the buffer is returned
+    /// without copying it.
+    pub fn into_vec<T: FromPrimitive + 'static>(self) -> GeoTiffResult<Vec<T>> {
+        match self.downcast() {
+            Ok(values) => Ok(values),
+            Err(data) => (0..data.len()).map(|index| data.try_get(index)).collect(),
+        }
+    }
+
+    /// Returns the complex values with 16-bit integer parts, or `None` if the values are of
+    /// another type.
+    pub fn as_cint16(&self) -> Option<&[Complex<i16>]> {
+        self.as_slice()
+    }
+
+    /// Returns the complex values with 32-bit integer parts, or `None` if the values are of
+    /// another type.
+    pub fn as_cint32(&self) -> Option<&[Complex<i32>]> {
+        self.as_slice()
+    }
+
+    /// Returns the complex values with 32-bit floating point parts, or `None` if the values are of
+    /// another type.
+    pub fn as_cfloat32(&self) -> Option<&[Complex<f32>]> {
+        self.as_slice()
+    }
+
+    /// Returns the complex values with 64-bit floating point parts, or `None` if the values are of
+    /// another type.
+    pub fn as_cfloat64(&self) -> Option<&[Complex<f64>]> {
+        self.as_slice()
+    }
+
+    /// Returns the magnitude of each value, e.g. the amplitude of SAR data. Real values are
+    /// treated as complex values without an imaginary part, so their magnitude is their absolute
+    /// value.
+    pub fn magnitude(&self) -> Vec<f64> {
+        (0..self.len())
+            .map(|index| self.complex_value(index).norm())
+            .collect()
+    }
+
+    /// Returns the phase of each value in radians, between -π and π, e.g. the interferometric
+    /// phase of SAR data. Real values are treated as complex values without an imaginary part,
+    /// so their phase is 0, or π if they are negative.
+    pub fn phase(&self) -> Vec<f64> {
+        (0..self.len())
+            .map(|index| self.complex_value(index).arg())
+            .collect()
+    }
+
+    /// Returns the value at the given index as a complex number.
+    fn complex_value(&self, index: usize) -> Complex<f64> {
+        match self {
+            RasterData::CI16(data) => Complex::new(data[index].re.into(), data[index].im.into()),
+            RasterData::CI32(data) => Complex::new(data[index].re.into(), data[index].im.into()),
+            RasterData::CF32(data) => Complex::new(data[index].re.into(), data[index].im.into()),
+            RasterData::CF64(data) => data[index],
+            _ => Complex::new(self.get(index), 0.0),
+        }
+    }
+
+    /// Returns the values as a slice if `T` is the type of the values.
+    pub(super) fn as_slice<T: 'static>(&self) -> Option<&[T]> {
+        macro_rules! as_slice {
+            ($($variant: ident),*) => {
+                match self {
+                    $(RasterData::$variant(data) => (data as &dyn Any).downcast_ref::<Vec<T>>(),)*
+                }
+            };
+        }
+
+        as_slice!(U8, U16, U32, U64, F16, F32, F64, I8, I16, I32, I64, CI16, CI32, CF32, CF64)
+            .map(Vec::as_slice)
+    }
+
+    /// Returns the buffer if `T` is the type of the values, or the data otherwise.
+    fn downcast<T: 'static>(self) -> Result<Vec<T>, Self> {
+        macro_rules! downcast {
+            ($($variant: ident),*) => {
+                match self {
+                    $(RasterData::$variant(data) => {
+                        let mut data = Some(data);
+                        let any = &mut data as &mut dyn Any;
+                        if let Some(values) = any.downcast_mut::<Option<Vec<T>>>() {
+                            return Ok(values.take().unwrap_or_default());
+                        }
+                        Err(RasterData::$variant(data.unwrap_or_default()))
+                    })*
+                }
+            };
         }
+
+        downcast!(U8, U16, U32, U64, F16, F32, F64, I8, I16, I32, I64, CI16, CI32, CF32, CF64)
     }
 }
+
+fn copy_block<T: Copy>(
+    dst: &mut [T],
+    layout: BufferLayout,
+    (dst_x, dst_y): (usize, usize),
+    src: &[T],
+    src_layout: BufferLayout,
+    src_window: &Window,
+    samples: &[(usize, usize)],
+) {
+    let width = src_window.width;
+    let (src_stride, dst_stride) = (src_layout.pixel_stride(), layout.pixel_stride());
+    let all_samples = src_layout.num_samples == layout.num_samples
+        && samples.len() == layout.num_samples
+        && samples
+            .iter()
+            .enumerate()
+            .all(|(i, &(src_sample, dst_sample))| src_sample == i && dst_sample == i);
+
+    for row in 0..src_window.height {
+        let src_y = src_window.y + row;
+
+        if src_layout.has_interleaved_rows() && layout.has_interleaved_rows() && all_samples {
+            // Whole rows of interleaved pixels can be copied at once
+            let src_start = src_layout.index(src_window.x, src_y, 0);
+            let dst_start = layout.index(dst_x, dst_y + row, 0);
+            let len = width * layout.num_samples;
+            dst[dst_start..dst_start + len].copy_from_slice(&src[src_start..src_start + len]);
+            continue;
+        }
+
+        for &(src_sample, dst_sample) in samples {
+            let src_start = src_layout.index(src_window.x, src_y, src_sample);
+            let dst_start = layout.index(dst_x, dst_y + row, dst_sample);
+
+            if src_stride == 1 && dst_stride == 1 {
+                dst[dst_start..dst_start + width]
+                    .copy_from_slice(&src[src_start..src_start + width]);
+            } else {
+                for pixel in 0..width {
+                    dst[dst_start + pixel * dst_stride] = src[src_start + pixel * src_stride];
+                }
+            }
+        }
+    }
+}
+
+/// Returns the pairs of source and destination samples copying each of the given number of
+/// samples to itself, see [`RasterData::copy_block`].
+pub(crate) fn all_samples(num_samples: usize) -> Vec<(usize, usize)> {
+    (0..num_samples).map(|sample| (sample, sample)).collect()
+}
@@ -0,0 +1,99 @@
+use std::io::{Read, Seek};
+
+use crate::error::{GeoTiffError, GeoTiffResult};
+use crate::layout::RasterDataType;
+use crate::reader::GeoTiffReader;
+use crate::window::Window;
+use crate::GeoTiff;
+
+/// A raster that can be read window by window, implemented by the decoded [`GeoTiff`] as well as
+/// the lazy [`GeoTiffReader`], so that processing can be written once for both.
+pub trait RasterSource {
+    /// Returns the width and height of the raster in pixels.
+    fn dimensions(&self) -> (usize, usize);
+
+    /// Returns the number of samples (bands) of the raster.
+    fn num_samples(&self) -> usize;
+
+    /// Returns the type of the values.
+    fn data_type(&self) -> RasterDataType;
+
+    /// Returns the affine transform from raster to model space as the six coefficients used by
+    /// GDAL, see [`GeoTiff::geotransform`].
+    fn geotransform(&self) -> Option<[f64; 6]>;
+
+    /// Returns the value marking pixels without data.
+    fn no_data(&self) -> Option<f64>;
+
+    /// Reads the pixels in the given window, with the coordinate transform adjusted so that they
+    /// keep their location in model space.
+    ///
+    /// Fails with [`GeoTiffError::Format`] if the window is empty or exceeds the raster
+    /// dimensions.
+    fn read_window(&mut self, window: &Window) -> GeoTiffResult<GeoTiff>;
+}
+
+impl RasterSource for GeoTiff {
+    fn dimensions(&self) -> (usize, usize) {
+        (self.raster_width, self.raster_height)
+    }
+
+    fn num_samples(&self) -> usize {
+        self.num_samples
+    }
+
+    fn data_type(&self) -> RasterDataType {
+        GeoTiff::data_type(self)
+    }
+
+    fn geotransform(&self) -> Option<[f64; 6]> {
+        GeoTiff::geotransform(self)
+    }
+
+    fn no_data(&self) -> Option<f64> {
+        self.no_data
+    }
+
+    /// Copies the pixels in the window, see [`GeoTiff::crop`].
+    fn read_window(&mut self, window: &Window) -> GeoTiffResult<GeoTiff> {
+        if window.is_empty()
+            || window.x + window.width > self.raster_width
+            || window.y + window.height > self.raster_height
+        {
+            return Err(GeoTiffError::Format(format!(
+                "Window {window:?} is empty or exceeds the raster dimensions {}x{}",
+                self.raster_width, self.raster_height
+            )));
+        }
+
+        self.subset(window)
+    }
+}
+
+impl<R: Read + Seek> RasterSource for GeoTiffReader<R> {
+    fn dimensions(&self) -> (usize, usize) {
+        (self.raster_width, self.raster_height)
+    }
+
+    fn num_samples(&self) -> usize {
+        self.num_samples
+    }
+
+    fn data_type(&self) -> RasterDataType {
+        GeoTiffReader::data_type(self)
+    }
+
+    fn geotransform(&self) -> Option<[f64; 6]> {
+        GeoTiffReader::geotransform(self)
+    }
+
+    fn no_data(&self) -> Option<f64> {
+        self.no_data
+    }
+
+    /// Decodes only the strips or tiles intersecting the window, with the samples, interleave and
+    /// order set for the reader, see [`GeoTiffReader::read_region`].
+    fn read_window(&mut self, window: &Window) -> GeoTiffResult<GeoTiff> {
+        self.read_region(window)
+    }
+}
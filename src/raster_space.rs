@@ -0,0 +1,229 @@
+use geo_types::{Coord, LineString, Polygon, Rect};
+
+use crate::clip::bounding_rect;
+use crate::coordinate_transform::CoordinateTransform;
+use crate::error::{GeoTiffError, GeoTiffResult};
+use crate::geo_key_directory::RasterType;
+use crate::georeference::Gcp;
+use crate::window::Window;
+
+/// Relates the pixels of a raster to locations in model space.
+///
+/// Ref: https://docs.ogc.org/is/19-008r4/19-008r4.html#_raster_space
+pub(crate) trait RasterSpace {
+    fn raster_dimensions(&self) -> (usize, usize);
+
+    fn raster_type(&self) -> Option<RasterType>;
+
+    fn coordinate_transform(&self) -> Option<&CoordinateTransform>;
+
+    fn num_samples(&self) -> usize;
+
+    fn gcps(&self) -> &[Gcp];
+
+    /// Returns the offset of the pixel grid in raster space, which depends on the raster type.
+    fn raster_offset(&self) -> f64 {
+        match self.raster_type() {
+            Some(RasterType::RasterPixelIsPoint) => -0.5,
+            _ => 0.0,
+        }
+    }
+
+    fn transform_to_model(&self, coord: &Coord) -> Coord {
+        match self.coordinate_transform() {
+            None => *coord,
+            Some(transform) => transform.transform_to_model(coord),
+        }
+    }
+
+    fn transform_to_raster(&self, coord: &Coord) -> Coord {
+        match self.coordinate_transform() {
+            None => *coord,
+            Some(transform) => transform.transform_to_raster(coord),
+        }
+    }
+
+    /// Returns the column and row of the pixel containing the given location in model space.
+    fn pixel_at(&self, coord: &Coord) -> Option<(usize, usize)> {
+        let (raster_width, raster_height) = self.raster_dimensions();
+        let mut coord = self.transform_to_raster(coord);
+
+        let raster_offset = self.raster_offset();
+        coord.x -= raster_offset;
+        coord.y -= raster_offset;
+
+        if coord.x < 0.0
+            || coord.x >= raster_width as f64
+            || coord.y < 0.0
+            || coord.y >= raster_height as f64
+        {
+            return None;
+        }
+
+        Some((coord.x as usize, coord.y as usize))
+    }
+
+    /// Returns the location of the center of the given pixel in model space.
+    fn pixel_center(&self, col: usize, row: usize) -> Coord {
+        let offset = self.raster_offset() + 0.5;
+        self.transform_to_model(&Coord {
+            x: col as f64 + offset,
+            y: row as f64 + offset,
+        })
+    }
+
+    fn compute_model_extent(&self) -> Rect {
+        bounding_rect(&self.compute_footprint()).expect("Footprints are not empty")
+    }
+
+    /// Returns the polygon connecting the corners of the raster in model space, starting at the
+    /// top left corner and turning clockwise in raster space.
+    fn compute_footprint(&self) -> Polygon {
+        let (raster_width, raster_height) = self.raster_dimensions();
+        let offset = self.raster_offset();
+        let corners = [
+            (0.0, 0.0),
+            (raster_width as f64, 0.0),
+            (raster_width as f64, raster_height as f64),
+            (0.0, raster_height as f64),
+        ]
+        .map(|(x, y)| {
+            self.transform_to_model(&Coord {
+                x: x + offset,
+                y: y + offset,
+            })
+        });
+
+        Polygon::new(LineString::from(corners.to_vec()), Vec::new())
+    }
+
+    /// Returns the affine transform from the corners of the pixels to model space in the order
+    /// used by GDAL, or `None` if there is no transform or it is not affine.
+    fn compute_geotransform(&self) -> Option<[f64; 6]> {
+        let [a, b, c, d, e, f] = self.coordinate_transform()?.affine_coefficients()?;
+        let offset = self.raster_offset();
+
+        Some([c + (a + b) * offset, a, b, f + (d + e) * offset, d, e])
+    }
+
+    /// Returns the distances in model space between the corners of a pixel along the rows and
+    /// columns of the raster, or `None` if there is no transform or it is not affine.
+    fn compute_resolution(&self) -> Option<Coord> {
+        let [_, a, b, _, d, e] = self.compute_geotransform()?;
+        Some(Coord {
+            x: a.hypot(d),
+            y: b.hypot(e),
+        })
+    }
+
+    /// Returns the smallest pixel window covering the intersection of the raster with the given
+    /// rectangle in model space.
+    fn compute_intersection_window(&self, rect: &Rect) -> Option<Window> {
+        let (raster_width, raster_height) = self.raster_dimensions();
+        let raster_offset = self.raster_offset();
+
+        let (min, max) = (rect.min(), rect.max());
+        let corners = [
+            min,
+            Coord { x: max.x, y: min.y },
+            max,
+            Coord { x: min.x, y: max.y },
+        ]
+        .map(|corner| self.transform_to_raster(&corner));
+        let (x, x_end, y, y_end) = corners.iter().fold(
+            (
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+            ),
+            |(x, x_end, y, y_end), corner| {
+                (
+                    x.min(corner.x),
+                    x_end.max(corner.x),
+                    y.min(corner.y),
+                    y_end.max(corner.y),
+                )
+            },
+        );
+
+        let x = (x - raster_offset).floor().max(0.0);
+        let x_end = (x_end - raster_offset).ceil().min(raster_width as f64);
+        let y = (y - raster_offset).floor().max(0.0);
+        let y_end = (y_end - raster_offset).ceil().min(raster_height as f64);
+
+        if x < x_end && y < y_end {
+            Some(Window::new(
+                x as usize,
+                y as usize,
+                (x_end - x) as usize,
+                (y_end - y) as usize,
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the origin and scale of the pixel grid of a raster of the given dimensions covering
+    /// the given window of this raster, see [`CoordinateTransform::derive_raster_grid`].
+    fn derive_raster_grid(&self, window: &Window, width: usize, height: usize) -> (Coord, Coord) {
+        let scale = Coord {
+            x: window.width as f64 / width as f64,
+            y: window.height as f64 / height as f64,
+        };
+
+        // Pixel edges of both rasters have to coincide, so the raster offset has to be considered
+        let raster_offset = self.raster_offset();
+        let origin = Coord {
+            x: window.x as f64 + raster_offset * (1.0 - scale.x),
+            y: window.y as f64 + raster_offset * (1.0 - scale.y),
+        };
+
+        (origin, scale)
+    }
+
+    /// Returns the coordinate transform of a raster of the given dimensions covering the given
+    /// window of this raster.
+    fn derive_coordinate_transform(
+        &self,
+        window: &Window,
+        width: usize,
+        height: usize,
+    ) -> GeoTiffResult<Option<CoordinateTransform>> {
+        let (origin, scale) = self.derive_raster_grid(window, width, height);
+
+        self.coordinate_transform()
+            .map(|transform| transform.derive_raster_grid(&origin, &scale))
+            .transpose()
+    }
+
+    /// Returns the ground control points of a raster of the given dimensions covering the given
+    /// window of this raster.
+    fn derive_gcps(&self, window: &Window, width: usize, height: usize) -> Vec<Gcp> {
+        let (origin, scale) = self.derive_raster_grid(window, width, height);
+
+        self.gcps()
+            .iter()
+            .map(|gcp| Gcp {
+                pixel: Coord {
+                    x: (gcp.pixel.x - origin.x) / scale.x,
+                    y: (gcp.pixel.y - origin.y) / scale.y,
+                },
+                model: gcp.model,
+            })
+            .collect()
+    }
+
+    /// Fails with [`GeoTiffError::SampleOutOfBounds`] if the raster does not have the given
+    /// sample.
+    fn sample_in_bounds(&self, sample: usize) -> GeoTiffResult<()> {
+        let num_samples = self.num_samples();
+        if sample >= num_samples {
+            return Err(GeoTiffError::SampleOutOfBounds {
+                sample,
+                num_samples,
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,1232 @@
+use std::io::{Read, Seek};
+#[cfg(feature = "async")]
+use std::ops::Range;
+
+use geo_types::{Coord, Polygon, Rect};
+use num_traits::FromPrimitive;
+use tiff::decoder::{ChunkType, Decoder, Limits};
+use tiff::tags::{CompressionMethod, PhotometricInterpretation, Tag};
+
+use crate::coordinate_transform::CoordinateTransform;
+use crate::decoder_ext::{self, ChunkLayout, DecoderExt, ImageChunks};
+use crate::error::{GeoTiffError, GeoTiffResult, TiffFeature, Unsupported};
+use crate::gdal_metadata::GdalMetadata;
+use crate::geo_key_directory::{GeoKeyDirectory, RasterType};
+use crate::georeference::Gcp;
+use crate::layout::{ArrayOrder, BandFormat, Interleave, RasterDataType, SampleRole};
+use crate::metadata::GeoTiffMetadata;
+use crate::profile::Profile;
+use crate::raster_data::{all_samples, BufferLayout, RasterData};
+use crate::raster_space::RasterSpace;
+use crate::storage::{self, StorageReport};
+use crate::tiff_tags::{self, DescriptiveTags, GpsPosition, TiffTag};
+use crate::window::Window;
+use crate::writer::Compression;
+use crate::GeoTiff;
+
+/// A GeoTIFF reader that keeps the underlying decoder alive and decodes strips or tiles on
+/// demand, instead of loading the whole raster into memory.
+pub struct GeoTiffReader<R: Read + Seek> {
+    pub geo_key_directory: GeoKeyDirectory,
+    pub raster_width: usize,
+    pub raster_height: usize,
+    pub num_samples: usize,
+    pub no_data: Option<f64>,
+    pub gdal_metadata: Option<GdalMetadata>,
+    pub descriptive_tags: DescriptiveTags,
+    decoder: Decoder<R>,
+    coordinate_transform: Option<CoordinateTransform>,
+    gcps: Vec<Gcp>,
+    chunk_layout: ChunkLayout,
+    /// The location of the encoded data of the strips or tiles of the full-resolution image.
+    chunks: ImageChunks,
+    band_formats: Vec<BandFormat>,
+    sample_roles: Vec<SampleRole>,
+    photometric_interpretation: PhotometricInterpretation,
+    compression: CompressionMethod,
+    data_type: RasterDataType,
+    interleave: Interleave,
+    order: ArrayOrder,
+    chunk_verification: bool,
+    alpha_mask: bool,
+    /// The samples kept by reads, or `None` to keep all of them.
+    samples: Option<Vec<usize>>,
+    limits: Limits,
+    bigtiff: bool,
+    /// The index of the image holding the transparency mask and the location of its strips or
+    /// tiles, once looked up.
+    mask_image: Option<Option<(usize, ImageChunks)>>,
+    cached_chunk: Option<(u32, RasterData)>,
+}
+
+/// A strip or tile whose encoded data is invalid or cannot be decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorruptChunk {
+    /// The index of the strip or tile in the file.
+    pub index: u32,
+    /// The pixels covered by the chunk, in the raster of the image it belongs to.
+    pub window: Window,
+    pub reason: String,
+}
+
+/// A reduced-resolution version of the image, stored in a separate image file directory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Overview {
+    /// The level to pass to [`GeoTiffReader::read_overview`], where level 0 is the first overview
+    /// following the full-resolution image.
+    pub level: usize,
+    pub width: usize,
+    pub height: usize,
+    /// The ratio of the width of the full-resolution image to the width of the overview.
+    pub decimation_x: f64,
+    /// The ratio of the height of the full-resolution image to the height of the overview.
+    pub decimation_y: f64,
+}
+
+/// Options for reading a GeoTIFF, see [`GeoTiffReader::open_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    strict: bool,
+    limits: Limits,
+}
+
+impl ReadOptions {
+    /// Sets whether metadata violating the GeoTIFF specification is rejected.
+    ///
+    /// By default, common deviations found in real-world files are accepted: GeoKey directories
+    /// with another version or a number of keys not matching their length, keys not sorted by
+    /// ID, ASCII values terminated by a NUL or without a terminating pipe, and a tie point with a
+    /// pixel scale given together with a transformation matrix, where the former is used, as in
+    /// GDAL. In strict mode, such files fail to open with [`GeoTiffError::Format`].
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Sets the limits on the memory used for decoding, e.g. to cap it for untrusted files.
+    ///
+    /// Besides limiting the buffers of the TIFF decoder, including the ones holding tag values,
+    /// the decoding buffer size limits the raster decoded by a single read and the encoded data of
+    /// each strip or tile, which is checked before reading or fetching it, e.g. for corrupt byte
+    /// counts. Reads exceeding the limits fail with
+    /// [`TiffError::LimitsExceeded`](tiff::TiffError::LimitsExceeded) wrapped in
+    /// [`GeoTiffError::Tiff`]. By default, the limits of the `tiff` crate apply.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+}
+
+impl<R: Read + Seek> GeoTiffReader<R> {
+    /// Opens a GeoTIFF from the given source, reading its metadata but no raster data.
+    ///
+    /// Fails with [`GeoTiffError::Unsupported`] if the image uses a feature that cannot be
+    /// decoded. Use [`probe_support`] to list all such features.
+    pub fn open(reader: R) -> GeoTiffResult<Self> {
+        Self::open_with_options(reader, &ReadOptions::default())
+    }
+
+    /// Opens a GeoTIFF from the given source with the given options, see [`Self::open`].
+    pub fn open_with_options(reader: R, options: &ReadOptions) -> GeoTiffResult<Self> {
+        trace_span!("open");
+        let mut decoder = Decoder::new(reader)?.with_limits(options.limits.clone());
+        // The first image file directory has been read with the default limits
+        decoder.seek_to_image(0)?;
+        let bigtiff = decoder.is_bigtiff()?;
+
+        if let Some(unsupported) = decoder.unsupported_features()?.into_iter().next() {
+            return Err(GeoTiffError::Unsupported(unsupported));
+        }
+
+        let geo_key_directory = decoder.geo_key_directory(options.strict)?;
+        let coordinate_transform = decoder.coordinate_transform(options.strict)?;
+        let gcps = decoder.gcps()?;
+        let (raster_width, raster_height) = decoder
+            .dimensions()
+            .map(|(width, height)| (width as usize, height as usize))?;
+        let num_samples = decoder.num_samples()?;
+        let no_data = decoder.no_data()?;
+        let gdal_metadata = decoder.gdal_metadata()?;
+        let descriptive_tags = decoder.descriptive_tags()?;
+        let chunk_layout = decoder.chunk_layout()?;
+        let chunks = decoder.image_chunks(&options.limits)?;
+        let band_formats = chunks.band_formats.clone();
+        let sample_roles = decoder.sample_roles()?;
+        let photometric_interpretation = decoder.photometric_interpretation()?;
+        let compression = decoder.compression_method()?;
+        // Supported images have been checked to hold values of a supported type
+        let data_type = band_formats[0]
+            .data_type()
+            .ok_or(GeoTiffError::Unsupported(Unsupported::new(
+                TiffFeature::SampleFormat,
+                band_formats[0].sample_format.to_u16(),
+            )))?;
+
+        trace_event!(
+            raster_width,
+            raster_height,
+            num_samples,
+            bigtiff,
+            "parsed header"
+        );
+
+        Ok(Self {
+            geo_key_directory,
+            raster_width,
+            raster_height,
+            num_samples,
+            no_data,
+            gdal_metadata,
+            descriptive_tags,
+            decoder,
+            coordinate_transform,
+            gcps,
+            chunk_layout,
+            chunks,
+            band_formats,
+            sample_roles,
+            photometric_interpretation,
+            compression,
+            data_type,
+            interleave: Interleave::default(),
+            order: ArrayOrder::default(),
+            chunk_verification: false,
+            alpha_mask: false,
+            samples: None,
+            limits: options.limits.clone(),
+            bigtiff,
+            mask_image: None,
+            cached_chunk: None,
+        })
+    }
+
+    /// Sets the arrangement of the samples in memory for subsequent reads, regardless of how they
+    /// are stored in the file.
+    pub fn with_interleave(mut self, interleave: Interleave) -> Self {
+        self.interleave = interleave;
+        self
+    }
+
+    /// Sets the order of the pixels in memory for subsequent reads, e.g. column-major order for
+    /// interoperability with Fortran-ordered code.
+    pub fn with_order(mut self, order: ArrayOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Sets whether the encoded data of each strip or tile is verified before decoding it in
+    /// subsequent reads.
+    ///
+    /// With verification, reads do not fail because of chunks that are truncated, have implausible
+    /// byte counts or cannot be decoded. Such chunks are filled with the no data value (or left
+    /// zeroed if there is none) and reported by [`GeoTiff::corrupt_chunks`].
+    pub fn with_chunk_verification(mut self, chunk_verification: bool) -> Self {
+        self.chunk_verification = chunk_verification;
+        self
+    }
+
+    /// Sets whether the alpha sample, if the image has one, is interpreted as a transparency mask
+    /// in subsequent reads, so that fully transparent pixels are treated as invalid, e.g. by
+    /// [`GeoTiff::get_masked_value_at`] and [`GeoTiff::valid_pixels`].
+    ///
+    /// The alpha mask is combined with the internal transparency mask of the image, if any.
+    pub fn with_alpha_mask(mut self, alpha_mask: bool) -> Self {
+        self.alpha_mask = alpha_mask;
+        self
+    }
+
+    /// Sets the samples (bands) kept by subsequent reads, in the order they are arranged in the
+    /// decoded raster, e.g. to read a single band of a hyperspectral image.
+    ///
+    /// Of images with a planar configuration, only the planes holding the samples are decoded.
+    /// Reads fail with [`GeoTiffError::SampleOutOfBounds`] if the image does not have one of the
+    /// samples. The alpha sample is only interpreted as a transparency mask if it is kept.
+    pub fn with_samples(mut self, samples: &[usize]) -> Self {
+        self.samples = Some(samples.to_vec());
+        self
+    }
+
+    /// Returns the underlying TIFF decoder, e.g. to read custom tags or chunks not covered by this
+    /// API without parsing the file again.
+    ///
+    /// Reads expect the decoder to be positioned at the full-resolution image, so after seeking to
+    /// another image, seek back with `seek_to_image(0)`.
+    pub fn decoder_mut(&mut self) -> &mut Decoder<R> {
+        &mut self.decoder
+    }
+
+    /// Verifies and decodes every strip or tile of the image, returning the ones that are corrupt.
+    pub fn verify_chunks(&mut self) -> GeoTiffResult<Vec<CorruptChunk>> {
+        let ChunkLayout {
+            chunks_across,
+            chunks_down,
+            num_planes,
+            ..
+        } = self.chunk_layout;
+
+        let mut corrupt_chunks = Vec::new();
+        for chunk_index in 0..(chunks_across * chunks_down * num_planes) as u32 {
+            if let Err(corrupt_chunk) = self
+                .decoder
+                .read_verified_chunk(chunk_index, &self.chunks)?
+            {
+                corrupt_chunks.push(corrupt_chunk);
+            }
+        }
+
+        Ok(corrupt_chunks)
+    }
+
+    /// Returns whether the file is a BigTIFF, which uses 64-bit offsets.
+    pub fn is_bigtiff(&self) -> bool {
+        self.bigtiff
+    }
+
+    /// Returns the reduced-resolution images (overviews) of the image, without decoding any raster
+    /// data.
+    ///
+    /// This allows choosing the level to read for a given output resolution.
+    pub fn overviews(&mut self) -> GeoTiffResult<Vec<Overview>> {
+        let overview_images = self.decoder.overview_images()?;
+        let overviews = overview_images
+            .into_iter()
+            .enumerate()
+            .map(|(level, image)| {
+                self.decoder.seek_to_image(image)?;
+                let (width, height) = self.decoder.dimensions()?;
+                Ok(Overview {
+                    level,
+                    width: width as usize,
+                    height: height as usize,
+                    decimation_x: self.raster_width as f64 / width as f64,
+                    decimation_y: self.raster_height as f64 / height as f64,
+                })
+            })
+            .collect();
+
+        self.decoder.seek_to_image(0)?;
+        overviews
+    }
+
+    /// Summarizes the compression and the strips or tiles of the image and its overviews from
+    /// their byte counts, without decoding any raster data.
+    ///
+    /// This helps deciding whether files are worth re-encoding, e.g. with another compression or
+    /// tile size.
+    pub fn storage_report(&mut self) -> GeoTiffResult<StorageReport> {
+        let mut images = vec![storage::image_storage(&mut self.decoder, None)?];
+        for (level, image) in self.decoder.overview_images()?.into_iter().enumerate() {
+            self.decoder.seek_to_image(image)?;
+            let overview = storage::image_storage(&mut self.decoder, Some(level));
+            self.decoder.seek_to_image(0)?;
+            images.push(overview?);
+        }
+
+        Ok(StorageReport { images })
+    }
+
+    /// Summarizes the metadata of the image, including its overviews, without decoding any raster
+    /// data.
+    pub fn metadata(&mut self) -> GeoTiffResult<GeoTiffMetadata> {
+        let (chunk_width, chunk_height) = self.decoder.chunk_dimensions();
+
+        Ok(GeoTiffMetadata {
+            width: self.raster_width,
+            height: self.raster_height,
+            num_samples: self.num_samples,
+            data_type: self.data_type,
+            band_formats: self.band_formats.clone(),
+            photometric_interpretation: self.photometric_interpretation,
+            compression: self.compression,
+            tiled: self.decoder.get_chunk_type() == ChunkType::Tile,
+            chunk_dimensions: (chunk_width as usize, chunk_height as usize),
+            planar: self.chunk_layout.num_planes > 1,
+            bigtiff: self.bigtiff,
+            geo_key_directory: self.geo_key_directory.clone(),
+            geotransform: self.geotransform(),
+            gcps: self.gcps.clone(),
+            model_extent: self.model_extent(),
+            no_data: self.no_data,
+            descriptive_tags: self.descriptive_tags.clone(),
+            overviews: self.overviews()?,
+        })
+    }
+
+    /// Returns all tags of the image file directory of the image with their types and values,
+    /// ordered by their codes, e.g. to inspect tags not modeled by this API.
+    pub fn tags(&mut self) -> GeoTiffResult<Vec<TiffTag>> {
+        tiff_tags::image_tags(&mut self.decoder)
+    }
+
+    /// Returns the tags of the EXIF directory of the image, holding capture metadata such as the
+    /// camera settings, or `None` if the image has none.
+    pub fn exif_tags(&mut self) -> GeoTiffResult<Option<Vec<TiffTag>>> {
+        tiff_tags::linked_directory_tags(&mut self.decoder, Tag::ExifDirectory)
+    }
+
+    /// Returns the tags of the GPS directory of the image, holding the location and time of the
+    /// capture, or `None` if the image has none. The tags are identified by their codes in the GPS
+    /// directory, which are not known to [`Tag`].
+    pub fn gps_tags(&mut self) -> GeoTiffResult<Option<Vec<TiffTag>>> {
+        tiff_tags::linked_directory_tags(&mut self.decoder, Tag::GpsDirectory)
+    }
+
+    /// Returns the location where the image was captured, as recorded in its GPS directory, e.g.
+    /// by drones, or `None` if it is not recorded.
+    pub fn gps_position(&mut self) -> GeoTiffResult<Option<GpsPosition>> {
+        Ok(self
+            .gps_tags()?
+            .and_then(|tags| GpsPosition::from_gps_tags(&tags)))
+    }
+
+    /// Returns the method the raster data is compressed with, e.g. to reject files whose codec
+    /// other software does not support. Codecs unknown to the `tiff` crate, such as LZMA, are given
+    /// as [`CompressionMethod::Unknown`].
+    pub fn compression(&self) -> CompressionMethod {
+        self.compression
+    }
+
+    /// Returns the ground control points of files georeferenced by multiple tie points, see
+    /// [`GeoTiff::gcps`].
+    pub fn gcps(&self) -> &[Gcp] {
+        &self.gcps
+    }
+
+    /// Returns the format of the samples of each band, as stored in the file.
+    pub fn band_formats(&self) -> &[BandFormat] {
+        &self.band_formats
+    }
+
+    /// Returns the number of bits of the samples of each band as stored in the file, which may be
+    /// fewer than those of the data type they are decoded to, e.g. 12 bits of samples decoded as
+    /// `U16`.
+    pub fn bits_per_sample(&self) -> Vec<u16> {
+        self.band_formats()
+            .iter()
+            .map(|band_format| band_format.bits_per_sample)
+            .collect()
+    }
+
+    /// Returns the type the raster data is decoded to.
+    pub fn data_type(&self) -> RasterDataType {
+        self.data_type
+    }
+
+    /// Returns the role of each sample, as given by the ExtraSamples tag.
+    pub fn sample_roles(&self) -> &[SampleRole] {
+        &self.sample_roles
+    }
+
+    /// Returns the index of the sample holding the opacity of the pixels, if there is one.
+    pub fn alpha_sample(&self) -> Option<usize> {
+        alpha_sample(&self.sample_roles)
+    }
+
+    /// Returns how the decoded samples are interpreted as colors, e.g. as RGB or CMYK.
+    ///
+    /// The samples of YCbCr images are converted to RGB and those of WhiteIsZero images inverted
+    /// when decoded, so these are reported as RGB and BlackIsZero.
+    pub fn photometric_interpretation(&self) -> PhotometricInterpretation {
+        self.photometric_interpretation
+    }
+
+    /// Returns the extent of the image in model space.
+    pub fn model_extent(&self) -> Rect {
+        self.compute_model_extent()
+    }
+
+    /// Returns the outline of the image in model space, starting at its top left corner.
+    ///
+    /// Unlike [`Self::model_extent`], which is its bounding rectangle, it follows the edges of
+    /// rasters that are rotated or sheared by their coordinate transform.
+    pub fn footprint(&self) -> Polygon {
+        self.compute_footprint()
+    }
+
+    /// Returns the affine transform from raster to model space as the six coefficients used by
+    /// GDAL, or `None` if there is no coordinate transform or it is not affine, e.g. one given by
+    /// multiple tie points.
+    ///
+    /// The model location of the top left corner of the pixel at column `col` and row `row` is
+    /// `(gt[0] + col * gt[1] + row * gt[2], gt[3] + col * gt[4] + row * gt[5])`, also for rasters
+    /// whose pixels represent points.
+    pub fn geotransform(&self) -> Option<[f64; 6]> {
+        self.compute_geotransform()
+    }
+
+    /// Returns the location of the top left corner of the raster in model space, i.e. the first
+    /// and fourth coefficient of [`Self::geotransform`].
+    pub fn origin(&self) -> Option<Coord> {
+        self.compute_geotransform().map(|geotransform| Coord {
+            x: geotransform[0],
+            y: geotransform[3],
+        })
+    }
+
+    /// Returns the width and height of a pixel in model space, i.e. the second and sixth
+    /// coefficient of [`Self::geotransform`], where the height is negative for rasters with
+    /// north up.
+    ///
+    /// The size is only meaningful if the raster is not rotated, see [`Self::geotransform`].
+    pub fn pixel_size(&self) -> Option<(f64, f64)> {
+        self.compute_geotransform()
+            .map(|geotransform| (geotransform[1], geotransform[5]))
+    }
+
+    /// Returns the width and height of a pixel in model units, which unlike [`Self::pixel_size`]
+    /// are positive and also apply to rotated rasters, or `None` if the raster has no affine
+    /// transform.
+    ///
+    /// See [`GeoTiff::ground_sample_distance`] for the size in metres.
+    pub fn resolution(&self) -> Option<Coord> {
+        self.compute_resolution()
+    }
+
+    /// Returns whether the raster intersects the given rectangle in model space.
+    pub fn intersects(&self, rect: &Rect) -> bool {
+        self.compute_intersection_window(rect).is_some()
+    }
+
+    /// Returns whether the given location in model space lies within the raster.
+    pub fn contains(&self, coord: &Coord) -> bool {
+        self.pixel_at(coord).is_some()
+    }
+
+    /// Returns the smallest pixel window covering the intersection of the raster with the given
+    /// rectangle in model space, or `None` if they do not intersect.
+    pub fn intersection_window(&self, rect: &Rect) -> Option<Window> {
+        self.compute_intersection_window(rect)
+    }
+
+    /// Returns the profile of the file, e.g. to create a GeoTIFF with the same properties, see
+    /// [`GeoTiff::from_profile`].
+    pub fn profile(&self) -> Profile {
+        Profile {
+            width: self.raster_width,
+            height: self.raster_height,
+            num_samples: self.num_samples,
+            data_type: self.data_type,
+            geotransform: self.geotransform(),
+            geo_key_directory: self.geo_key_directory.clone(),
+            no_data: self.no_data,
+            compression: Compression::from_method(self.compression).unwrap_or_default(),
+            tile_size: self.is_tiled().then(|| self.chunk_dimensions()),
+        }
+    }
+
+    /// Returns whether the raster data is stored in tiles rather than strips.
+    pub fn is_tiled(&self) -> bool {
+        self.decoder.get_chunk_type() == ChunkType::Tile
+    }
+
+    /// Returns the width and height of the strips or tiles in pixels. Strips span the width of
+    /// the raster, and the last strip or tile in each direction may extend past the raster.
+    pub fn chunk_dimensions(&self) -> (usize, usize) {
+        (
+            self.chunk_layout.chunk_width,
+            self.chunk_layout.chunk_height,
+        )
+    }
+
+    /// Returns the number of strips or tiles, which includes the chunks of every sample for planar
+    /// images.
+    pub fn chunk_count(&self) -> usize {
+        let ChunkLayout {
+            chunks_across,
+            chunks_down,
+            num_planes,
+            ..
+        } = self.chunk_layout;
+        chunks_across * chunks_down * num_planes
+    }
+
+    /// Expands the given window to the boundaries of the strips or tiles it touches, clipped to the
+    /// raster dimensions.
+    pub fn align_window_to_blocks(&self, window: &Window) -> Window {
+        window.align_to(
+            self.chunk_layout.chunk_width,
+            self.chunk_layout.chunk_height,
+            self.raster_width,
+            self.raster_height,
+        )
+    }
+
+    /// Returns the value at the given location for the specified sample.
+    /// The coordinates are in model space.
+    ///
+    /// Only the strip or tile containing the location is decoded. The most recently decoded chunk
+    /// is kept, so that subsequent queries of nearby locations do not decode it again.
+    ///
+    /// Fails with [`GeoTiffError::SampleOutOfBounds`] if the image does not have the sample and
+    /// with [`GeoTiffError::ValueConversion`] if `T` cannot represent the value.
+    pub fn get_value_at<T: FromPrimitive + 'static>(
+        &mut self,
+        coord: &Coord,
+        sample: usize,
+    ) -> GeoTiffResult<Option<T>> {
+        self.sample_in_bounds(sample)?;
+
+        let Some((chunk_index, index)) = self.chunk_position(coord, sample) else {
+            return Ok(None);
+        };
+
+        self.read_chunk(chunk_index)?.try_get(index).map(Some)
+    }
+
+    /// Returns the values at the given locations for the specified sample, in the order of the
+    /// locations. The coordinates are in model space.
+    ///
+    /// The locations are grouped by the strip or tile containing them, so that each chunk is
+    /// decoded at most once regardless of the order of the locations. Fails like
+    /// [`Self::get_value_at`].
+    pub fn get_values_at<T: FromPrimitive + 'static>(
+        &mut self,
+        coords: &[Coord],
+        sample: usize,
+    ) -> GeoTiffResult<Vec<Option<T>>> {
+        self.sample_in_bounds(sample)?;
+
+        let mut positions = coords
+            .iter()
+            .enumerate()
+            .filter_map(|(i, coord)| {
+                self.chunk_position(coord, sample)
+                    .map(|(chunk_index, index)| (chunk_index, index, i))
+            })
+            .collect::<Vec<_>>();
+        positions.sort_unstable();
+
+        let mut values = coords.iter().map(|_| None).collect::<Vec<_>>();
+        for (chunk_index, index, i) in positions {
+            values[i] = Some(self.read_chunk(chunk_index)?.try_get(index)?);
+        }
+
+        Ok(values)
+    }
+
+    /// Returns an iterator over the valid pixels of the specified sample, decoding the strips or
+    /// tiles one at a time, see [`GeoTiff::valid_pixels`].
+    ///
+    /// Strips or tiles without encoded data, e.g. the omitted tiles of sparse files, hold no valid
+    /// pixels and are skipped without decoding them.
+    ///
+    /// The sample indexes the samples selected with [`GeoTiffReader::with_samples`], if any. Fails
+    /// with [`GeoTiffError::SampleOutOfBounds`] if there is no such sample, while the iterator
+    /// yields the errors of decoding the strips or tiles.
+    pub fn valid_pixels<T: FromPrimitive + 'static>(
+        &mut self,
+        sample: usize,
+    ) -> GeoTiffResult<impl Iterator<Item = GeoTiffResult<(Coord, T)>> + '_> {
+        let samples = self.selected_samples()?;
+        if sample >= samples.len() {
+            return Err(GeoTiffError::SampleOutOfBounds {
+                sample,
+                num_samples: samples.len(),
+            });
+        }
+
+        let ChunkLayout {
+            chunk_width,
+            chunk_height,
+            chunks_across,
+            chunks_down,
+            num_planes,
+        } = self.chunk_layout;
+        let plane = match num_planes {
+            1 => 0,
+            _ => samples[sample],
+        };
+        let byte_ranges = &self.chunks.byte_ranges;
+        let windows: Vec<_> = (0..chunks_down)
+            .flat_map(|chunk_y| (0..chunks_across).map(move |chunk_x| (chunk_x, chunk_y)))
+            .filter(|&(chunk_x, chunk_y)| {
+                let chunk_index = self.chunk_layout.chunk_index(chunk_x, chunk_y, plane);
+                byte_ranges
+                    .get(chunk_index as usize)
+                    .is_some_and(|byte_range| !byte_range.is_empty() && byte_range.start != 0)
+            })
+            .map(|(chunk_x, chunk_y)| {
+                let (x, y) = (chunk_x * chunk_width, chunk_y * chunk_height);
+                Window::new(
+                    x,
+                    y,
+                    chunk_width.min(self.raster_width - x),
+                    chunk_height.min(self.raster_height - y),
+                )
+            })
+            .collect();
+
+        Ok(windows.into_iter().flat_map(move |window| {
+            let pixels = self
+                .read_region(&window)
+                .and_then(|region| Ok(region.valid_pixels::<T>(sample)?.collect::<Vec<_>>()));
+            match pixels {
+                Ok(pixels) => pixels,
+                Err(error) => vec![Err(error)],
+            }
+        }))
+    }
+
+    /// Reads the given pixel window, decoding only the strips or tiles intersecting it.
+    pub fn read_region(&mut self, window: &Window) -> GeoTiffResult<GeoTiff> {
+        let samples = self.selected_samples()?;
+        self.check_buffer_size(window.width, window.height, samples.len())?;
+        let (raster_data, corrupt_chunks) = self.decoder.read_window(
+            window,
+            self.interleave,
+            self.order,
+            &samples,
+            self.chunk_verification,
+            &self.chunks,
+        )?;
+        let mask = self.read_mask(0, window)?;
+        let mask = self.apply_alpha_mask(mask, &raster_data, window, &samples);
+        let coordinate_transform =
+            self.derive_coordinate_transform(window, window.width, window.height)?;
+        let gcps = self.derive_gcps(window, window.width, window.height);
+
+        Ok(GeoTiff {
+            geo_key_directory: self.geo_key_directory.clone(),
+            raster_width: window.width,
+            raster_height: window.height,
+            num_samples: samples.len(),
+            no_data: self.no_data,
+            gdal_metadata: self.selected_gdal_metadata(&samples),
+            photometric_interpretation: Some(self.selected_photometric_interpretation(&samples)),
+            descriptive_tags: self.descriptive_tags.clone(),
+            coordinate_transform,
+            gcps,
+            chunk_dimensions: (
+                self.chunk_layout.chunk_width.min(window.width),
+                self.chunk_layout.chunk_height.min(window.height),
+            ),
+            interleave: self.interleave,
+            order: self.order,
+            band_formats: select(&self.band_formats, &samples),
+            sample_roles: select(&self.sample_roles, &samples),
+            raster_data,
+            corrupt_chunks,
+            mask,
+            bigtiff: self.bigtiff,
+            overviews: Vec::new(),
+        })
+    }
+
+    /// Decodes the whole raster.
+    pub fn read(mut self) -> GeoTiffResult<GeoTiff> {
+        let window = Window::new(0, 0, self.raster_width, self.raster_height);
+        let samples = self.selected_samples()?;
+        self.check_buffer_size(window.width, window.height, samples.len())?;
+        let (raster_data, corrupt_chunks) = self.decoder.read_window(
+            &window,
+            self.interleave,
+            self.order,
+            &samples,
+            self.chunk_verification,
+            &self.chunks,
+        )?;
+        let mask = self.read_mask(0, &window)?;
+        let mask = self.apply_alpha_mask(mask, &raster_data, &window, &samples);
+
+        Ok(GeoTiff {
+            gdal_metadata: self.selected_gdal_metadata(&samples),
+            photometric_interpretation: Some(self.selected_photometric_interpretation(&samples)),
+            descriptive_tags: self.descriptive_tags.clone(),
+            geo_key_directory: self.geo_key_directory,
+            raster_width: self.raster_width,
+            raster_height: self.raster_height,
+            num_samples: samples.len(),
+            no_data: self.no_data,
+            coordinate_transform: self.coordinate_transform,
+            gcps: self.gcps,
+            chunk_dimensions: (
+                self.chunk_layout.chunk_width.min(self.raster_width),
+                self.chunk_layout.chunk_height.min(self.raster_height),
+            ),
+            interleave: self.interleave,
+            order: self.order,
+            band_formats: select(&self.band_formats, &samples),
+            sample_roles: select(&self.sample_roles, &samples),
+            raster_data,
+            corrupt_chunks,
+            mask,
+            bigtiff: self.bigtiff,
+            overviews: Vec::new(),
+        })
+    }
+
+    /// Decodes the reduced-resolution image (overview) at the given level, where level 0 is the
+    /// first overview following the full-resolution image.
+    ///
+    /// The overview is georeferenced by the metadata of the full-resolution image.
+    pub fn read_overview(mut self, level: usize) -> GeoTiffResult<GeoTiff> {
+        let overview_images = self.decoder.overview_images()?;
+        let Some(image) = overview_images.get(level) else {
+            return Err(GeoTiffError::Format(format!(
+                "Overview level out of bounds: the number of overviews is {} but the level is {level}",
+                overview_images.len()
+            )));
+        };
+
+        self.decoder.seek_to_image(*image)?;
+        let (raster_width, raster_height) = self
+            .decoder
+            .dimensions()
+            .map(|(width, height)| (width as usize, height as usize))?;
+        let (chunk_width, chunk_height) = self.decoder.chunk_dimensions();
+        let chunks = self.decoder.image_chunks(&self.limits)?;
+        let window = Window::new(0, 0, raster_width, raster_height);
+        let samples = self.selected_samples()?;
+        self.check_buffer_size(window.width, window.height, samples.len())?;
+        let (raster_data, corrupt_chunks) = self.decoder.read_window(
+            &window,
+            self.interleave,
+            self.order,
+            &samples,
+            self.chunk_verification,
+            &chunks,
+        )?;
+        let mask = self.read_mask(*image, &window)?;
+        let mask = self.apply_alpha_mask(mask, &raster_data, &window, &samples);
+
+        let full_window = Window::new(0, 0, self.raster_width, self.raster_height);
+        let coordinate_transform =
+            self.derive_coordinate_transform(&full_window, raster_width, raster_height)?;
+        let gcps = self.derive_gcps(&full_window, raster_width, raster_height);
+
+        Ok(GeoTiff {
+            gdal_metadata: self.selected_gdal_metadata(&samples),
+            photometric_interpretation: Some(self.selected_photometric_interpretation(&samples)),
+            descriptive_tags: self.descriptive_tags.clone(),
+            geo_key_directory: self.geo_key_directory,
+            raster_width,
+            raster_height,
+            num_samples: samples.len(),
+            no_data: self.no_data,
+            coordinate_transform,
+            gcps,
+            chunk_dimensions: (
+                (chunk_width as usize).clamp(1, raster_width.max(1)),
+                (chunk_height as usize).clamp(1, raster_height.max(1)),
+            ),
+            interleave: self.interleave,
+            order: self.order,
+            band_formats: select(&chunks.band_formats, &samples),
+            sample_roles: select(&self.sample_roles, &samples),
+            raster_data,
+            corrupt_chunks,
+            mask,
+            bigtiff: self.bigtiff,
+            overviews: Vec::new(),
+        })
+    }
+
+    /// Decodes the raster reduced by the given decimation factor, e.g. to show a preview of a
+    /// huge file.
+    ///
+    /// The dimensions are the dimensions of the raster divided by the factor, rounded up, like
+    /// those of the overviews built by [`GeoTiff::build_overviews`]. If the file has an overview
+    /// of these dimensions, it is read instead. Otherwise, each pixel takes the value of the pixel
+    /// nearest to its center, and the strips or tiles are decoded one by one, skipping the ones
+    /// without such a pixel, so the full-resolution raster is never held in memory.
+    pub fn read_decimated(mut self, factor: usize) -> GeoTiffResult<GeoTiff> {
+        if factor == 0 {
+            return Err(GeoTiffError::Format("Decimation factor 0".into()));
+        }
+        let (width, height) = (
+            self.raster_width.div_ceil(factor),
+            self.raster_height.div_ceil(factor),
+        );
+        if let Some(overview) = self
+            .overviews()?
+            .into_iter()
+            .find(|overview| overview.width == width && overview.height == height)
+        {
+            return self.read_overview(overview.level);
+        }
+
+        let samples = self.selected_samples()?;
+        self.check_buffer_size(width, height, samples.len())?;
+        let layout = BufferLayout {
+            width,
+            height,
+            num_samples: samples.len(),
+            interleave: self.interleave,
+            order: self.order,
+        };
+        let mut raster_data = RasterData::zeroed(self.data_type, width * height * samples.len());
+        let mut mask = None;
+        let mut corrupt_chunks = Vec::new();
+
+        // The pixels of the raster holding the values of the decimated columns and rows
+        let source = |count: usize, len: usize| -> Vec<usize> {
+            (0..count)
+                .map(|i| (i * factor + factor / 2).min(len - 1))
+                .collect()
+        };
+        let (source_cols, source_rows) = (
+            source(width, self.raster_width),
+            source(height, self.raster_height),
+        );
+        let in_range = |source: &[usize], start: usize, len: usize| {
+            let first = source.partition_point(|&i| i < start);
+            let end = source.partition_point(|&i| i < start + len);
+            first..end
+        };
+        let all_samples = all_samples(samples.len());
+
+        let ChunkLayout {
+            chunk_width,
+            chunk_height,
+            chunks_across,
+            chunks_down,
+            ..
+        } = self.chunk_layout;
+        for chunk_y in 0..chunks_down {
+            for chunk_x in 0..chunks_across {
+                let (x, y) = (chunk_x * chunk_width, chunk_y * chunk_height);
+                let window = Window::new(
+                    x,
+                    y,
+                    chunk_width.min(self.raster_width - x),
+                    chunk_height.min(self.raster_height - y),
+                );
+                let (cols, rows) = (
+                    in_range(&source_cols, window.x, window.width),
+                    in_range(&source_rows, window.y, window.height),
+                );
+                if cols.is_empty() || rows.is_empty() {
+                    continue;
+                }
+
+                let (chunk, chunk_corrupt) = self.decoder.read_window(
+                    &window,
+                    Interleave::Pixel,
+                    ArrayOrder::RowMajor,
+                    &samples,
+                    self.chunk_verification,
+                    &self.chunks,
+                )?;
+                corrupt_chunks.extend(chunk_corrupt);
+                let chunk_mask = self.read_mask(0, &window)?;
+                let chunk_layout = BufferLayout {
+                    width: window.width,
+                    height: window.height,
+                    num_samples: samples.len(),
+                    interleave: Interleave::Pixel,
+                    order: ArrayOrder::RowMajor,
+                };
+
+                for row in rows {
+                    for col in cols.clone() {
+                        let (src_x, src_y) = (source_cols[col] - x, source_rows[row] - y);
+                        raster_data.copy_block(
+                            layout,
+                            (col, row),
+                            &chunk,
+                            chunk_layout,
+                            &Window::new(src_x, src_y, 1, 1),
+                            &all_samples,
+                        )?;
+                        if let Some(chunk_mask) = &chunk_mask {
+                            mask.get_or_insert_with(|| vec![false; width * height])
+                                [row * width + col] = chunk_mask[src_y * window.width + src_x];
+                        }
+                    }
+                }
+            }
+        }
+
+        let decimated_window = Window::new(0, 0, width, height);
+        let mask = self.apply_alpha_mask(mask, &raster_data, &decimated_window, &samples);
+        let full_window = Window::new(0, 0, self.raster_width, self.raster_height);
+        let coordinate_transform = self.derive_coordinate_transform(&full_window, width, height)?;
+        let gcps = self.derive_gcps(&full_window, width, height);
+
+        Ok(GeoTiff {
+            gdal_metadata: self.selected_gdal_metadata(&samples),
+            photometric_interpretation: Some(self.selected_photometric_interpretation(&samples)),
+            descriptive_tags: self.descriptive_tags.clone(),
+            geo_key_directory: self.geo_key_directory,
+            raster_width: width,
+            raster_height: height,
+            num_samples: samples.len(),
+            no_data: self.no_data,
+            coordinate_transform,
+            gcps,
+            chunk_dimensions: (chunk_width.min(width), chunk_height.min(height)),
+            interleave: self.interleave,
+            order: self.order,
+            band_formats: select(&self.band_formats, &samples),
+            sample_roles: select(&self.sample_roles, &samples),
+            raster_data,
+            corrupt_chunks,
+            mask,
+            bigtiff: self.bigtiff,
+            overviews: Vec::new(),
+        })
+    }
+
+    /// Decodes the given window of the transparency mask of the given image, if it has one,
+    /// leaving the decoder positioned at the image.
+    fn read_mask(&mut self, image: usize, window: &Window) -> GeoTiffResult<Option<Vec<bool>>> {
+        let mask_image = match (image, self.mask_image.take()) {
+            (0, Some(mask_image)) => mask_image,
+            _ => self.find_mask_image(image)?,
+        };
+        let mask = match &mask_image {
+            Some((mask_image, chunks)) => {
+                self.decoder.seek_to_image(*mask_image)?;
+                let mask = self.decoder.read_mask(window, chunks);
+                self.decoder.seek_to_image(image)?;
+                mask.map(Some)
+            }
+            None => Ok(None),
+        };
+        if image == 0 {
+            self.mask_image = Some(mask_image);
+        }
+
+        mask
+    }
+
+    /// Looks up the image holding the transparency mask of the given image, if it has one, and
+    /// the location of its strips or tiles, leaving the decoder positioned at the image.
+    fn find_mask_image(&mut self, image: usize) -> GeoTiffResult<Option<(usize, ImageChunks)>> {
+        let Some(mask_image) = self.decoder.mask_image(image)? else {
+            return Ok(None);
+        };
+
+        self.decoder.seek_to_image(mask_image)?;
+        let chunks = self.decoder.image_chunks(&self.limits);
+        self.decoder.seek_to_image(image)?;
+
+        Ok(Some((mask_image, chunks?)))
+    }
+
+    /// Returns the samples kept by reads, see [`Self::with_samples`].
+    fn selected_samples(&self) -> GeoTiffResult<Vec<usize>> {
+        let Some(samples) = &self.samples else {
+            return Ok((0..self.num_samples).collect());
+        };
+        if samples.is_empty() {
+            return Err(GeoTiffError::Format("No samples selected".into()));
+        }
+        for &sample in samples {
+            self.sample_in_bounds(sample)?;
+        }
+
+        Ok(samples.clone())
+    }
+
+    /// Fails with [`TiffError::LimitsExceeded`](tiff::TiffError::LimitsExceeded) if a raster of
+    /// the given dimensions and number of samples exceeds the decoding buffer size of the limits
+    /// the reader was opened with.
+    fn check_buffer_size(
+        &self,
+        width: usize,
+        height: usize,
+        num_samples: usize,
+    ) -> GeoTiffResult<()> {
+        let (bits_per_sample, _) = self.data_type.sample_type();
+        let size = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(num_samples))
+            .and_then(|values| values.checked_mul(bits_per_sample as usize / 8));
+
+        decoder_ext::check_buffer_size(size, &self.limits)
+    }
+
+    /// Returns the GDAL metadata of the given samples, renumbered by their position among them.
+    fn selected_gdal_metadata(&self, samples: &[usize]) -> Option<GdalMetadata> {
+        self.gdal_metadata
+            .as_ref()
+            .map(|gdal_metadata| gdal_metadata.select_samples(samples))
+    }
+
+    /// Returns the photometric interpretation of the given samples, which is the one of the image
+    /// if they start with its color samples in order and grayscale otherwise.
+    fn selected_photometric_interpretation(&self, samples: &[usize]) -> PhotometricInterpretation {
+        let num_color_samples = match self.photometric_interpretation {
+            PhotometricInterpretation::RGB | PhotometricInterpretation::CIELab => 3,
+            PhotometricInterpretation::CMYK => 4,
+            _ => 1,
+        };
+        if samples.len() >= num_color_samples
+            && samples[..num_color_samples]
+                .iter()
+                .copied()
+                .eq(0..num_color_samples)
+        {
+            self.photometric_interpretation
+        } else {
+            PhotometricInterpretation::BlackIsZero
+        }
+    }
+
+    /// Returns whether reads decode the given chunk, i.e. whether any of the kept samples lies in
+    /// its plane.
+    #[cfg(feature = "async")]
+    pub(crate) fn reads_chunk(&self, chunk_index: u32) -> bool {
+        let ChunkLayout {
+            chunks_across,
+            chunks_down,
+            num_planes,
+            ..
+        } = self.chunk_layout;
+        let plane = chunk_index as usize / (chunks_across * chunks_down);
+        num_planes == 1
+            || self
+                .samples
+                .as_ref()
+                .is_none_or(|samples| samples.contains(&plane))
+    }
+
+    /// Combines the given transparency mask of the decoded window with the alpha sample, if alpha
+    /// is interpreted as a mask and the alpha sample of the image is among the given decoded ones.
+    fn apply_alpha_mask(
+        &self,
+        mask: Option<Vec<bool>>,
+        raster_data: &RasterData,
+        window: &Window,
+        samples: &[usize],
+    ) -> Option<Vec<bool>> {
+        let Some(alpha_sample) = self
+            .alpha_sample()
+            .filter(|_| self.alpha_mask)
+            .and_then(|alpha_sample| samples.iter().position(|&sample| sample == alpha_sample))
+        else {
+            return mask;
+        };
+
+        let layout = BufferLayout {
+            width: window.width,
+            height: window.height,
+            num_samples: samples.len(),
+            interleave: self.interleave,
+            order: self.order,
+        };
+        let mut mask = mask.unwrap_or_else(|| vec![true; window.width * window.height]);
+        for (pixel, valid) in mask.iter_mut().enumerate() {
+            let (col, row) = (pixel % window.width, pixel / window.width);
+            *valid &= raster_data.get::<f64>(layout.index(col, row, alpha_sample)) != 0.0;
+        }
+
+        Some(mask)
+    }
+
+    pub(crate) fn chunk_layout(&self) -> ChunkLayout {
+        self.chunk_layout
+    }
+
+    /// Returns the location of the encoded data of each strip or tile in the file.
+    #[cfg(feature = "async")]
+    pub(crate) fn chunk_byte_ranges(&self) -> &[Range<u64>] {
+        &self.chunks.byte_ranges
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    /// Returns the index of the chunk containing the given location in model space, together with
+    /// the index of the value of the given sample within the chunk.
+    pub(crate) fn chunk_position(&self, coord: &Coord, sample: usize) -> Option<(u32, usize)> {
+        let (col, row) = self.pixel_at(coord)?;
+
+        let ChunkLayout {
+            chunk_width,
+            chunk_height,
+            num_planes,
+            ..
+        } = self.chunk_layout;
+        let chunk_x = col / chunk_width;
+        let chunk_y = row / chunk_height;
+        let (plane, sample_in_chunk, samples_per_chunk_pixel) = if num_planes > 1 {
+            (sample, 0, 1)
+        } else {
+            (0, sample, self.num_samples)
+        };
+
+        let chunk_index = self.chunk_layout.chunk_index(chunk_x, chunk_y, plane);
+        let (data_width, _) = self.decoder.chunk_data_dimensions(chunk_index);
+        let index = ((row - chunk_y * chunk_height) * data_width as usize
+            + (col - chunk_x * chunk_width))
+            * samples_per_chunk_pixel
+            + sample_in_chunk;
+
+        Some((chunk_index, index))
+    }
+
+    fn read_chunk(&mut self, chunk_index: u32) -> GeoTiffResult<&RasterData> {
+        let cache_hit = matches!(&self.cached_chunk, Some((index, _)) if *index == chunk_index);
+        trace_event!(chunk_index, cache_hit, "chunk lookup");
+        let chunk = match self.cached_chunk.take() {
+            Some((_, chunk)) if cache_hit => chunk,
+            _ => {
+                trace_span!("decode_chunk", chunk_index);
+                self.decoder
+                    .decode_chunk(chunk_index, &self.chunks)?
+                    .reinterpret(self.data_type)
+            }
+        };
+
+        Ok(&self.cached_chunk.insert((chunk_index, chunk)).1)
+    }
+}
+
+/// Returns the values of the given samples.
+pub(crate) fn select<T: Clone>(values: &[T], samples: &[usize]) -> Vec<T> {
+    samples
+        .iter()
+        .map(|&sample| values[sample].clone())
+        .collect()
+}
+
+/// Returns the index of the first alpha sample.
+pub(crate) fn alpha_sample(sample_roles: &[SampleRole]) -> Option<usize> {
+    sample_roles.iter().position(SampleRole::is_alpha)
+}
+
+/// Reports the features of the first image in the given source that prevent it from being
+/// decoded, without decoding any raster data.
+///
+/// Returns an empty list if the image is supported.
+pub fn probe_support<R: Read + Seek>(reader: R) -> GeoTiffResult<Vec<Unsupported>> {
+    let mut decoder = match Decoder::new(reader) {
+        Ok(decoder) => decoder,
+        Err(error) => {
+            // The decoder rejects some features, e.g. inconsistent bits per sample, while reading
+            // the first image
+            return match GeoTiffError::from(error) {
+                GeoTiffError::Unsupported(unsupported) => Ok(vec![unsupported]),
+                error => Err(error),
+            };
+        }
+    };
+
+    decoder.unsupported_features()
+}
+
+impl<R: Read + Seek> RasterSpace for GeoTiffReader<R> {
+    fn raster_dimensions(&self) -> (usize, usize) {
+        (self.raster_width, self.raster_height)
+    }
+
+    fn raster_type(&self) -> Option<RasterType> {
+        self.geo_key_directory.raster_type
+    }
+
+    fn coordinate_transform(&self) -> Option<&CoordinateTransform> {
+        self.coordinate_transform.as_ref()
+    }
+
+    fn num_samples(&self) -> usize {
+        self.num_samples
+    }
+
+    fn gcps(&self) -> &[Gcp] {
+        &self.gcps
+    }
+}
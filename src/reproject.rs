@@ -0,0 +1,72 @@
+use geo_types::Coord;
+use num_traits::FromPrimitive;
+use proj::Proj;
+
+use crate::epsg::WGS_84;
+use crate::error::{GeoTiffError, GeoTiffResult};
+use crate::geo_key_directory::GeoKeyDirectory;
+use crate::GeoTiff;
+
+impl GeoTiff {
+    /// Returns the value at the given location for the specified sample, or `None` if the location
+    /// lies outside the raster.
+    ///
+    /// The coordinates are in the coordinate reference system with the given EPSG code and are
+    /// reprojected into the one of the model space using PROJ. Geographic coordinates are given
+    /// as longitude and latitude.
+    pub fn get_value_at_crs<T: FromPrimitive + 'static>(
+        &self,
+        coord: &Coord,
+        source_epsg: u16,
+        sample: usize,
+    ) -> GeoTiffResult<Option<T>> {
+        let coord = self.to_model_crs(coord, source_epsg)?;
+        self.try_get_value_at(&coord, sample)
+    }
+
+    /// Returns the value at the given longitude and latitude in degrees on WGS 84 for the
+    /// specified sample, or `None` if the location lies outside the raster.
+    ///
+    /// See [`GeoTiff::get_value_at_crs`].
+    pub fn get_value_at_lon_lat<T: FromPrimitive + 'static>(
+        &self,
+        lon_lat: &Coord,
+        sample: usize,
+    ) -> GeoTiffResult<Option<T>> {
+        self.get_value_at_crs(lon_lat, WGS_84, sample)
+    }
+
+    /// Reprojects the given coordinates from the coordinate reference system with the given EPSG
+    /// code into the one of the model space.
+    pub(crate) fn to_model_crs(&self, coord: &Coord, source_epsg: u16) -> GeoTiffResult<Coord> {
+        let source = format!("EPSG:{source_epsg}");
+        let target = self.model_crs()?;
+        if source == target {
+            return Ok(*coord);
+        }
+
+        Ok(Proj::new_known_crs(&source, &target, None)?.convert(*coord)?)
+    }
+
+    /// Returns the definition of the coordinate reference system of the model space for PROJ, see
+    /// [`GeoKeyDirectory::model_crs`].
+    pub(crate) fn model_crs(&self) -> GeoTiffResult<String> {
+        self.geo_key_directory.model_crs()
+    }
+}
+
+impl GeoKeyDirectory {
+    /// Returns the definition of the coordinate reference system of the model space for PROJ,
+    /// preferring its EPSG code and falling back to WKT for user-defined systems.
+    pub(crate) fn model_crs(&self) -> GeoTiffResult<String> {
+        match self.epsg_code() {
+            Some(code) => Ok(format!("EPSG:{code}")),
+            None => self.to_wkt().ok_or_else(|| {
+                GeoTiffError::Crs(
+                    "The GeoKeys do not define the coordinate reference system of the model space."
+                        .into(),
+                )
+            }),
+        }
+    }
+}
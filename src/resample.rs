@@ -0,0 +1,210 @@
+use crate::error::{GeoTiffError, GeoTiffResult};
+use crate::interpolation::linear_weight;
+use crate::raster_data::BufferLayout;
+use crate::raster_space::RasterSpace;
+use crate::window::Window;
+use crate::GeoTiff;
+
+/// The method used to compute the values of resampled pixels, see [`GeoTiff::resample`] and
+/// [`GeoTiff::build_overviews`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Resampling {
+    /// The value of the pixel containing the center of the resampled pixel.
+    #[default]
+    Nearest,
+    /// The linear interpolation between the centers of the four pixels nearest to the center of
+    /// the resampled pixel, or the value of the pixel containing it if any of them is unknown.
+    Bilinear,
+    /// The mean of the known values of the pixels covered by the resampled pixel.
+    Average,
+}
+
+impl GeoTiff {
+    /// Returns the raster resampled to the given dimensions with the given method, e.g. to create
+    /// a thumbnail or to harmonize the resolution with another raster.
+    ///
+    /// The coordinate transform is adjusted so that the resampled raster covers the same extent
+    /// in model space. Pixels holding the no data value or NaN and pixels marked invalid by the
+    /// transparency mask are ignored. Resampled pixels without any known value are set to the no
+    /// data value, or left zeroed for integer data without one.
+    pub fn resample(
+        &self,
+        width: usize,
+        height: usize,
+        resampling: Resampling,
+    ) -> GeoTiffResult<GeoTiff> {
+        if width == 0 || height == 0 || self.raster_width == 0 || self.raster_height == 0 {
+            return Err(GeoTiffError::Format(format!(
+                "Cannot resample {}x{} pixels to {width}x{height} pixels",
+                self.raster_width, self.raster_height
+            )));
+        }
+
+        self.resampled(
+            width,
+            height,
+            (
+                self.raster_width as f64 / width as f64,
+                self.raster_height as f64 / height as f64,
+            ),
+            resampling,
+        )
+    }
+
+    /// Computes reduced-resolution versions (overviews) of the raster for the given decimation
+    /// factors, e.g. `[2, 4, 8]`, replacing previously built ones.
+    ///
+    /// The overviews are written after the full-resolution image as additional image file
+    /// directories, which is needed to use the file as a cloud optimized GeoTIFF. The dimensions
+    /// of each overview are the dimensions of the raster divided by its factor, rounded up, and
+    /// each of its pixels is computed from a block of factor by factor pixels, like GDAL does.
+    pub fn build_overviews(
+        &mut self,
+        factors: &[usize],
+        resampling: Resampling,
+    ) -> GeoTiffResult<()> {
+        let mut overviews = Vec::with_capacity(factors.len());
+        for &factor in factors {
+            if factor < 2 {
+                return Err(GeoTiffError::Format(format!(
+                    "Overview factor {factor} is smaller than 2"
+                )));
+            }
+            overviews.push(self.resampled(
+                self.raster_width.div_ceil(factor),
+                self.raster_height.div_ceil(factor),
+                (factor as f64, factor as f64),
+                resampling,
+            )?);
+        }
+
+        self.overviews = overviews;
+        Ok(())
+    }
+
+    /// Returns the overviews built by [`Self::build_overviews`], from the highest to the lowest
+    /// resolution.
+    pub fn overviews(&self) -> &[GeoTiff] {
+        &self.overviews
+    }
+
+    /// Returns the raster resampled to the given non-zero dimensions, where each resampled pixel
+    /// covers the given number of pixels horizontally and vertically, clipped to the raster, see
+    /// [`Self::resample`].
+    pub(crate) fn resampled(
+        &self,
+        width: usize,
+        height: usize,
+        (scale_x, scale_y): (f64, f64),
+        resampling: Resampling,
+    ) -> GeoTiffResult<GeoTiff> {
+        let layout = self.buffer_layout();
+        let resampled_layout = BufferLayout {
+            width,
+            height,
+            ..layout
+        };
+        let mut raster_data = self
+            .raster_data
+            .zeroed_like(width * height * self.num_samples);
+        let mut mask = self.mask.as_ref().map(|_| vec![false; width * height]);
+
+        for row in 0..height {
+            for col in 0..width {
+                // The pixels of the raster the resampled pixel is computed from
+                let source = match resampling {
+                    Resampling::Nearest | Resampling::Bilinear => Window::new(
+                        (((col as f64 + 0.5) * scale_x) as usize).min(self.raster_width - 1),
+                        (((row as f64 + 0.5) * scale_y) as usize).min(self.raster_height - 1),
+                        1,
+                        1,
+                    ),
+                    Resampling::Average => {
+                        let x = ((col as f64 * scale_x) as usize).min(self.raster_width - 1);
+                        let y = ((row as f64 * scale_y) as usize).min(self.raster_height - 1);
+                        let x_end = (((col + 1) as f64 * scale_x).ceil() as usize)
+                            .clamp(x + 1, self.raster_width);
+                        let y_end = (((row + 1) as f64 * scale_y).ceil() as usize)
+                            .clamp(y + 1, self.raster_height);
+                        Window::new(x, y, x_end - x, y_end - y)
+                    }
+                };
+                let pixels = || {
+                    (source.y..source.y + source.height).flat_map(move |y| {
+                        (source.x..source.x + source.width)
+                            .map(move |x| (x, y))
+                            .filter(|(x, y)| {
+                                self.mask
+                                    .as_ref()
+                                    .is_none_or(|mask| mask[y * self.raster_width + x])
+                            })
+                    })
+                };
+
+                if let Some(mask) = &mut mask {
+                    mask[row * width + col] = pixels().next().is_some();
+                }
+                for sample in 0..self.num_samples {
+                    let interpolated = match resampling {
+                        Resampling::Bilinear => {
+                            // The center relative to the center of the pixel at the top left of
+                            // the four nearest ones
+                            let x = (col as f64 + 0.5) * scale_x - 0.5;
+                            let y = (row as f64 + 0.5) * scale_y - 0.5;
+                            let (tx, ty) = (x - x.floor(), y - y.floor());
+                            self.convolve(
+                                x.floor() as isize,
+                                y.floor() as isize,
+                                0..2,
+                                sample,
+                                |dx, dy| linear_weight(tx, dx) * linear_weight(ty, dy),
+                            )
+                        }
+                        _ => None,
+                    };
+                    if let Some(value) = interpolated {
+                        raster_data.set(resampled_layout.index(col, row, sample), value);
+                        continue;
+                    }
+
+                    let (sum, count) = pixels()
+                        .map(|(x, y)| self.raster_data.get::<f64>(layout.index(x, y, sample)))
+                        .filter(|value| !value.is_nan() && Some(*value) != self.no_data)
+                        .fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+                    let value = match count {
+                        0 => self.no_data.unwrap_or(f64::NAN),
+                        _ => sum / count as f64,
+                    };
+                    raster_data.set(resampled_layout.index(col, row, sample), value);
+                }
+            }
+        }
+
+        let (chunk_width, chunk_height) = self.chunk_dimensions;
+        let full_window = Window::new(0, 0, self.raster_width, self.raster_height);
+
+        Ok(GeoTiff {
+            geo_key_directory: self.geo_key_directory.clone(),
+            raster_width: width,
+            raster_height: height,
+            num_samples: self.num_samples,
+            no_data: self.no_data,
+            gdal_metadata: self.gdal_metadata.clone(),
+            descriptive_tags: self.descriptive_tags.clone(),
+            coordinate_transform: self.derive_coordinate_transform(&full_window, width, height)?,
+            gcps: self.derive_gcps(&full_window, width, height),
+            chunk_dimensions: (chunk_width.min(width), chunk_height.min(height)),
+            interleave: self.interleave,
+            order: self.order,
+            band_formats: self.band_formats.clone(),
+            sample_roles: self.sample_roles.clone(),
+            photometric_interpretation: self.photometric_interpretation,
+            raster_data,
+            corrupt_chunks: Vec::new(),
+            mask,
+            bigtiff: self.bigtiff,
+            overviews: Vec::new(),
+        })
+    }
+}
@@ -0,0 +1,137 @@
+use geo_types::Coord;
+use serde_json::{json, Map, Value};
+
+use crate::georeference::ReferenceLocation;
+use crate::GeoTiff;
+
+/// The version of the STAC specification the items conform to.
+const STAC_VERSION: &str = "1.0.0";
+
+/// The schema of the projection extension defining the `proj:` fields.
+const PROJECTION_EXTENSION: &str =
+    "https://stac-extensions.github.io/projection/v1.1.0/schema.json";
+
+impl GeoTiff {
+    /// Returns a STAC Item describing the raster with the given ID, e.g. to ingest it into a
+    /// catalog.
+    ///
+    /// The `geometry` and `bbox` are the footprint of the raster in longitudes and latitudes on
+    /// WGS 84, which are only known for geographic CRSs in degrees unless the `proj` feature is
+    /// enabled, and `null` and omitted otherwise. The `properties` hold the `datetime` from the
+    /// DateTime tag, which is `null` if the tag is missing or malformed and must then be set by
+    /// the caller, and the fields of the projection extension: `proj:epsg`, `proj:wkt2` for CRSs
+    /// without an EPSG code, `proj:shape`, and `proj:transform` and `proj:bbox` for rasters with
+    /// an affine transform. The `links` and `assets` are left empty for the caller to fill in.
+    pub fn to_stac_item(&self, id: &str) -> Value {
+        let mut properties = Map::new();
+        properties.insert("datetime".into(), self.stac_datetime().into());
+
+        let directory = &self.geo_key_directory;
+        let epsg = directory.epsg_code();
+        properties.insert("proj:epsg".into(), epsg.into());
+        if epsg.is_none() {
+            if let Some(wkt) = directory.to_wkt() {
+                properties.insert("proj:wkt2".into(), wkt.into());
+            }
+        }
+        properties.insert(
+            "proj:shape".into(),
+            json!([self.raster_height, self.raster_width]),
+        );
+        if let Some([x, a, b, y, d, e]) = self.geotransform() {
+            properties.insert("proj:transform".into(), json!([a, b, x, d, e, y, 0, 0, 1]));
+            let extent = self.model_extent();
+            properties.insert(
+                "proj:bbox".into(),
+                json!([
+                    extent.min().x,
+                    extent.min().y,
+                    extent.max().x,
+                    extent.max().y
+                ]),
+            );
+        }
+
+        let mut item = Map::new();
+        item.insert("type".into(), "Feature".into());
+        item.insert("stac_version".into(), STAC_VERSION.into());
+        item.insert("stac_extensions".into(), json!([PROJECTION_EXTENSION]));
+        item.insert("id".into(), id.into());
+        match self.lon_lat_corners() {
+            Some(corners) => {
+                let (lons, lats) = (
+                    corners.map(|corner| corner.x),
+                    corners.map(|corner| corner.y),
+                );
+                let min = |values: [f64; 4]| values.into_iter().fold(f64::INFINITY, f64::min);
+                let max = |values: [f64; 4]| values.into_iter().fold(f64::NEG_INFINITY, f64::max);
+                // The exterior ring of GeoJSON polygons is counterclockwise
+                let [upper_left, upper_right, lower_left, lower_right] =
+                    corners.map(|corner| json!([corner.x, corner.y]));
+                item.insert(
+                    "geometry".into(),
+                    json!({
+                        "type": "Polygon",
+                        "coordinates": [[
+                            upper_left.clone(), lower_left, lower_right, upper_right, upper_left
+                        ]],
+                    }),
+                );
+                item.insert(
+                    "bbox".into(),
+                    json!([min(lons), min(lats), max(lons), max(lats)]),
+                );
+            }
+            None => {
+                item.insert("geometry".into(), Value::Null);
+            }
+        }
+        item.insert("properties".into(), properties.into());
+        item.insert("links".into(), json!([]));
+        item.insert("assets".into(), json!({}));
+
+        item.into()
+    }
+
+    /// Returns the upper left, upper right, lower left and lower right corners of the raster in
+    /// longitudes and latitudes, if they are all known.
+    fn lon_lat_corners(&self) -> Option<[Coord; 4]> {
+        let report = self.georeference_report();
+        let corner = |location| {
+            report
+                .points
+                .iter()
+                .find(|point| point.location == location)
+                .and_then(|point| point.lon_lat)
+        };
+
+        Some([
+            corner(ReferenceLocation::UpperLeft)?,
+            corner(ReferenceLocation::UpperRight)?,
+            corner(ReferenceLocation::LowerLeft)?,
+            corner(ReferenceLocation::LowerRight)?,
+        ])
+    }
+
+    /// Returns the DateTime tag as an RFC 3339 timestamp in UTC, as TIFF does not record the
+    /// time zone, or `None` if it is missing or not of the form `YYYY:MM:DD HH:MM:SS`.
+    fn stac_datetime(&self) -> Option<String> {
+        let date_time = self.descriptive_tags.date_time.as_deref()?.trim();
+        let is_valid = date_time.len() == 19
+            && date_time.bytes().enumerate().all(|(i, byte)| match i {
+                4 | 7 | 13 | 16 => byte == b':',
+                10 => byte == b' ',
+                _ => byte.is_ascii_digit(),
+            });
+
+        is_valid.then(|| {
+            format!(
+                "{}-{}-{}T{}Z",
+                &date_time[0..4],
+                &date_time[5..7],
+                &date_time[8..10],
+                &date_time[11..19]
+            )
+        })
+    }
+}
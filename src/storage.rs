@@ -0,0 +1,152 @@
+use std::io::{Read, Seek};
+
+use tiff::decoder::{ChunkType, Decoder};
+use tiff::tags::CompressionMethod;
+
+use crate::decoder_ext::{ChunkLayout, DecoderExt};
+use crate::error::GeoTiffResult;
+
+/// The size of encoded raster data and the size it is decoded to, in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct StorageSize {
+    pub compressed: u64,
+    pub uncompressed: u64,
+}
+
+impl StorageSize {
+    /// Returns the ratio of the uncompressed size to the compressed size, which is infinite if
+    /// nothing is stored.
+    pub fn compression_ratio(&self) -> f64 {
+        self.uncompressed as f64 / self.compressed as f64
+    }
+
+    fn add(&mut self, other: StorageSize) {
+        self.compressed += other.compressed;
+        self.uncompressed += other.uncompressed;
+    }
+}
+
+/// The storage of the raster data of an image of a GeoTIFF, see [`StorageReport`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageStorage {
+    /// The level of the overview, or `None` for the full-resolution image.
+    pub overview_level: Option<usize>,
+    pub width: usize,
+    pub height: usize,
+    pub compression: CompressionMethod,
+    /// Whether the raster data is stored in tiles rather than strips.
+    pub tiled: bool,
+    /// The number of strips or tiles.
+    pub chunk_count: usize,
+    /// The number of strips or tiles without encoded data, e.g. the omitted tiles of sparse
+    /// files.
+    pub empty_chunk_count: usize,
+    pub size: StorageSize,
+    /// The size of each band, if the bands are stored in separate planes.
+    pub band_sizes: Option<Vec<StorageSize>>,
+}
+
+/// The layout and compression of the raster data of a GeoTIFF, see
+/// [`GeoTiffReader::storage_report`](crate::GeoTiffReader::storage_report).
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageReport {
+    /// The full-resolution image followed by its overviews. Transparency masks are not included.
+    pub images: Vec<ImageStorage>,
+}
+
+impl StorageReport {
+    /// Returns the total size of all images.
+    pub fn size(&self) -> StorageSize {
+        let mut size = StorageSize::default();
+        for image in &self.images {
+            size.add(image.size);
+        }
+        size
+    }
+
+    /// Returns the number of strips or tiles of all images.
+    pub fn chunk_count(&self) -> usize {
+        self.images.iter().map(|image| image.chunk_count).sum()
+    }
+
+    /// Returns the number of strips or tiles without encoded data of all images.
+    pub fn empty_chunk_count(&self) -> usize {
+        self.images
+            .iter()
+            .map(|image| image.empty_chunk_count)
+            .sum()
+    }
+
+    /// Returns the ratio of the uncompressed to the compressed size of all images.
+    pub fn compression_ratio(&self) -> f64 {
+        self.size().compression_ratio()
+    }
+}
+
+/// Summarizes the storage of the image the decoder is positioned at from the byte counts of its
+/// strips or tiles, without decoding them.
+pub(crate) fn image_storage<R: Read + Seek>(
+    decoder: &mut Decoder<R>,
+    overview_level: Option<usize>,
+) -> GeoTiffResult<ImageStorage> {
+    let (width, height) = decoder.dimensions()?;
+    let compression = decoder.compression_method()?;
+    let tiled = decoder.get_chunk_type() == ChunkType::Tile;
+    let ChunkLayout {
+        chunks_across,
+        chunks_down,
+        num_planes,
+        ..
+    } = decoder.chunk_layout()?;
+    let bits_per_sample: Vec<_> = decoder
+        .band_formats()?
+        .iter()
+        .map(|format| format.bits_per_sample as u64)
+        .collect();
+    let chunks_per_plane = chunks_across * chunks_down;
+    let mut byte_ranges = decoder.chunk_byte_ranges()?;
+    byte_ranges.truncate(chunks_per_plane * num_planes);
+
+    let mut plane_sizes = vec![StorageSize::default(); num_planes];
+    let mut empty_chunk_count = 0;
+    for (chunk_index, byte_range) in byte_ranges.iter().enumerate() {
+        let plane = chunk_index / chunks_per_plane;
+        // Tiles are always stored whole, while the last strip only holds the remaining rows
+        let (chunk_width, chunk_height) = if tiled {
+            decoder.chunk_dimensions()
+        } else {
+            decoder.chunk_data_dimensions(chunk_index as u32)
+        };
+        let bits_per_pixel: u64 = if num_planes > 1 {
+            bits_per_sample[plane]
+        } else {
+            bits_per_sample.iter().sum()
+        };
+        let row_size = (chunk_width as u64 * bits_per_pixel).div_ceil(8);
+
+        if byte_range.is_empty() || byte_range.start == 0 {
+            empty_chunk_count += 1;
+        }
+        plane_sizes[plane].add(StorageSize {
+            compressed: byte_range.end - byte_range.start,
+            uncompressed: row_size * chunk_height as u64,
+        });
+    }
+
+    let mut size = StorageSize::default();
+    for plane_size in &plane_sizes {
+        size.add(*plane_size);
+    }
+
+    Ok(ImageStorage {
+        overview_level,
+        width: width as usize,
+        height: height as usize,
+        compression,
+        tiled,
+        chunk_count: byte_ranges.len(),
+        empty_chunk_count,
+        size,
+        band_sizes: (num_planes > 1).then_some(plane_sizes),
+    })
+}
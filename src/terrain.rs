@@ -0,0 +1,281 @@
+use std::f64::consts::{FRAC_PI_2, TAU};
+
+use geo_types::Coord;
+use tiff::tags::PhotometricInterpretation;
+
+use crate::error::{GeoTiffError, GeoTiffResult};
+use crate::layout::{ArrayOrder, BandFormat, Interleave, RasterDataType, SampleRole};
+use crate::raster_data::RasterData;
+use crate::raster_space::RasterSpace;
+use crate::tiff_tags::DescriptiveTags;
+use crate::GeoTiff;
+
+/// The value of hillshade pixels without a known elevation.
+const HILLSHADE_NO_DATA: u8 = 0;
+
+/// The value of slope and aspect pixels without a known elevation, as used by `gdaldem`.
+const TERRAIN_NO_DATA: f32 = -9999.0;
+
+/// The unit of slopes, see [`GeoTiff::slope`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SlopeUnit {
+    /// The angle from the horizontal plane in degrees.
+    #[default]
+    Degrees,
+    /// The rise over the horizontal distance in percent.
+    Percent,
+}
+
+/// The encoding of elevations in the red, green and blue samples of terrain tiles, see
+/// [`GeoTiff::decode_terrain_rgb`] and [`GeoTiff::encode_terrain_rgb`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum TerrainEncoding {
+    /// Mapbox Terrain-RGB, encoding `-10000 + (R * 65536 + G * 256 + B) * 0.1` metres.
+    #[default]
+    Mapbox,
+    /// Mapzen Terrarium, encoding `R * 256 + G + B / 256 - 32768` metres.
+    Terrarium,
+}
+
+impl TerrainEncoding {
+    /// Returns the elevation in metres encoded by the given color.
+    fn decode(self, [red, green, blue]: [u8; 3]) -> f64 {
+        let (red, green, blue) = (red as f64, green as f64, blue as f64);
+        match self {
+            Self::Mapbox => -10000.0 + (red * 65536.0 + green * 256.0 + blue) * 0.1,
+            Self::Terrarium => red * 256.0 + green + blue / 256.0 - 32768.0,
+        }
+    }
+
+    /// Returns the color encoding the given elevation in metres, clamped to the range of the
+    /// encoding and rounded to its precision.
+    fn encode(self, elevation: f64) -> [u8; 3] {
+        let value = match self {
+            Self::Mapbox => (elevation + 10000.0) * 10.0,
+            Self::Terrarium => (elevation + 32768.0) * 256.0,
+        };
+        let value = value.round().clamp(0.0, 16777215.0) as u32;
+        [(value >> 16) as u8, (value >> 8) as u8, value as u8]
+    }
+}
+
+impl GeoTiff {
+    /// Decodes the elevations of a terrain tile in the given encoding, returning a single-band
+    /// raster of 32-bit floats in metres.
+    ///
+    /// The elevations are decoded from the first three samples. Pixels marked invalid by the
+    /// transparency mask or by a transparent alpha sample are set to the no data value -9999.
+    ///
+    /// Fails with [`GeoTiffError::Format`] if the raster does not have at least three 8-bit
+    /// samples.
+    pub fn decode_terrain_rgb(&self, encoding: TerrainEncoding) -> GeoTiffResult<GeoTiff> {
+        if self.data_type() != RasterDataType::U8 || self.num_samples < 3 {
+            return Err(GeoTiffError::Format(format!(
+                "{} samples of type {:?} are not the RGB samples of a terrain tile",
+                self.num_samples,
+                self.data_type()
+            )));
+        }
+
+        let layout = self.buffer_layout();
+        let alpha = self.alpha_sample();
+        let mut values = vec![TERRAIN_NO_DATA; self.raster_width * self.raster_height];
+        for row in 0..self.raster_height {
+            for col in 0..self.raster_width {
+                let value = |sample| self.raster_data.get::<u8>(layout.index(col, row, sample));
+                let pixel = row * self.raster_width + col;
+                if self.mask.as_ref().is_some_and(|mask| !mask[pixel])
+                    || alpha.is_some_and(|alpha| value(alpha) == 0)
+                {
+                    continue;
+                }
+                values[pixel] = encoding.decode([value(0), value(1), value(2)]) as f32;
+            }
+        }
+
+        Ok(self.derive_band(RasterData::F32(values), Some(TERRAIN_NO_DATA as f64)))
+    }
+
+    /// Encodes the elevations of a DEM in metres in the given encoding, returning a raster with
+    /// 8-bit red, green and blue samples, e.g. to be cut into terrain tiles.
+    ///
+    /// The elevations are taken from the first sample. Pixels without a known elevation are
+    /// encoded as 0 metres and marked invalid by the transparency mask.
+    pub fn encode_terrain_rgb(&self, encoding: TerrainEncoding) -> GeoTiff {
+        let (bits_per_sample, sample_format) = RasterDataType::U8.sample_type();
+        let mut values = Vec::with_capacity(self.raster_width * self.raster_height * 3);
+        let mut mask = Vec::with_capacity(self.raster_width * self.raster_height);
+        for row in 0..self.raster_height {
+            for col in 0..self.raster_width {
+                let elevation = self.known_value(col as isize, row as isize, 0);
+                values.extend(encoding.encode(elevation.unwrap_or(0.0)));
+                mask.push(elevation.is_some());
+            }
+        }
+
+        GeoTiff {
+            geo_key_directory: self.geo_key_directory.clone(),
+            raster_width: self.raster_width,
+            raster_height: self.raster_height,
+            num_samples: 3,
+            no_data: None,
+            gdal_metadata: None,
+            descriptive_tags: DescriptiveTags::default(),
+            coordinate_transform: self.coordinate_transform.clone(),
+            gcps: self.gcps.clone(),
+            chunk_dimensions: self.chunk_dimensions,
+            interleave: Interleave::Pixel,
+            order: ArrayOrder::RowMajor,
+            band_formats: vec![
+                BandFormat {
+                    sample_format,
+                    bits_per_sample,
+                };
+                3
+            ],
+            sample_roles: vec![SampleRole::Data; 3],
+            photometric_interpretation: Some(PhotometricInterpretation::RGB),
+            raster_data: RasterData::U8(values),
+            corrupt_chunks: Vec::new(),
+            mask: mask.contains(&false).then_some(mask),
+            bigtiff: self.bigtiff,
+            overviews: Vec::new(),
+        }
+    }
+
+    /// Computes the shaded relief of a DEM, i.e. the brightness of the terrain lit by the sun,
+    /// returning a single-band raster of 8-bit values like `gdaldem hillshade`.
+    ///
+    /// The azimuth of the sun is given in degrees clockwise from north and its altitude in
+    /// degrees above the horizon. The elevations of the first sample are multiplied by the given
+    /// factor, e.g. to exaggerate the relief or to convert them to the units of model space. The
+    /// slopes are derived as by [`Self::slope`].
+    ///
+    /// The values range from 1 for terrain facing away from the sun to 255 for terrain facing it.
+    /// Pixels without a known elevation are set to the no data value 0, while unknown neighbors
+    /// are replaced by the elevation of the pixel itself.
+    pub fn hillshade(&self, azimuth: f64, altitude: f64, z_factor: f64) -> GeoTiff {
+        let (zenith, azimuth) = (
+            FRAC_PI_2 - altitude.to_radians(),
+            (FRAC_PI_2 - azimuth.to_radians()).rem_euclid(TAU),
+        );
+        let values = self.map_gradient(HILLSHADE_NO_DATA, |dz_dx, dz_dy| {
+            let slope = (z_factor * dz_dx.hypot(dz_dy)).atan();
+            let aspect = dz_dy.atan2(-dz_dx);
+            let brightness =
+                zenith.cos() * slope.cos() + zenith.sin() * slope.sin() * (azimuth - aspect).cos();
+            Some((1.0 + 254.0 * brightness.max(0.0)).round() as u8)
+        });
+
+        self.derive_band(RasterData::U8(values), Some(HILLSHADE_NO_DATA as f64))
+    }
+
+    /// Computes the steepness of the terrain of a DEM in the given unit, returning a single-band
+    /// raster of 32-bit floats like `gdaldem slope`.
+    ///
+    /// The slopes are derived from the elevations of the first sample with Horn's method, which
+    /// weights the neighbors in the same row or column twice. The size of the pixels is taken in
+    /// metres for geographic CRSs, assuming elevations in metres, and in the units of model space
+    /// otherwise. Pixels without a known elevation are set to the no data value -9999, while
+    /// unknown neighbors are replaced by the elevation of the pixel itself.
+    pub fn slope(&self, unit: SlopeUnit) -> GeoTiff {
+        let values = self.map_gradient(TERRAIN_NO_DATA, |dz_dx, dz_dy| {
+            let gradient = dz_dx.hypot(dz_dy);
+            Some(match unit {
+                SlopeUnit::Degrees => gradient.atan().to_degrees(),
+                SlopeUnit::Percent => gradient * 100.0,
+            } as f32)
+        });
+
+        self.derive_band(RasterData::F32(values), Some(TERRAIN_NO_DATA as f64))
+    }
+
+    /// Computes the direction the terrain of a DEM faces in degrees clockwise from north,
+    /// returning a single-band raster of 32-bit floats like `gdaldem aspect`.
+    ///
+    /// The aspects are derived as by [`Self::slope`]. Flat pixels have no aspect and are set to
+    /// the no data value -9999, like pixels without a known elevation.
+    pub fn aspect(&self) -> GeoTiff {
+        let values = self.map_gradient(TERRAIN_NO_DATA, |dz_dx, dz_dy| {
+            if dz_dx == 0.0 && dz_dy == 0.0 {
+                return None;
+            }
+            let aspect = dz_dy.atan2(-dz_dx).to_degrees();
+            Some((90.0 - aspect).rem_euclid(360.0) as f32)
+        });
+
+        self.derive_band(RasterData::F32(values), Some(TERRAIN_NO_DATA as f64))
+    }
+
+    /// Returns the values computed from the rates of change of the first sample to the right and
+    /// downwards at each pixel in row-major order, or the given no data value if the pixel is
+    /// unknown or no value is computed.
+    ///
+    /// The direction the terrain faces counterclockwise from east is the arctangent of the rate
+    /// downwards and the negated rate to the right.
+    fn map_gradient<T: Copy>(&self, no_data: T, value: impl Fn(f64, f64) -> Option<T>) -> Vec<T> {
+        let mut values = vec![no_data; self.raster_width * self.raster_height];
+        for row in 0..self.raster_height {
+            let spacing = self.pixel_spacing(row);
+            for col in 0..self.raster_width {
+                if let Some(computed) = self
+                    .horn_gradient(col, row, spacing)
+                    .and_then(|(dz_dx, dz_dy)| value(dz_dx, dz_dy))
+                {
+                    values[row * self.raster_width + col] = computed;
+                }
+            }
+        }
+
+        values
+    }
+
+    /// Returns the distances between the center of the given row and its neighbors to the right
+    /// and below, in metres for geographic CRSs and in the units of model space otherwise.
+    fn pixel_spacing(&self, row: usize) -> (f64, f64) {
+        let center = Coord {
+            x: self.raster_width as f64 / 2.0,
+            y: row as f64 + 0.5 + self.raster_offset(),
+        };
+        let [origin, right, below] = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)].map(|(dx, dy)| {
+            self.transform_to_model(&Coord {
+                x: center.x + dx,
+                y: center.y + dy,
+            })
+        });
+
+        let directory = &self.geo_key_directory;
+        let geographic = directory.is_projected() == Some(false)
+            && (directory.model_type.is_some() || directory.geographic_type.is_some());
+        let distance = |to: &Coord| {
+            geographic
+                .then(|| self.geographic_distance(&origin, to))
+                .flatten()
+                .unwrap_or_else(|| (to.x - origin.x).hypot(to.y - origin.y))
+        };
+
+        (distance(&right), distance(&below))
+    }
+
+    /// Returns the rates of change of the first sample to the right and downwards at the given
+    /// pixel by Horn's method, or `None` if the pixel is unknown.
+    fn horn_gradient(
+        &self,
+        col: usize,
+        row: usize,
+        (spacing_x, spacing_y): (f64, f64),
+    ) -> Option<(f64, f64)> {
+        let (col, row) = (col as isize, row as isize);
+        let center = self.known_value(col, row, 0)?;
+        let z = |dx: isize, dy: isize| self.known_value(col + dx, row + dy, 0).unwrap_or(center);
+
+        let dz_dx = ((z(1, -1) + 2.0 * z(1, 0) + z(1, 1))
+            - (z(-1, -1) + 2.0 * z(-1, 0) + z(-1, 1)))
+            / (8.0 * spacing_x);
+        let dz_dy = ((z(-1, 1) + 2.0 * z(0, 1) + z(1, 1))
+            - (z(-1, -1) + 2.0 * z(0, -1) + z(1, -1)))
+            / (8.0 * spacing_y);
+
+        Some((dz_dx, dz_dy))
+    }
+}
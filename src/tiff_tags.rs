@@ -0,0 +1,174 @@
+use std::io::{Read, Seek};
+
+use tiff::decoder::ifd::Value;
+use tiff::decoder::Decoder;
+use tiff::tags::{Tag, Type};
+use tiff::Directory;
+
+use crate::error::{GeoTiffError, GeoTiffResult};
+
+/// The GPS tag holding the latitude, preceded by the one holding its hemisphere.
+const GPS_LATITUDE: u16 = 2;
+/// The GPS tag holding the longitude, preceded by the one holding its hemisphere.
+const GPS_LONGITUDE: u16 = 4;
+/// The GPS tag telling whether the altitude lies below sea level.
+const GPS_ALTITUDE_REF: u16 = 5;
+/// The GPS tag holding the altitude.
+const GPS_ALTITUDE: u16 = 6;
+
+/// The standard TIFF tags describing the provenance of an image, which are kept when the image
+/// is read and written again.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DescriptiveTags {
+    /// The description of the image, where producers often note the meaning of the bands or the
+    /// processing applied.
+    pub image_description: Option<String>,
+    /// The date and time the image was created, as `YYYY:MM:DD HH:MM:SS`.
+    pub date_time: Option<String>,
+    /// The software that created the image.
+    pub software: Option<String>,
+    /// The person who created the image.
+    pub artist: Option<String>,
+    /// The copyright notice of the image.
+    pub copyright: Option<String>,
+    /// The computer the image was created on.
+    pub host_computer: Option<String>,
+}
+
+impl DescriptiveTags {
+    /// Returns the tags paired with the values that are set.
+    pub(crate) fn tags(&self) -> impl Iterator<Item = (Tag, &str)> {
+        [
+            (Tag::ImageDescription, &self.image_description),
+            (Tag::DateTime, &self.date_time),
+            (Tag::Software, &self.software),
+            (Tag::Artist, &self.artist),
+            (Tag::Copyright, &self.copyright),
+            (Tag::HostComputer, &self.host_computer),
+        ]
+        .into_iter()
+        .filter_map(|(tag, value)| Some((tag, value.as_deref()?)))
+    }
+}
+
+/// A tag of an image file directory with its value, see
+/// [`GeoTiffReader::tags`](crate::GeoTiffReader::tags).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TiffTag {
+    pub tag: Tag,
+    /// The type of the values as stored in the file.
+    pub field_type: Type,
+    /// The number of values.
+    pub count: u64,
+    /// The values, as a list if there are several of them.
+    pub value: Value,
+}
+
+/// Reads all tags of the image the decoder is positioned at, in the order of their codes.
+pub(crate) fn image_tags<R: Read + Seek>(decoder: &mut Decoder<R>) -> GeoTiffResult<Vec<TiffTag>> {
+    let Some(pointer) = decoder.ifd_pointer() else {
+        return Err(GeoTiffError::Format("No image file directory".into()));
+    };
+    let directory = decoder.read_directory(pointer)?;
+    directory_tags(decoder, &directory)
+}
+
+/// Reads all tags of the given image file directory, in the order of their codes.
+pub(crate) fn directory_tags<R: Read + Seek>(
+    decoder: &mut Decoder<R>,
+    directory: &Directory,
+) -> GeoTiffResult<Vec<TiffTag>> {
+    let values = decoder
+        .read_directory_tags(directory)
+        .tag_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(directory
+        .iter()
+        .zip(values)
+        .map(|((tag, entry), (_, value))| TiffTag {
+            tag,
+            field_type: entry.field_type(),
+            count: entry.count(),
+            value,
+        })
+        .collect())
+}
+
+/// Reads the tags of the image file directory the given pointer tag of the image the decoder is
+/// positioned at points to, e.g. the EXIF directory, or `None` if the image has no such tag.
+pub(crate) fn linked_directory_tags<R: Read + Seek>(
+    decoder: &mut Decoder<R>,
+    pointer_tag: Tag,
+) -> GeoTiffResult<Option<Vec<TiffTag>>> {
+    let Some(pointer) = decoder.find_tag(pointer_tag)? else {
+        return Ok(None);
+    };
+    let directory = decoder.read_directory(pointer.into_ifd_pointer()?)?;
+    directory_tags(decoder, &directory).map(Some)
+}
+
+/// The location where an image was captured, as recorded in its GPS tags, e.g. by drones, see
+/// [`GeoTiffReader::gps_position`](crate::GeoTiffReader::gps_position).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GpsPosition {
+    /// The latitude in degrees, negative in the southern hemisphere.
+    pub latitude: f64,
+    /// The longitude in degrees, negative in the western hemisphere.
+    pub longitude: f64,
+    /// The altitude in metres, negative below sea level.
+    pub altitude: Option<f64>,
+}
+
+impl GpsPosition {
+    /// Parses the position from the tags of a GPS directory, or returns `None` if the latitude or
+    /// the longitude is missing or invalid.
+    pub(crate) fn from_gps_tags(tags: &[TiffTag]) -> Option<Self> {
+        let value = |code: u16| {
+            tags.iter()
+                .find(|tag| tag.tag.to_u16() == code)
+                .map(|tag| &tag.value)
+        };
+        let reference = |code: u16| match value(code) {
+            Some(Value::Ascii(reference)) => reference.trim_end_matches('\0').to_string(),
+            _ => String::new(),
+        };
+        // Degrees, minutes and seconds, negated for the southern or western hemisphere
+        let angle = |code: u16, negative_reference: &str| {
+            let [degrees, minutes, seconds] = numbers(value(code)?).try_into().ok()?;
+            let angle = degrees + minutes / 60.0 + seconds / 3600.0;
+            Some(match reference(code - 1) == negative_reference {
+                true => -angle,
+                false => angle,
+            })
+        };
+
+        let altitude = value(GPS_ALTITUDE)
+            .and_then(|altitude| numbers(altitude).first().copied())
+            .map(|altitude| match value(GPS_ALTITUDE_REF) {
+                Some(Value::Byte(1)) => -altitude,
+                _ => altitude,
+            });
+
+        Some(Self {
+            latitude: angle(GPS_LATITUDE, "S")?,
+            longitude: angle(GPS_LONGITUDE, "W")?,
+            altitude,
+        })
+    }
+}
+
+/// Returns the numbers held by the given value, with rationals divided out.
+fn numbers(value: &Value) -> Vec<f64> {
+    match value {
+        Value::List(values) => values.iter().flat_map(numbers).collect(),
+        Value::Rational(numerator, denominator) => vec![*numerator as f64 / *denominator as f64],
+        Value::SRational(numerator, denominator) => vec![*numerator as f64 / *denominator as f64],
+        Value::Byte(value) => vec![*value as f64],
+        Value::Short(value) => vec![*value as f64],
+        Value::Unsigned(value) => vec![*value as f64],
+        Value::Float(value) => vec![*value as f64],
+        Value::Double(value) => vec![*value],
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,214 @@
+use std::f64::consts::PI;
+use std::io::{Read, Seek};
+
+use geo_types::{Coord, Rect};
+
+use crate::coordinate_transform::CoordinateTransform;
+use crate::epsg::{WEB_MERCATOR, WGS_84};
+use crate::error::{GeoTiffError, GeoTiffResult};
+use crate::geo_key_directory::{GeoKeyDirectory, GeoKeyDirectoryBuilder};
+use crate::interpolation::Interpolation;
+use crate::layout::{ArrayOrder, Interleave};
+use crate::raster_data::BufferLayout;
+use crate::reader::GeoTiffReader;
+use crate::window::Window;
+use crate::GeoTiff;
+
+/// The radius of the sphere of the web mercator projection in metres.
+const WEB_MERCATOR_RADIUS: f64 = 6378137.0;
+
+/// The highest zoom level whose tiles are addressed by [`MapTile`].
+const MAX_ZOOM: u8 = 30;
+
+/// The number of pixels added around the pixels covering a map tile, so that the bicubic
+/// interpolation at its edges uses the same neighbourhood as for the full raster.
+const MARGIN: usize = 2;
+
+/// A tile of the web mercator tiling scheme of slippy maps, with the column `x` and the row `y`
+/// counted from the top left at zoom level `z`, see [`GeoTiffReader::read_map_tile`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MapTile {
+    pub z: u8,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl MapTile {
+    pub fn new(z: u8, x: u32, y: u32) -> Self {
+        Self { z, x, y }
+    }
+
+    /// Returns the extent of the tile in web mercator (EPSG:3857) in metres.
+    pub fn bounds(&self) -> Rect {
+        let half_extent = PI * WEB_MERCATOR_RADIUS;
+        let size = 2.0 * half_extent / 2f64.powi(self.z as i32);
+        let min = Coord {
+            x: -half_extent + self.x as f64 * size,
+            y: half_extent - (self.y as f64 + 1.0) * size,
+        };
+
+        Rect::new(min, min + Coord { x: size, y: size })
+    }
+
+    /// Returns whether the tile exists at its zoom level.
+    fn is_valid(&self) -> bool {
+        self.z <= MAX_ZOOM && self.x < 1 << self.z && self.y < 1 << self.z
+    }
+}
+
+impl<R: Read + Seek> GeoTiffReader<R> {
+    /// Reads the given slippy map tile as a raster of `tile_size` by `tile_size` pixels in web
+    /// mercator (EPSG:3857), e.g. to serve map tiles from a cloud optimized GeoTIFF, or returns
+    /// `None` if the tile does not intersect the raster.
+    ///
+    /// Only the strips or tiles covering the map tile are decoded, at full resolution. The value
+    /// of each pixel of the map tile is estimated at its center with the given interpolation
+    /// method, see [`GeoTiff::sample`], after reprojecting it into the CRS of the model space.
+    /// Rasters in web mercator or WGS 84 are reprojected directly, while other CRSs require the
+    /// `proj` feature. Unknown values are set to the no data value, or left zeroed without one,
+    /// and pixels without any known value are marked invalid by the transparency mask.
+    ///
+    /// Fails with [`GeoTiffError::Format`] if the tile does not exist at its zoom level or its
+    /// size is zero, and with [`GeoTiffError::Crs`] if the raster cannot be reprojected.
+    pub fn read_map_tile(
+        &mut self,
+        tile: &MapTile,
+        tile_size: usize,
+        interpolation: Interpolation,
+    ) -> GeoTiffResult<Option<GeoTiff>> {
+        if !tile.is_valid() || tile_size == 0 {
+            return Err(GeoTiffError::Format(format!(
+                "Cannot read {tile_size}x{tile_size} pixels of the map tile {tile:?}"
+            )));
+        }
+
+        let bounds = tile.bounds();
+        let pixel_size = bounds.width() / tile_size as f64;
+        let mut centers: Vec<_> = (0..tile_size * tile_size)
+            .map(|pixel| Coord {
+                x: bounds.min().x + ((pixel % tile_size) as f64 + 0.5) * pixel_size,
+                y: bounds.max().y - ((pixel / tile_size) as f64 + 0.5) * pixel_size,
+            })
+            .collect();
+        web_mercator_to_model(&self.geo_key_directory, &mut centers)?;
+
+        let (mut min, mut max) = (centers[0], centers[0]);
+        for center in &centers {
+            min = Coord {
+                x: min.x.min(center.x),
+                y: min.y.min(center.y),
+            };
+            max = Coord {
+                x: max.x.max(center.x),
+                y: max.y.max(center.y),
+            };
+        }
+        let Some(window) = self.intersection_window(&Rect::new(min, max)) else {
+            return Ok(None);
+        };
+        let x = window.x.saturating_sub(MARGIN);
+        let y = window.y.saturating_sub(MARGIN);
+        let window = Window::new(
+            x,
+            y,
+            (window.x + window.width + MARGIN).min(self.raster_width) - x,
+            (window.y + window.height + MARGIN).min(self.raster_height) - y,
+        );
+        let region = self.read_region(&window)?;
+
+        let layout = BufferLayout {
+            width: tile_size,
+            height: tile_size,
+            num_samples: region.num_samples,
+            interleave: Interleave::Pixel,
+            order: ArrayOrder::RowMajor,
+        };
+        let mut raster_data = region
+            .raster_data
+            .zeroed_like(tile_size * tile_size * region.num_samples);
+        let mut mask = vec![false; tile_size * tile_size];
+        for (pixel, center) in centers.iter().enumerate() {
+            let (col, row) = (pixel % tile_size, pixel / tile_size);
+            for sample in 0..region.num_samples {
+                let index = layout.index(col, row, sample);
+                match region.sample(center, sample, interpolation)? {
+                    Some(value) => {
+                        raster_data.set(index, value);
+                        mask[pixel] = true;
+                    }
+                    None => {
+                        if let Some(no_data) = region.no_data {
+                            raster_data.set(index, no_data);
+                        }
+                    }
+                }
+            }
+        }
+
+        let geotransform = [
+            bounds.min().x,
+            pixel_size,
+            0.0,
+            bounds.max().y,
+            0.0,
+            -pixel_size,
+        ];
+        Ok(Some(GeoTiff {
+            geo_key_directory: GeoKeyDirectoryBuilder::from_epsg(WEB_MERCATOR)
+                .map_or_else(GeoKeyDirectory::default, GeoKeyDirectoryBuilder::build),
+            raster_width: tile_size,
+            raster_height: tile_size,
+            coordinate_transform: Some(CoordinateTransform::from_geotransform(geotransform, 0.0)?),
+            gcps: Vec::new(),
+            chunk_dimensions: (tile_size, tile_size),
+            interleave: Interleave::Pixel,
+            order: ArrayOrder::RowMajor,
+            raster_data,
+            corrupt_chunks: Vec::new(),
+            mask: mask.contains(&false).then_some(mask),
+            overviews: Vec::new(),
+            ..region
+        }))
+    }
+}
+
+/// Reprojects the given web mercator coordinates into the model space of a raster with the given
+/// GeoKeys.
+fn web_mercator_to_model(directory: &GeoKeyDirectory, coords: &mut [Coord]) -> GeoTiffResult<()> {
+    match directory.epsg_code() {
+        Some(WEB_MERCATOR) => {}
+        Some(WGS_84) => {
+            for coord in coords {
+                *coord = Coord {
+                    x: (coord.x / WEB_MERCATOR_RADIUS).to_degrees(),
+                    y: (coord.y / WEB_MERCATOR_RADIUS).sinh().atan().to_degrees(),
+                };
+            }
+        }
+        _ => reproject(directory, coords)?,
+    }
+
+    Ok(())
+}
+
+/// Reprojects the given web mercator coordinates into the model space using PROJ.
+#[cfg(feature = "proj")]
+fn reproject(directory: &GeoKeyDirectory, coords: &mut [Coord]) -> GeoTiffResult<()> {
+    let target = directory.model_crs()?;
+    let proj = proj::Proj::new_known_crs(&format!("EPSG:{WEB_MERCATOR}"), &target, None)?;
+    for coord in coords {
+        *coord = proj.convert(*coord)?;
+    }
+
+    Ok(())
+}
+
+/// Fails as reprojecting into CRSs other than web mercator and WGS 84 requires PROJ.
+#[cfg(not(feature = "proj"))]
+fn reproject(_directory: &GeoKeyDirectory, _coords: &mut [Coord]) -> GeoTiffResult<()> {
+    Err(GeoTiffError::Crs(
+        "Reprojecting from web mercator into CRSs other than EPSG:3857 and EPSG:4326 requires \
+         the proj feature."
+            .into(),
+    ))
+}
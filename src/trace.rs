@@ -0,0 +1,47 @@
+//! Macros emitting [`tracing`](https://docs.rs/tracing) spans and events if the `tracing` feature
+//! is enabled, and only referencing their fields otherwise, so that variables used solely for
+//! tracing are not reported as unused.
+
+/// Enters a span at debug level for the rest of the enclosing block.
+macro_rules! trace_span {
+    ($name: literal $(, $($fields: tt)*)?) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($name $(, $($fields)*)?).entered();
+        #[cfg(not(feature = "tracing"))]
+        trace_fields!($($($fields)*)?);
+    };
+}
+
+/// Emits an event at debug level.
+macro_rules! trace_event {
+    ($($arguments: tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arguments)*);
+        #[cfg(not(feature = "tracing"))]
+        trace_fields!($($arguments)*);
+    };
+}
+
+/// References the values of the fields of a span or event, given as `name`, `name = value`,
+/// `name = ?value` or `name = %value`, optionally followed by a message.
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_fields {
+    () => {};
+    ($message: literal $(, $($arguments: tt)*)?) => {};
+    ($name: ident = ? $value: expr $(, $($rest: tt)*)?) => {
+        let _ = (&$value,);
+        trace_fields!($($($rest)*)?);
+    };
+    ($name: ident = % $value: expr $(, $($rest: tt)*)?) => {
+        let _ = (&$value,);
+        trace_fields!($($($rest)*)?);
+    };
+    ($name: ident = $value: expr $(, $($rest: tt)*)?) => {
+        let _ = (&$value,);
+        trace_fields!($($($rest)*)?);
+    };
+    ($name: ident $(, $($rest: tt)*)?) => {
+        let _ = (&$name,);
+        trace_fields!($($($rest)*)?);
+    };
+}
@@ -0,0 +1,207 @@
+use std::io::{Read, Seek, Write};
+use std::slice;
+
+use geo_types::{Coord, Rect};
+
+use crate::coordinate_transform::CoordinateTransform;
+use crate::error::{GeoTiffError, GeoTiffResult};
+use crate::geo_key_directory::{GeoKeyDirectory, RasterType};
+use crate::interpolation::Interpolation;
+use crate::profile::Profile;
+use crate::raster_data::RasterData;
+use crate::reader::GeoTiffReader;
+use crate::window::Window;
+use crate::GeoTiff;
+
+/// The number of pixels added around the pixels covering a block, so that the bicubic
+/// interpolation at its edges uses the same neighbourhood as for the full raster.
+const MARGIN: usize = 2;
+
+impl<R: Read + Seek> GeoTiffReader<R> {
+    /// Warps the raster into the grid and CRS of the given profile and writes the result to the
+    /// given destination block by block, see [`Self::mosaic`].
+    pub fn warp<W: Write + Seek>(
+        &mut self,
+        writer: W,
+        profile: &Profile,
+        interpolation: Interpolation,
+    ) -> GeoTiffResult<()> {
+        Self::mosaic(slice::from_mut(self), writer, profile, interpolation)
+    }
+
+    /// Mosaics the given rasters into the grid and CRS of the given profile and writes the
+    /// result to the given destination block by block with [`GeoTiff::write_blocks`], using the
+    /// compression and tiling of the profile.
+    ///
+    /// For each strip or tile written, only the strips or tiles of the rasters covering it are
+    /// decoded, so neither the input nor the output is held in memory as a whole, as long as the
+    /// resolutions of the rasters and the profile are similar. The value of each pixel is
+    /// estimated at its center with the given interpolation method, see [`GeoTiff::sample`],
+    /// taking the first raster with a known value for each sample. Unknown values are set to the
+    /// no data value of the profile, or left zeroed without one.
+    ///
+    /// Rasters in the CRS of the profile are not reprojected, while other CRSs require the `proj`
+    /// feature. Fails with [`GeoTiffError::Format`] if the profile has no geotransform or the
+    /// rasters do not have as many samples as the profile, and with [`GeoTiffError::Crs`] if a
+    /// raster cannot be reprojected.
+    pub fn mosaic<W: Write + Seek>(
+        sources: &mut [Self],
+        writer: W,
+        profile: &Profile,
+        interpolation: Interpolation,
+    ) -> GeoTiffResult<()> {
+        let Some(geotransform) = profile.geotransform else {
+            return Err(GeoTiffError::Format(
+                "Cannot warp into a profile without geotransform".into(),
+            ));
+        };
+        if let Some(source) = sources
+            .iter()
+            .find(|source| source.num_samples != profile.num_samples)
+        {
+            return Err(GeoTiffError::Format(format!(
+                "Cannot warp a raster with {} samples into a profile with {}",
+                source.num_samples, profile.num_samples
+            )));
+        }
+        let offset = match profile.geo_key_directory.raster_type {
+            Some(RasterType::RasterPixelIsPoint) => -0.5,
+            _ => 0.0,
+        };
+        let transform = CoordinateTransform::from_geotransform(geotransform, offset)?;
+
+        GeoTiff::write_blocks(writer, profile, &profile.write_options(), |window| {
+            let centers: Vec<_> = (0..window.width * window.height)
+                .map(|pixel| {
+                    transform.transform_to_model(&Coord {
+                        x: (window.x + pixel % window.width) as f64 + 0.5,
+                        y: (window.y + pixel / window.width) as f64 + 0.5,
+                    })
+                })
+                .collect();
+
+            let len = centers.len() * profile.num_samples;
+            let mut raster_data = RasterData::zeroed(profile.data_type, len);
+            if let Some(no_data) = profile.no_data {
+                for index in 0..len {
+                    raster_data.set(index, no_data);
+                }
+            }
+            let mut known = vec![false; len];
+            for source in sources.iter_mut() {
+                if !known.contains(&false) {
+                    break;
+                }
+                let mut coords = centers.clone();
+                reproject(
+                    &profile.geo_key_directory,
+                    &source.geo_key_directory,
+                    &mut coords,
+                )?;
+                let Some(region) = source.read_covering_region(&coords)? else {
+                    continue;
+                };
+                for (pixel, coord) in coords.iter().enumerate() {
+                    for sample in 0..profile.num_samples {
+                        let index = pixel * profile.num_samples + sample;
+                        if known[index] {
+                            continue;
+                        }
+                        if let Some(value) = region.sample(coord, sample, interpolation)? {
+                            raster_data.set(index, value);
+                            known[index] = true;
+                        }
+                    }
+                }
+            }
+
+            Ok(raster_data)
+        })
+    }
+
+    /// Reads the pixels around the given locations in model space, or returns `None` if none of
+    /// them lie within the raster.
+    fn read_covering_region(&mut self, coords: &[Coord]) -> GeoTiffResult<Option<GeoTiff>> {
+        let (mut min, mut max) = (
+            Coord {
+                x: f64::INFINITY,
+                y: f64::INFINITY,
+            },
+            Coord {
+                x: f64::NEG_INFINITY,
+                y: f64::NEG_INFINITY,
+            },
+        );
+        for coord in coords
+            .iter()
+            .filter(|coord| coord.x.is_finite() && coord.y.is_finite())
+        {
+            min = Coord {
+                x: min.x.min(coord.x),
+                y: min.y.min(coord.y),
+            };
+            max = Coord {
+                x: max.x.max(coord.x),
+                y: max.y.max(coord.y),
+            };
+        }
+        if min.x > max.x || min.y > max.y {
+            return Ok(None);
+        }
+        let Some(window) = self.intersection_window(&Rect::new(min, max)) else {
+            return Ok(None);
+        };
+
+        let x = window.x.saturating_sub(MARGIN);
+        let y = window.y.saturating_sub(MARGIN);
+        let window = Window::new(
+            x,
+            y,
+            (window.x + window.width + MARGIN).min(self.raster_width) - x,
+            (window.y + window.height + MARGIN).min(self.raster_height) - y,
+        );
+        self.read_region(&window).map(Some)
+    }
+}
+
+/// Reprojects the given coordinates from the CRS of the target GeoKeys into the model space of a
+/// raster with the source GeoKeys, unless they share the CRS.
+fn reproject(
+    target: &GeoKeyDirectory,
+    source: &GeoKeyDirectory,
+    coords: &mut [Coord],
+) -> GeoTiffResult<()> {
+    if target == source || target.epsg_code().is_some() && target.epsg_code() == source.epsg_code()
+    {
+        return Ok(());
+    }
+
+    reproject_with_proj(target, source, coords)
+}
+
+/// Reprojects the given coordinates between the CRSs of the given GeoKeys using PROJ.
+#[cfg(feature = "proj")]
+fn reproject_with_proj(
+    target: &GeoKeyDirectory,
+    source: &GeoKeyDirectory,
+    coords: &mut [Coord],
+) -> GeoTiffResult<()> {
+    let proj = proj::Proj::new_known_crs(&target.model_crs()?, &source.model_crs()?, None)?;
+    for coord in coords {
+        *coord = proj.convert(*coord)?;
+    }
+
+    Ok(())
+}
+
+/// Fails as reprojecting between different CRSs requires PROJ.
+#[cfg(not(feature = "proj"))]
+fn reproject_with_proj(
+    _target: &GeoKeyDirectory,
+    _source: &GeoKeyDirectory,
+    _coords: &mut [Coord],
+) -> GeoTiffResult<()> {
+    Err(GeoTiffError::Crs(
+        "Warping between different CRSs requires the proj feature.".into(),
+    ))
+}
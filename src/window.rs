@@ -0,0 +1,70 @@
+/// A rectangular region of the raster, given in pixel coordinates.
+///
+/// `x` and `y` denote the upper left pixel of the window, `width` and `height` its size in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Window {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Window {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Returns `true` if the window does not contain any pixels.
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Returns the pixels both windows contain, or `None` if they do not intersect.
+    pub(crate) fn intersection(&self, other: &Window) -> Option<Window> {
+        let (x, y) = (self.x.max(other.x), self.y.max(other.y));
+        let x_end = (self.x + self.width).min(other.x + other.width);
+        let y_end = (self.y + self.height).min(other.y + other.height);
+
+        (x < x_end && y < y_end).then(|| Window::new(x, y, x_end - x, y_end - y))
+    }
+
+    /// Returns the columns and rows of the pixels in the window, row by row.
+    #[cfg(any(feature = "arrow", feature = "parquet"))]
+    pub(crate) fn pixels(&self) -> impl Iterator<Item = (usize, usize)> {
+        let Window {
+            x,
+            y,
+            width,
+            height,
+        } = *self;
+        (y..y + height).flat_map(move |row| (x..x + width).map(move |col| (col, row)))
+    }
+
+    /// Expands the window so that its edges coincide with the boundaries of the chunk grid, and
+    /// clips it to the raster dimensions.
+    pub(crate) fn align_to(
+        &self,
+        chunk_width: usize,
+        chunk_height: usize,
+        raster_width: usize,
+        raster_height: usize,
+    ) -> Self {
+        let x_end = (self.x + self.width).min(raster_width);
+        let y_end = (self.y + self.height).min(raster_height);
+        if self.x >= x_end || self.y >= y_end {
+            return Self::new(self.x.min(raster_width), self.y.min(raster_height), 0, 0);
+        }
+
+        let x = self.x - self.x % chunk_width;
+        let y = self.y - self.y % chunk_height;
+        let x_end = x_end.next_multiple_of(chunk_width).min(raster_width);
+        let y_end = y_end.next_multiple_of(chunk_height).min(raster_height);
+
+        Self::new(x, y, x_end - x, y_end - y)
+    }
+}
@@ -0,0 +1,362 @@
+use std::f64::consts::PI;
+use std::fmt::Write;
+
+use crate::epsg;
+use crate::geo_key_directory::{CoordinateTransformationMethod, GeoKeyDirectory, USER_DEFINED};
+
+/// EPSG code of the Greenwich prime meridian.
+const GREENWICH: u16 = 8901;
+
+/// A unit of measure, given by its WKT keyword, name and conversion factor to the SI unit.
+pub(crate) struct Unit {
+    pub keyword: &'static str,
+    pub name: &'static str,
+    pub factor: f64,
+}
+
+impl Unit {
+    fn to_wkt(&self) -> String {
+        format!("{}[\"{}\",{}]", self.keyword, self.name, self.factor)
+    }
+}
+
+/// The kind of value of a projection parameter, determining its unit.
+enum ParameterKind {
+    Angle,
+    Length,
+    Scale,
+}
+
+/// Projection parameters by EPSG code.
+const PARAMETERS: &[(u16, &str, ParameterKind)] = &[
+    (8801, "Latitude of natural origin", ParameterKind::Angle),
+    (8802, "Longitude of natural origin", ParameterKind::Angle),
+    (8805, "Scale factor at natural origin", ParameterKind::Scale),
+    (8806, "False easting", ParameterKind::Length),
+    (8807, "False northing", ParameterKind::Length),
+    (8811, "Latitude of projection centre", ParameterKind::Angle),
+    (8812, "Longitude of projection centre", ParameterKind::Angle),
+    (8813, "Azimuth at projection centre", ParameterKind::Angle),
+    (
+        8814,
+        "Angle from Rectified to Skew Grid",
+        ParameterKind::Angle,
+    ),
+    (
+        8815,
+        "Scale factor at projection centre",
+        ParameterKind::Scale,
+    ),
+    (8816, "Easting at projection centre", ParameterKind::Length),
+    (8817, "Northing at projection centre", ParameterKind::Length),
+    (8821, "Latitude of false origin", ParameterKind::Angle),
+    (8822, "Longitude of false origin", ParameterKind::Angle),
+    (
+        8823,
+        "Latitude of 1st standard parallel",
+        ParameterKind::Angle,
+    ),
+    (
+        8824,
+        "Latitude of 2nd standard parallel",
+        ParameterKind::Angle,
+    ),
+    (8826, "Easting at false origin", ParameterKind::Length),
+    (8827, "Northing at false origin", ParameterKind::Length),
+];
+
+impl GeoKeyDirectory {
+    /// Returns the coordinate reference system described by the keys as WKT2 (ISO 19162:2019),
+    /// e.g. to hand it to PROJ, GDAL or a database.
+    ///
+    /// The datum, ellipsoid, units and projection parameters are taken from the geodetic and
+    /// projected keys, and EPSG codes are added as identifiers. With the `epsg` feature, keys
+    /// implied by EPSG codes are resolved first, see [`GeoKeyDirectory::resolve_epsg`].
+    ///
+    /// Returns `None` if the keys do not describe the coordinate reference system completely,
+    /// e.g. if the ellipsoid or the map projection is missing, or if it uses a unit or a
+    /// coordinate transformation method that cannot be expressed.
+    pub fn to_wkt(&self) -> Option<String> {
+        #[cfg(feature = "epsg")]
+        let directory = &self.resolve_epsg();
+        #[cfg(not(feature = "epsg"))]
+        let directory = self;
+
+        if directory.is_projected()? {
+            directory.projected_wkt()
+        } else {
+            directory.geographic_wkt()
+        }
+    }
+
+    fn geographic_wkt(&self) -> Option<String> {
+        let angular_unit = self.angular_unit()?.to_wkt();
+        Some(format!(
+            "GEOGCRS[\"{}\",{},\
+             CS[ellipsoidal,2],\
+             AXIS[\"geodetic latitude (Lat)\",north,ORDER[1]],\
+             AXIS[\"geodetic longitude (Lon)\",east,ORDER[2]],\
+             {angular_unit}{}]",
+            self.geographic_name(),
+            self.datum_wkt()?,
+            id(self.geographic_type),
+        ))
+    }
+
+    fn projected_wkt(&self) -> Option<String> {
+        let linear_unit = self.linear_unit(self.proj_linear_units, self.proj_linear_unit_size)?;
+        let name = self
+            .citations()
+            .pcs_name
+            .or_else(|| {
+                self.projected_type
+                    .and_then(epsg::lookup)
+                    .map(|crs| crs.name)
+            })
+            .unwrap_or_else(|| "unknown".into());
+
+        Some(format!(
+            "PROJCRS[\"{}\",BASEGEOGCRS[\"{}\",{}{}],{},\
+             CS[Cartesian,2],\
+             AXIS[\"easting (E)\",east,ORDER[1]],\
+             AXIS[\"northing (N)\",north,ORDER[2]],\
+             {}{}]",
+            escape(&name),
+            self.geographic_name(),
+            self.datum_wkt()?,
+            id(self.geographic_type),
+            self.conversion_wkt(&linear_unit)?,
+            linear_unit.to_wkt(),
+            id(self.projected_type),
+        ))
+    }
+
+    /// Returns the datum and prime meridian of the geographic CRS.
+    fn datum_wkt(&self) -> Option<String> {
+        // Ellipsoids known by their code need not be defined by the keys
+        let ellipsoid = self.geog_ellipsoid.and_then(epsg::ellipsoid);
+        let semi_major_axis = self
+            .geog_semi_major_axis
+            .or(ellipsoid.map(|(_, semi_major_axis, _)| semi_major_axis))?;
+        let inv_flattening = self
+            .geog_inv_flattening
+            .or_else(|| {
+                // A sphere has an inverse flattening of 0 by convention
+                self.geog_semi_minor_axis.map(|semi_minor_axis| {
+                    if semi_minor_axis == semi_major_axis {
+                        0.0
+                    } else {
+                        semi_major_axis / (semi_major_axis - semi_minor_axis)
+                    }
+                })
+            })
+            .or(ellipsoid.map(|(.., inv_flattening)| inv_flattening))?;
+        // Names of user-defined objects may be kept in the citations
+        let citations = self.citations();
+        let ellipsoid_name = ellipsoid
+            .map(|(name, ..)| name.into())
+            .or(citations.ellipsoid.map(|name| escape(&name)))
+            .unwrap_or_else(|| "unknown".into());
+        let datum_name = self
+            .geog_geodetic_datum
+            .and_then(epsg::datum_name)
+            .map(Into::into)
+            .or(citations.datum.map(|name| escape(&name)))
+            .unwrap_or_else(|| "unknown".into());
+        let prime_meridian_name = match self.geog_prime_meridian {
+            Some(GREENWICH) | None => "Greenwich".into(),
+            Some(_) => citations
+                .prime_meridian
+                .map(|name| escape(&name))
+                .unwrap_or_else(|| "unknown".into()),
+        };
+
+        Some(format!(
+            "DATUM[\"{datum_name}\",ELLIPSOID[\"{ellipsoid_name}\",{semi_major_axis},{inv_flattening},{}{}]{}],\
+             PRIMEM[\"{prime_meridian_name}\",{},{}{}]",
+            self.linear_unit(self.geog_linear_units, self.geog_linear_unit_size)?
+                .to_wkt(),
+            id(self.geog_ellipsoid),
+            id(self.geog_geodetic_datum),
+            self.geog_prime_meridian_long.unwrap_or(0.0),
+            self.angular_unit()?.to_wkt(),
+            id(self.geog_prime_meridian),
+        ))
+    }
+
+    /// Returns the map projection, with angular parameters in the angular unit of the geographic
+    /// CRS and linear parameters in the given unit.
+    fn conversion_wkt(&self, linear_unit: &Unit) -> Option<String> {
+        use CoordinateTransformationMethod::*;
+
+        const NATURAL_ORIGIN: &[u16] = &[8801, 8802, 8805, 8806, 8807];
+        const FALSE_ORIGIN: &[u16] = &[8821, 8822, 8823, 8824, 8826, 8827];
+
+        let (method, method_code, parameters) = match self.coordinate_transformation_method() {
+            Some(TransverseMercator) => ("Transverse Mercator", 9807, NATURAL_ORIGIN),
+            Some(ObliqueMercator) => (
+                "Hotine Oblique Mercator (variant B)",
+                9815,
+                &[8811, 8812, 8813, 8814, 8815, 8816, 8817][..],
+            ),
+            // Mercator with the scale given by a standard parallel instead of a scale factor
+            Some(Mercator) if self.proj_std_parallel1.is_some() => {
+                ("Mercator (variant B)", 9805, &[8823, 8802, 8806, 8807][..])
+            }
+            Some(Mercator) => ("Mercator (variant A)", 9804, NATURAL_ORIGIN),
+            Some(LambertConformalConic2Sp) => ("Lambert Conic Conformal (2SP)", 9802, FALSE_ORIGIN),
+            Some(LambertConformalConic1Sp) => {
+                ("Lambert Conic Conformal (1SP)", 9801, NATURAL_ORIGIN)
+            }
+            Some(LambertAzimuthalEqualArea) => (
+                "Lambert Azimuthal Equal Area",
+                9820,
+                &[8801, 8802, 8806, 8807][..],
+            ),
+            Some(AlbersEqualArea) => ("Albers Equal Area", 9822, FALSE_ORIGIN),
+            Some(PolarStereographic) => ("Polar Stereographic (variant A)", 9810, NATURAL_ORIGIN),
+            Some(ObliqueStereographic) => ("Oblique Stereographic", 9809, NATURAL_ORIGIN),
+            Some(Equirectangular) => (
+                "Equidistant Cylindrical",
+                1028,
+                &[8823, 8802, 8806, 8807][..],
+            ),
+            Some(CassiniSoldner) => ("Cassini-Soldner", 9806, &[8801, 8802, 8806, 8807][..]),
+            // Web Mercator has no coordinate transformation code
+            None if self.proj_coord_trans.is_none() && self.projected_type == Some(3857) => (
+                "Popular Visualisation Pseudo Mercator",
+                1024,
+                &[8801, 8802, 8806, 8807][..],
+            ),
+            _ => return None,
+        };
+
+        let conversion_name = self
+            .projected_type
+            .and_then(epsg::lookup)
+            .and_then(|crs| Some(escape(crs.name.split_once(" / ")?.1)))
+            .unwrap_or_else(|| "unknown".into());
+        let angular_unit = self.angular_unit()?.to_wkt();
+        let linear_unit = linear_unit.to_wkt();
+
+        let mut wkt = format!(
+            "CONVERSION[\"{conversion_name}\",METHOD[\"{method}\",ID[\"EPSG\",{method_code}]]"
+        );
+        for &code in parameters {
+            let (name, kind) = PARAMETERS
+                .iter()
+                .find(|(c, ..)| *c == code)
+                .map(|(_, name, kind)| (name, kind))?;
+            // Missing parameters default to zero, or one for scale factors
+            let (value, unit) = match kind {
+                ParameterKind::Angle => (self.parameter(code).unwrap_or(0.0), &angular_unit[..]),
+                ParameterKind::Length => (self.parameter(code).unwrap_or(0.0), &linear_unit[..]),
+                ParameterKind::Scale => (
+                    self.parameter(code).unwrap_or(1.0),
+                    "SCALEUNIT[\"unity\",1]",
+                ),
+            };
+            write!(
+                wkt,
+                ",PARAMETER[\"{name}\",{value},{unit},ID[\"EPSG\",{code}]]"
+            )
+            .ok()?;
+        }
+        write!(wkt, "{}]", id(self.projection)).ok()?;
+
+        Some(wkt)
+    }
+
+    /// Returns the value of the projection parameter with the given EPSG code, falling back to
+    /// the keys that GeoTIFF writers use interchangeably for it.
+    fn parameter(&self, code: u16) -> Option<f64> {
+        match code {
+            8801 => self.proj_nat_origin_lat.or(self.proj_center_lat),
+            8802 => self
+                .proj_nat_origin_long
+                .or(self.proj_center_long)
+                .or(self.proj_straight_vert_pole_long),
+            8805 => self.proj_scale_at_nat_origin,
+            8806 => self.proj_false_easting,
+            8807 => self.proj_false_northing,
+            8811 => self.proj_center_lat,
+            8812 => self.proj_center_long,
+            // GeoTIFF has no key for the angle of the rectified grid, which usually equals the
+            // azimuth
+            8813 | 8814 => self.proj_azimuth_angle,
+            8815 => self.proj_scale_at_center,
+            8816 => self.proj_center_easting.or(self.proj_false_easting),
+            8817 => self.proj_center_northing.or(self.proj_false_northing),
+            8821 => self.proj_false_origin_lat.or(self.proj_nat_origin_lat),
+            8822 => self.proj_false_origin_long.or(self.proj_nat_origin_long),
+            8823 => self.proj_std_parallel1,
+            8824 => self.proj_std_parallel2,
+            8826 => self.proj_false_origin_easting.or(self.proj_false_easting),
+            8827 => self.proj_false_origin_northing.or(self.proj_false_northing),
+            _ => None,
+        }
+    }
+
+    fn geographic_name(&self) -> String {
+        self.citations()
+            .gcs_name
+            .or_else(|| {
+                self.geographic_type
+                    .and_then(epsg::lookup)
+                    .map(|crs| crs.name)
+            })
+            .map(|name| escape(&name))
+            .unwrap_or_else(|| "unknown".into())
+    }
+
+    /// Returns the angular unit of the geographic CRS, defaulting to degrees.
+    pub(crate) fn angular_unit(&self) -> Option<Unit> {
+        let (name, factor) = match self.geog_angular_units {
+            Some(9101) => ("radian", 1.0),
+            Some(9102) | None => ("degree", PI / 180.0),
+            Some(9103) => ("arc-minute", PI / 10800.0),
+            Some(9104) => ("arc-second", PI / 648000.0),
+            Some(9105) => ("grad", PI / 200.0),
+            Some(USER_DEFINED) => ("unknown", self.geog_angular_unit_size?),
+            Some(_) => return None,
+        };
+
+        Some(Unit {
+            keyword: "ANGLEUNIT",
+            name,
+            factor,
+        })
+    }
+
+    /// Returns the linear unit with the given code or size, defaulting to metres.
+    pub(crate) fn linear_unit(&self, code: Option<u16>, size: Option<f64>) -> Option<Unit> {
+        let (name, factor) = match code {
+            Some(epsg::METRE) | None => ("metre", 1.0),
+            Some(9002) => ("foot", 0.3048),
+            Some(9003) => ("US survey foot", 1200.0 / 3937.0),
+            Some(9036) => ("kilometre", 1000.0),
+            Some(USER_DEFINED) => ("unknown", size?),
+            Some(_) => return None,
+        };
+
+        Some(Unit {
+            keyword: "LENGTHUNIT",
+            name,
+            factor,
+        })
+    }
+}
+
+/// Returns the EPSG identifier of an object with the given code, or nothing for user-defined
+/// objects.
+fn id(code: Option<u16>) -> String {
+    match code {
+        Some(code) if code != 0 && code != USER_DEFINED => format!(",ID[\"EPSG\",{code}]"),
+        _ => String::new(),
+    }
+}
+
+/// Escapes a name for use in a quoted WKT string, in which quotes are doubled.
+fn escape(name: &str) -> String {
+    name.replace('"', "\"\"")
+}
@@ -0,0 +1,920 @@
+use std::io::{self, Seek, Write};
+use std::num::NonZeroUsize;
+use std::{panic, thread};
+
+use tiff::decoder::ifd::Value;
+use tiff::encoder::compression::{
+    CompressionAlgorithm, Compressor, Deflate, Lzw, Packbits, Uncompressed,
+};
+use tiff::encoder::{DirectoryEncoder, Rational, SRational, TiffEncoder, TiffKind};
+use tiff::tags::{
+    CompressionMethod, PhotometricInterpretation, PlanarConfiguration, SampleFormat, Tag,
+};
+#[cfg(feature = "lzma")]
+use xz2::write::XzEncoder;
+
+use crate::error::{GeoTiffError, GeoTiffResult};
+use crate::gdal_metadata::GDAL_METADATA_TAG;
+use crate::layout::{ArrayOrder, Interleave};
+use crate::profile::Profile;
+use crate::raster_data::{BufferLayout, RasterData};
+use crate::window::Window;
+use crate::GeoTiff;
+
+/// The size in bytes a strip should not exceed by default, unless a single row is larger.
+const STRIP_SIZE: usize = 64 * 1024;
+
+/// The number of pixels the width and height of tiles have to be a multiple of.
+const TILE_SIZE_MULTIPLE: usize = 16;
+
+/// The size in bytes of the raster data above which a BigTIFF is written, leaving room below the
+/// 4 GiB limit of classic TIFF files for the tags following the data.
+const BIGTIFF_THRESHOLD: u64 = u32::MAX as u64 - 16 * 1024 * 1024;
+
+/// The value of the NewSubfileType tag marking a reduced-resolution version of another image.
+const REDUCED_RESOLUTION_IMAGE: u32 = 1;
+
+/// The compression method of LZMA-compressed images, which the encoder does not support.
+#[cfg(feature = "lzma")]
+const LZMA_COMPRESSION: u16 = 34925;
+
+/// The LZMA preset used when compressing, the default of GDAL.
+#[cfg(feature = "lzma")]
+const LZMA_PRESET: u32 = 6;
+
+/// The ZSTD level used when compressing, the default of GDAL.
+#[cfg(feature = "zstd")]
+const ZSTD_LEVEL: i32 = 9;
+
+/// The number of chunks compressed by each worker thread before the compressed chunks are written
+/// to the file, bounding the memory held by compressed chunks waiting to be written.
+const CHUNKS_PER_THREAD: usize = 4;
+
+/// The compression applied to the raster data when writing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Compression {
+    #[default]
+    None,
+    Lzw,
+    Deflate,
+    PackBits,
+    #[cfg(feature = "zstd")]
+    Zstd,
+    #[cfg(feature = "lzma")]
+    Lzma,
+}
+
+impl Compression {
+    /// Returns the compression with the given method, or `None` if it cannot be written.
+    pub(crate) fn from_method(method: CompressionMethod) -> Option<Self> {
+        match method {
+            CompressionMethod::None => Some(Compression::None),
+            CompressionMethod::LZW => Some(Compression::Lzw),
+            CompressionMethod::Deflate | CompressionMethod::OldDeflate => {
+                Some(Compression::Deflate)
+            }
+            CompressionMethod::PackBits => Some(Compression::PackBits),
+            #[cfg(feature = "zstd")]
+            CompressionMethod::ZSTD => Some(Compression::Zstd),
+            #[cfg(feature = "lzma")]
+            CompressionMethod::Unknown(LZMA_COMPRESSION) => Some(Compression::Lzma),
+            _ => None,
+        }
+    }
+
+    fn method(&self) -> CompressionMethod {
+        match self {
+            Compression::None => CompressionMethod::None,
+            Compression::Lzw => CompressionMethod::LZW,
+            Compression::Deflate => CompressionMethod::Deflate,
+            Compression::PackBits => CompressionMethod::PackBits,
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => CompressionMethod::ZSTD,
+            #[cfg(feature = "lzma")]
+            Compression::Lzma => CompressionMethod::Unknown(LZMA_COMPRESSION),
+        }
+    }
+
+    /// Compresses the given bytes, using the given compressor of the encoder if it supports the
+    /// compression.
+    fn compress(&self, compressor: &mut Compressor, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        let mut compressed = Vec::new();
+        match self {
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => zstd::stream::copy_encode(bytes, &mut compressed, ZSTD_LEVEL)?,
+            #[cfg(feature = "lzma")]
+            Compression::Lzma => {
+                let mut encoder = XzEncoder::new(&mut compressed, LZMA_PRESET);
+                encoder.write_all(bytes)?;
+                encoder.finish()?;
+            }
+            _ => {
+                compressor.write_to(&mut compressed, bytes)?;
+            }
+        }
+        Ok(compressed)
+    }
+
+    fn compressor(&self) -> Compressor {
+        match self {
+            Compression::None => Compressor::Uncompressed(Uncompressed),
+            Compression::Lzw => Compressor::Lzw(Lzw),
+            Compression::Deflate => Compressor::Deflate(Deflate::default()),
+            Compression::PackBits => Compressor::Packbits(Packbits),
+            // Compressed by `compress` instead
+            #[cfg(any(feature = "zstd", feature = "lzma"))]
+            _ => Compressor::Uncompressed(Uncompressed),
+        }
+    }
+}
+
+/// The predictor applied to the raster data before compressing it when writing, which makes
+/// smooth data like elevations compress better.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Predictor {
+    #[default]
+    None,
+    /// Horizontal differencing of integer samples.
+    Horizontal,
+    /// Horizontal differencing of the bytes of floating point samples, rearranged by significance.
+    FloatingPoint,
+}
+
+impl Predictor {
+    fn method(&self) -> tiff::tags::Predictor {
+        match self {
+            Predictor::None => tiff::tags::Predictor::None,
+            Predictor::Horizontal => tiff::tags::Predictor::Horizontal,
+            Predictor::FloatingPoint => tiff::tags::Predictor::FloatingPoint,
+        }
+    }
+
+    /// Applies the predictor to the native-endian bytes of a strip or tile with rows of the given
+    /// number of samples, of which the given number form a pixel.
+    fn apply(&self, bytes: &mut [u8], row_len: usize, samples_per_pixel: usize, value_size: usize) {
+        match self {
+            Predictor::None => {}
+            Predictor::Horizontal => {
+                macro_rules! subtract_previous {
+                    ($ty:ty) => {{
+                        const SIZE: usize = std::mem::size_of::<$ty>();
+                        let stride = samples_per_pixel * SIZE;
+                        for row in bytes.chunks_exact_mut(row_len * SIZE) {
+                            for i in (stride..row.len()).step_by(SIZE).rev() {
+                                let value =
+                                    <$ty>::from_ne_bytes(row[i..i + SIZE].try_into().unwrap());
+                                let previous = <$ty>::from_ne_bytes(
+                                    row[i - stride..i - stride + SIZE].try_into().unwrap(),
+                                );
+                                row[i..i + SIZE]
+                                    .copy_from_slice(&value.wrapping_sub(previous).to_ne_bytes());
+                            }
+                        }
+                    }};
+                }
+                match value_size {
+                    1 => subtract_previous!(u8),
+                    2 => subtract_previous!(u16),
+                    4 => subtract_previous!(u32),
+                    _ => subtract_previous!(u64),
+                }
+            }
+            Predictor::FloatingPoint => {
+                // The bytes of each row are arranged from the most to the least significant ones
+                // of all samples, then differenced like horizontal differencing of bytes
+                let mut arranged = vec![0; row_len * value_size];
+                for row in bytes.chunks_exact_mut(row_len * value_size) {
+                    for (i, value) in row.chunks_exact(value_size).enumerate() {
+                        for (significance, &byte) in value.iter().enumerate() {
+                            let significance = match cfg!(target_endian = "little") {
+                                true => value_size - 1 - significance,
+                                false => significance,
+                            };
+                            arranged[significance * row_len + i] = byte;
+                        }
+                    }
+                    for i in (samples_per_pixel..arranged.len()).rev() {
+                        arranged[i] = arranged[i].wrapping_sub(arranged[i - samples_per_pixel]);
+                    }
+                    row.copy_from_slice(&arranged);
+                }
+            }
+        }
+    }
+}
+
+/// The number of rows of each strip, given directly or by the size of the strips.
+#[derive(Debug, Clone, Copy)]
+enum StripSize {
+    Rows(NonZeroUsize),
+    Bytes(NonZeroUsize),
+}
+
+/// Options for writing a GeoTIFF.
+#[derive(Debug, Clone, Default)]
+pub struct WriteOptions {
+    compression: Compression,
+    predictor: Predictor,
+    interleave: Option<Interleave>,
+    strip_size: Option<StripSize>,
+    tile_size: Option<(usize, usize)>,
+    num_threads: Option<NonZeroUsize>,
+    extra_tags: Vec<(Tag, Value)>,
+}
+
+impl WriteOptions {
+    /// Sets the compression applied to the raster data.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Sets the predictor applied to the raster data before compressing it.
+    ///
+    /// Writing fails with [`GeoTiffError::Format`] for the horizontal predictor with floating
+    /// point samples and for the floating point predictor with integer samples.
+    pub fn with_predictor(mut self, predictor: Predictor) -> Self {
+        self.predictor = predictor;
+        self
+    }
+
+    /// Sets the arrangement of the samples in the file, regardless of how they are stored in
+    /// memory, like the `INTERLEAVE` creation option of GDAL.
+    ///
+    /// By default, pixel interleaved data is written with a chunky planar configuration and band
+    /// sequential data with a planar configuration, storing each band in separate strips or
+    /// tiles.
+    pub fn with_interleave(mut self, interleave: Interleave) -> Self {
+        self.interleave = Some(interleave);
+        self
+    }
+
+    /// Sets the number of rows of each strip, except the last one, which holds the remaining
+    /// rows. Strips holding all rows store the raster data in a single strip.
+    ///
+    /// By default, strips hold as many rows as fit into 64 KiB. Ignored when writing tiles.
+    pub fn with_rows_per_strip(mut self, rows_per_strip: NonZeroUsize) -> Self {
+        self.strip_size = Some(StripSize::Rows(rows_per_strip));
+        self
+    }
+
+    /// Sets the size in bytes the uncompressed strips should not exceed, unless a single row is
+    /// larger, see [`Self::with_rows_per_strip`].
+    pub fn with_strip_size(mut self, strip_size: NonZeroUsize) -> Self {
+        self.strip_size = Some(StripSize::Bytes(strip_size));
+        self
+    }
+
+    /// Writes the raster data in tiles of the given width and height, e.g. 256 or 512 pixels,
+    /// instead of strips, as needed for windowed or cloud access.
+    ///
+    /// The width and height have to be positive multiples of 16. Tiles at the right and bottom
+    /// edges are padded with zeros.
+    pub fn with_tile_size(mut self, width: usize, height: usize) -> Self {
+        self.tile_size = Some((width, height));
+        self
+    }
+
+    /// Sets the number of threads compressing strips or tiles in parallel, which defaults to the
+    /// available parallelism.
+    ///
+    /// The written file does not depend on the number of threads.
+    pub fn with_num_threads(mut self, num_threads: NonZeroUsize) -> Self {
+        self.num_threads = Some(num_threads);
+        self
+    }
+
+    /// Sets tags written verbatim with the given values after all others, e.g. to keep vendor
+    /// tags of a file read with [`GeoTiffReader::tags`](crate::GeoTiffReader::tags).
+    ///
+    /// The tags replace the ones written from the GeoTIFF and are only written for the
+    /// full-resolution image. Writing fails with [`GeoTiffError::Format`] for lists of values of
+    /// different types and 64-bit rationals.
+    pub fn with_extra_tags(mut self, extra_tags: Vec<(Tag, Value)>) -> Self {
+        self.extra_tags = extra_tags;
+        self
+    }
+
+    fn num_threads(&self) -> usize {
+        self.num_threads
+            .or_else(|| thread::available_parallelism().ok())
+            .map_or(1, NonZeroUsize::get)
+    }
+}
+
+impl GeoTiff {
+    /// Writes the GeoTIFF uncompressed to the given destination.
+    ///
+    /// The raster data is written in strips, or in tiles if set with
+    /// [`WriteOptions::with_tile_size`], together with the GeoKey directory, the coordinate
+    /// transform, the no data value and the GDAL metadata. Band sequential data is written with a
+    /// planar configuration, unless another arrangement is set with
+    /// [`WriteOptions::with_interleave`].
+    ///
+    /// Overviews built with [`Self::build_overviews`] are written after the full-resolution image,
+    /// with the same options.
+    ///
+    /// The file is written as a BigTIFF if it was read from one or if the raster data is too large
+    /// for the 32-bit offsets of a classic TIFF file.
+    pub fn write<W: Write + Seek>(&self, writer: W) -> GeoTiffResult<()> {
+        self.write_with_options(writer, &WriteOptions::default())
+    }
+
+    /// Writes the GeoTIFF to the given destination with the given options, see [`Self::write`].
+    ///
+    /// Strips or tiles are compressed in parallel by worker threads, while the calling thread
+    /// writes them to the file in order.
+    pub fn write_with_options<W: Write + Seek>(
+        &self,
+        writer: W,
+        options: &WriteOptions,
+    ) -> GeoTiffResult<()> {
+        let (bits_per_sample, _) = self.raster_data.data_type().sample_type();
+        let data_size = [self]
+            .into_iter()
+            .chain(&self.overviews)
+            .map(|image| image.raster_data.len() as u64 * bits_per_sample as u64 / 8)
+            .sum::<u64>();
+        if self.bigtiff || data_size > BIGTIFF_THRESHOLD {
+            self.write_images(&mut TiffEncoder::new_big(writer)?, options)
+        } else {
+            self.write_images(&mut TiffEncoder::new(writer)?, options)
+        }
+    }
+
+    fn write_images<W: Write + Seek, K: TiffKind>(
+        &self,
+        encoder: &mut TiffEncoder<W, K>,
+        options: &WriteOptions,
+    ) -> GeoTiffResult<()> {
+        self.write_image(encoder, options, false)?;
+        for overview in &self.overviews {
+            overview.write_image(encoder, options, true)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the raster data and its tags, omitting the georeference and the metadata for
+    /// overviews, which share them with the full-resolution image.
+    fn write_image<W: Write + Seek, K: TiffKind>(
+        &self,
+        encoder: &mut TiffEncoder<W, K>,
+        options: &WriteOptions,
+        overview: bool,
+    ) -> GeoTiffResult<()> {
+        let mut directory = encoder.image_directory()?;
+
+        // Chunks hold consecutive rows of the interleave written, so column-major data or data
+        // arranged differently has to be rearranged
+        let interleave = options.interleave.unwrap_or(self.interleave);
+        let grid = self.chunk_grid(options, interleave)?;
+        let layout = self.buffer_layout();
+        let rearranged;
+        let raster_data = if self.order == ArrayOrder::RowMajor && self.interleave == interleave {
+            &self.raster_data
+        } else {
+            rearranged = self
+                .raster_data
+                .rearrange(layout, layout.with(interleave, ArrayOrder::RowMajor))?;
+            &rearranged
+        };
+
+        // The window of the raster covered by each chunk of each plane
+        let windows = self.chunk_windows(&grid);
+        let chunks: Vec<_> = (0..grid.num_planes)
+            .flat_map(|plane| windows.iter().map(move |window| (plane, *window)))
+            .collect();
+        let raster_size = (self.raster_width, self.raster_height);
+        let chunk_bytes = |(plane, window): &(usize, Window)| {
+            grid.chunk_bytes(raster_data, raster_size, *plane, window, options.predictor)
+        };
+
+        let mut chunk_offsets = Vec::with_capacity(chunks.len());
+        let mut chunk_byte_counts = Vec::with_capacity(chunks.len());
+        let num_threads = options.num_threads();
+        for batch in chunks.chunks(num_threads * CHUNKS_PER_THREAD) {
+            for chunk in compress_chunks(batch, &chunk_bytes, options.compression, num_threads) {
+                let chunk = chunk?;
+                chunk_offsets.push(K::convert_offset(directory.write_data(&chunk[..])?)?);
+                chunk_byte_counts.push(to_u32(chunk.len() as u64)?);
+            }
+        }
+
+        self.write_tags(
+            directory,
+            options,
+            overview,
+            &grid,
+            &chunk_offsets,
+            &chunk_byte_counts,
+        )
+    }
+
+    /// Writes the raster data of a GeoTIFF with the given profile block by block to the given
+    /// destination with the given options, e.g. [`Profile::write_options`], without holding the
+    /// whole raster in memory.
+    ///
+    /// The given function is called with the window of each strip or tile in row-major order and
+    /// returns its pixel interleaved, row-major raster data, which is compressed and written
+    /// before further blocks are requested. Only as many blocks as are compressed in parallel are
+    /// held in memory at once, so rasters larger than the available memory can be produced, e.g.
+    /// by [`GeoTiffReader::warp`](crate::GeoTiffReader::warp).
+    ///
+    /// The file is written as a BigTIFF if the raster data is too large for the 32-bit offsets of
+    /// a classic TIFF file. Fails with [`GeoTiffError::Format`] if a block does not match the
+    /// type of the profile or the size of its window, and with the first error returned by the
+    /// given function.
+    pub fn write_blocks<W: Write + Seek>(
+        writer: W,
+        profile: &Profile,
+        options: &WriteOptions,
+        mut read_block: impl FnMut(&Window) -> GeoTiffResult<RasterData>,
+    ) -> GeoTiffResult<()> {
+        let header =
+            GeoTiff::from_profile_unchecked(profile, RasterData::zeroed(profile.data_type, 0))?;
+        let (bits_per_sample, _) = profile.data_type.sample_type();
+        let data_size = (profile.width * profile.height * profile.num_samples) as u64
+            * bits_per_sample as u64
+            / 8;
+        if data_size > BIGTIFF_THRESHOLD {
+            header.write_image_blocks(&mut TiffEncoder::new_big(writer)?, options, &mut read_block)
+        } else {
+            header.write_image_blocks(&mut TiffEncoder::new(writer)?, options, &mut read_block)
+        }
+    }
+
+    /// Writes the raster data returned by the given function for the window of each chunk and
+    /// the tags of the GeoTIFF, whose own raster data is ignored.
+    fn write_image_blocks<W: Write + Seek, K: TiffKind>(
+        &self,
+        encoder: &mut TiffEncoder<W, K>,
+        options: &WriteOptions,
+        read_block: &mut dyn FnMut(&Window) -> GeoTiffResult<RasterData>,
+    ) -> GeoTiffResult<()> {
+        let mut directory = encoder.image_directory()?;
+
+        let interleave = options.interleave.unwrap_or(Interleave::Pixel);
+        let grid = self.chunk_grid(options, interleave)?;
+        let data_type = self.raster_data.data_type();
+
+        // The chunks of all planes covering a window are written together, so each block is only
+        // requested once, while the offsets are stored plane by plane
+        let windows = self.chunk_windows(&grid);
+        let mut chunk_offsets = vec![0; windows.len() * grid.num_planes];
+        let mut chunk_byte_counts = vec![0; windows.len() * grid.num_planes];
+        let num_threads = options.num_threads();
+        let batch_len = (num_threads * CHUNKS_PER_THREAD).div_ceil(grid.num_planes);
+        for (batch_index, batch) in windows.chunks(batch_len).enumerate() {
+            let blocks = batch
+                .iter()
+                .map(|window| {
+                    let block = read_block(window)?;
+                    if block.data_type() != data_type {
+                        return Err(GeoTiffError::Format(format!(
+                            "The block of type {:?} does not match the profile of type {data_type:?}",
+                            block.data_type()
+                        )));
+                    }
+                    let len = window.width * window.height * self.num_samples;
+                    if block.len() != len {
+                        return Err(GeoTiffError::Format(format!(
+                            "The block has {} values but its window {len}",
+                            block.len()
+                        )));
+                    }
+                    if interleave == Interleave::Pixel {
+                        return Ok(block);
+                    }
+                    let layout = BufferLayout {
+                        width: window.width,
+                        height: window.height,
+                        num_samples: self.num_samples,
+                        interleave: Interleave::Pixel,
+                        order: ArrayOrder::RowMajor,
+                    };
+                    block.rearrange(layout, layout.with(interleave, ArrayOrder::RowMajor))
+                })
+                .collect::<GeoTiffResult<Vec<_>>>()?;
+
+            let chunks: Vec<_> = (0..batch.len())
+                .flat_map(|block| (0..grid.num_planes).map(move |plane| (plane, block)))
+                .collect();
+            let chunk_bytes = |&(plane, block): &(usize, usize)| {
+                let window = &batch[block];
+                grid.chunk_bytes(
+                    &blocks[block],
+                    (window.width, window.height),
+                    plane,
+                    &Window::new(0, 0, window.width, window.height),
+                    options.predictor,
+                )
+            };
+            let compressed =
+                compress_chunks(&chunks, &chunk_bytes, options.compression, num_threads);
+            for (&(plane, block), chunk) in chunks.iter().zip(compressed) {
+                let chunk = chunk?;
+                let index = plane * windows.len() + batch_index * batch_len + block;
+                chunk_offsets[index] = directory.write_data(&chunk[..])?;
+                chunk_byte_counts[index] = to_u32(chunk.len() as u64)?;
+            }
+        }
+
+        let chunk_offsets = chunk_offsets
+            .into_iter()
+            .map(K::convert_offset)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.write_tags(
+            directory,
+            options,
+            false,
+            &grid,
+            &chunk_offsets,
+            &chunk_byte_counts,
+        )
+    }
+
+    /// Returns the arrangement of the strips or tiles written with the given options and
+    /// interleave, failing if the tile size or the predictor are invalid.
+    fn chunk_grid(
+        &self,
+        options: &WriteOptions,
+        interleave: Interleave,
+    ) -> GeoTiffResult<ChunkGrid> {
+        if let Some((tile_width, tile_height)) = options.tile_size {
+            if tile_width == 0
+                || tile_height == 0
+                || tile_width % TILE_SIZE_MULTIPLE != 0
+                || tile_height % TILE_SIZE_MULTIPLE != 0
+            {
+                return Err(GeoTiffError::Format(format!(
+                    "Tile size {tile_width}x{tile_height} is not a positive multiple of {TILE_SIZE_MULTIPLE}"
+                )));
+            }
+        }
+
+        let (bits_per_sample, sample_format) = self.raster_data.data_type().sample_type();
+        let is_float = sample_format == SampleFormat::IEEEFP;
+        // Horizontal differencing applies to integers of up to 64 bits
+        if matches!(
+            (options.predictor, is_float, bits_per_sample),
+            (Predictor::Horizontal, true, _)
+                | (Predictor::Horizontal, _, 65..)
+                | (Predictor::FloatingPoint, false, _)
+        ) {
+            return Err(GeoTiffError::Format(format!(
+                "Predictor {:?} does not apply to samples of type {:?}",
+                options.predictor,
+                self.raster_data.data_type()
+            )));
+        }
+        // Band sequential data is written as one plane per sample, each split into strips or tiles
+        let (planar_configuration, num_planes) = match interleave {
+            Interleave::Pixel => (PlanarConfiguration::Chunky, 1),
+            Interleave::Band => (PlanarConfiguration::Planar, self.num_samples),
+        };
+        let samples_per_plane = self.num_samples / num_planes;
+        let value_size = bits_per_sample as usize / 8;
+        let row_size = self.raster_width * samples_per_plane * value_size;
+        let rows_per_strip = match options.strip_size {
+            Some(StripSize::Rows(rows_per_strip)) => rows_per_strip.get(),
+            Some(StripSize::Bytes(strip_size)) => strip_size.get() / row_size.max(1),
+            None => STRIP_SIZE / row_size.max(1),
+        }
+        .clamp(1, self.raster_height.max(1));
+        let (chunk_width, chunk_height) = options
+            .tile_size
+            .unwrap_or((self.raster_width, rows_per_strip));
+
+        Ok(ChunkGrid {
+            planar_configuration,
+            num_planes,
+            samples_per_plane,
+            value_size,
+            rows_per_strip,
+            chunk_width,
+            chunk_height,
+            tiled: options.tile_size.is_some(),
+        })
+    }
+
+    /// Returns the windows of the raster covered by the chunks of each plane in row-major order.
+    fn chunk_windows(&self, grid: &ChunkGrid) -> Vec<Window> {
+        (0..self.raster_height)
+            .step_by(grid.chunk_height)
+            .flat_map(|y| {
+                (0..self.raster_width.max(1))
+                    .step_by(grid.chunk_width.max(1))
+                    .map(move |x| {
+                        Window::new(
+                            x,
+                            y,
+                            grid.chunk_width.min(self.raster_width - x),
+                            grid.chunk_height.min(self.raster_height - y),
+                        )
+                    })
+            })
+            .collect()
+    }
+
+    /// Writes the tags of the raster data written to the chunks at the given offsets, omitting the
+    /// georeference and the metadata for overviews.
+    fn write_tags<W: Write + Seek, K: TiffKind>(
+        &self,
+        mut directory: DirectoryEncoder<'_, W, K>,
+        options: &WriteOptions,
+        overview: bool,
+        grid: &ChunkGrid,
+        chunk_offsets: &[K::OffsetType],
+        chunk_byte_counts: &[u32],
+    ) -> GeoTiffResult<()> {
+        let (bits_per_sample, sample_format) = self.raster_data.data_type().sample_type();
+        if overview {
+            directory.write_tag(Tag::NewSubfileType, REDUCED_RESOLUTION_IMAGE)?;
+        }
+        directory.write_tag(Tag::ImageWidth, to_u32(self.raster_width as u64)?)?;
+        directory.write_tag(Tag::ImageLength, to_u32(self.raster_height as u64)?)?;
+        directory.write_tag(
+            Tag::BitsPerSample,
+            &vec![bits_per_sample; self.num_samples][..],
+        )?;
+        directory.write_tag(Tag::Compression, options.compression.method().to_u16())?;
+        if options.predictor != Predictor::None {
+            directory.write_tag(Tag::Predictor, options.predictor.method().to_u16())?;
+        }
+
+        let photometric_interpretation = self.photometric_interpretation();
+        directory.write_tag(
+            Tag::PhotometricInterpretation,
+            photometric_interpretation.to_u16(),
+        )?;
+        let num_color_samples = match photometric_interpretation {
+            PhotometricInterpretation::RGB | PhotometricInterpretation::CIELab => 3,
+            PhotometricInterpretation::CMYK => 4,
+            _ => 1,
+        };
+        // Extra samples keep their roles, so only samples read as alpha are written as alpha
+        let extra_samples: Vec<_> = (num_color_samples..self.num_samples)
+            .map(|sample| {
+                self.sample_roles
+                    .get(sample)
+                    .copied()
+                    .unwrap_or_default()
+                    .to_extra_sample()
+            })
+            .collect();
+        if !extra_samples.is_empty() {
+            directory.write_tag(Tag::ExtraSamples, &extra_samples[..])?;
+        }
+
+        directory.write_tag(Tag::SamplesPerPixel, self.num_samples as u16)?;
+        if grid.tiled {
+            directory.write_tag(Tag::TileWidth, to_u32(grid.chunk_width as u64)?)?;
+            directory.write_tag(Tag::TileLength, to_u32(grid.chunk_height as u64)?)?;
+            directory.write_tag(Tag::TileOffsets, K::convert_slice(chunk_offsets))?;
+            directory.write_tag(Tag::TileByteCounts, chunk_byte_counts)?;
+        } else {
+            directory.write_tag(Tag::StripOffsets, K::convert_slice(chunk_offsets))?;
+            directory.write_tag(Tag::RowsPerStrip, grid.rows_per_strip as u32)?;
+            directory.write_tag(Tag::StripByteCounts, chunk_byte_counts)?;
+        }
+        directory.write_tag(Tag::PlanarConfiguration, grid.planar_configuration.to_u16())?;
+        directory.write_tag(
+            Tag::SampleFormat,
+            &vec![sample_format.to_u16(); self.num_samples][..],
+        )?;
+
+        if let Some(no_data) = self.no_data {
+            let no_data = if no_data.is_nan() {
+                "nan".to_string()
+            } else {
+                no_data.to_string()
+            };
+            directory.write_tag(Tag::GdalNodata, &no_data[..])?;
+        }
+
+        if overview {
+            return Ok(directory.finish()?);
+        }
+
+        let (directory_data, double_params_data, ascii_params_data) =
+            self.geo_key_directory.to_tag_data()?;
+        directory.write_tag(Tag::GeoKeyDirectoryTag, &directory_data[..])?;
+        if !double_params_data.is_empty() {
+            directory.write_tag(Tag::GeoDoubleParamsTag, &double_params_data[..])?;
+        }
+        if !ascii_params_data.is_empty() {
+            directory.write_tag(Tag::GeoAsciiParamsTag, &ascii_params_data[..])?;
+        }
+
+        if let Some(coordinate_transform) = &self.coordinate_transform {
+            let tag_data = coordinate_transform.to_tag_data();
+            if let Some(pixel_scale) = tag_data.pixel_scale {
+                directory.write_tag(Tag::ModelPixelScaleTag, &pixel_scale[..])?;
+            }
+            if let Some(tie_points) = tag_data.tie_points {
+                directory.write_tag(Tag::ModelTiepointTag, &tie_points[..])?;
+            }
+            if let Some(model_transformation) = tag_data.model_transformation {
+                directory.write_tag(Tag::ModelTransformationTag, &model_transformation[..])?;
+            }
+        } else if !self.gcps.is_empty() {
+            let tie_points: Vec<f64> = self
+                .gcps
+                .iter()
+                .flat_map(|gcp| [gcp.pixel.x, gcp.pixel.y, 0.0, gcp.model.x, gcp.model.y, 0.0])
+                .collect();
+            directory.write_tag(Tag::ModelTiepointTag, &tie_points[..])?;
+        }
+
+        if let Some(gdal_metadata) = &self.gdal_metadata {
+            directory.write_tag(Tag::Unknown(GDAL_METADATA_TAG), &gdal_metadata.to_xml()[..])?;
+        }
+        for (tag, value) in self.descriptive_tags.tags() {
+            directory.write_tag(tag, value)?;
+        }
+        if !overview {
+            for (tag, value) in &options.extra_tags {
+                write_value(&mut directory, *tag, value)?;
+            }
+        }
+
+        Ok(directory.finish()?)
+    }
+}
+
+/// The arrangement of the raster data of an image in strips or tiles.
+struct ChunkGrid {
+    planar_configuration: PlanarConfiguration,
+    num_planes: usize,
+    samples_per_plane: usize,
+    value_size: usize,
+    rows_per_strip: usize,
+    chunk_width: usize,
+    chunk_height: usize,
+    tiled: bool,
+}
+
+impl ChunkGrid {
+    /// Returns the bytes of the chunk of the given plane covering the given window of the given
+    /// row-major raster data of the given width and height, with the predictor applied.
+    ///
+    /// Tiles are padded to their full size, while the last strip only holds the remaining rows.
+    fn chunk_bytes(
+        &self,
+        raster_data: &RasterData,
+        (width, height): (usize, usize),
+        plane: usize,
+        window: &Window,
+        predictor: Predictor,
+    ) -> Vec<u8> {
+        let row_len = width * self.samples_per_plane;
+        let start =
+            |row: usize| (plane * height + row) * row_len + window.x * self.samples_per_plane;
+        let chunk_size =
+            self.chunk_width * self.chunk_height * self.samples_per_plane * self.value_size;
+        let mut bytes =
+            if window.width == width && !self.tiled {
+                raster_data.to_ne_bytes(start(window.y)..start(window.y + window.height))
+            } else {
+                let mut bytes = Vec::with_capacity(chunk_size);
+                for row in window.y..window.y + window.height {
+                    bytes.extend(raster_data.to_ne_bytes(
+                        start(row)..start(row) + window.width * self.samples_per_plane,
+                    ));
+                    bytes.resize(
+                        bytes.len()
+                            + (self.chunk_width - window.width)
+                                * self.samples_per_plane
+                                * self.value_size,
+                        0,
+                    );
+                }
+                bytes.resize(chunk_size, 0);
+                bytes
+            };
+        predictor.apply(
+            &mut bytes,
+            self.chunk_width * self.samples_per_plane,
+            self.samples_per_plane,
+            self.value_size,
+        );
+        bytes
+    }
+}
+
+/// Compresses the bytes of the given chunks, distributing them among the given number of threads.
+///
+/// The compressed chunks are returned in the order of the given ones, so the output does not
+/// depend on the number of threads.
+fn compress_chunks<C: Sync>(
+    chunks: &[C],
+    chunk_bytes: &(impl Fn(&C) -> Vec<u8> + Sync),
+    compression: Compression,
+    num_threads: usize,
+) -> Vec<io::Result<Vec<u8>>> {
+    let compress = |chunks: &[C]| -> Vec<io::Result<Vec<u8>>> {
+        let mut compressor = compression.compressor();
+        chunks
+            .iter()
+            .map(|chunk| compression.compress(&mut compressor, &chunk_bytes(chunk)))
+            .collect()
+    };
+
+    if num_threads <= 1 || chunks.len() <= 1 {
+        return compress(chunks);
+    }
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = chunks
+            .chunks(chunks.len().div_ceil(num_threads))
+            .map(|chunks| scope.spawn(move || compress(chunks)))
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|payload| panic::resume_unwind(payload))
+            })
+            .collect()
+    })
+}
+
+/// Writes the given tag with the given value decoded from a TIFF file, keeping its type.
+fn write_value<W: Write + Seek, K: TiffKind>(
+    directory: &mut DirectoryEncoder<'_, W, K>,
+    tag: Tag,
+    value: &Value,
+) -> GeoTiffResult<()> {
+    macro_rules! write {
+        ($($variant: ident),*) => {
+            match value {
+                $(Value::$variant(value) => directory.write_tag(tag, *value)?,)*
+                Value::Rational(n, d) => directory.write_tag(tag, Rational { n: *n, d: *d })?,
+                Value::SRational(n, d) => directory.write_tag(tag, SRational { n: *n, d: *d })?,
+                Value::Ascii(value) => directory.write_tag(tag, &value[..])?,
+                Value::Ifd(value) => directory.write_tag(tag, *value)?,
+                Value::IfdBig(value) => directory.write_tag(tag, *value)?,
+                Value::List(values) => match values.first() {
+                    $(Some(Value::$variant(_)) => {
+                        let values = values
+                            .iter()
+                            .map(|value| match value {
+                                Value::$variant(value) => Some(*value),
+                                _ => None,
+                            })
+                            .collect::<Option<Vec<_>>>()
+                            .ok_or_else(|| mixed_types(tag))?;
+                        directory.write_tag(tag, &values[..])?
+                    })*
+                    Some(Value::Rational(..)) => {
+                        let values = values
+                            .iter()
+                            .map(|value| match value {
+                                Value::Rational(n, d) => Some(Rational { n: *n, d: *d }),
+                                _ => None,
+                            })
+                            .collect::<Option<Vec<_>>>()
+                            .ok_or_else(|| mixed_types(tag))?;
+                        directory.write_tag(tag, &values[..])?
+                    }
+                    _ => return Err(unsupported_value(tag)),
+                },
+                _ => return Err(unsupported_value(tag)),
+            }
+        };
+    }
+
+    write! {
+        Byte,
+        Short,
+        SignedByte,
+        SignedShort,
+        Signed,
+        SignedBig,
+        Unsigned,
+        UnsignedBig,
+        Float,
+        Double
+    }
+    Ok(())
+}
+
+fn mixed_types(tag: Tag) -> GeoTiffError {
+    GeoTiffError::Format(format!(
+        "The values of the tag {tag:?} have different types"
+    ))
+}
+
+fn unsupported_value(tag: Tag) -> GeoTiffError {
+    GeoTiffError::Format(format!("The value of the tag {tag:?} cannot be written"))
+}
+
+fn to_u32(value: u64) -> GeoTiffResult<u32> {
+    u32::try_from(value).map_err(|_| {
+        GeoTiffError::Format("The image is too large to be written as a TIFF file".into())
+    })
+}
@@ -0,0 +1,153 @@
+use std::io::{Read, Seek};
+
+use geo_types::Polygon;
+
+use crate::clip::{bounding_rect, polygon_contains};
+use crate::error::GeoTiffResult;
+use crate::raster_space::RasterSpace;
+use crate::reader::GeoTiffReader;
+use crate::window::Window;
+use crate::GeoTiff;
+
+/// Statistics of the known values of a sample within a polygon, see
+/// [`GeoTiffReader::zonal_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ZonalStatistics {
+    /// The number of pixels within the polygon holding a known value.
+    pub count: u64,
+    pub sum: f64,
+    pub minimum: Option<f64>,
+    pub maximum: Option<f64>,
+    pub mean: Option<f64>,
+}
+
+impl ZonalStatistics {
+    fn add(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        self.minimum = Some(self.minimum.map_or(value, |minimum| minimum.min(value)));
+        self.maximum = Some(self.maximum.map_or(value, |maximum| maximum.max(value)));
+        self.mean = Some(self.sum / self.count as f64);
+    }
+}
+
+impl<R: Read + Seek> GeoTiffReader<R> {
+    /// Computes statistics of the specified sample within each of the given polygons in model
+    /// space, e.g. the mean elevation of administrative regions, in the order of the polygons.
+    ///
+    /// Pixels belong to a polygon if their centers lie within it and outside of its holes.
+    /// Pixels holding the no data value or NaN and pixels marked invalid by the transparency mask
+    /// are skipped. The strips or tiles intersecting the bounding rectangles of the polygons are
+    /// decoded one at a time, so that the raster is never loaded into memory as a whole.
+    ///
+    /// Fails with [`GeoTiffError::SampleOutOfBounds`] if the image does not have the sample.
+    ///
+    /// [`GeoTiffError::SampleOutOfBounds`]: crate::GeoTiffError::SampleOutOfBounds
+    pub fn zonal_stats(
+        &mut self,
+        polygons: &[Polygon],
+        sample: usize,
+    ) -> GeoTiffResult<Vec<ZonalStatistics>> {
+        self.sample_in_bounds(sample)?;
+
+        let windows = polygon_windows(self, polygons);
+        let mut statistics = vec![ZonalStatistics::default(); polygons.len()];
+        let layout = self.chunk_layout();
+        for chunk_y in 0..layout.chunks_down {
+            for chunk_x in 0..layout.chunks_across {
+                let (x, y) = (chunk_x * layout.chunk_width, chunk_y * layout.chunk_height);
+                let chunk = Window::new(
+                    x,
+                    y,
+                    layout.chunk_width.min(self.raster_width - x),
+                    layout.chunk_height.min(self.raster_height - y),
+                );
+                if windows
+                    .iter()
+                    .flatten()
+                    .all(|window| window.intersection(&chunk).is_none())
+                {
+                    continue;
+                }
+
+                let region = self.read_region(&chunk)?;
+                region.accumulate(self, &chunk, polygons, &windows, sample, &mut statistics);
+            }
+        }
+
+        Ok(statistics)
+    }
+}
+
+impl GeoTiff {
+    /// Computes statistics of the specified sample within each of the given polygons in model
+    /// space, in the order of the polygons, like [`GeoTiffReader::zonal_stats`].
+    ///
+    /// Fails with [`GeoTiffError::SampleOutOfBounds`] if the image does not have the sample.
+    ///
+    /// [`GeoTiffError::SampleOutOfBounds`]: crate::GeoTiffError::SampleOutOfBounds
+    pub fn zonal_stats(
+        &self,
+        polygons: &[Polygon],
+        sample: usize,
+    ) -> GeoTiffResult<Vec<ZonalStatistics>> {
+        self.sample_in_bounds(sample)?;
+
+        let windows = polygon_windows(self, polygons);
+        let mut statistics = vec![ZonalStatistics::default(); polygons.len()];
+        self.accumulate(
+            self,
+            &Window::new(0, 0, self.raster_width, self.raster_height),
+            polygons,
+            &windows,
+            sample,
+            &mut statistics,
+        );
+
+        Ok(statistics)
+    }
+
+    /// Adds the known values of the specified sample within each polygon to its statistics,
+    /// where this raster covers the given window of the raster the polygon windows refer to.
+    fn accumulate(
+        &self,
+        raster: &impl RasterSpace,
+        region: &Window,
+        polygons: &[Polygon],
+        windows: &[Option<Window>],
+        sample: usize,
+        statistics: &mut [ZonalStatistics],
+    ) {
+        for ((polygon, window), statistics) in polygons.iter().zip(windows).zip(statistics) {
+            let Some(window) = window.and_then(|window| window.intersection(region)) else {
+                continue;
+            };
+
+            // Pixel centers are located in the raster, since the region lacks a transform if the
+            // raster has none
+            for row in window.y..window.y + window.height {
+                for col in window.x..window.x + window.width {
+                    if !polygon_contains(polygon, &raster.pixel_center(col, row)) {
+                        continue;
+                    }
+                    let (region_col, region_row) = (col - region.x, row - region.y);
+                    if let Some(value) =
+                        self.known_value(region_col as isize, region_row as isize, sample)
+                    {
+                        statistics.add(value);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns the windows of the given raster covering the bounding rectangles of the polygons.
+fn polygon_windows(raster: &impl RasterSpace, polygons: &[Polygon]) -> Vec<Option<Window>> {
+    polygons
+        .iter()
+        .map(|polygon| {
+            bounding_rect(polygon).and_then(|rect| raster.compute_intersection_window(&rect))
+        })
+        .collect()
+}
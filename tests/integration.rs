@@ -1,6 +1,30 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+use std::fs::File;
+use std::io::Cursor;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::Duration;
+
 use common::read_geotiff;
-use geo_types::{Coord, Rect};
-use geotiff::{GeoKeyDirectory, RasterType};
+use geo_types::{Coord, LineString, MultiLineString, Polygon, Rect};
+use geotiff::{
+    probe_support, ArrayOrder, BandFormat, BandStatistics, Citations, Compression, ContourLevels,
+    CoordinateTransformationMethod, CorruptChunk, DatasetPool, DescriptiveTags, Gcp,
+    GeoKeyDirectory, GeoKeyDirectoryBuilder, GeoKeyValue, GeoTiff, GeoTiffError, GeoTiffReader,
+    GeoTiffResult, GeoreferenceIssue, Interleave, Interpolation, MapTile, Overview, Predictor,
+    Profile, RasterData, RasterDataType, RasterSource, RasterType, ReadOptions, ReferenceLocation,
+    Resampling, SampleRole, SlopeUnit, StorageSize, TerrainEncoding, TiffFeature, Unsupported,
+    Window, WriteOptions, ZonalStatistics,
+};
+use num_complex::Complex;
+use tiff::decoder::ifd::Value;
+use tiff::decoder::{Decoder, Limits};
+use tiff::encoder::compression::{CompressionAlgorithm, Lzw};
+use tiff::encoder::{colortype, Rational, TiffEncoder};
+use tiff::tags::{
+    CompressionMethod, PhotometricInterpretation, PlanarConfiguration, SampleFormat, Tag, Type,
+};
+use tiff::TiffError;
 
 mod common;
 
@@ -129,6 +153,10 @@ fn test_load_merc() {
             ..Default::default()
         }
     );
+    assert_eq!(
+        geotiff.geo_key_directory.coordinate_transformation_method(),
+        Some(CoordinateTransformationMethod::Mercator)
+    );
 
     assert_eq!(
         geotiff.model_extent(),
@@ -144,3 +172,5407 @@ fn test_load_merc() {
         )
     );
 }
+
+#[test]
+fn test_align_window_to_blocks() {
+    let geotiff = read_geotiff("resources/zh_dem_25.tif");
+
+    // The raster is organized in strips of 10 rows spanning the full width
+    assert_eq!(geotiff.chunk_dimensions(), (399, 10));
+    assert_eq!(
+        geotiff.align_window_to_blocks(&Window::new(5, 13, 10, 4)),
+        Window::new(0, 10, 399, 10)
+    );
+    assert_eq!(
+        geotiff.align_window_to_blocks(&Window::new(0, 19, 1, 2)),
+        Window::new(0, 10, 399, 20)
+    );
+    assert_eq!(
+        geotiff.align_window_to_blocks(&Window::new(100, 355, 500, 500)),
+        Window::new(0, 350, 399, 16)
+    );
+    assert!(geotiff
+        .align_window_to_blocks(&Window::new(400, 0, 10, 10))
+        .is_empty());
+
+    let file = File::open("resources/zh_dem_25.tif").expect("File I/O error");
+    let reader = GeoTiffReader::open(file).expect("File I/O error");
+    assert!(!reader.is_tiled());
+    assert_eq!(reader.chunk_dimensions(), (399, 10));
+    assert_eq!(reader.chunk_count(), 37);
+}
+
+#[test]
+fn test_read_region() {
+    let geotiff = read_geotiff("resources/zh_dem_25.tif");
+    let window = Window::new(120, 15, 30, 20);
+    let region = GeoTiff::read_region(
+        File::open("resources/zh_dem_25.tif").expect("File I/O error"),
+        &window,
+    )
+    .expect("File I/O error");
+
+    assert_eq!(region.raster_width, 30);
+    assert_eq!(region.raster_height, 20);
+    assert_eq!(
+        region.model_extent(),
+        Rect::new(
+            Coord {
+                x: 680562.5,
+                y: 252137.5
+            },
+            Coord {
+                x: 681312.5,
+                y: 252637.5
+            }
+        )
+    );
+
+    // Values inside the window match the full raster, values outside are not available
+    for coord in [
+        Coord {
+            x: 680575.0,
+            y: 252625.0,
+        },
+        Coord {
+            x: 681000.0,
+            y: 252400.0,
+        },
+        Coord {
+            x: 681300.0,
+            y: 252150.0,
+        },
+    ] {
+        assert_eq!(
+            region.get_value_at::<i16>(&coord, 0),
+            geotiff.get_value_at::<i16>(&coord, 0)
+        );
+    }
+    assert_eq!(
+        region.get_value_at::<i16>(
+            &Coord {
+                x: 677575.0,
+                y: 253000.0
+            },
+            0
+        ),
+        None
+    );
+
+    let region = GeoTiff::read_region(
+        File::open("resources/marbles.tif").expect("File I/O error"),
+        &Window::new(750, 590, 20, 20),
+    )
+    .expect("File I/O error");
+    assert_eq!(region.num_samples, 3);
+    assert_eq!(
+        region.get_value_at::<u8>(&Coord { x: 11.0, y: 9.0 }, 0),
+        Some(147)
+    );
+    assert_eq!(
+        region.get_value_at::<u8>(&Coord { x: 11.0, y: 9.0 }, 2),
+        Some(165)
+    );
+}
+
+#[test]
+fn test_raster_source() {
+    // Summarizes a window of any raster source
+    fn summarize(source: &mut impl RasterSource, window: &Window) -> (Rect, Vec<i16>) {
+        assert_eq!(source.dimensions(), (399, 366));
+        assert_eq!(source.num_samples(), 1);
+        assert_eq!(source.data_type(), RasterDataType::I16);
+        assert_eq!(
+            source.geotransform(),
+            Some([677562.5, 25.0, 0.0, 253012.5, 0.0, -25.0])
+        );
+        assert_eq!(source.no_data(), Some(-9999.0));
+        assert!(matches!(
+            source.read_window(&Window::new(390, 0, 10, 10)),
+            Err(GeoTiffError::Format(_))
+        ));
+
+        let region = source.read_window(window).expect("File I/O error");
+        (region.model_extent(), region.values().unwrap())
+    }
+
+    let window = Window::new(120, 15, 30, 20);
+    let mut geotiff = read_geotiff("resources/zh_dem_25.tif");
+    let file = File::open("resources/zh_dem_25.tif").expect("File I/O error");
+    let mut reader = GeoTiffReader::open(file).expect("File I/O error");
+    let (extent, values) = summarize(&mut geotiff, &window);
+    assert_eq!(values.len(), 30 * 20);
+    assert_eq!(summarize(&mut reader, &window), (extent, values));
+}
+
+#[test]
+fn test_read_map_tile() {
+    let open = |epsg: u16, geotransform: [f64; 6], width: usize, height: usize| {
+        let profile = Profile {
+            geotransform: Some(geotransform),
+            geo_key_directory: GeoKeyDirectoryBuilder::from_epsg(epsg).unwrap().build(),
+            no_data: Some(255.0),
+            ..Profile::new(width, height, 1, RasterDataType::U8)
+        };
+        let values = (0..width * height).map(|value| value as u8).collect();
+        let geotiff = GeoTiff::from_profile(&profile, RasterData::U8(values)).unwrap();
+        let mut data = Cursor::new(Vec::new());
+        geotiff.write(&mut data).unwrap();
+        data.set_position(0);
+        GeoTiff::open(data).expect("File I/O error")
+    };
+
+    // A 4x4 raster in web mercator covering the map tile 1/0/0
+    let half_extent = std::f64::consts::PI * 6378137.0;
+    let quarter = half_extent / 4.0;
+    let mut reader = open(
+        3857,
+        [-half_extent, quarter, 0.0, half_extent, 0.0, -quarter],
+        4,
+        4,
+    );
+    assert_eq!(
+        MapTile::new(1, 0, 0).bounds(),
+        Rect::new(
+            Coord {
+                x: -half_extent,
+                y: 0.0
+            },
+            Coord {
+                x: 0.0,
+                y: half_extent
+            }
+        )
+    );
+
+    let tile = reader
+        .read_map_tile(&MapTile::new(1, 0, 0), 4, Interpolation::Nearest)
+        .unwrap()
+        .unwrap();
+    assert_eq!(tile.dimensions(), (4, 4));
+    assert_eq!(tile.values::<u8>().unwrap(), (0..16).collect::<Vec<_>>());
+    assert_eq!(tile.mask(), None);
+    assert_eq!(tile.geo_key_directory.projected_type, Some(3857));
+    assert_eq!(tile.geotransform().unwrap()[1], quarter);
+
+    let tile = reader
+        .read_map_tile(&MapTile::new(2, 1, 1), 2, Interpolation::Nearest)
+        .unwrap()
+        .unwrap();
+    assert_eq!(tile.values::<u8>().unwrap(), [10, 11, 14, 15]);
+
+    // Tiles partially covering the raster are padded with no data
+    let tile = reader
+        .read_map_tile(&MapTile::new(0, 0, 0), 4, Interpolation::Nearest)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        tile.values::<u8>().unwrap(),
+        [5, 7, 255, 255, 13, 15, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255]
+    );
+    assert_eq!(
+        tile.mask().unwrap().iter().filter(|&&valid| valid).count(),
+        4
+    );
+
+    assert!(reader
+        .read_map_tile(&MapTile::new(1, 1, 0), 4, Interpolation::Nearest)
+        .unwrap()
+        .is_none());
+    for (tile, tile_size) in [(MapTile::new(1, 2, 0), 4), (MapTile::new(1, 0, 0), 0)] {
+        assert!(matches!(
+            reader.read_map_tile(&tile, tile_size, Interpolation::Nearest),
+            Err(GeoTiffError::Format(_))
+        ));
+    }
+
+    // A 2x2 raster in WGS 84 covering the world
+    let mut reader = open(4326, [-180.0, 180.0, 0.0, 90.0, 0.0, -90.0], 2, 2);
+    let tile = reader
+        .read_map_tile(&MapTile::new(0, 0, 0), 2, Interpolation::Nearest)
+        .unwrap()
+        .unwrap();
+    assert_eq!(tile.values::<u8>().unwrap(), [0, 1, 2, 3]);
+}
+
+#[test]
+fn test_geotransform() {
+    let geotiff = read_geotiff("resources/zh_dem_25.tif");
+    assert_eq!(
+        geotiff.geotransform(),
+        Some([677562.5, 25.0, 0.0, 253012.5, 0.0, -25.0])
+    );
+    assert_eq!(
+        geotiff.origin(),
+        Some(Coord {
+            x: 677562.5,
+            y: 253012.5
+        })
+    );
+    assert_eq!(geotiff.pixel_size(), Some((25.0, -25.0)));
+    assert_eq!(geotiff.resolution(), Some(Coord { x: 25.0, y: 25.0 }));
+
+    // The origin of a region is its top left corner
+    let mut reader = GeoTiffReader::open(File::open("resources/zh_dem_25.tif").unwrap()).unwrap();
+    assert_eq!(reader.geotransform(), geotiff.geotransform());
+    assert_eq!(reader.resolution(), geotiff.resolution());
+    let region = reader.read_region(&Window::new(10, 20, 50, 50)).unwrap();
+    assert_eq!(
+        region.geotransform(),
+        Some([677812.5, 25.0, 0.0, 252512.5, 0.0, -25.0])
+    );
+
+    // The coefficients refer to the corners of the pixels regardless of the raster type
+    for path in [
+        "resources/austrian_capitals_model_tie_point_and_pixel_scale_pixel_is_area.tif",
+        "resources/austrian_capitals_model_transformation_pixel_is_area.tif",
+    ] {
+        assert_eq!(
+            read_geotiff(path).geotransform(),
+            Some([4302000.0, 1000.0, 0.0, 2811000.0, 0.0, -1000.0])
+        );
+    }
+    for path in [
+        "resources/austrian_capitals_model_tie_point_and_pixel_scale_pixel_is_point.tif",
+        "resources/austrian_capitals_model_transformation_pixel_is_point.tif",
+    ] {
+        assert_eq!(
+            read_geotiff(path).geotransform(),
+            Some([4301500.0, 1000.0, 0.0, 2811500.0, 0.0, -1000.0])
+        );
+    }
+
+    // Multiple tie points do not define an affine transform
+    let geotiff = read_geotiff("resources/austrian_capitals_model_tie_points_pixel_is_area.tif");
+    assert_eq!(geotiff.geotransform(), None);
+    assert_eq!(geotiff.origin(), None);
+    assert_eq!(geotiff.pixel_size(), None);
+    assert_eq!(geotiff.resolution(), None);
+}
+
+#[test]
+fn test_footprint() {
+    let geotiff = read_geotiff("resources/zh_dem_25.tif");
+    assert_eq!(
+        geotiff.footprint().exterior(),
+        &LineString::from(vec![
+            (677562.5, 253012.5),
+            (687537.5, 253012.5),
+            (687537.5, 243862.5),
+            (677562.5, 243862.5),
+            (677562.5, 253012.5),
+        ])
+    );
+
+    // A raster rotated by 45 degrees has a diamond-shaped footprint
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut image = encoder.new_image::<colortype::Gray8>(2, 1).unwrap();
+    let mut rotated = [0.0; 16];
+    rotated[..8].copy_from_slice(&[1.0, 1.0, 0.0, 100.0, -1.0, 1.0, 0.0, 200.0]);
+    rotated[15] = 1.0;
+    image
+        .encoder()
+        .write_tag(Tag::ModelTransformationTag, &rotated[..])
+        .unwrap();
+    image.write_data(&[0, 0]).unwrap();
+    data.set_position(0);
+    let geotiff = GeoTiff::read(data).unwrap();
+
+    assert_eq!(
+        geotiff.footprint(),
+        Polygon::new(
+            LineString::from(vec![
+                (100.0, 200.0),
+                (102.0, 198.0),
+                (103.0, 199.0),
+                (101.0, 201.0),
+                (100.0, 200.0),
+            ]),
+            vec![]
+        )
+    );
+    assert_eq!(
+        geotiff.model_extent(),
+        Rect::new(Coord { x: 100.0, y: 198.0 }, Coord { x: 103.0, y: 201.0 })
+    );
+    assert_eq!(
+        geotiff.resolution(),
+        Some(Coord {
+            x: 2f64.sqrt(),
+            y: 2f64.sqrt()
+        })
+    );
+    // Without a CRS, the units of model space are unknown
+    assert_eq!(geotiff.ground_sample_distance(), None);
+}
+
+#[test]
+fn test_intersection_window() {
+    let geotiff = read_geotiff("resources/zh_dem_25.tif");
+
+    let rect = Rect::new(
+        Coord {
+            x: 677600.0,
+            y: 252950.0,
+        },
+        Coord {
+            x: 677700.0,
+            y: 253000.0,
+        },
+    );
+    assert!(geotiff.intersects(&rect));
+    assert_eq!(
+        geotiff.intersection_window(&rect),
+        Some(Window::new(1, 0, 5, 3))
+    );
+
+    let rect = Rect::new(
+        Coord {
+            x: 600000.0,
+            y: 200000.0,
+        },
+        Coord {
+            x: 700000.0,
+            y: 300000.0,
+        },
+    );
+    assert_eq!(
+        geotiff.intersection_window(&rect),
+        Some(Window::new(0, 0, 399, 366))
+    );
+
+    let rect = Rect::new(Coord { x: 0.0, y: 0.0 }, Coord { x: 10.0, y: 10.0 });
+    assert!(!geotiff.intersects(&rect));
+    assert_eq!(geotiff.intersection_window(&rect), None);
+
+    assert!(geotiff.contains(&Coord {
+        x: 677575.0,
+        y: 253000.0
+    }));
+    assert!(!geotiff.contains(&Coord {
+        x: 677550.0,
+        y: 253000.0
+    }));
+}
+
+#[test]
+fn test_valid_pixels() {
+    let geotiff = read_geotiff("resources/zh_dem_25.tif");
+    assert_eq!(geotiff.no_data, Some(-9999.0));
+
+    let valid_pixels = geotiff
+        .valid_pixels::<i16>(0)
+        .unwrap()
+        .collect::<GeoTiffResult<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        valid_pixels[0],
+        (
+            Coord {
+                x: 677575.0,
+                y: 253000.0
+            },
+            551
+        )
+    );
+    assert!(valid_pixels.iter().all(|(_, value)| *value != -9999));
+
+    let extent = geotiff.model_extent();
+    let num_no_data = (0..geotiff.raster_height)
+        .flat_map(|row| (0..geotiff.raster_width).map(move |col| (col, row)))
+        .filter(|(col, row)| {
+            let coord = Coord {
+                x: extent.min().x + *col as f64 * 25.0 + 12.5,
+                y: extent.max().y - *row as f64 * 25.0 - 12.5,
+            };
+            geotiff.get_value_at::<i16>(&coord, 0) == Some(-9999)
+        })
+        .count();
+    assert_eq!(
+        valid_pixels.len() + num_no_data,
+        geotiff.raster_width * geotiff.raster_height
+    );
+    assert!(matches!(
+        geotiff.valid_pixels::<i16>(1),
+        Err(GeoTiffError::SampleOutOfBounds { sample: 1, .. })
+    ));
+    // Elevations do not fit in a byte
+    assert!(matches!(
+        geotiff.valid_pixels::<u8>(0).unwrap().next(),
+        Some(Err(GeoTiffError::ValueConversion { .. }))
+    ));
+    assert_eq!(
+        geotiff.get_masked_value_at::<u8>(&valid_pixels[0].0, 0),
+        None
+    );
+    assert!(matches!(
+        geotiff.values::<u8>(),
+        Err(GeoTiffError::ValueConversion { .. })
+    ));
+
+    // Reading chunk by chunk yields the same pixels
+    let mut reader =
+        GeoTiff::open(File::open("resources/zh_dem_25.tif").expect("File I/O error")).unwrap();
+    assert_eq!(
+        reader
+            .valid_pixels::<i16>(0)
+            .unwrap()
+            .collect::<GeoTiffResult<Vec<_>>>()
+            .unwrap(),
+        valid_pixels
+    );
+    assert!(reader.valid_pixels::<i16>(1).is_err());
+
+    // Tiles without encoded data are skipped without decoding them
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut directory = encoder.image_directory().unwrap();
+    let tile: Vec<u8> = (0..16 * 16).map(|value| (value % 255 + 1) as u8).collect();
+    let offset = directory.write_data(&tile[..]).unwrap() as u32;
+    directory.write_tag(Tag::ImageWidth, 32u32).unwrap();
+    directory.write_tag(Tag::ImageLength, 16u32).unwrap();
+    directory.write_tag(Tag::BitsPerSample, 8u16).unwrap();
+    directory.write_tag(Tag::SamplesPerPixel, 1u16).unwrap();
+    directory.write_tag(Tag::Compression, 1u16).unwrap();
+    directory
+        .write_tag(Tag::PhotometricInterpretation, 1u16)
+        .unwrap();
+    directory.write_tag(Tag::TileWidth, 16u32).unwrap();
+    directory.write_tag(Tag::TileLength, 16u32).unwrap();
+    directory
+        .write_tag(Tag::TileOffsets, &[offset, 0][..])
+        .unwrap();
+    directory
+        .write_tag(Tag::TileByteCounts, &[256u32, 0][..])
+        .unwrap();
+    directory.finish().unwrap();
+    data.set_position(0);
+    let values = GeoTiff::open(data)
+        .unwrap()
+        .valid_pixels::<u8>(0)
+        .unwrap()
+        .map(|pixel| pixel.map(|(_, value)| value))
+        .collect::<GeoTiffResult<Vec<_>>>()
+        .unwrap();
+    assert_eq!(values, tile);
+}
+
+#[test]
+fn test_write_csv() {
+    let geotiff = read_geotiff("resources/zh_dem_25.tif");
+
+    let mut csv = Vec::new();
+    geotiff
+        .write_csv(&mut csv, &Window::new(0, 0, 2, 2))
+        .expect("CSV export failed");
+    let csv = String::from_utf8(csv).unwrap();
+    let lines = csv.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 5);
+    assert_eq!(lines[0], "x,y,sample_0");
+    assert_eq!(lines[1], "677575,253000,551");
+
+    let marbles = read_geotiff("resources/marbles.tif");
+    let mut csv = Vec::new();
+    marbles
+        .write_csv(&mut csv, &Window::new(761, 599, 1, 1))
+        .expect("CSV export failed");
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "x,y,sample_0,sample_1,sample_2\n761.5,599.5,147,128,165\n"
+    );
+
+    assert!(geotiff
+        .write_csv(Vec::new(), &Window::new(398, 0, 2, 1))
+        .is_err());
+}
+
+#[test]
+fn test_lazy_reader() {
+    let mut reader =
+        GeoTiffReader::open(File::open("resources/zh_dem_25.tif").expect("File I/O error"))
+            .expect("File I/O error");
+
+    assert_eq!(reader.raster_width, 399);
+    assert_eq!(reader.raster_height, 366);
+    assert_eq!(
+        reader.model_extent(),
+        read_geotiff("resources/zh_dem_25.tif").model_extent()
+    );
+    assert_eq!(
+        reader
+            .get_value_at::<i16>(
+                &Coord {
+                    x: 679250.0,
+                    y: 251875.0
+                },
+                0
+            )
+            .expect("File I/O error"),
+        Some(530)
+    );
+    assert_eq!(
+        reader
+            .get_value_at::<i16>(
+                &Coord {
+                    x: 685700.0,
+                    y: 249450.0
+                },
+                0
+            )
+            .expect("File I/O error"),
+        Some(587)
+    );
+    assert_eq!(
+        reader
+            .get_value_at::<i16>(&Coord { x: 0.0, y: 0.0 }, 0)
+            .expect("File I/O error"),
+        None
+    );
+
+    let mut reader =
+        GeoTiffReader::open(File::open("resources/marbles.tif").expect("File I/O error"))
+            .expect("File I/O error");
+    for (sample, expected) in [147, 128, 165].into_iter().enumerate() {
+        assert_eq!(
+            reader
+                .get_value_at::<u8>(&Coord { x: 761.0, y: 599.0 }, sample)
+                .expect("File I/O error"),
+            Some(expected)
+        );
+    }
+}
+
+#[test]
+fn test_open() {
+    let header = GeoTiff::open(File::open("resources/zh_dem_25.tif").expect("File I/O error"))
+        .expect("File I/O error");
+    assert_eq!((header.raster_width, header.raster_height), (399, 366));
+    assert_eq!(
+        header.geotransform(),
+        Some([677562.5, 25.0, 0.0, 253012.5, 0.0, -25.0])
+    );
+
+    let geotiff = header.read().expect("File I/O error");
+    assert_eq!(
+        geotiff.values::<f64>().unwrap(),
+        read_geotiff("resources/zh_dem_25.tif")
+            .values::<f64>()
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_metadata() {
+    let metadata =
+        GeoTiff::read_metadata(File::open("resources/zh_dem_25.tif").expect("File I/O error"))
+            .expect("File I/O error");
+    let geotiff = read_geotiff("resources/zh_dem_25.tif");
+    assert_eq!((metadata.width, metadata.height), (399, 366));
+    assert_eq!(metadata.num_samples, 1);
+    assert_eq!(metadata.data_type, geotiff.data_type());
+    assert_eq!(
+        metadata.band_formats,
+        [BandFormat {
+            sample_format: SampleFormat::Int,
+            bits_per_sample: 16
+        }]
+    );
+    assert_eq!(metadata.geo_key_directory, geotiff.geo_key_directory);
+    assert_eq!(metadata.geotransform, geotiff.geotransform());
+    assert_eq!(metadata.model_extent, geotiff.model_extent());
+    assert_eq!(metadata.no_data, geotiff.no_data);
+    assert!(!metadata.bigtiff && !metadata.planar);
+    assert!(metadata.overviews.is_empty());
+
+    let mut reader = GeoTiffReader::open(write_tiled_image(true)).expect("File I/O error");
+    assert_eq!(reader.compression(), CompressionMethod::LZW);
+    let metadata = reader.metadata().expect("File I/O error");
+    assert_eq!(metadata.compression, CompressionMethod::LZW);
+    assert!(metadata.tiled && metadata.planar);
+    assert_eq!(metadata.chunk_dimensions, (16, 16));
+    assert!(reader.is_tiled());
+    assert_eq!(reader.chunk_dimensions(), (16, 16));
+    // 2 by 2 tiles for each of the 3 samples
+    assert_eq!(reader.chunk_count(), 12);
+    assert_eq!(metadata.geotransform, None);
+}
+
+#[test]
+fn test_info_json() {
+    let geotiff = read_geotiff("resources/zh_dem_25.tif");
+    let json = geotiff.info_json();
+    assert!(json.starts_with(r#"{"driverShortName":"GTiff","size":[399,366],"#));
+    assert!(json.contains(r#""geoTransform":[677562.5,25,0,253012.5,0,-25]"#));
+    assert!(json.contains(r#""upperLeft":[677562.5,253012.5]"#));
+    assert!(json.contains(r#""lowerRight":[687537.5,243862.5]"#));
+    assert!(json.ends_with(r#""bands":[{"band":1,"type":"Int16","noDataValue":-9999}]}"#));
+
+    let file = File::open("resources/zh_dem_25.tif").expect("File I/O error");
+    let mut reader = GeoTiffReader::open(file).expect("File I/O error");
+    let metadata = reader.metadata().expect("File I/O error");
+    assert_eq!(metadata.info_json(), json);
+
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let image = encoder.new_image::<colortype::Gray8>(4, 2).unwrap();
+    image.write_data(&[0; 8]).unwrap();
+    data.set_position(0);
+    let mut geotiff = GeoTiff::read(data).expect("File I/O error");
+    geotiff.descriptive_tags.software = Some("a \"quoted\"\nname".into());
+    geotiff.no_data = Some(f64::NAN);
+    geotiff
+        .build_overviews(&[2], Resampling::Nearest)
+        .expect("Valid factors");
+    assert_eq!(
+        geotiff.info_json(),
+        concat!(
+            r#"{"driverShortName":"GTiff","size":[4,2],"#,
+            r#""metadata":{"":{"TIFFTAG_SOFTWARE":"a \"quoted\"\nname"}},"#,
+            r#""bands":[{"band":1,"type":"Byte","noDataValue":"NaN","overviews":[{"size":[2,1]}]}]}"#
+        )
+    );
+}
+
+#[cfg(feature = "stac")]
+#[test]
+fn test_to_stac_item() {
+    use serde_json::{json, Value};
+
+    let path = "resources/austrian_capitals_model_tie_point_and_pixel_scale_pixel_is_area.tif";
+    let geotiff = read_geotiff(path);
+    let item = geotiff.to_stac_item("austrian_capitals");
+    assert_eq!(item["type"], "Feature");
+    assert_eq!(item["id"], "austrian_capitals");
+    assert_eq!(
+        item["stac_extensions"],
+        json!(["https://stac-extensions.github.io/projection/v1.1.0/schema.json"])
+    );
+    let properties = &item["properties"];
+    assert_eq!(properties["datetime"], Value::Null);
+    assert_eq!(properties["proj:epsg"], 3035);
+    assert_eq!(properties.get("proj:wkt2"), None);
+    assert_eq!(properties["proj:shape"], json!([190, 507]));
+    assert_eq!(
+        properties["proj:transform"],
+        json!([1000.0, 0.0, 4302000.0, 0.0, -1000.0, 2811000.0, 0, 0, 1])
+    );
+    assert_eq!(
+        properties["proj:bbox"],
+        json!([4302000.0, 2621000.0, 4809000.0, 2811000.0])
+    );
+
+    // User-defined CRSs are given as WKT
+    let item = read_geotiff("resources/merc.tif").to_stac_item("merc");
+    assert_eq!(item["properties"]["proj:epsg"], Value::Null);
+    assert!(item["properties"]["proj:wkt2"]
+        .as_str()
+        .unwrap()
+        .starts_with("PROJCRS[\"Mercator North American 1927\""));
+
+    // A geographic raster with pixels of half a degree
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut image = encoder.new_image::<colortype::Gray8>(4, 2).unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ModelPixelScaleTag, &[0.5, 0.5, 0.0][..])
+        .unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ModelTiepointTag, &[0.0, 0.0, 0.0, 10.0, 60.0, 0.0][..])
+        .unwrap();
+    image.write_data(&[0; 8]).unwrap();
+    data.set_position(0);
+    let mut geotiff = GeoTiff::read(data).expect("File I/O error");
+    geotiff.geo_key_directory = GeoKeyDirectoryBuilder::from_epsg(4326).unwrap().build();
+    geotiff.descriptive_tags.date_time = Some("2024:05:17 08:30:00".into());
+
+    let item = geotiff.to_stac_item("geographic");
+    assert_eq!(item["properties"]["datetime"], "2024-05-17T08:30:00Z");
+    assert_eq!(item["properties"]["proj:epsg"], 4326);
+    #[cfg(not(feature = "proj"))]
+    {
+        assert_eq!(
+            item["geometry"],
+            json!({
+                "type": "Polygon",
+                "coordinates": [[[10.0, 60.0], [10.0, 59.0], [12.0, 59.0], [12.0, 60.0], [10.0, 60.0]]],
+            })
+        );
+        assert_eq!(item["bbox"], json!([10.0, 59.0, 12.0, 60.0]));
+    }
+
+    // Rasters without a known location have no geometry
+    geotiff.geo_key_directory = GeoKeyDirectory::default();
+    geotiff.descriptive_tags.date_time = Some("yesterday".into());
+    let item = geotiff.to_stac_item("unknown");
+    assert_eq!(item["geometry"], Value::Null);
+    assert_eq!(item.get("bbox"), None);
+    assert_eq!(item["properties"]["datetime"], Value::Null);
+    assert_eq!(item["properties"]["proj:epsg"], Value::Null);
+}
+
+#[test]
+fn test_profile() {
+    let file = File::open("resources/zh_dem_25.tif").expect("File I/O error");
+    let mut reader = GeoTiffReader::open(file).expect("File I/O error");
+    let profile = reader.profile();
+    assert_eq!((profile.width, profile.height), (399, 366));
+    assert_eq!(profile.num_samples, 1);
+    assert_eq!(profile.data_type, RasterDataType::I16);
+    assert_eq!(
+        profile.geotransform,
+        Some([677562.5, 25.0, 0.0, 253012.5, 0.0, -25.0])
+    );
+    assert_eq!(profile.no_data, Some(-9999.0));
+    assert_eq!(profile.compression, Compression::None);
+    assert_eq!(profile.tile_size, None);
+    assert_eq!(
+        reader.metadata().expect("File I/O error").profile(),
+        profile
+    );
+
+    // Write the elevations shifted by 1, compressed and tiled
+    let geotiff = reader.read().expect("File I/O error");
+    let values = geotiff.values::<i16>().unwrap();
+    let shifted = values.iter().map(|value| value + 1).collect::<Vec<_>>();
+    let profile = Profile {
+        compression: Compression::Deflate,
+        tile_size: Some((32, 32)),
+        ..profile
+    };
+    let shifted_geotiff =
+        GeoTiff::from_profile(&profile, RasterData::I16(shifted.clone())).expect("Valid profile");
+    assert_eq!(shifted_geotiff.model_extent(), geotiff.model_extent());
+    assert_eq!(shifted_geotiff.data_type(), RasterDataType::I16);
+
+    let mut data = Cursor::new(Vec::new());
+    shifted_geotiff
+        .write_with_options(&mut data, &profile.write_options())
+        .expect("Could not write GeoTIFF");
+    data.set_position(0);
+    let reader = GeoTiffReader::open(data).expect("Could not read written GeoTIFF");
+    assert_eq!(reader.profile(), profile);
+    assert_eq!(
+        reader
+            .read()
+            .expect("File I/O error")
+            .values::<i16>()
+            .unwrap(),
+        shifted
+    );
+
+    assert!(matches!(
+        GeoTiff::from_profile(&profile, RasterData::U8(vec![0; 399 * 366])),
+        Err(GeoTiffError::Format(_))
+    ));
+    assert!(matches!(
+        GeoTiff::from_profile(&profile, RasterData::I16(vec![0; 399])),
+        Err(GeoTiffError::Format(_))
+    ));
+    let profile = Profile {
+        geotransform: Some([0.0; 6]),
+        ..Profile::new(1, 1, 1, RasterDataType::U8)
+    };
+    assert!(matches!(
+        GeoTiff::from_profile(&profile, RasterData::U8(vec![0])),
+        Err(GeoTiffError::Transform(_))
+    ));
+}
+
+#[test]
+fn test_write_multiband() {
+    // Grayscale rasters with several bands, e.g. a two-band index or a hyperspectral cube
+    for num_samples in [2, 5] {
+        let profile = Profile::new(3, 2, num_samples, RasterDataType::U16);
+        let values: Vec<u16> = (0..6 * num_samples as u16).collect();
+        let geotiff = GeoTiff::from_profile(&profile, RasterData::U16(values.clone())).unwrap();
+        let mut data = Cursor::new(Vec::new());
+        geotiff.write(&mut data).unwrap();
+
+        data.set_position(0);
+        assert_eq!(probe_support(data.clone()).unwrap(), vec![]);
+        let geotiff = GeoTiff::read(data).expect("File I/O error");
+        assert_eq!(geotiff.num_samples, num_samples);
+        assert_eq!(geotiff.values::<u16>().unwrap(), values);
+    }
+}
+
+#[test]
+fn test_tags() {
+    let mut reader =
+        GeoTiffReader::open(File::open("resources/zh_dem_25.tif").expect("File I/O error"))
+            .expect("File I/O error");
+    let tags = reader.tags().expect("File I/O error");
+    assert!(tags
+        .windows(2)
+        .all(|pair| pair[0].tag.to_u16() < pair[1].tag.to_u16()));
+
+    let width = tags
+        .iter()
+        .find(|tag| tag.tag == Tag::ImageWidth)
+        .expect("ImageWidth tag");
+    assert_eq!(width.count, 1);
+    assert_eq!(width.value.clone().into_u32().unwrap(), 399);
+
+    let pixel_scale = tags
+        .iter()
+        .find(|tag| tag.tag == Tag::ModelPixelScaleTag)
+        .expect("ModelPixelScale tag");
+    assert_eq!(pixel_scale.field_type, Type::DOUBLE);
+    assert_eq!(pixel_scale.count, 3);
+    assert_eq!(
+        pixel_scale.value.clone().into_f64_vec().unwrap(),
+        vec![25.0, 25.0, 0.0]
+    );
+
+    // The raster can still be read afterwards
+    assert_eq!(reader.read().expect("File I/O error").raster_width, 399);
+}
+
+#[test]
+fn test_exif_and_gps() {
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let rational = |n, d| Rational { n, d };
+
+    let mut exif = encoder.extra_directory().unwrap();
+    exif.write_tag(Tag::Unknown(0x829a), rational(1, 500))
+        .unwrap();
+    let exif = exif.finish_with_offsets().unwrap();
+    // 47°22'30"N 8°32'24"W at 408.5 m
+    let mut gps = encoder.extra_directory().unwrap();
+    gps.write_tag(Tag::Unknown(1), "N").unwrap();
+    gps.write_tag(
+        Tag::Unknown(2),
+        &[rational(47, 1), rational(22, 1), rational(30, 1)][..],
+    )
+    .unwrap();
+    gps.write_tag(Tag::Unknown(3), "W").unwrap();
+    gps.write_tag(
+        Tag::Unknown(4),
+        &[rational(8, 1), rational(32, 1), rational(24, 1)][..],
+    )
+    .unwrap();
+    gps.write_tag(Tag::Unknown(5), 0u8).unwrap();
+    gps.write_tag(Tag::Unknown(6), rational(817, 2)).unwrap();
+    let gps = gps.finish_with_offsets().unwrap();
+
+    let mut image = encoder.new_image::<colortype::Gray8>(1, 1).unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ExifDirectory, exif.offset)
+        .unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::GpsDirectory, gps.offset)
+        .unwrap();
+    image.write_data(&[0]).unwrap();
+    data.set_position(0);
+
+    let mut reader = GeoTiffReader::open(data).expect("File I/O error");
+    let exif_tags = reader.exif_tags().expect("File I/O error").unwrap();
+    assert_eq!(exif_tags.len(), 1);
+    assert_eq!(exif_tags[0].tag.to_u16(), 0x829a);
+    assert_eq!(exif_tags[0].field_type, Type::RATIONAL);
+    assert_eq!(reader.gps_tags().expect("File I/O error").unwrap().len(), 6);
+
+    let position = reader
+        .gps_position()
+        .expect("File I/O error")
+        .expect("GPS position");
+    assert_eq!(position.latitude, 47.375);
+    assert_eq!(position.longitude, -8.54);
+    assert_eq!(position.altitude, Some(408.5));
+
+    let mut reader = GeoTiffReader::open(File::open("resources/zh_dem_25.tif").unwrap())
+        .expect("File I/O error");
+    assert_eq!(reader.exif_tags().expect("File I/O error"), None);
+    assert_eq!(reader.gps_position().expect("File I/O error"), None);
+}
+
+#[test]
+fn test_extra_samples() {
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut image = encoder.new_image::<colortype::RGBA8>(2, 1).unwrap();
+    image.encoder().write_tag(Tag::ExtraSamples, 1u16).unwrap();
+    image.write_data(&[10, 20, 30, 255, 40, 50, 60, 0]).unwrap();
+    let data = data.into_inner();
+
+    let reader = GeoTiffReader::open(Cursor::new(&data)).expect("File I/O error");
+    assert_eq!(
+        reader.sample_roles(),
+        [
+            SampleRole::Data,
+            SampleRole::Data,
+            SampleRole::Data,
+            SampleRole::AssociatedAlpha
+        ]
+    );
+    assert_eq!(reader.alpha_sample(), Some(3));
+    let geotiff = reader.read().expect("File I/O error");
+    assert_eq!(geotiff.alpha_sample(), Some(3));
+    assert_eq!(geotiff.mask(), None);
+    let transparent = Coord { x: 1.5, y: 0.5 };
+    assert_eq!(geotiff.get_masked_value_at::<u8>(&transparent, 0), Some(40));
+
+    // Fully transparent pixels are masked when alpha is interpreted as a mask
+    let geotiff = GeoTiffReader::open(Cursor::new(&data))
+        .expect("File I/O error")
+        .with_alpha_mask(true)
+        .read()
+        .expect("File I/O error");
+    assert_eq!(geotiff.mask(), Some(&[true, false][..]));
+    assert_eq!(geotiff.get_masked_value_at::<u8>(&transparent, 0), None);
+    assert_eq!(
+        geotiff.get_masked_value_at::<u8>(&Coord { x: 0.5, y: 0.5 }, 2),
+        Some(30)
+    );
+    assert_eq!(
+        geotiff.get_masked_value_at::<u8>(&Coord { x: 0.5, y: 0.5 }, 4),
+        None
+    );
+
+    // The roles are preserved when writing
+    let mut written = Cursor::new(Vec::new());
+    geotiff
+        .write(&mut written)
+        .expect("Could not write GeoTIFF");
+    written.set_position(0);
+    let written = GeoTiff::read(written).expect("Could not read written GeoTIFF");
+    assert_eq!(written.sample_roles(), geotiff.sample_roles());
+
+    // A fourth data band is not written as alpha
+    let profile = Profile::new(2, 1, 4, RasterDataType::U8);
+    let geotiff = GeoTiff::from_profile(&profile, RasterData::U8(vec![0; 8])).unwrap();
+    let mut written = Cursor::new(Vec::new());
+    geotiff
+        .write(&mut written)
+        .expect("Could not write GeoTIFF");
+    written.set_position(0);
+    let written = GeoTiff::read(written).expect("Could not read written GeoTIFF");
+    assert_eq!(written.sample_roles(), &[SampleRole::Data; 4]);
+    assert_eq!(written.alpha_sample(), None);
+}
+
+#[test]
+fn test_cmyk() {
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let image = encoder.new_image::<colortype::CMYK8>(2, 1).unwrap();
+    image.write_data(&[0, 0, 0, 0, 255, 0, 0, 51]).unwrap();
+    let data = data.into_inner();
+
+    let mut reader = GeoTiffReader::open(Cursor::new(&data)).expect("File I/O error");
+    assert_eq!(
+        reader.photometric_interpretation(),
+        PhotometricInterpretation::CMYK
+    );
+    assert_eq!(
+        reader.metadata().unwrap().photometric_interpretation,
+        PhotometricInterpretation::CMYK
+    );
+    let geotiff = reader.read().expect("Could not read GeoTIFF");
+    assert_eq!(
+        geotiff.photometric_interpretation(),
+        PhotometricInterpretation::CMYK
+    );
+    assert_eq!(
+        geotiff.values::<u8>().unwrap(),
+        vec![0, 0, 0, 0, 255, 0, 0, 51]
+    );
+
+    let rgb = geotiff.cmyk_to_rgb().expect("Could not convert CMYK");
+    assert_eq!(rgb.num_samples, 3);
+    assert_eq!(
+        rgb.photometric_interpretation(),
+        PhotometricInterpretation::RGB
+    );
+    assert_eq!(
+        rgb.values::<u8>().unwrap(),
+        vec![255, 255, 255, 0, 204, 204]
+    );
+    assert!(matches!(rgb.cmyk_to_rgb(), Err(GeoTiffError::Format(_))));
+
+    // CMYK is written as such instead of RGB with alpha
+    let mut written = Cursor::new(Vec::new());
+    geotiff
+        .write(&mut written)
+        .expect("Could not write GeoTIFF");
+    written.set_position(0);
+    let written = GeoTiff::read(written).expect("Could not read written GeoTIFF");
+    assert_eq!(
+        written.photometric_interpretation(),
+        PhotometricInterpretation::CMYK
+    );
+    assert_eq!(written.alpha_sample(), None);
+
+    // A single sample is no longer CMYK
+    let cyan = GeoTiffReader::open(Cursor::new(&data))
+        .expect("File I/O error")
+        .with_samples(&[0])
+        .read()
+        .expect("Could not read GeoTIFF");
+    assert_eq!(
+        cyan.photometric_interpretation(),
+        PhotometricInterpretation::BlackIsZero
+    );
+}
+
+#[test]
+fn test_write_photometric_interpretation() {
+    // Three grayscale bands are written as such instead of RGB
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut image = encoder.new_image::<colortype::RGB8>(2, 1).unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::PhotometricInterpretation, 1u16)
+        .unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ExtraSamples, &[0u16, 0][..])
+        .unwrap();
+    image.write_data(&[1, 2, 3, 4, 5, 6]).unwrap();
+    data.set_position(0);
+    let geotiff = GeoTiff::read(data).expect("File I/O error");
+    assert_eq!(
+        geotiff.photometric_interpretation(),
+        PhotometricInterpretation::BlackIsZero
+    );
+    let mut written = Cursor::new(Vec::new());
+    geotiff
+        .write(&mut written)
+        .expect("Could not write GeoTIFF");
+    written.set_position(0);
+    let written = GeoTiff::read(written).expect("Could not read written GeoTIFF");
+    assert_eq!(
+        written.photometric_interpretation(),
+        PhotometricInterpretation::BlackIsZero
+    );
+    assert_eq!(written.values::<u8>().unwrap(), vec![1, 2, 3, 4, 5, 6]);
+
+    // Without a photometric interpretation, three bytes per pixel are written as RGB
+    let profile = Profile::new(2, 1, 3, RasterDataType::U8);
+    let geotiff = GeoTiff::from_profile(&profile, RasterData::U8(vec![0; 6])).unwrap();
+    assert_eq!(
+        geotiff.photometric_interpretation(),
+        PhotometricInterpretation::RGB
+    );
+    let mut written = Cursor::new(Vec::new());
+    geotiff
+        .write(&mut written)
+        .expect("Could not write GeoTIFF");
+    written.set_position(0);
+    let written = GeoTiff::read(written).expect("Could not read written GeoTIFF");
+    assert_eq!(
+        written.photometric_interpretation(),
+        PhotometricInterpretation::RGB
+    );
+}
+
+#[test]
+fn test_histogram() {
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    encoder
+        .new_image::<colortype::Gray8>(4, 4)
+        .unwrap()
+        .write_data(&(0..16).collect::<Vec<u8>>())
+        .unwrap();
+    data.set_position(0);
+    let mut geotiff = GeoTiff::read(data).expect("File I/O error");
+
+    let histogram = geotiff.histogram(0, 4, None).unwrap();
+    assert_eq!((histogram.minimum, histogram.maximum), (0.0, 15.0));
+    assert_eq!(histogram.counts, vec![4, 4, 4, 4]);
+    assert_eq!(histogram.bin_range(1), 3.75..7.5);
+
+    // The maximum is included in the last bin
+    let histogram = geotiff.histogram(0, 2, Some((0.0, 8.0))).unwrap();
+    assert_eq!(histogram.counts, vec![4, 5]);
+    assert_eq!(histogram.outside, 7);
+
+    geotiff.no_data = Some(0.0);
+    let histogram = geotiff.histogram(0, 4, Some((0.0, 16.0))).unwrap();
+    assert_eq!(histogram.counts, vec![3, 4, 4, 4]);
+    assert_eq!(histogram.total(), 15);
+
+    let marbles = read_geotiff("resources/marbles.tif");
+    let histogram = marbles.histogram(2, 256, Some((0.0, 256.0))).unwrap();
+    assert_eq!(histogram.bin_width(), 1.0);
+    assert_eq!(histogram.total(), 1419 * 1001);
+    assert!(matches!(
+        marbles.histogram(3, 256, None),
+        Err(GeoTiffError::SampleOutOfBounds { sample: 3, .. })
+    ));
+}
+
+#[test]
+fn test_sample_interpolation() {
+    // A 4x4 image whose values grow linearly, 10 per column and 40 per row
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut image = encoder.new_image::<colortype::Gray8>(4, 4).unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ModelPixelScaleTag, &[1.0, 1.0, 0.0][..])
+        .unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ModelTiepointTag, &[0.0, 0.0, 0.0, 0.0, 4.0, 0.0][..])
+        .unwrap();
+    let values = (0..16)
+        .map(|i| i % 4 * 10 + i / 4 * 40)
+        .collect::<Vec<u8>>();
+    image.write_data(&values).unwrap();
+    data.set_position(0);
+    let mut geotiff = GeoTiff::read(data).expect("File I/O error");
+
+    let center = Coord { x: 2.0, y: 2.0 };
+    assert_eq!(
+        geotiff.sample(&center, 0, Interpolation::Nearest).unwrap(),
+        Some(100.0)
+    );
+    assert_eq!(
+        geotiff.sample(&center, 0, Interpolation::Bilinear).unwrap(),
+        Some(75.0)
+    );
+    assert_eq!(
+        geotiff.sample(&center, 0, Interpolation::Bicubic).unwrap(),
+        Some(75.0)
+    );
+
+    // Pixels at the edge are repeated
+    let edge = Coord { x: 0.1, y: 3.0 };
+    assert_eq!(
+        geotiff.sample(&edge, 0, Interpolation::Bilinear).unwrap(),
+        Some(20.0)
+    );
+    assert_eq!(
+        geotiff
+            .sample(&Coord { x: 4.5, y: 2.0 }, 0, Interpolation::Bilinear)
+            .unwrap(),
+        None
+    );
+
+    // Unknown pixels fall back to the nearest value
+    geotiff.no_data = Some(0.0);
+    assert_eq!(
+        geotiff.sample(&center, 0, Interpolation::Bilinear).unwrap(),
+        Some(75.0)
+    );
+    assert_eq!(
+        geotiff.sample(&center, 0, Interpolation::Bicubic).unwrap(),
+        Some(100.0)
+    );
+    assert_eq!(
+        geotiff
+            .sample(&Coord { x: 0.5, y: 3.5 }, 0, Interpolation::Bilinear)
+            .unwrap(),
+        None
+    );
+    assert!(matches!(
+        geotiff.sample(&center, 1, Interpolation::Nearest),
+        Err(GeoTiffError::SampleOutOfBounds { sample: 1, .. })
+    ));
+}
+
+#[test]
+fn test_sample_along() {
+    let geotiff = read_geotiff("resources/zh_dem_25.tif");
+    let extent = geotiff.model_extent();
+    let start = extent.min() + Coord { x: 100.0, y: 100.0 };
+    let line = LineString::from(vec![
+        start,
+        start + Coord { x: 300.0, y: 0.0 },
+        start + Coord { x: 300.0, y: 400.0 },
+    ]);
+
+    let profile = geotiff
+        .sample_along(&line, 100.0, 0, Interpolation::Bilinear)
+        .unwrap();
+    let distances: Vec<_> = profile.iter().map(|(distance, _)| *distance).collect();
+    assert_eq!(
+        distances,
+        vec![0.0, 100.0, 200.0, 300.0, 400.0, 500.0, 600.0, 700.0]
+    );
+    assert_eq!(
+        profile[4].1,
+        geotiff
+            .sample(
+                &(start + Coord { x: 300.0, y: 100.0 }),
+                0,
+                Interpolation::Bilinear
+            )
+            .unwrap()
+    );
+    assert!(profile.iter().all(|(_, value)| value.is_some()));
+
+    // The end of the line is sampled even if it is closer than the spacing
+    let profile = geotiff
+        .sample_along(&line, 300.0, 0, Interpolation::Nearest)
+        .unwrap();
+    let distances: Vec<_> = profile.iter().map(|(distance, _)| *distance).collect();
+    assert_eq!(distances, vec![0.0, 300.0, 600.0, 700.0]);
+
+    // Points outside the raster have no value
+    let outside = LineString::from(vec![start, extent.min() - Coord { x: 100.0, y: 0.0 }]);
+    let profile = geotiff
+        .sample_along(&outside, 150.0, 0, Interpolation::Nearest)
+        .unwrap();
+    assert!(profile[0].1.is_some());
+    assert_eq!(profile.last().unwrap().1, None);
+    assert!(matches!(
+        geotiff.sample_along(&line, 100.0, 1, Interpolation::Nearest),
+        Err(GeoTiffError::SampleOutOfBounds { sample: 1, .. })
+    ));
+}
+
+#[cfg(feature = "proj")]
+#[test]
+fn test_elevation_at() {
+    // A 4x4 geographic DEM whose elevations grow linearly, 10 per column and 40 per row
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut image = encoder.new_image::<colortype::Gray8>(4, 4).unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ModelPixelScaleTag, &[1.0, 1.0, 0.0][..])
+        .unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ModelTiepointTag, &[0.0, 0.0, 0.0, 10.0, 50.0, 0.0][..])
+        .unwrap();
+    let values = (0..16)
+        .map(|i| i % 4 * 10 + i / 4 * 40)
+        .collect::<Vec<u8>>();
+    image.write_data(&values).unwrap();
+    data.set_position(0);
+    let mut geotiff = GeoTiff::read(data).expect("File I/O error");
+    geotiff.geo_key_directory = GeoKeyDirectoryBuilder::from_epsg(4326).unwrap().build();
+
+    assert_eq!(geotiff.elevation_at(12.0, 48.0).unwrap(), Some(75.0));
+    assert_eq!(geotiff.elevation_at(20.0, 48.0).unwrap(), None);
+
+    // Packed elevations in feet
+    geotiff.gdal_metadata = Some(geotiff::GdalMetadata {
+        items: [("scale", "0.5"), ("offset", "1"), ("unittype", "ft")]
+            .map(|(role, value)| geotiff::GdalMetadataItem {
+                name: role.to_uppercase(),
+                value: value.into(),
+                sample: Some(0),
+                role: Some(role.into()),
+                domain: None,
+            })
+            .to_vec(),
+    });
+    let elevation = geotiff.elevation_at(12.0, 48.0).unwrap().unwrap();
+    assert!((elevation - 38.5 * 0.3048).abs() < 1e-9);
+
+    // The vertical unit of the GeoKeys takes precedence
+    geotiff.geo_key_directory.vertical_units = Some(9036);
+    assert_eq!(geotiff.elevation_at(12.0, 48.0).unwrap(), Some(38500.0));
+    geotiff.geo_key_directory.vertical_units = Some(9101);
+    assert!(matches!(
+        geotiff.elevation_at(12.0, 48.0),
+        Err(GeoTiffError::Crs(_))
+    ));
+}
+
+#[test]
+fn test_try_get_value_at() {
+    let geotiff = read_geotiff("resources/marbles.tif");
+    let coord = Coord { x: 761.0, y: 599.0 };
+
+    assert_eq!(
+        geotiff.try_get_value_at::<u8>(&coord, 2).unwrap(),
+        Some(165)
+    );
+    assert_eq!(
+        geotiff
+            .try_get_value_at::<u8>(&Coord { x: -1.0, y: 0.0 }, 0)
+            .unwrap(),
+        None
+    );
+    // Band indices supplied by users are checked even outside the raster
+    for coord in [coord, Coord { x: -1.0, y: 0.0 }] {
+        assert!(matches!(
+            geotiff.try_get_value_at::<u8>(&coord, 3),
+            Err(GeoTiffError::SampleOutOfBounds {
+                sample: 3,
+                num_samples: 3
+            })
+        ));
+    }
+    assert!(matches!(
+        geotiff.try_get_value_at::<i8>(&coord, 0),
+        Err(GeoTiffError::ValueConversion { .. })
+    ));
+}
+
+#[test]
+fn test_get_values_at() {
+    let geotiff = read_geotiff("resources/zh_dem_25.tif");
+    let mut reader =
+        GeoTiffReader::open(File::open("resources/zh_dem_25.tif").expect("File I/O error"))
+            .expect("File I/O error");
+
+    let extent = geotiff.model_extent();
+    let coords = (0..200)
+        .map(|i| Coord {
+            x: extent.min().x + extent.width() * ((i * 37) % 200) as f64 / 200.0,
+            y: extent.min().y + extent.height() * ((i * 53) % 200) as f64 / 200.0,
+        })
+        .chain([Coord { x: 0.0, y: 0.0 }])
+        .collect::<Vec<_>>();
+
+    let values = reader
+        .get_values_at::<i16>(&coords, 0)
+        .expect("File I/O error");
+    assert_eq!(values.len(), coords.len());
+    assert_eq!(values.last(), Some(&None));
+    for (coord, value) in coords.iter().zip(values) {
+        assert_eq!(value, geotiff.get_value_at::<i16>(coord, 0));
+    }
+}
+
+#[test]
+fn test_reader_errors() {
+    let mut reader =
+        GeoTiffReader::open(File::open("resources/zh_dem_25.tif").expect("File I/O error"))
+            .expect("File I/O error");
+    let coord = Coord {
+        x: 679250.0,
+        y: 251875.0,
+    };
+
+    let error = reader.get_value_at::<i16>(&coord, 1).unwrap_err();
+    assert!(matches!(
+        error,
+        GeoTiffError::SampleOutOfBounds {
+            sample: 1,
+            num_samples: 1
+        }
+    ));
+    assert_eq!(
+        error.to_string(),
+        "Sample out of bounds: the number of samples is 1 but the sample is 1"
+    );
+    assert!(matches!(
+        reader.get_values_at::<i16>(&[coord], 2),
+        Err(GeoTiffError::SampleOutOfBounds { sample: 2, .. })
+    ));
+
+    // The elevation of 530 m does not fit in a byte
+    let error = reader.get_value_at::<u8>(&coord, 0).unwrap_err();
+    assert!(matches!(error, GeoTiffError::ValueConversion { .. }));
+    assert_eq!(error.to_string(), "Cannot represent i16 as u8");
+    assert_eq!(reader.get_value_at::<u16>(&coord, 0).unwrap(), Some(530));
+
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut image = encoder.new_image::<colortype::Gray8>(1, 1).unwrap();
+    let mut singular = [0.0; 16];
+    singular[15] = 1.0;
+    image
+        .encoder()
+        .write_tag(Tag::ModelTransformationTag, &singular[..])
+        .unwrap();
+    image.write_data(&[0]).unwrap();
+    data.set_position(0);
+    assert!(matches!(
+        GeoTiffReader::open(data),
+        Err(GeoTiffError::Transform(_))
+    ));
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn test_async_reader() {
+    use std::io::{Read, Seek, SeekFrom};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use futures::executor::block_on;
+    use futures::io::AllowStdIo;
+    use geotiff::AsyncGeoTiff;
+
+    block_on(async {
+        let file = File::open("resources/zh_dem_25.tif").expect("File I/O error");
+        let mut reader = AsyncGeoTiff::open(AllowStdIo::new(file))
+            .await
+            .expect("File I/O error");
+        let geotiff = read_geotiff("resources/zh_dem_25.tif");
+
+        assert_eq!(reader.raster_width, 399);
+        assert_eq!(reader.raster_height, 366);
+        assert_eq!(reader.no_data, Some(-9999.0));
+        assert_eq!(reader.model_extent(), geotiff.model_extent());
+        assert_eq!(
+            reader.photometric_interpretation(),
+            PhotometricInterpretation::BlackIsZero
+        );
+        assert_eq!(reader.compression(), CompressionMethod::None);
+        assert!(!reader.is_tiled());
+        assert_eq!(reader.chunk_dimensions(), (399, 10));
+        assert_eq!(reader.chunk_count(), 37);
+        assert_eq!(reader.overviews().await.expect("File I/O error"), vec![]);
+
+        let coord = Coord {
+            x: 685700.0,
+            y: 249450.0,
+        };
+        assert_eq!(
+            reader
+                .get_value_at::<i16>(&coord, 0)
+                .await
+                .expect("File I/O error"),
+            Some(587)
+        );
+
+        let coords = [
+            coord,
+            Coord { x: 0.0, y: 0.0 },
+            geotiff.model_extent().center(),
+        ];
+        assert_eq!(
+            reader
+                .get_values_at::<i16>(&coords, 0)
+                .await
+                .expect("File I/O error"),
+            coords
+                .iter()
+                .map(|coord| geotiff.get_value_at::<i16>(coord, 0))
+                .collect::<Vec<_>>()
+        );
+        assert!(matches!(
+            reader.get_value_at::<i16>(&coord, 1).await,
+            Err(GeoTiffError::SampleOutOfBounds {
+                sample: 1,
+                num_samples: 1
+            })
+        ));
+        assert!(matches!(
+            reader.get_values_at::<i16>(&coords, 1).await,
+            Err(GeoTiffError::SampleOutOfBounds {
+                sample: 1,
+                num_samples: 1
+            })
+        ));
+
+        let region = reader
+            .read_region(&Window::new(120, 15, 30, 20))
+            .await
+            .expect("File I/O error");
+        let extent = region.model_extent();
+        assert_eq!(
+            region.get_value_at::<i16>(&extent.center(), 0),
+            geotiff.get_value_at::<i16>(&extent.center(), 0)
+        );
+        assert!(reader
+            .read_region(&Window::new(390, 0, 10, 10))
+            .await
+            .is_err());
+
+        // Strips whose encoded data of 7980 bytes exceeds the limits are not fetched
+        struct CountingReader(File, Arc<AtomicUsize>);
+        impl Read for CountingReader {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let count = self.0.read(buf)?;
+                self.1.fetch_add(count, Ordering::Relaxed);
+                Ok(count)
+            }
+        }
+        impl Seek for CountingReader {
+            fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+                self.0.seek(pos)
+            }
+        }
+        let mut limits = Limits::default();
+        limits.decoding_buffer_size = 4000;
+        let bytes_read = Arc::new(AtomicUsize::new(0));
+        let file = File::open("resources/zh_dem_25.tif").expect("File I/O error");
+        let options = ReadOptions::default().with_limits(limits);
+        let mut limited = AsyncGeoTiff::open_with_options(
+            AllowStdIo::new(CountingReader(file, bytes_read.clone())),
+            &options,
+        )
+        .await
+        .expect("File I/O error");
+        let opened = bytes_read.load(Ordering::Relaxed);
+        assert!(matches!(
+            limited.get_value_at::<i16>(&coord, 0).await,
+            Err(GeoTiffError::Tiff(TiffError::LimitsExceeded))
+        ));
+        assert_eq!(bytes_read.load(Ordering::Relaxed), opened);
+
+        let full = reader.read().await.expect("File I/O error");
+        assert_eq!(full.model_extent(), geotiff.model_extent());
+        assert_eq!(
+            full.valid_pixels::<i16>(0)
+                .unwrap()
+                .collect::<GeoTiffResult<Vec<_>>>()
+                .unwrap(),
+            geotiff
+                .valid_pixels::<i16>(0)
+                .unwrap()
+                .collect::<GeoTiffResult<Vec<_>>>()
+                .unwrap()
+        );
+    });
+}
+
+/// Serves the given file over HTTP on a local port, supporting range requests. Returns the URL
+/// of the file and the number of bytes served so far.
+#[cfg(feature = "http")]
+fn serve_file(path: &str) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let data = std::fs::read(path).expect("File I/O error");
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind listener");
+    let url = format!("http://{}/file.tif", listener.local_addr().unwrap());
+    let bytes_served = Arc::new(AtomicUsize::new(0));
+
+    let counter = bytes_served.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut range = None;
+            for line in BufReader::new(&mut stream).lines() {
+                let line = line.unwrap();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(value) = line.strip_prefix("Range: bytes=") {
+                    let (start, end) = value.split_once('-').unwrap();
+                    let start = start.parse::<usize>().unwrap();
+                    let end = end.parse::<usize>().unwrap().min(data.len() - 1);
+                    range = Some(start..end + 1);
+                }
+            }
+
+            let range = range.unwrap();
+            if range.start >= data.len() {
+                write!(stream, "HTTP/1.1 416 Range Not Satisfiable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").unwrap();
+                continue;
+            }
+            write!(
+                stream,
+                "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}-{}/{}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                range.start,
+                range.end - 1,
+                data.len(),
+                range.len()
+            )
+            .unwrap();
+            stream.write_all(&data[range.clone()]).unwrap();
+            counter.fetch_add(range.len(), Ordering::SeqCst);
+        }
+    });
+
+    (url, bytes_served)
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn test_http_range_reader() {
+    use geotiff::HttpRangeReader;
+    use std::sync::atomic::Ordering;
+
+    let (url, bytes_served) = serve_file("resources/marbles.tif");
+    let mut reader = GeoTiffReader::open(HttpRangeReader::new(url)).expect("HTTP error");
+    assert_eq!(reader.raster_width, 1419);
+    assert_eq!(reader.raster_height, 1001);
+
+    for (sample, expected) in [147, 128, 165].into_iter().enumerate() {
+        assert_eq!(
+            reader
+                .get_value_at::<u8>(&Coord { x: 761.0, y: 599.0 }, sample)
+                .expect("HTTP error"),
+            Some(expected)
+        );
+    }
+
+    let file_size = std::fs::metadata("resources/marbles.tif").unwrap().len() as usize;
+    assert!(bytes_served.load(Ordering::SeqCst) < file_size / 10);
+
+    let region = reader
+        .read_region(&Window::new(700, 550, 100, 100))
+        .expect("HTTP error");
+    assert_eq!(
+        region.get_value_at::<u8>(&Coord { x: 61.0, y: 49.0 }, 0),
+        Some(147)
+    );
+}
+
+#[test]
+fn test_crop() {
+    let geotiff = read_geotiff("resources/zh_dem_25.tif");
+    let extent = geotiff.model_extent();
+    let rect = Rect::new(
+        extent.min()
+            + Coord {
+                x: 1010.0,
+                y: 2010.0,
+            },
+        extent.min()
+            + Coord {
+                x: 2000.0,
+                y: 3000.0,
+            },
+    );
+
+    let cropped = geotiff
+        .crop(&rect)
+        .expect("File I/O error")
+        .expect("The rectangle intersects the raster");
+    // Partially covered pixels of 25 m are included
+    assert_eq!((cropped.raster_width, cropped.raster_height), (40, 40));
+    assert_eq!(
+        cropped.model_extent(),
+        Rect::new(
+            extent.min()
+                + Coord {
+                    x: 1000.0,
+                    y: 2000.0
+                },
+            extent.min()
+                + Coord {
+                    x: 2000.0,
+                    y: 3000.0
+                },
+        )
+    );
+    assert_eq!(cropped.no_data, geotiff.no_data);
+    for coord in [
+        rect.min(),
+        rect.max() - Coord { x: 1.0, y: 1.0 },
+        rect.center(),
+    ] {
+        assert_eq!(
+            cropped.get_value_at::<f32>(&coord, 0),
+            geotiff.get_value_at::<f32>(&coord, 0)
+        );
+    }
+
+    // The layout in memory is kept
+    let column_major = read_geotiff("resources/zh_dem_25.tif")
+        .into_layout(Interleave::Band, ArrayOrder::ColumnMajor)
+        .expect("File I/O error")
+        .crop(&rect)
+        .expect("File I/O error")
+        .unwrap();
+    assert_eq!(column_major.order(), ArrayOrder::ColumnMajor);
+    assert_eq!(
+        column_major.get_value_at::<f32>(&rect.center(), 0),
+        cropped.get_value_at::<f32>(&rect.center(), 0)
+    );
+
+    let outside = Rect::new(
+        extent.max() + Coord { x: 10.0, y: 10.0 },
+        extent.max() + Coord { x: 20.0, y: 20.0 },
+    );
+    assert!(geotiff.crop(&outside).expect("File I/O error").is_none());
+}
+
+#[test]
+fn test_read_overview() {
+    let full_resolution = (0..64).collect::<Vec<u8>>();
+    let overview = (100..116).collect::<Vec<u8>>();
+
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut image = encoder.new_image::<colortype::Gray8>(8, 8).unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ModelPixelScaleTag, &[10.0, 10.0, 0.0][..])
+        .unwrap();
+    image
+        .encoder()
+        .write_tag(
+            Tag::ModelTiepointTag,
+            &[0.0, 0.0, 0.0, 1000.0, 2000.0, 0.0][..],
+        )
+        .unwrap();
+    image.write_data(&full_resolution).unwrap();
+    let mut image = encoder.new_image::<colortype::Gray8>(4, 4).unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::NewSubfileType, 1u32)
+        .unwrap();
+    image.write_data(&overview).unwrap();
+
+    data.set_position(0);
+    let mut reader = GeoTiffReader::open(data.clone()).expect("File I/O error");
+    assert_eq!(
+        reader.overviews().expect("File I/O error"),
+        vec![Overview {
+            level: 0,
+            width: 4,
+            height: 4,
+            decimation_x: 2.0,
+            decimation_y: 2.0
+        }]
+    );
+    // Listing the overviews leaves the reader at the full-resolution image
+    assert_eq!(
+        reader
+            .read()
+            .expect("File I/O error")
+            .values::<u8>()
+            .unwrap(),
+        full_resolution
+    );
+
+    let geotiff = GeoTiff::read_overview(data.clone(), 0).expect("File I/O error");
+
+    assert_eq!(geotiff.raster_width, 4);
+    assert_eq!(geotiff.raster_height, 4);
+    assert_eq!(
+        geotiff.model_extent(),
+        Rect::new(
+            Coord {
+                x: 1000.0,
+                y: 1920.0
+            },
+            Coord {
+                x: 1080.0,
+                y: 2000.0
+            }
+        )
+    );
+    assert_eq!(
+        geotiff.get_value_at::<u8>(
+            &Coord {
+                x: 1030.0,
+                y: 1970.0
+            },
+            0
+        ),
+        Some(105)
+    );
+
+    assert!(GeoTiff::read_overview(data, 1).is_err());
+}
+
+#[test]
+fn test_write_round_trip() {
+    let mut geotiff = read_geotiff("resources/zh_dem_25.tif");
+    geotiff.geo_key_directory.citation = Some("CH1903+ / LV95".into());
+    geotiff.geo_key_directory.geog_citation =
+        Some("GCS Name = CH1903+|Datum = CH1903+|Ellipsoid = Bessel 1841".into());
+    geotiff.geo_key_directory.vertical_citation = Some(String::new());
+
+    let mut data = Cursor::new(Vec::new());
+    geotiff.write(&mut data).expect("File I/O error");
+    data.set_position(0);
+    let written = GeoTiff::read(data).expect("File I/O error");
+
+    assert_eq!(written.geo_key_directory, geotiff.geo_key_directory);
+    assert_eq!(written.raster_width, geotiff.raster_width);
+    assert_eq!(written.raster_height, geotiff.raster_height);
+    assert_eq!(written.no_data, geotiff.no_data);
+    assert_eq!(written.model_extent(), geotiff.model_extent());
+    assert_eq!(
+        written
+            .valid_pixels::<i16>(0)
+            .unwrap()
+            .collect::<GeoTiffResult<Vec<_>>>()
+            .unwrap(),
+        geotiff
+            .valid_pixels::<i16>(0)
+            .unwrap()
+            .collect::<GeoTiffResult<Vec<_>>>()
+            .unwrap()
+    );
+
+    geotiff.geo_key_directory.citation = Some("Zürich".into());
+    assert!(geotiff.write(Cursor::new(Vec::new())).is_err());
+}
+
+/// Writes a 20x18 RGB image with 16-bit samples in LZW-compressed 16x16 tiles, whose
+/// value of each sample is its index in the pixel-interleaved raster.
+fn write_tiled_image(planar: bool) -> Cursor<Vec<u8>> {
+    let (width, height, tile_size) = (20, 18, 16);
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut directory = encoder.image_directory().unwrap();
+
+    let planes: &[&[usize]] = if planar {
+        &[&[0], &[1], &[2]]
+    } else {
+        &[&[0, 1, 2]]
+    };
+    let (mut offsets, mut byte_counts) = (Vec::new(), Vec::new());
+    for samples in planes {
+        for tile_y in (0..height).step_by(tile_size) {
+            for tile_x in (0..width).step_by(tile_size) {
+                // Tiles are padded to the full tile size at the edges
+                let mut tile = Vec::new();
+                for y in tile_y..tile_y + tile_size {
+                    for x in tile_x..tile_x + tile_size {
+                        for sample in *samples {
+                            let value = match x < width && y < height {
+                                true => ((y * width + x) * 3 + sample) as u16,
+                                false => 0,
+                            };
+                            tile.extend(value.to_le_bytes());
+                        }
+                    }
+                }
+                let mut compressed = Vec::new();
+                Lzw.write_to(&mut compressed, &tile).unwrap();
+                offsets.push(directory.write_data(&compressed[..]).unwrap() as u32);
+                byte_counts.push(compressed.len() as u32);
+            }
+        }
+    }
+
+    directory.write_tag(Tag::ImageWidth, width as u32).unwrap();
+    directory
+        .write_tag(Tag::ImageLength, height as u32)
+        .unwrap();
+    directory
+        .write_tag(Tag::BitsPerSample, &[16u16, 16, 16][..])
+        .unwrap();
+    directory.write_tag(Tag::SamplesPerPixel, 3u16).unwrap();
+    directory
+        .write_tag(Tag::Compression, CompressionMethod::LZW.to_u16())
+        .unwrap();
+    directory
+        .write_tag(Tag::PhotometricInterpretation, 2u16)
+        .unwrap();
+    directory
+        .write_tag(Tag::PlanarConfiguration, if planar { 2u16 } else { 1 })
+        .unwrap();
+    directory
+        .write_tag(Tag::TileWidth, tile_size as u32)
+        .unwrap();
+    directory
+        .write_tag(Tag::TileLength, tile_size as u32)
+        .unwrap();
+    directory.write_tag(Tag::TileOffsets, &offsets[..]).unwrap();
+    directory
+        .write_tag(Tag::TileByteCounts, &byte_counts[..])
+        .unwrap();
+    directory.finish().unwrap();
+
+    data.set_position(0);
+    data
+}
+
+#[test]
+fn test_write_extra_tags() {
+    let geotiff = read_geotiff("resources/zh_dem_25.tif");
+    let extra_tags = vec![
+        (Tag::Unknown(65000), Value::Ascii("vendor".into())),
+        (
+            Tag::Unknown(65001),
+            Value::List(vec![Value::Short(1), Value::Short(2)]),
+        ),
+        (Tag::Unknown(65002), Value::Rational(1, 3)),
+        (Tag::Software, Value::Ascii("rewritten".into())),
+    ];
+    let mut data = Cursor::new(Vec::new());
+    geotiff
+        .write_with_options(
+            &mut data,
+            &WriteOptions::default().with_extra_tags(extra_tags.clone()),
+        )
+        .expect("File I/O error");
+    data.set_position(0);
+
+    let tags = GeoTiffReader::open(data)
+        .expect("Could not read written GeoTIFF")
+        .tags()
+        .expect("File I/O error");
+    for (tag, value) in extra_tags {
+        let written = tags.iter().find(|written| written.tag == tag).unwrap();
+        assert_eq!(written.value, value);
+    }
+    let written = tags
+        .iter()
+        .find(|written| written.tag == Tag::Unknown(65001))
+        .unwrap();
+    assert_eq!(written.field_type, Type::SHORT);
+
+    let options = WriteOptions::default().with_extra_tags(vec![(
+        Tag::Unknown(65000),
+        Value::List(vec![Value::Short(1), Value::Double(2.0)]),
+    )]);
+    assert!(matches!(
+        geotiff.write_with_options(Cursor::new(Vec::new()), &options),
+        Err(GeoTiffError::Format(_))
+    ));
+}
+
+#[test]
+fn test_descriptive_tags() {
+    let mut geotiff = read_geotiff("resources/zh_dem_25.tif");
+    assert_eq!(geotiff.descriptive_tags, DescriptiveTags::default());
+
+    geotiff.descriptive_tags = DescriptiveTags {
+        image_description: Some("Elevation in metres".into()),
+        date_time: Some("2024:05:17 12:30:00".into()),
+        software: Some("geotiff".into()),
+        artist: Some("Swisstopo".into()),
+        copyright: Some("CC BY 4.0".into()),
+        host_computer: None,
+    };
+    let mut data = Cursor::new(Vec::new());
+    geotiff.write(&mut data).expect("File I/O error");
+    data.set_position(0);
+    let metadata = GeoTiff::read_metadata(data.clone()).expect("File I/O error");
+    assert_eq!(metadata.descriptive_tags, geotiff.descriptive_tags);
+
+    // The tags survive another round trip
+    let mut rewritten = Cursor::new(Vec::new());
+    GeoTiff::read(data)
+        .expect("File I/O error")
+        .write(&mut rewritten)
+        .expect("File I/O error");
+    rewritten.set_position(0);
+    assert_eq!(
+        GeoTiff::read(rewritten)
+            .expect("File I/O error")
+            .descriptive_tags,
+        geotiff.descriptive_tags
+    );
+}
+
+#[test]
+fn test_image_description() {
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut image = encoder.new_image::<colortype::Gray8>(1, 1).unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ImageDescription, "Band 1: NDVI")
+        .unwrap();
+    image.write_data(&[0]).unwrap();
+    data.set_position(0);
+
+    let metadata = GeoTiff::read_metadata(data.clone()).expect("File I/O error");
+    assert_eq!(metadata.image_description(), Some("Band 1: NDVI"));
+    let geotiff = GeoTiff::read(data).expect("File I/O error");
+    assert_eq!(geotiff.image_description(), Some("Band 1: NDVI"));
+
+    assert_eq!(
+        read_geotiff("resources/zh_dem_25.tif").image_description(),
+        None
+    );
+}
+
+#[test]
+fn test_read_tiles() {
+    for planar in [false, true] {
+        let geotiff = GeoTiff::read(write_tiled_image(planar)).expect("File I/O error");
+        assert_eq!(
+            geotiff.values::<u16>().unwrap(),
+            (0..20 * 18 * 3).collect::<Vec<u16>>()
+        );
+
+        // A window across all four tiles
+        let mut reader = GeoTiffReader::open(write_tiled_image(planar)).expect("File I/O error");
+        let region = reader
+            .read_region(&Window::new(14, 15, 4, 2))
+            .expect("File I/O error");
+        let expected: Vec<u16> = (15..17)
+            .flat_map(|y| {
+                (14..18).flat_map(move |x| [0, 1, 2].map(|sample| (y * 20 + x) * 3 + sample))
+            })
+            .collect();
+        assert_eq!(region.values::<u16>().unwrap(), expected);
+    }
+}
+
+#[test]
+fn test_read_samples() {
+    for planar in [false, true] {
+        let reader = GeoTiffReader::open(write_tiled_image(planar)).expect("File I/O error");
+        let geotiff = reader.with_samples(&[2, 0]).read().expect("File I/O error");
+        assert_eq!(geotiff.num_samples, 2);
+        assert_eq!(geotiff.band_formats().len(), 2);
+        assert_eq!(
+            geotiff.values::<u16>().unwrap(),
+            (0..20 * 18)
+                .flat_map(|pixel| [pixel * 3 + 2, pixel * 3])
+                .collect::<Vec<u16>>()
+        );
+
+        let mut reader = GeoTiffReader::open(write_tiled_image(planar))
+            .expect("File I/O error")
+            .with_samples(&[1])
+            .with_interleave(Interleave::Band);
+        let region = reader
+            .read_region(&Window::new(14, 15, 4, 2))
+            .expect("File I/O error");
+        let expected: Vec<u16> = (15..17)
+            .flat_map(|y| (14..18).map(move |x| (y * 20 + x) * 3 + 1))
+            .collect();
+        assert_eq!(region.values::<u16>().unwrap(), expected);
+
+        // Valid pixels are iterated by the index among the selected samples
+        let mut reader = GeoTiffReader::open(write_tiled_image(planar))
+            .expect("File I/O error")
+            .with_samples(&[2, 0]);
+        let mut values = reader
+            .valid_pixels::<u16>(1)
+            .unwrap()
+            .map(|pixel| pixel.map(|(_, value)| value))
+            .collect::<GeoTiffResult<Vec<_>>>()
+            .unwrap();
+        values.sort_unstable();
+        assert_eq!(
+            values,
+            (0..20 * 18).map(|pixel| pixel * 3).collect::<Vec<u16>>()
+        );
+        assert!(matches!(
+            reader.valid_pixels::<u16>(2),
+            Err(GeoTiffError::SampleOutOfBounds {
+                sample: 2,
+                num_samples: 2
+            })
+        ));
+
+        let reader = GeoTiffReader::open(write_tiled_image(planar))
+            .expect("File I/O error")
+            .with_samples(&[3]);
+        assert!(matches!(
+            reader.read(),
+            Err(GeoTiffError::SampleOutOfBounds {
+                sample: 3,
+                num_samples: 3
+            })
+        ));
+    }
+}
+
+#[test]
+fn test_zonal_stats() {
+    // 20x18 pixels in 16x16 tiles without a transform, whose first samples are three times
+    // their index
+    let polygons = [
+        Rect::new((0.0, 0.0), (20.0, 18.0)).to_polygon(),
+        // 4x3 pixels spanning all four tiles
+        Rect::new((14.0, 14.0), (18.0, 17.0)).to_polygon(),
+        Rect::new((100.0, 100.0), (101.0, 101.0)).to_polygon(),
+    ];
+    let mut reader = GeoTiffReader::open(write_tiled_image(false)).expect("File I/O error");
+    let statistics = reader.zonal_stats(&polygons, 0).expect("File I/O error");
+    assert_eq!(
+        statistics,
+        vec![
+            ZonalStatistics {
+                count: 360,
+                sum: 193860.0,
+                minimum: Some(0.0),
+                maximum: Some(1077.0),
+                mean: Some(538.5),
+            },
+            ZonalStatistics {
+                count: 12,
+                sum: 11358.0,
+                minimum: Some(882.0),
+                maximum: Some(1011.0),
+                mean: Some(946.5),
+            },
+            ZonalStatistics::default(),
+        ]
+    );
+    assert!(matches!(
+        reader.zonal_stats(&polygons, 3),
+        Err(GeoTiffError::SampleOutOfBounds { .. })
+    ));
+
+    let mut geotiff = reader.read().expect("File I/O error");
+    assert_eq!(geotiff.zonal_stats(&polygons, 0).unwrap(), statistics);
+    assert!(matches!(
+        geotiff.zonal_stats(&polygons, 3),
+        Err(GeoTiffError::SampleOutOfBounds { .. })
+    ));
+    // Pixels holding the no data value are skipped
+    geotiff.no_data = Some(882.0);
+    let statistics = geotiff.zonal_stats(&polygons[1..2], 0).unwrap();
+    assert_eq!(
+        (statistics[0].count, statistics[0].minimum),
+        (11, Some(885.0))
+    );
+}
+
+/// Writes a single-strip 2x1 image with 8 bits per sample, using the given tags in addition to or
+/// instead of the default ones.
+fn write_image_with_tags(tags: &[(Tag, &[u16])]) -> Cursor<Vec<u8>> {
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut directory = encoder.image_directory().unwrap();
+    let offset = directory.write_data(&[0u8, 0][..]).unwrap() as u32;
+
+    directory.write_tag(Tag::ImageWidth, 2u32).unwrap();
+    directory.write_tag(Tag::ImageLength, 1u32).unwrap();
+    directory.write_tag(Tag::BitsPerSample, 8u16).unwrap();
+    directory.write_tag(Tag::Compression, 1u16).unwrap();
+    directory
+        .write_tag(Tag::PhotometricInterpretation, 1u16)
+        .unwrap();
+    directory.write_tag(Tag::StripOffsets, offset).unwrap();
+    directory.write_tag(Tag::RowsPerStrip, 1u32).unwrap();
+    directory.write_tag(Tag::StripByteCounts, 2u32).unwrap();
+    for (tag, value) in tags {
+        directory.write_tag(*tag, *value).unwrap();
+    }
+    directory.finish().unwrap();
+
+    data.set_position(0);
+    data
+}
+
+#[test]
+fn test_unsupported_features() {
+    assert_eq!(probe_support(write_image_with_tags(&[])).unwrap(), vec![]);
+    assert!(GeoTiff::read(write_image_with_tags(&[])).is_ok());
+
+    let data = write_image_with_tags(&[
+        (Tag::Compression, &[3]),
+        (Tag::PhotometricInterpretation, &[4]),
+        (Tag::Predictor, &[3]),
+    ]);
+    assert_eq!(
+        probe_support(data.clone()).unwrap(),
+        vec![
+            Unsupported {
+                feature: TiffFeature::Compression,
+                value: 3
+            },
+            Unsupported {
+                feature: TiffFeature::PhotometricInterpretation,
+                value: 4
+            },
+            Unsupported {
+                feature: TiffFeature::Predictor,
+                value: 3
+            },
+        ]
+    );
+    assert!(matches!(
+        GeoTiff::read(data),
+        Err(GeoTiffError::Unsupported(Unsupported {
+            feature: TiffFeature::Compression,
+            value: 3
+        }))
+    ));
+
+    let data = write_image_with_tags(&[(Tag::BitsPerSample, &[24])]);
+    assert_eq!(
+        probe_support(data).unwrap(),
+        vec![Unsupported {
+            feature: TiffFeature::BitsPerSample,
+            value: 24
+        }]
+    );
+
+    // Multiple WhiteIsZero samples cannot be inverted
+    let data = write_image_with_tags(&[
+        (Tag::SamplesPerPixel, &[2]),
+        (Tag::BitsPerSample, &[8, 8]),
+        (Tag::PhotometricInterpretation, &[0]),
+    ]);
+    assert_eq!(
+        probe_support(data).unwrap(),
+        vec![Unsupported {
+            feature: TiffFeature::SamplesPerPixel,
+            value: 2
+        }]
+    );
+
+    let data =
+        write_image_with_tags(&[(Tag::SamplesPerPixel, &[2]), (Tag::BitsPerSample, &[8, 16])]);
+    assert_eq!(
+        probe_support(data).unwrap(),
+        vec![Unsupported {
+            feature: TiffFeature::BitsPerSample,
+            value: 16
+        }]
+    );
+}
+
+#[test]
+fn test_read_ycbcr() {
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut directory = encoder.image_directory().unwrap();
+    let offset = directory
+        .write_data(&[128u8, 128, 128, 76, 85, 255][..])
+        .unwrap() as u32;
+    directory.write_tag(Tag::ImageWidth, 2u32).unwrap();
+    directory.write_tag(Tag::ImageLength, 1u32).unwrap();
+    directory
+        .write_tag(Tag::BitsPerSample, &[8u16; 3][..])
+        .unwrap();
+    directory.write_tag(Tag::SamplesPerPixel, 3u16).unwrap();
+    directory.write_tag(Tag::Compression, 1u16).unwrap();
+    directory
+        .write_tag(Tag::PhotometricInterpretation, 6u16)
+        .unwrap();
+    directory
+        .write_tag(Tag::Unknown(530), &[1u16, 1][..])
+        .unwrap();
+    directory.write_tag(Tag::StripOffsets, offset).unwrap();
+    directory.write_tag(Tag::RowsPerStrip, 1u32).unwrap();
+    directory.write_tag(Tag::StripByteCounts, 6u32).unwrap();
+    directory.finish().unwrap();
+    data.set_position(0);
+
+    let geotiff = GeoTiff::read(data).unwrap();
+    assert_eq!(
+        geotiff.values::<u8>().unwrap(),
+        vec![128, 128, 128, 254, 0, 0]
+    );
+
+    // Without JPEG compression, subsampled chroma is not supported
+    let data = write_image_with_tags(&[
+        (Tag::SamplesPerPixel, &[3]),
+        (Tag::BitsPerSample, &[8, 8, 8]),
+        (Tag::PhotometricInterpretation, &[6]),
+    ]);
+    assert_eq!(
+        probe_support(data).unwrap(),
+        vec![Unsupported {
+            feature: TiffFeature::PhotometricInterpretation,
+            value: 6
+        }]
+    );
+}
+
+#[test]
+fn test_read_packed_samples() {
+    // 1-bit samples in strips, with rows padded to whole bytes
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut directory = encoder.image_directory().unwrap();
+    let offset = directory
+        .write_data(&[0b1010_0000u8, 0b1100_0000, 0xff, 0][..])
+        .unwrap() as u32;
+    directory.write_tag(Tag::ImageWidth, 10u32).unwrap();
+    directory.write_tag(Tag::ImageLength, 2u32).unwrap();
+    directory.write_tag(Tag::BitsPerSample, 1u16).unwrap();
+    directory.write_tag(Tag::Compression, 1u16).unwrap();
+    directory
+        .write_tag(Tag::PhotometricInterpretation, 1u16)
+        .unwrap();
+    directory.write_tag(Tag::StripOffsets, offset).unwrap();
+    directory.write_tag(Tag::RowsPerStrip, 2u32).unwrap();
+    directory.write_tag(Tag::StripByteCounts, 4u32).unwrap();
+    directory.finish().unwrap();
+    data.set_position(0);
+
+    let geotiff = GeoTiff::read(data).unwrap();
+    assert_eq!(geotiff.data_type(), RasterDataType::U8);
+    assert_eq!(geotiff.band_formats()[0].bits_per_sample, 1);
+    assert_eq!(
+        geotiff.values::<u8>().unwrap(),
+        vec![1, 0, 1, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0]
+    );
+
+    // 4-bit samples in 16x16 tiles, of which the second one is partial
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut directory = encoder.image_directory().unwrap();
+    // Rows of the samples 0 to 15 in turn
+    let tile: Vec<u8> = (0..16).flat_map(|_| (0..8).map(|i| i * 0x22 + 1)).collect();
+    let offsets = [
+        directory.write_data(&tile[..]).unwrap() as u32,
+        directory.write_data(&tile[..]).unwrap() as u32,
+    ];
+    directory.write_tag(Tag::ImageWidth, 20u32).unwrap();
+    directory.write_tag(Tag::ImageLength, 2u32).unwrap();
+    directory.write_tag(Tag::BitsPerSample, 4u16).unwrap();
+    directory.write_tag(Tag::Compression, 1u16).unwrap();
+    directory
+        .write_tag(Tag::PhotometricInterpretation, 1u16)
+        .unwrap();
+    directory.write_tag(Tag::TileWidth, 16u32).unwrap();
+    directory.write_tag(Tag::TileLength, 16u32).unwrap();
+    directory.write_tag(Tag::TileOffsets, &offsets[..]).unwrap();
+    directory
+        .write_tag(Tag::TileByteCounts, &[128u32, 128][..])
+        .unwrap();
+    directory.finish().unwrap();
+    data.set_position(0);
+
+    let row: Vec<u8> = (0..20).map(|x| x % 16).collect();
+    let mut reader = GeoTiffReader::open(data).unwrap();
+    assert_eq!(
+        reader
+            .get_value_at::<u8>(&Coord { x: 17.5, y: 0.5 }, 0)
+            .unwrap(),
+        Some(1)
+    );
+    let region = reader.read_region(&Window::new(14, 1, 6, 1)).unwrap();
+    assert_eq!(region.values::<u8>().unwrap(), row[14..]);
+    let geotiff = reader.read().unwrap();
+    assert_eq!(geotiff.values::<u8>().unwrap(), [row.clone(), row].concat());
+}
+
+#[test]
+fn test_read_12_bit_samples() {
+    // 12-bit samples in strips, with rows padded to whole bytes
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut directory = encoder.image_directory().unwrap();
+    let offset = directory
+        .write_data(&[0x12u8, 0x34, 0x56, 0x78, 0x90, 0xff, 0xf0, 0x00, 0xab, 0xc0][..])
+        .unwrap() as u32;
+    directory.write_tag(Tag::ImageWidth, 3u32).unwrap();
+    directory.write_tag(Tag::ImageLength, 2u32).unwrap();
+    directory.write_tag(Tag::BitsPerSample, 12u16).unwrap();
+    directory.write_tag(Tag::Compression, 1u16).unwrap();
+    directory
+        .write_tag(Tag::PhotometricInterpretation, 1u16)
+        .unwrap();
+    directory.write_tag(Tag::StripOffsets, offset).unwrap();
+    directory.write_tag(Tag::RowsPerStrip, 2u32).unwrap();
+    directory.write_tag(Tag::StripByteCounts, 10u32).unwrap();
+    directory.finish().unwrap();
+    data.set_position(0);
+
+    let metadata = GeoTiff::read_metadata(data.clone()).unwrap();
+    assert_eq!(metadata.bits_per_sample(), [12]);
+    let geotiff = GeoTiff::read(data).unwrap();
+    assert_eq!(geotiff.data_type(), RasterDataType::U16);
+    assert_eq!(geotiff.bits_per_sample(), [12]);
+    assert_eq!(
+        geotiff.values::<u16>().unwrap(),
+        vec![0x123, 0x456, 0x789, 0xfff, 0x000, 0xabc]
+    );
+
+    // 12-bit samples in 16x16 tiles, of which the second one is partial
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut directory = encoder.image_directory().unwrap();
+    // Rows of the samples 0x000 to 0xfff in steps of 0x111
+    let tile: Vec<u8> = (0..16)
+        .flat_map(|_| {
+            (0..8u16).flat_map(|i| {
+                let (a, b) = (i * 0x222, i * 0x222 + 0x111);
+                [(a >> 4) as u8, ((a & 0xf) << 4 | b >> 8) as u8, b as u8]
+            })
+        })
+        .collect();
+    let offsets = [
+        directory.write_data(&tile[..]).unwrap() as u32,
+        directory.write_data(&tile[..]).unwrap() as u32,
+    ];
+    directory.write_tag(Tag::ImageWidth, 20u32).unwrap();
+    directory.write_tag(Tag::ImageLength, 2u32).unwrap();
+    directory.write_tag(Tag::BitsPerSample, 12u16).unwrap();
+    directory.write_tag(Tag::Compression, 1u16).unwrap();
+    directory
+        .write_tag(Tag::PhotometricInterpretation, 1u16)
+        .unwrap();
+    directory.write_tag(Tag::TileWidth, 16u32).unwrap();
+    directory.write_tag(Tag::TileLength, 16u32).unwrap();
+    directory.write_tag(Tag::TileOffsets, &offsets[..]).unwrap();
+    directory
+        .write_tag(Tag::TileByteCounts, &[384u32, 384][..])
+        .unwrap();
+    directory.finish().unwrap();
+    data.set_position(0);
+
+    let row: Vec<u16> = (0..20).map(|x| x % 16 * 0x111).collect();
+    let mut reader = GeoTiffReader::open(data).unwrap();
+    assert_eq!(reader.bits_per_sample(), [12]);
+    let region = reader.read_region(&Window::new(14, 1, 6, 1)).unwrap();
+    assert_eq!(region.values::<u16>().unwrap(), row[14..]);
+    let geotiff = reader.read().unwrap();
+    assert_eq!(
+        geotiff.values::<u16>().unwrap(),
+        [row.clone(), row].concat()
+    );
+}
+
+#[test]
+fn test_read_complex_integers() {
+    let values = [
+        (3, 4),
+        (-1, 0),
+        (0, 2),
+        (0, 0),
+        (-5, -12),
+        (i16::MAX, i16::MIN),
+    ];
+    let write_image = |bits_per_sample: u16| {
+        let bytes: Vec<u8> = values
+            .iter()
+            .flat_map(|&(re, im)| match bits_per_sample {
+                32 => [re.to_le_bytes(), im.to_le_bytes()].concat(),
+                _ => [(re as i32).to_le_bytes(), (im as i32).to_le_bytes()].concat(),
+            })
+            .collect();
+        let mut data = Cursor::new(Vec::new());
+        let mut encoder = TiffEncoder::new(&mut data).unwrap();
+        let mut directory = encoder.image_directory().unwrap();
+        let offset = directory.write_data(&bytes[..]).unwrap() as u32;
+        directory.write_tag(Tag::ImageWidth, 3u32).unwrap();
+        directory.write_tag(Tag::ImageLength, 2u32).unwrap();
+        directory
+            .write_tag(Tag::BitsPerSample, bits_per_sample)
+            .unwrap();
+        directory.write_tag(Tag::SampleFormat, 5u16).unwrap();
+        directory.write_tag(Tag::Compression, 1u16).unwrap();
+        directory
+            .write_tag(Tag::PhotometricInterpretation, 1u16)
+            .unwrap();
+        directory.write_tag(Tag::StripOffsets, offset).unwrap();
+        directory.write_tag(Tag::RowsPerStrip, 2u32).unwrap();
+        directory
+            .write_tag(Tag::StripByteCounts, bytes.len() as u32)
+            .unwrap();
+        directory.finish().unwrap();
+        data.set_position(0);
+        data
+    };
+
+    let geotiff = GeoTiff::read(write_image(64)).unwrap();
+    assert_eq!(geotiff.data_type(), RasterDataType::CI32);
+    let raster_data = geotiff.into_raster_data();
+    assert!(raster_data.as_cint16().is_none());
+    assert_eq!(
+        raster_data.as_cint32().unwrap(),
+        values.map(|(re, im)| Complex::new(re as i32, im as i32))
+    );
+
+    let geotiff = GeoTiff::read(write_image(32)).unwrap();
+    assert_eq!(geotiff.data_type(), RasterDataType::CI16);
+    assert!(geotiff.band_formats()[0].is_complex());
+    assert_eq!(
+        geotiff.get_value_at::<f64>(&Coord { x: 1.5, y: 0.5 }, 0),
+        Some(-1.0)
+    );
+    assert!(matches!(
+        geotiff.try_get_value_at::<f64>(&Coord { x: 0.5, y: 0.5 }, 0),
+        Err(GeoTiffError::ValueConversion { .. })
+    ));
+
+    // Written as tiles with the horizontal predictor, whose differences wrap around
+    let options = WriteOptions::default()
+        .with_compression(Compression::Deflate)
+        .with_predictor(Predictor::Horizontal)
+        .with_tile_size(16, 16);
+    let mut data = Cursor::new(Vec::new());
+    geotiff.write_with_options(&mut data, &options).unwrap();
+    data.set_position(0);
+    let raster_data = GeoTiff::read(data).unwrap().into_raster_data();
+    assert_eq!(
+        raster_data.as_cint16().unwrap(),
+        values.map(|(re, im)| Complex::new(re, im))
+    );
+
+    let magnitude = raster_data.magnitude();
+    assert_eq!(magnitude[..5], [5.0, 1.0, 2.0, 0.0, 13.0]);
+    let phase = raster_data.phase();
+    assert_eq!(phase[..4], [4f64.atan2(3.0), PI, FRAC_PI_2, 0.0]);
+    assert_eq!(RasterData::I8(vec![-2, 2]).magnitude(), [2.0, 2.0]);
+    assert_eq!(RasterData::I8(vec![-2, 2]).phase(), [PI, 0.0]);
+}
+
+#[test]
+fn test_read_complex_floats() {
+    let values: [(f64, f64); 4] = [(3.0, 4.0), (-1.5, 0.0), (0.0, -2.0), (f64::NAN, 0.25)];
+    let write_image = |bits_per_sample: u16| {
+        let bytes: Vec<u8> = values
+            .iter()
+            .flat_map(|&(re, im)| match bits_per_sample {
+                64 => [(re as f32).to_le_bytes(), (im as f32).to_le_bytes()].concat(),
+                _ => [re.to_le_bytes(), im.to_le_bytes()].concat(),
+            })
+            .collect();
+        let mut data = Cursor::new(Vec::new());
+        let mut encoder = TiffEncoder::new(&mut data).unwrap();
+        let mut directory = encoder.image_directory().unwrap();
+        let offset = directory.write_data(&bytes[..]).unwrap() as u32;
+        directory.write_tag(Tag::ImageWidth, 2u32).unwrap();
+        directory.write_tag(Tag::ImageLength, 2u32).unwrap();
+        directory
+            .write_tag(Tag::BitsPerSample, bits_per_sample)
+            .unwrap();
+        directory.write_tag(Tag::SampleFormat, 6u16).unwrap();
+        directory.write_tag(Tag::Compression, 1u16).unwrap();
+        directory
+            .write_tag(Tag::PhotometricInterpretation, 1u16)
+            .unwrap();
+        directory.write_tag(Tag::StripOffsets, offset).unwrap();
+        directory.write_tag(Tag::RowsPerStrip, 2u32).unwrap();
+        directory
+            .write_tag(Tag::StripByteCounts, bytes.len() as u32)
+            .unwrap();
+        directory.finish().unwrap();
+        data.set_position(0);
+        data
+    };
+
+    for (bits_per_sample, data_type) in [(64, RasterDataType::CF32), (128, RasterDataType::CF64)] {
+        let geotiff = GeoTiff::read(write_image(bits_per_sample)).unwrap();
+        assert_eq!(geotiff.data_type(), data_type);
+
+        // Written as compressed tiles, with the horizontal predictor if it applies
+        let mut options = WriteOptions::default()
+            .with_compression(Compression::Deflate)
+            .with_tile_size(16, 16);
+        if bits_per_sample == 64 {
+            options = options.with_predictor(Predictor::Horizontal);
+        }
+        let mut data = Cursor::new(Vec::new());
+        geotiff.write_with_options(&mut data, &options).unwrap();
+        data.set_position(0);
+        let raster_data = GeoTiff::read(data).unwrap().into_raster_data();
+
+        let written: Vec<_> = match data_type {
+            RasterDataType::CF32 => raster_data
+                .as_cfloat32()
+                .unwrap()
+                .iter()
+                .map(|value| (value.re as f64, value.im as f64))
+                .collect(),
+            _ => raster_data
+                .as_cfloat64()
+                .unwrap()
+                .iter()
+                .map(|value| (value.re, value.im))
+                .collect(),
+        };
+        assert_eq!(written[..3], values[..3]);
+        assert!(written[3].0.is_nan());
+        assert_eq!(written[3].1, 0.25);
+        assert_eq!(raster_data.magnitude()[..3], [5.0, 1.5, 2.0]);
+        assert_eq!(raster_data.phase()[..3], [4f64.atan2(3.0), PI, -FRAC_PI_2]);
+    }
+
+    // The horizontal predictor does not apply to samples of 128 bits
+    let options = WriteOptions::default().with_predictor(Predictor::Horizontal);
+    assert!(matches!(
+        GeoTiff::read(write_image(128))
+            .unwrap()
+            .write_with_options(Cursor::new(Vec::new()), &options),
+        Err(GeoTiffError::Format(_))
+    ));
+}
+
+#[test]
+fn test_chunk_verification() {
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut directory = encoder.image_directory().unwrap();
+    let offset = directory.write_data(&[1u8, 2, 3, 4, 5, 6][..]).unwrap() as u32;
+
+    directory.write_tag(Tag::ImageWidth, 2u32).unwrap();
+    directory.write_tag(Tag::ImageLength, 3u32).unwrap();
+    directory.write_tag(Tag::BitsPerSample, 8u16).unwrap();
+    directory.write_tag(Tag::Compression, 1u16).unwrap();
+    directory
+        .write_tag(Tag::PhotometricInterpretation, 1u16)
+        .unwrap();
+    directory.write_tag(Tag::RowsPerStrip, 1u32).unwrap();
+    // The second strip lies beyond the end of the file, the third one is too short
+    directory
+        .write_tag(Tag::StripOffsets, &[offset, 1_000_000, offset + 4][..])
+        .unwrap();
+    directory
+        .write_tag(Tag::StripByteCounts, &[2u32, 2, 1][..])
+        .unwrap();
+    directory.write_tag(Tag::GdalNodata, "255").unwrap();
+    directory.finish().unwrap();
+
+    data.set_position(0);
+    assert!(GeoTiff::read(data.clone()).is_err());
+
+    let mut reader = GeoTiffReader::open(data).expect("File I/O error");
+    let corrupt_chunks = reader.verify_chunks().expect("File I/O error");
+    assert_eq!(
+        corrupt_chunks
+            .iter()
+            .map(|chunk| (chunk.index, chunk.window))
+            .collect::<Vec<_>>(),
+        vec![(1, Window::new(0, 1, 2, 1)), (2, Window::new(0, 2, 2, 1))]
+    );
+
+    let geotiff = reader
+        .with_chunk_verification(true)
+        .read()
+        .expect("File I/O error");
+    assert_eq!(geotiff.corrupt_chunks(), &corrupt_chunks[..]);
+    assert_eq!(
+        geotiff.values::<u8>().unwrap(),
+        vec![1, 2, 255, 255, 255, 255]
+    );
+    assert!(geotiff
+        .corrupt_chunks()
+        .iter()
+        .all(|CorruptChunk { reason, .. }| !reason.is_empty()));
+}
+
+#[test]
+fn test_band_formats() {
+    let geotiff = read_geotiff("resources/marbles.tif");
+    assert_eq!(
+        geotiff.band_formats(),
+        &[BandFormat {
+            sample_format: SampleFormat::Uint,
+            bits_per_sample: 8
+        }; 3]
+    );
+    assert_eq!(geotiff.data_type(), RasterDataType::U8);
+
+    let reader =
+        GeoTiffReader::open(File::open("resources/zh_dem_25.tif").expect("File I/O error"))
+            .expect("File I/O error");
+    assert_eq!(
+        reader.band_formats(),
+        &[BandFormat {
+            sample_format: SampleFormat::Int,
+            bits_per_sample: 16
+        }]
+    );
+    assert_eq!(reader.data_type(), RasterDataType::I16);
+    assert_eq!(
+        reader.read().expect("File I/O error").data_type(),
+        RasterDataType::I16
+    );
+
+    // Samples of different formats cannot be decoded into a single type
+    let data = write_image_with_tags(&[
+        (Tag::SamplesPerPixel, &[3]),
+        (Tag::PhotometricInterpretation, &[2]),
+        (Tag::BitsPerSample, &[8, 8, 8]),
+        (Tag::SampleFormat, &[1, 2, 1]),
+    ]);
+    assert_eq!(
+        probe_support(data.clone()).unwrap(),
+        vec![Unsupported {
+            feature: TiffFeature::SampleFormat,
+            value: 2
+        }]
+    );
+    assert!(matches!(
+        GeoTiffReader::open(data),
+        Err(GeoTiffError::Unsupported(_))
+    ));
+}
+
+#[test]
+fn test_bigtiff() {
+    let geotiff = read_geotiff("resources/merc.tif");
+    assert!(!geotiff.is_bigtiff());
+
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new_big(&mut data).unwrap();
+    let mut directory = encoder.image_directory().unwrap();
+    let offset = directory.write_data(&[1u8, 2, 3, 4][..]).unwrap();
+    directory.write_tag(Tag::ImageWidth, 2u32).unwrap();
+    directory.write_tag(Tag::ImageLength, 2u32).unwrap();
+    directory.write_tag(Tag::BitsPerSample, 8u16).unwrap();
+    directory.write_tag(Tag::Compression, 1u16).unwrap();
+    directory
+        .write_tag(Tag::PhotometricInterpretation, 1u16)
+        .unwrap();
+    directory.write_tag(Tag::StripOffsets, offset).unwrap();
+    directory.write_tag(Tag::RowsPerStrip, 2u32).unwrap();
+    directory.write_tag(Tag::StripByteCounts, 4u32).unwrap();
+    directory.finish().unwrap();
+    data.set_position(0);
+
+    let reader = GeoTiffReader::open(data.clone()).expect("Could not open BigTIFF");
+    assert!(reader.is_bigtiff());
+    let geotiff = GeoTiff::read(data).expect("Could not read BigTIFF");
+    assert!(geotiff.is_bigtiff());
+    assert_eq!(geotiff.values::<u8>().unwrap(), vec![1, 2, 3, 4]);
+
+    // BigTIFFs are written as BigTIFFs again
+    let mut written = Cursor::new(Vec::new());
+    geotiff
+        .write(&mut written)
+        .expect("Could not write GeoTIFF");
+    assert_eq!(&written.get_ref()[2..4], &43u16.to_le_bytes());
+    written.set_position(0);
+    let geotiff = GeoTiff::read(written).expect("Could not read written BigTIFF");
+    assert!(geotiff.is_bigtiff());
+    assert_eq!(geotiff.values::<u8>().unwrap(), vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn test_write_compressed() {
+    let geotiff = read_geotiff("resources/marbles.tif");
+
+    let mut uncompressed = Cursor::new(Vec::new());
+    geotiff
+        .write(&mut uncompressed)
+        .expect("Could not write GeoTIFF");
+
+    for compression in [
+        Compression::Lzw,
+        Compression::Deflate,
+        Compression::PackBits,
+        #[cfg(feature = "zstd")]
+        Compression::Zstd,
+        #[cfg(feature = "lzma")]
+        Compression::Lzma,
+    ] {
+        let write = |num_threads| {
+            let mut data = Cursor::new(Vec::new());
+            let options = WriteOptions::default()
+                .with_compression(compression)
+                .with_num_threads(NonZeroUsize::new(num_threads).unwrap());
+            geotiff
+                .write_with_options(&mut data, &options)
+                .expect("Could not write GeoTIFF");
+            data
+        };
+
+        // The output does not depend on the number of threads compressing the strips
+        let mut data = write(1);
+        assert_eq!(data.get_ref(), write(3).get_ref());
+        if compression != Compression::PackBits {
+            assert!(data.get_ref().len() < uncompressed.get_ref().len());
+        }
+
+        data.set_position(0);
+        let written = GeoTiff::read(data).expect("Could not read written GeoTIFF");
+        assert_eq!(
+            written.values::<u8>().unwrap(),
+            geotiff.values::<u8>().unwrap()
+        );
+
+        // Partial tiles at the edges are padded
+        let mut data = Cursor::new(Vec::new());
+        let options = WriteOptions::default()
+            .with_compression(compression)
+            .with_tile_size(48, 48);
+        geotiff
+            .write_with_options(&mut data, &options)
+            .expect("Could not write GeoTIFF");
+        data.set_position(0);
+        let written = GeoTiff::read(data).expect("Could not read written GeoTIFF");
+        assert_eq!(
+            written.values::<u8>().unwrap(),
+            geotiff.values::<u8>().unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_write_predictor() {
+    let dem = read_geotiff("resources/zh_dem_25.tif");
+    for (geotiff, predictor) in [
+        (read_geotiff("resources/marbles.tif"), Predictor::Horizontal),
+        (dem.map(|values| values[0]), Predictor::FloatingPoint),
+        (dem, Predictor::Horizontal),
+    ] {
+        for tile_size in [None, Some((48, 48))] {
+            let write = |predictor| {
+                let mut options = WriteOptions::default()
+                    .with_compression(Compression::Deflate)
+                    .with_predictor(predictor);
+                if let Some((width, height)) = tile_size {
+                    options = options.with_tile_size(width, height);
+                }
+                let mut data = Cursor::new(Vec::new());
+                geotiff
+                    .write_with_options(&mut data, &options)
+                    .expect("Could not write GeoTIFF");
+                data.set_position(0);
+                data
+            };
+
+            let data = write(predictor);
+            // The elevations compress better with the predictor
+            if geotiff.num_samples == 1 {
+                assert!(data.get_ref().len() < write(Predictor::None).get_ref().len());
+            }
+            let written = GeoTiff::read(data).expect("Could not read written GeoTIFF");
+            assert_eq!(written.data_type(), geotiff.data_type());
+            assert_eq!(
+                written.values::<f64>().unwrap(),
+                geotiff.values::<f64>().unwrap()
+            );
+        }
+    }
+
+    let options = WriteOptions::default().with_predictor(Predictor::FloatingPoint);
+    assert!(matches!(
+        read_geotiff("resources/marbles.tif").write_with_options(Cursor::new(Vec::new()), &options),
+        Err(GeoTiffError::Format(_))
+    ));
+}
+
+#[test]
+fn test_into_vec() {
+    let geotiff = read_geotiff("resources/marbles.tif");
+    let values = geotiff.values::<u8>().unwrap();
+    assert_eq!(geotiff.into_vec::<u8>().expect("Matching type"), values);
+
+    let geotiff = read_geotiff("resources/marbles.tif");
+    let converted = geotiff.into_vec::<f32>().expect("Representable values");
+    assert_eq!(
+        converted[..3],
+        values[..3].iter().map(|&v| v as f32).collect::<Vec<_>>()
+    );
+    assert!(matches!(
+        read_geotiff("resources/marbles.tif").into_vec::<i8>(),
+        Err(GeoTiffError::ValueConversion { .. })
+    ));
+
+    let raster_data = read_geotiff("resources/marbles.tif").into_raster_data();
+    assert_eq!(raster_data.data_type(), RasterDataType::U8);
+    assert_eq!(raster_data.len(), values.len());
+    match raster_data {
+        RasterData::U8(data) => assert_eq!(data, values),
+        _ => panic!("Expected u8 values"),
+    }
+}
+
+#[test]
+fn test_band_view() {
+    let geotiff = read_geotiff("resources/marbles.tif");
+    let values = geotiff.values::<u8>().unwrap();
+    let band = geotiff.band(1).unwrap();
+    assert_eq!(band.sample(), 1);
+    assert_eq!(band.dimensions(), (1419, 1001));
+    assert_eq!(band.data_type(), RasterDataType::U8);
+    assert_eq!(band.get::<u8>(2, 1), Some(values[(1419 + 2) * 3 + 1]));
+    assert_eq!(band.get::<u8>(1419, 0), None);
+    let green: Vec<u8> = band.iter().collect();
+    assert_eq!(
+        green,
+        values
+            .iter()
+            .skip(1)
+            .step_by(3)
+            .copied()
+            .collect::<Vec<_>>()
+    );
+    // The values of pixel-interleaved bands are not contiguous
+    assert_eq!(band.as_slice::<u8>(), None);
+    assert_eq!(geotiff.bands().count(), 3);
+    assert!(matches!(
+        geotiff.band(3),
+        Err(GeoTiffError::SampleOutOfBounds { sample: 3, .. })
+    ));
+
+    let geotiff = geotiff
+        .into_layout(Interleave::Band, ArrayOrder::RowMajor)
+        .expect("File I/O error");
+    let band = geotiff.band(1).unwrap();
+    assert_eq!(band.iter::<u8>().collect::<Vec<_>>(), green);
+    assert_eq!(band.as_slice::<u8>(), Some(&green[..]));
+    assert_eq!(band.as_slice::<u16>(), None);
+}
+
+#[test]
+fn test_map() {
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    encoder
+        .write_image::<colortype::RGB8>(2, 1, &[10, 30, 5, 20, 20, 0])
+        .unwrap();
+    data.set_position(0);
+    let mut geotiff = GeoTiff::read(data).expect("File I/O error");
+
+    // A normalized difference of the first two samples
+    let index = |values: &[f64]| (values[1] - values[0]) / (values[1] + values[0]);
+    let mapped = geotiff.map(index);
+    assert_eq!(mapped.num_samples, 1);
+    assert_eq!(mapped.data_type(), RasterDataType::F32);
+    assert_eq!(mapped.values::<f32>().unwrap(), vec![0.5, 0.0]);
+    assert!(mapped.no_data.is_some_and(f64::is_nan));
+
+    // Pixels with a sample holding the no data value are not computed
+    geotiff.no_data = Some(0.0);
+    let mapped = geotiff.map(index);
+    assert_eq!(mapped.values::<f32>().unwrap()[0], 0.5);
+    assert!(mapped.values::<f32>().unwrap()[1].is_nan());
+
+    // 32-bit integers are promoted to 64-bit floats
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    encoder
+        .write_image::<colortype::Gray32>(1, 1, &[16777217])
+        .unwrap();
+    data.set_position(0);
+    let geotiff = GeoTiff::read(data).expect("File I/O error");
+    let mapped = geotiff.map(|values| values[0] + 1.0);
+    assert_eq!(mapped.data_type(), RasterDataType::F64);
+    assert_eq!(mapped.values::<f64>().unwrap(), vec![16777218.0]);
+}
+
+#[test]
+fn test_write_interleave() {
+    let geotiff = read_geotiff("resources/marbles.tif");
+    assert_eq!(geotiff.interleave(), Interleave::Pixel);
+    let band_sequential = read_geotiff("resources/marbles.tif")
+        .into_layout(Interleave::Band, ArrayOrder::ColumnMajor)
+        .expect("File I/O error");
+
+    let write = |geotiff: &GeoTiff, options: WriteOptions| {
+        let mut data = Cursor::new(Vec::new());
+        geotiff
+            .write_with_options(&mut data, &options.with_compression(Compression::Lzw))
+            .expect("Could not write GeoTIFF");
+        data.into_inner()
+    };
+    let planar = write(
+        &geotiff,
+        WriteOptions::default().with_interleave(Interleave::Band),
+    );
+    assert_eq!(planar, write(&band_sequential, WriteOptions::default()));
+    assert_eq!(
+        write(
+            &band_sequential,
+            WriteOptions::default().with_interleave(Interleave::Pixel)
+        ),
+        write(&geotiff, WriteOptions::default())
+    );
+
+    // Each band is split into the same strips
+    let mut decoder = Decoder::new(Cursor::new(&planar)).expect("Could not read TIFF");
+    assert_eq!(
+        decoder.get_tag_u32(Tag::PlanarConfiguration).unwrap(),
+        PlanarConfiguration::Planar.to_u16() as u32
+    );
+    let rows_per_strip = decoder.get_tag_u32(Tag::RowsPerStrip).unwrap() as usize;
+    let strips_per_band = geotiff.raster_height.div_ceil(rows_per_strip);
+    assert!(strips_per_band > 1);
+    assert_eq!(
+        decoder.get_tag_u64_vec(Tag::StripOffsets).unwrap().len(),
+        strips_per_band * geotiff.num_samples
+    );
+
+    let written = GeoTiff::read(Cursor::new(planar)).expect("Could not read written GeoTIFF");
+    assert_eq!(
+        written.values::<u8>().unwrap(),
+        geotiff.values::<u8>().unwrap()
+    );
+}
+
+#[test]
+fn test_write_strip_size() {
+    let geotiff = read_geotiff("resources/marbles.tif");
+    let rows_per_strip = |options: WriteOptions| {
+        let mut data = Cursor::new(Vec::new());
+        geotiff
+            .write_with_options(&mut data, &options)
+            .expect("Could not write GeoTIFF");
+        let mut decoder = Decoder::new(Cursor::new(data.get_ref())).expect("Could not read TIFF");
+        let strips = decoder.get_tag_u64_vec(Tag::StripOffsets).unwrap().len();
+        let rows_per_strip = decoder.get_tag_u32(Tag::RowsPerStrip).unwrap();
+
+        data.set_position(0);
+        let written = GeoTiff::read(data).expect("Could not read written GeoTIFF");
+        assert_eq!(
+            written.values::<u8>().unwrap(),
+            geotiff.values::<u8>().unwrap()
+        );
+        (rows_per_strip, strips)
+    };
+
+    // Rows of 1419 RGB pixels take 4257 bytes
+    assert_eq!(rows_per_strip(WriteOptions::default()), (15, 67));
+    assert_eq!(
+        rows_per_strip(
+            WriteOptions::default().with_rows_per_strip(NonZeroUsize::new(100).unwrap())
+        ),
+        (100, 11)
+    );
+    assert_eq!(
+        rows_per_strip(WriteOptions::default().with_rows_per_strip(NonZeroUsize::MAX)),
+        (1001, 1)
+    );
+    assert_eq!(
+        rows_per_strip(WriteOptions::default().with_strip_size(NonZeroUsize::new(10_000).unwrap())),
+        (2, 501)
+    );
+    assert_eq!(
+        rows_per_strip(WriteOptions::default().with_strip_size(NonZeroUsize::new(1).unwrap())),
+        (1, 1001)
+    );
+}
+
+#[test]
+fn test_write_tiles() {
+    let geotiff = read_geotiff("resources/marbles.tif");
+    for interleave in [Interleave::Pixel, Interleave::Band] {
+        let mut data = Cursor::new(Vec::new());
+        geotiff
+            .write_with_options(
+                &mut data,
+                &WriteOptions::default()
+                    .with_compression(Compression::Deflate)
+                    .with_interleave(interleave)
+                    .with_tile_size(256, 512),
+            )
+            .expect("Could not write GeoTIFF");
+
+        let mut decoder = Decoder::new(Cursor::new(data.get_ref())).expect("Could not read TIFF");
+        assert_eq!(decoder.get_tag_u32(Tag::TileWidth).unwrap(), 256);
+        assert_eq!(decoder.get_tag_u32(Tag::TileLength).unwrap(), 512);
+        // The tiles of 1419x1001 pixels are padded at the right and bottom edges
+        let num_planes = if interleave == Interleave::Band { 3 } else { 1 };
+        assert_eq!(
+            decoder.get_tag_u64_vec(Tag::TileOffsets).unwrap().len(),
+            6 * 2 * num_planes
+        );
+
+        data.set_position(0);
+        let mut reader = GeoTiffReader::open(data).expect("Could not read written GeoTIFF");
+        assert!(reader.storage_report().expect("File I/O error").images[0].tiled);
+        let written = reader.read().expect("File I/O error");
+        assert_eq!(
+            written
+                .into_layout(Interleave::Pixel, ArrayOrder::RowMajor)
+                .expect("File I/O error")
+                .values::<u8>()
+                .unwrap(),
+            geotiff.values::<u8>().unwrap()
+        );
+    }
+
+    assert!(matches!(
+        geotiff.write_with_options(
+            Cursor::new(Vec::new()),
+            &WriteOptions::default().with_tile_size(250, 256)
+        ),
+        Err(GeoTiffError::Format(_))
+    ));
+}
+
+#[test]
+fn test_build_overviews() {
+    // A 5x4 image with a pixel scale of 1, whose values grow by 1 per column and 10 per row
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut image = encoder.new_image::<colortype::Gray8>(5, 4).unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ModelPixelScaleTag, &[1.0, 1.0, 0.0][..])
+        .unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ModelTiepointTag, &[0.0, 0.0, 0.0, 0.0, 4.0, 0.0][..])
+        .unwrap();
+    let values: Vec<u8> = (0..20).map(|i| i % 5 + i / 5 * 10).collect();
+    image.write_data(&values).unwrap();
+    data.set_position(0);
+    let mut geotiff = GeoTiff::read(data).expect("File I/O error");
+
+    geotiff
+        .build_overviews(&[2, 4], Resampling::Average)
+        .expect("Valid factors");
+    let overviews = geotiff.overviews();
+    assert_eq!(overviews.len(), 2);
+    assert_eq!(
+        (overviews[0].raster_width, overviews[0].raster_height),
+        (3, 2)
+    );
+    assert_eq!(
+        (overviews[1].raster_width, overviews[1].raster_height),
+        (2, 1)
+    );
+    // The means of 2x2 blocks, rounded, and of the 1x2 blocks at the right edge
+    assert_eq!(
+        overviews[0].values::<u8>().unwrap(),
+        vec![6, 8, 9, 26, 28, 29]
+    );
+    assert_eq!(overviews[0].model_extent(), geotiff.model_extent());
+
+    geotiff
+        .build_overviews(&[2], Resampling::Nearest)
+        .expect("Valid factors");
+    assert_eq!(
+        geotiff.overviews()[0].values::<u8>().unwrap(),
+        vec![11, 13, 14, 31, 33, 34]
+    );
+    assert!(matches!(
+        geotiff.build_overviews(&[1], Resampling::Nearest),
+        Err(GeoTiffError::Format(_))
+    ));
+
+    // Overviews are written as reduced-resolution images after the full-resolution image
+    geotiff
+        .build_overviews(&[2, 4], Resampling::Average)
+        .expect("Valid factors");
+    let mut data = Cursor::new(Vec::new());
+    geotiff.write(&mut data).expect("Could not write GeoTIFF");
+    data.set_position(0);
+    let mut reader = GeoTiffReader::open(data).expect("Could not read written GeoTIFF");
+    assert_eq!(
+        reader
+            .overviews()
+            .expect("File I/O error")
+            .iter()
+            .map(|overview| (overview.width, overview.height))
+            .collect::<Vec<_>>(),
+        vec![(3, 2), (2, 1)]
+    );
+    let overview = reader.read_overview(0).expect("File I/O error");
+    assert_eq!(overview.values::<u8>().unwrap(), vec![6, 8, 9, 26, 28, 29]);
+    assert_eq!(overview.model_extent(), geotiff.model_extent());
+}
+
+#[test]
+fn test_read_decimated() {
+    for planar in [false, true] {
+        let mut geotiff = GeoTiff::read(write_tiled_image(planar)).expect("File I/O error");
+        geotiff
+            .build_overviews(&[3, 17], Resampling::Nearest)
+            .expect("Valid factors");
+
+        // Without overviews, the chunks are decimated one by one like overviews are built
+        for (factor, overview) in [3, 17].into_iter().zip(geotiff.overviews()) {
+            let decimated = GeoTiffReader::open(write_tiled_image(planar))
+                .expect("File I/O error")
+                .read_decimated(factor)
+                .expect("File I/O error");
+            assert_eq!(
+                (decimated.raster_width, decimated.raster_height),
+                (overview.raster_width, overview.raster_height)
+            );
+            assert_eq!(
+                decimated.values::<u16>().unwrap(),
+                overview.values::<u16>().unwrap()
+            );
+        }
+
+        let decimated = GeoTiffReader::open(write_tiled_image(planar))
+            .expect("File I/O error")
+            .with_samples(&[1])
+            .read_decimated(3)
+            .expect("File I/O error");
+        assert_eq!(decimated.num_samples, 1);
+        assert_eq!(
+            decimated.values::<u16>().unwrap(),
+            (0..6)
+                .flat_map(|row| (0..7).map(move |col| ((row * 3 + 1) * 20 + col * 3 + 1) * 3 + 1))
+                .collect::<Vec<u16>>()
+        );
+    }
+
+    // An overview of the decimated dimensions is read instead
+    let mut geotiff = GeoTiff::read(write_tiled_image(false)).expect("File I/O error");
+    geotiff
+        .build_overviews(&[2], Resampling::Average)
+        .expect("Valid factors");
+    let mut data = Cursor::new(Vec::new());
+    geotiff.write(&mut data).expect("Could not write GeoTIFF");
+    data.set_position(0);
+    let decimated = GeoTiffReader::open(data)
+        .expect("Could not read written GeoTIFF")
+        .read_decimated(2)
+        .expect("File I/O error");
+    assert_eq!(
+        decimated.values::<u16>().unwrap(),
+        geotiff.overviews()[0].values::<u16>().unwrap()
+    );
+
+    let reader = GeoTiffReader::open(write_tiled_image(false)).expect("File I/O error");
+    assert!(matches!(
+        reader.read_decimated(0),
+        Err(GeoTiffError::Format(_))
+    ));
+}
+
+#[test]
+fn test_resample() {
+    // A 5x4 image with a pixel scale of 1, whose values grow by 1 per column and 10 per row
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut image = encoder.new_image::<colortype::Gray8>(5, 4).unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ModelPixelScaleTag, &[1.0, 1.0, 0.0][..])
+        .unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ModelTiepointTag, &[0.0, 0.0, 0.0, 0.0, 4.0, 0.0][..])
+        .unwrap();
+    let values: Vec<u8> = (0..20).map(|i| i % 5 + i / 5 * 10).collect();
+    image.write_data(&values).unwrap();
+    data.set_position(0);
+    let geotiff = GeoTiff::read(data).expect("File I/O error");
+
+    let nearest = geotiff
+        .resample(10, 8, Resampling::Nearest)
+        .expect("Valid dimensions");
+    assert_eq!((nearest.raster_width, nearest.raster_height), (10, 8));
+    assert_eq!(nearest.model_extent(), geotiff.model_extent());
+    assert_eq!(
+        nearest.values::<u8>().unwrap()[..10],
+        [0, 0, 1, 1, 2, 2, 3, 3, 4, 4]
+    );
+
+    // Interpolated between the pixel centers, clamped at the edges and rounded
+    let bilinear = geotiff
+        .resample(10, 8, Resampling::Bilinear)
+        .expect("Valid dimensions");
+    assert_eq!(bilinear.model_extent(), geotiff.model_extent());
+    assert_eq!(
+        bilinear.values::<u8>().unwrap()[..10],
+        [0, 0, 1, 1, 2, 2, 3, 3, 4, 4]
+    );
+    assert_eq!(
+        bilinear.get_value_at::<u8>(&Coord { x: 1.25, y: 2.75 }, 0),
+        Some(8)
+    );
+    assert_eq!(
+        geotiff
+            .resample(5, 4, Resampling::Bilinear)
+            .expect("Valid dimensions")
+            .values::<u8>()
+            .unwrap(),
+        values
+    );
+
+    // The means of the 3x2 blocks overlapping the resampled pixels
+    let average = geotiff
+        .resample(2, 2, Resampling::Average)
+        .expect("Valid dimensions");
+    assert_eq!(average.model_extent(), geotiff.model_extent());
+    assert_eq!(average.values::<u8>().unwrap(), vec![6, 8, 26, 28]);
+
+    assert!(matches!(
+        geotiff.resample(0, 2, Resampling::Average),
+        Err(GeoTiffError::Format(_))
+    ));
+}
+
+#[test]
+fn test_clip() {
+    // A 5x4 image with a pixel scale of 1, whose values grow by 1 per column and 10 per row
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut image = encoder.new_image::<colortype::Gray8>(5, 4).unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ModelPixelScaleTag, &[1.0, 1.0, 0.0][..])
+        .unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ModelTiepointTag, &[0.0, 0.0, 0.0, 0.0, 4.0, 0.0][..])
+        .unwrap();
+    let values: Vec<u8> = (0..20).map(|i| i % 5 + i / 5 * 10).collect();
+    image.write_data(&values).unwrap();
+    data.set_position(0);
+    let mut geotiff = GeoTiff::read(data).expect("File I/O error");
+
+    // A square with a hole covering the centers of the 2x2 pixels in the middle
+    let polygon = Polygon::new(
+        LineString::from(vec![(0.2, 0.2), (3.8, 0.2), (3.8, 3.8), (0.2, 3.8)]),
+        vec![LineString::from(vec![
+            (1.2, 1.2),
+            (2.8, 1.2),
+            (2.8, 2.8),
+            (1.2, 2.8),
+        ])],
+    );
+
+    // Without a no data value, pixels outside of the polygon are masked
+    let clipped = geotiff
+        .clip(&polygon)
+        .expect("Valid transform")
+        .expect("Intersecting polygon");
+    assert_eq!((clipped.raster_width, clipped.raster_height), (4, 4));
+    assert_eq!(
+        clipped.values::<u8>().unwrap(),
+        vec![0, 1, 2, 3, 10, 11, 12, 13, 20, 21, 22, 23, 30, 31, 32, 33]
+    );
+    assert_eq!(
+        clipped.mask(),
+        Some(
+            &[
+                true, true, true, true, true, false, false, true, true, false, false, true, true,
+                true, true, true
+            ][..]
+        )
+    );
+    assert_eq!(
+        clipped.get_masked_value_at::<u8>(&Coord { x: 1.5, y: 1.5 }, 0),
+        None
+    );
+
+    // With a no data value, they are set to it
+    geotiff.no_data = Some(255.0);
+    let clipped = geotiff
+        .clip(&polygon)
+        .expect("Valid transform")
+        .expect("Intersecting polygon");
+    assert_eq!(
+        clipped.values::<u8>().unwrap(),
+        vec![0, 1, 2, 3, 10, 255, 255, 13, 20, 255, 255, 23, 30, 31, 32, 33]
+    );
+    assert_eq!(clipped.mask(), None);
+
+    let outside = Polygon::new(
+        LineString::from(vec![(10.0, 10.0), (11.0, 10.0), (11.0, 11.0)]),
+        vec![],
+    );
+    assert!(geotiff.clip(&outside).expect("Valid transform").is_none());
+}
+
+#[test]
+fn test_polygonize() {
+    // A 4x3 image with a pixel scale of 1, where a region of ones surrounds a pixel of two
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut image = encoder.new_image::<colortype::Gray8>(4, 3).unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ModelPixelScaleTag, &[1.0, 1.0, 0.0][..])
+        .unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ModelTiepointTag, &[0.0, 0.0, 0.0, 0.0, 3.0, 0.0][..])
+        .unwrap();
+    image
+        .write_data(&[1, 1, 2, 2, 1, 2, 1, 2, 1, 1, 1, 2])
+        .unwrap();
+    data.set_position(0);
+    let mut geotiff = GeoTiff::read(data).expect("File I/O error");
+
+    let polygons = geotiff.polygonize(0).unwrap();
+    assert_eq!(polygons.len(), 2);
+    let (value, ones) = &polygons[0];
+    assert_eq!(*value, 1.0);
+    // The hole touches the exterior at a corner, where the ones touch themselves diagonally
+    assert_eq!(
+        ones.0,
+        vec![Polygon::new(
+            LineString::from(vec![
+                (0.0, 3.0),
+                (2.0, 3.0),
+                (2.0, 2.0),
+                (3.0, 2.0),
+                (3.0, 0.0),
+                (0.0, 0.0),
+            ]),
+            vec![LineString::from(vec![
+                (1.0, 2.0),
+                (1.0, 1.0),
+                (2.0, 1.0),
+                (2.0, 2.0),
+            ])],
+        )]
+    );
+    // Twos only touching at a corner form separate polygons
+    let (value, twos) = &polygons[1];
+    assert_eq!(*value, 2.0);
+    assert_eq!(
+        twos.0,
+        vec![
+            Polygon::new(
+                LineString::from(vec![
+                    (2.0, 3.0),
+                    (4.0, 3.0),
+                    (4.0, 0.0),
+                    (3.0, 0.0),
+                    (3.0, 2.0),
+                    (2.0, 2.0),
+                ]),
+                vec![],
+            ),
+            Polygon::new(
+                LineString::from(vec![(1.0, 2.0), (2.0, 2.0), (2.0, 1.0), (1.0, 1.0)]),
+                vec![],
+            ),
+        ]
+    );
+
+    // Pixels holding the no data value are not covered
+    geotiff.no_data = Some(2.0);
+    let polygons = geotiff.polygonize(0).unwrap();
+    assert_eq!(polygons.len(), 1);
+    assert_eq!(polygons[0].0, 1.0);
+    assert!(matches!(
+        geotiff.polygonize(1),
+        Err(GeoTiffError::SampleOutOfBounds { sample: 1, .. })
+    ));
+}
+
+#[test]
+fn test_contours() {
+    // Images with a pixel scale of 1 and their top left corner at the origin of the rows
+    let read = |width: u32, height: u32, values: &[u8]| {
+        let mut data = Cursor::new(Vec::new());
+        let mut encoder = TiffEncoder::new(&mut data).unwrap();
+        let mut image = encoder
+            .new_image::<colortype::Gray8>(width, height)
+            .unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::ModelPixelScaleTag, &[1.0, 1.0, 0.0][..])
+            .unwrap();
+        image
+            .encoder()
+            .write_tag(
+                Tag::ModelTiepointTag,
+                &[0.0, 0.0, 0.0, 0.0, height as f64, 0.0][..],
+            )
+            .unwrap();
+        image.write_data(values).unwrap();
+        data.set_position(0);
+        GeoTiff::read(data).expect("File I/O error")
+    };
+
+    // A peak in the center is surrounded by a closed line, with the peak to its right
+    let geotiff = read(3, 3, &[0, 0, 0, 0, 10, 0, 0, 0, 0]);
+    let contours = geotiff
+        .contours(0, ContourLevels::Levels(vec![5.0]))
+        .unwrap();
+    assert_eq!(
+        contours,
+        vec![(
+            5.0,
+            MultiLineString::new(vec![LineString::from(vec![
+                (1.5, 2.0),
+                (2.0, 1.5),
+                (1.5, 1.0),
+                (1.0, 1.5),
+                (1.5, 2.0),
+            ])])
+        )]
+    );
+    let levels: Vec<_> = geotiff
+        .contours(0, ContourLevels::Interval(4.0))
+        .unwrap()
+        .into_iter()
+        .map(|(level, lines)| (level, lines.0.len()))
+        .collect();
+    assert_eq!(levels, vec![(0.0, 0), (4.0, 1), (8.0, 1)]);
+    assert!(matches!(
+        geotiff.contours(1, ContourLevels::Interval(4.0)),
+        Err(GeoTiffError::SampleOutOfBounds { sample: 1, .. })
+    ));
+
+    // A slope to the east is crossed by an open line heading north
+    let geotiff = read(3, 2, &[0, 10, 20, 0, 10, 20]);
+    assert_eq!(
+        geotiff
+            .contours(0, ContourLevels::Levels(vec![15.0]))
+            .unwrap()[0]
+            .1,
+        MultiLineString::new(vec![LineString::from(vec![(2.0, 0.5), (2.0, 1.5)])])
+    );
+}
+
+#[test]
+fn test_hillshade() {
+    // A 3x3 DEM with a pixel scale of 10, rising by 10 per column, i.e. facing west at 45°
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut image = encoder.new_image::<colortype::Gray8>(3, 3).unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ModelPixelScaleTag, &[10.0, 10.0, 0.0][..])
+        .unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ModelTiepointTag, &[0.0, 0.0, 0.0, 0.0, 30.0, 0.0][..])
+        .unwrap();
+    image
+        .write_data(&[0, 10, 20, 0, 10, 20, 0, 10, 20])
+        .unwrap();
+    data.set_position(0);
+    let mut geotiff = GeoTiff::read(data).expect("File I/O error");
+
+    let center = Coord { x: 15.0, y: 15.0 };
+    // Lit perpendicularly by the sun in the west, and not at all by the sun in the east
+    let hillshade = geotiff.hillshade(270.0, 45.0, 1.0);
+    assert_eq!(hillshade.num_samples, 1);
+    assert_eq!(hillshade.no_data, Some(0.0));
+    assert_eq!(hillshade.model_extent(), geotiff.model_extent());
+    assert_eq!(hillshade.get_value_at::<u8>(&center, 0), Some(255));
+    let hillshade = geotiff.hillshade(90.0, 45.0, 1.0);
+    assert_eq!(hillshade.get_value_at::<u8>(&center, 0), Some(1));
+    // Flat terrain is lit by the sine of the altitude
+    let hillshade = geotiff.hillshade(90.0, 45.0, 0.0);
+    assert!(hillshade
+        .values::<u8>()
+        .unwrap()
+        .iter()
+        .all(|&value| value == 181));
+
+    geotiff.no_data = Some(10.0);
+    let hillshade = geotiff.hillshade(270.0, 45.0, 1.0);
+    assert_eq!(hillshade.get_value_at::<u8>(&center, 0), Some(0));
+}
+
+#[test]
+fn test_slope_and_aspect() {
+    // A 3x3 DEM with a pixel scale of 10, rising by 10 per column, i.e. facing west at 45°
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut image = encoder.new_image::<colortype::Gray8>(3, 3).unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ModelPixelScaleTag, &[10.0, 10.0, 0.0][..])
+        .unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::ModelTiepointTag, &[0.0, 0.0, 0.0, 0.0, 30.0, 0.0][..])
+        .unwrap();
+    image
+        .write_data(&[0, 10, 20, 0, 10, 20, 0, 10, 20])
+        .unwrap();
+    data.set_position(0);
+    let mut geotiff = GeoTiff::read(data).expect("File I/O error");
+
+    let center = Coord { x: 15.0, y: 15.0 };
+    let slope = geotiff.slope(SlopeUnit::Degrees);
+    assert_eq!(slope.no_data, Some(-9999.0));
+    assert_eq!(slope.get_value_at::<f32>(&center, 0), Some(45.0));
+    let slope = geotiff.slope(SlopeUnit::Percent);
+    assert_eq!(slope.get_value_at::<f32>(&center, 0), Some(100.0));
+    let aspect = geotiff.aspect();
+    assert_eq!(aspect.get_value_at::<f32>(&center, 0), Some(270.0));
+
+    // Pixels of geographic CRSs are measured in metres along the parallel of their row
+    geotiff.geo_key_directory = GeoKeyDirectoryBuilder::from_epsg(4326).unwrap().build();
+    let slope = geotiff.slope(SlopeUnit::Percent);
+    let pixel_width = 6371008.8 * 10f64.to_radians() * 15f64.to_radians().cos();
+    let expected = 100.0 * 10.0 / pixel_width;
+    let actual = slope.get_value_at::<f32>(&center, 0).unwrap() as f64;
+    assert!((actual - expected).abs() < expected * 1e-6);
+
+    // Flat terrain, e.g. the constant hillshade without relief, has no aspect
+    let flat = geotiff.hillshade(90.0, 45.0, 0.0).aspect();
+    assert!(flat
+        .values::<f32>()
+        .unwrap()
+        .iter()
+        .all(|&value| value == -9999.0));
+    geotiff.no_data = Some(10.0);
+    assert_eq!(
+        geotiff.aspect().get_value_at::<f32>(&center, 0),
+        Some(-9999.0)
+    );
+}
+
+#[test]
+fn test_terrain_rgb() {
+    let mut geotiff = read_geotiff("resources/zh_dem_25.tif");
+    let elevations = geotiff.values::<f64>().unwrap();
+
+    let rgb = geotiff.encode_terrain_rgb(TerrainEncoding::Mapbox);
+    assert_eq!(rgb.num_samples, 3);
+    assert_eq!(rgb.data_type(), RasterDataType::U8);
+    assert_eq!(
+        rgb.photometric_interpretation(),
+        PhotometricInterpretation::RGB
+    );
+    assert_eq!(rgb.mask(), None);
+    assert_eq!(rgb.geotransform(), geotiff.geotransform());
+
+    // The elevations survive a round trip through a file to the precision of the encoding
+    let mut data = Cursor::new(Vec::new());
+    rgb.write(&mut data).unwrap();
+    data.set_position(0);
+    let decoded = GeoTiff::read(data)
+        .expect("File I/O error")
+        .decode_terrain_rgb(TerrainEncoding::Mapbox)
+        .unwrap();
+    assert_eq!(decoded.data_type(), RasterDataType::F32);
+    assert_eq!(decoded.no_data, Some(-9999.0));
+    assert!(decoded
+        .values::<f64>()
+        .unwrap()
+        .iter()
+        .zip(&elevations)
+        .all(|(decoded, elevation)| (decoded - elevation).abs() <= 0.05 + 1e-3));
+
+    let terrarium = geotiff
+        .encode_terrain_rgb(TerrainEncoding::Terrarium)
+        .decode_terrain_rgb(TerrainEncoding::Terrarium)
+        .unwrap();
+    assert!(terrarium
+        .values::<f64>()
+        .unwrap()
+        .iter()
+        .zip(&elevations)
+        .all(|(decoded, elevation)| (decoded - elevation).abs() <= 1.0 / 512.0 + 1e-3));
+
+    // Sea level in both encodings
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    encoder.write_image::<colortype::Gray8>(1, 1, &[0]).unwrap();
+    data.set_position(0);
+    let flat = GeoTiff::read(data).expect("File I/O error");
+    for (encoding, color) in [
+        (TerrainEncoding::Mapbox, [1, 134, 160]),
+        (TerrainEncoding::Terrarium, [128, 0, 0]),
+    ] {
+        let rgb = flat.encode_terrain_rgb(encoding);
+        assert_eq!(rgb.values::<u8>().unwrap()[..3], color);
+    }
+
+    // Unknown elevations are masked and decoded as no data
+    geotiff.no_data = Some(elevations[0]);
+    let rgb = geotiff.encode_terrain_rgb(TerrainEncoding::Mapbox);
+    assert_eq!(rgb.mask().map(|mask| mask[0]), Some(false));
+    let decoded = rgb.decode_terrain_rgb(TerrainEncoding::Mapbox).unwrap();
+    assert_eq!(decoded.values::<f32>().unwrap()[0], -9999.0);
+
+    assert!(matches!(
+        geotiff.decode_terrain_rgb(TerrainEncoding::Mapbox),
+        Err(GeoTiffError::Format(_))
+    ));
+}
+
+#[test]
+fn test_gcps() {
+    let path = "resources/austrian_capitals_model_tie_points_pixel_is_area.tif";
+    let geotiff = read_geotiff(path);
+
+    let gcps = geotiff.gcps();
+    assert_eq!(gcps.len(), 4);
+    assert_eq!(
+        gcps[0],
+        Gcp {
+            pixel: Coord { x: 124.0, y: 126.0 },
+            model: Coord {
+                x: 4426000.0,
+                y: 2685000.0
+            },
+        }
+    );
+    #[cfg(not(feature = "tie-points"))]
+    assert_eq!(
+        geotiff.model_extent(),
+        Rect::new(
+            Coord { x: 0.0, y: 0.0 },
+            Coord {
+                x: geotiff.raster_width as f64,
+                y: geotiff.raster_height as f64
+            }
+        )
+    );
+
+    // The points of a region are relative to its top left pixel
+    let mut reader = GeoTiffReader::open(File::open(path).unwrap()).unwrap();
+    assert_eq!(reader.gcps(), gcps);
+    let region = reader.read_region(&Window::new(10, 5, 100, 100)).unwrap();
+    for (gcp, region_gcp) in gcps.iter().zip(region.gcps()) {
+        assert_eq!(region_gcp.pixel.x, gcp.pixel.x - 10.0);
+        assert_eq!(region_gcp.pixel.y, gcp.pixel.y - 5.0);
+        assert_eq!(region_gcp.model, gcp.model);
+    }
+
+    // The points are written as tie points, in the order of the transform if there is one
+    let mut written = Cursor::new(Vec::new());
+    geotiff.write(&mut written).unwrap();
+    written.set_position(0);
+    let written = GeoTiff::read(written).unwrap();
+    assert_eq!(written.gcps().len(), gcps.len());
+    assert!(gcps.iter().all(|gcp| written.gcps().contains(gcp)));
+}
+
+#[test]
+fn test_storage_report() {
+    let mut reader =
+        GeoTiffReader::open(File::open("resources/marbles.tif").unwrap()).expect("File I/O error");
+    let report = reader.storage_report().expect("File I/O error");
+    assert_eq!(report.images.len(), 1);
+    let image = &report.images[0];
+    assert_eq!(image.overview_level, None);
+    assert_eq!((image.width, image.height), (1419, 1001));
+    assert!(!image.tiled);
+    assert_eq!(image.band_sizes, None);
+    assert_eq!(image.size.uncompressed, 1419 * 1001 * 3);
+    assert_eq!(report.chunk_count(), image.chunk_count);
+    assert_eq!(report.empty_chunk_count(), 0);
+
+    // A compressed planar file with an overview
+    let geotiff = reader.read().expect("File I/O error");
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let image = encoder.new_image::<colortype::Gray8>(8, 8).unwrap();
+    image.write_data(&[7; 64]).unwrap();
+    let mut image = encoder.new_image::<colortype::Gray8>(4, 4).unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::NewSubfileType, 1u32)
+        .unwrap();
+    image.write_data(&[7; 16]).unwrap();
+    let overview = data.into_inner();
+
+    let mut data = Cursor::new(Vec::new());
+    let options = WriteOptions::default()
+        .with_compression(Compression::Deflate)
+        .with_interleave(Interleave::Band);
+    geotiff
+        .write_with_options(&mut data, &options)
+        .expect("Could not write GeoTIFF");
+    data.set_position(0);
+    let report = GeoTiffReader::open(data)
+        .expect("File I/O error")
+        .storage_report()
+        .expect("File I/O error");
+    let image = &report.images[0];
+    assert_eq!(image.compression, CompressionMethod::Deflate);
+    let band_sizes = image.band_sizes.as_ref().unwrap();
+    assert_eq!(band_sizes.len(), 3);
+    assert!(band_sizes
+        .iter()
+        .all(|size| size.uncompressed == 1419 * 1001 && size.compression_ratio() > 1.0));
+    assert_eq!(
+        band_sizes.iter().map(|size| size.compressed).sum::<u64>(),
+        image.size.compressed
+    );
+    assert_eq!(image.chunk_count % 3, 0);
+
+    let report = GeoTiffReader::open(Cursor::new(overview))
+        .expect("File I/O error")
+        .storage_report()
+        .expect("File I/O error");
+    assert_eq!(report.images.len(), 2);
+    assert_eq!(report.images[1].overview_level, Some(0));
+    assert_eq!(
+        report.images[1].size,
+        StorageSize {
+            compressed: 16,
+            uncompressed: 16
+        }
+    );
+    assert_eq!(
+        report.size(),
+        StorageSize {
+            compressed: 80,
+            uncompressed: 80
+        }
+    );
+    assert_eq!(report.compression_ratio(), 1.0);
+}
+
+#[test]
+fn test_dataset_pool() {
+    let pool = DatasetPool::for_files(2);
+    assert!(pool.is_empty());
+
+    let marbles = pool
+        .get("resources/marbles.tif")
+        .expect("Could not open file");
+    assert!(Arc::ptr_eq(
+        &marbles,
+        &pool.get("resources/marbles.tif").unwrap()
+    ));
+    assert_eq!(marbles.lock().unwrap().raster_width, 1419);
+
+    pool.get("resources/merc.tif").expect("Could not open file");
+    pool.get("resources/marbles.tif").unwrap();
+    // The least recently used reader is evicted
+    pool.get("resources/zh_dem_25.tif")
+        .expect("Could not open file");
+    assert_eq!(pool.len(), 2);
+    assert!(Arc::ptr_eq(
+        &marbles,
+        &pool.get("resources/marbles.tif").unwrap()
+    ));
+    assert!(pool.remove("resources/zh_dem_25.tif"));
+    assert_eq!(pool.len(), 1);
+
+    assert!(matches!(
+        pool.get("resources/missing.tif"),
+        Err(GeoTiffError::Tiff(_))
+    ));
+    assert_eq!(pool.len(), 1);
+
+    // Expired readers are reopened
+    let pool = DatasetPool::for_files(2).with_ttl(Duration::ZERO);
+    let marbles = pool.get("resources/marbles.tif").unwrap();
+    assert!(!Arc::ptr_eq(
+        &marbles,
+        &pool.get("resources/marbles.tif").unwrap()
+    ));
+}
+
+#[test]
+fn test_internal_mask() {
+    let write = |mask_byte_count: Option<u32>| {
+        let mut data = Cursor::new(Vec::new());
+        let mut encoder = TiffEncoder::new(&mut data).unwrap();
+        let mut image = encoder.new_image::<colortype::Gray8>(4, 2).unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::ModelPixelScaleTag, &[1.0, 1.0, 0.0][..])
+            .unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::ModelTiepointTag, &[0.0, 0.0, 0.0, 0.0, 2.0, 0.0][..])
+            .unwrap();
+        image.write_data(&(1..9).collect::<Vec<u8>>()).unwrap();
+
+        if let Some(mask_byte_count) = mask_byte_count {
+            // A 1-bit mask as written by GDAL, marking the pixels 0, 2, 5 and 6 as valid
+            let mut directory = encoder.image_directory().unwrap();
+            let offset = directory
+                .write_data(&[0b1010_0000u8, 0b0110_0000][..])
+                .unwrap() as u32;
+            directory.write_tag(Tag::NewSubfileType, 4u32).unwrap();
+            directory.write_tag(Tag::ImageWidth, 4u32).unwrap();
+            directory.write_tag(Tag::ImageLength, 2u32).unwrap();
+            directory.write_tag(Tag::BitsPerSample, 1u16).unwrap();
+            directory.write_tag(Tag::Compression, 1u16).unwrap();
+            directory
+                .write_tag(Tag::PhotometricInterpretation, 4u16)
+                .unwrap();
+            directory.write_tag(Tag::StripOffsets, offset).unwrap();
+            directory.write_tag(Tag::RowsPerStrip, 2u32).unwrap();
+            directory
+                .write_tag(Tag::StripByteCounts, mask_byte_count)
+                .unwrap();
+            directory.finish().unwrap();
+        }
+
+        data.set_position(0);
+        data
+    };
+
+    let geotiff = GeoTiff::read(write(None)).expect("File I/O error");
+    assert_eq!(geotiff.mask(), None);
+    assert_eq!(
+        geotiff.get_masked_value_at::<u8>(&Coord { x: 1.5, y: 1.5 }, 0),
+        Some(2)
+    );
+
+    let geotiff = GeoTiff::read(write(Some(2))).expect("File I/O error");
+    assert_eq!(
+        geotiff.mask(),
+        Some(&[true, false, true, false, false, true, true, false][..])
+    );
+    assert_eq!(geotiff.values::<u8>().unwrap(), (1..9).collect::<Vec<u8>>());
+    assert_eq!(
+        geotiff.get_masked_value_at::<u8>(&Coord { x: 0.5, y: 1.5 }, 0),
+        Some(1)
+    );
+    assert_eq!(
+        geotiff.get_masked_value_at::<u8>(&Coord { x: 1.5, y: 1.5 }, 0),
+        None
+    );
+    assert_eq!(
+        geotiff
+            .valid_pixels::<u8>(0)
+            .unwrap()
+            .map(|pixel| pixel.map(|(_, value)| value))
+            .collect::<GeoTiffResult<Vec<_>>>()
+            .unwrap(),
+        vec![1, 3, 6, 7]
+    );
+
+    let region = GeoTiff::read_region(write(Some(2)), &Window::new(1, 1, 3, 1)).unwrap();
+    assert_eq!(region.mask(), Some(&[true, true, false][..]));
+
+    // A corrupt byte count of the mask is not allocated
+    assert!(matches!(
+        GeoTiff::read(write(Some(u32::MAX))),
+        Err(GeoTiffError::Tiff(TiffError::LimitsExceeded))
+    ));
+}
+
+#[test]
+fn test_tiled_internal_mask() {
+    // A 20x18 image with a mask in tiles of 16x16 pixels, padded at the right and bottom edges
+    let (width, height, tile_size) = (20, 18, 16);
+    let is_valid = |x: usize, y: usize| (x + 2 * y).is_multiple_of(3);
+    for bits_per_sample in [1u16, 8] {
+        let mut data = Cursor::new(Vec::new());
+        let mut encoder = TiffEncoder::new(&mut data).unwrap();
+        encoder
+            .write_image::<colortype::Gray8>(width as u32, height as u32, &vec![1; width * height])
+            .unwrap();
+
+        let mut directory = encoder.image_directory().unwrap();
+        let (mut offsets, mut byte_counts) = (Vec::new(), Vec::new());
+        for (tile_x, tile_y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            let mut tile = Vec::new();
+            for y in tile_y * tile_size..(tile_y + 1) * tile_size {
+                let row = (tile_x * tile_size..(tile_x + 1) * tile_size)
+                    .map(|x| x < width && y < height && is_valid(x, y));
+                match bits_per_sample {
+                    1 => tile.extend(row.collect::<Vec<_>>().chunks(8).map(|bits| {
+                        bits.iter()
+                            .enumerate()
+                            .map(|(i, &bit)| (bit as u8) << (7 - i))
+                            .sum::<u8>()
+                    })),
+                    _ => tile.extend(row.map(|valid| valid as u8 * 255)),
+                }
+            }
+            offsets.push(directory.write_data(&tile[..]).unwrap() as u32);
+            byte_counts.push(tile.len() as u32);
+        }
+        directory.write_tag(Tag::NewSubfileType, 4u32).unwrap();
+        directory.write_tag(Tag::ImageWidth, width as u32).unwrap();
+        directory
+            .write_tag(Tag::ImageLength, height as u32)
+            .unwrap();
+        directory
+            .write_tag(Tag::BitsPerSample, bits_per_sample)
+            .unwrap();
+        directory.write_tag(Tag::Compression, 1u16).unwrap();
+        directory
+            .write_tag(Tag::PhotometricInterpretation, 4u16)
+            .unwrap();
+        directory
+            .write_tag(Tag::TileWidth, tile_size as u32)
+            .unwrap();
+        directory
+            .write_tag(Tag::TileLength, tile_size as u32)
+            .unwrap();
+        directory.write_tag(Tag::TileOffsets, &offsets[..]).unwrap();
+        directory
+            .write_tag(Tag::TileByteCounts, &byte_counts[..])
+            .unwrap();
+        directory.finish().unwrap();
+        data.set_position(0);
+
+        let geotiff = GeoTiff::read(data).expect("File I/O error");
+        let expected: Vec<_> = (0..width * height)
+            .map(|pixel| is_valid(pixel % width, pixel / width))
+            .collect();
+        assert_eq!(geotiff.mask(), Some(&expected[..]));
+    }
+}
+
+#[test]
+fn test_f16() {
+    // Bit patterns of the half-precision floats 1.5, -2.0, 0.25 and 65504.0
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut directory = encoder.image_directory().unwrap();
+    let offset = directory
+        .write_data(&[0x3e00u16, 0xc000, 0x3400, 0x7bff][..])
+        .unwrap() as u32;
+    directory.write_tag(Tag::ImageWidth, 2u32).unwrap();
+    directory.write_tag(Tag::ImageLength, 2u32).unwrap();
+    directory.write_tag(Tag::BitsPerSample, 16u16).unwrap();
+    directory.write_tag(Tag::SampleFormat, 3u16).unwrap();
+    directory.write_tag(Tag::Compression, 1u16).unwrap();
+    directory
+        .write_tag(Tag::PhotometricInterpretation, 1u16)
+        .unwrap();
+    directory.write_tag(Tag::StripOffsets, offset).unwrap();
+    directory.write_tag(Tag::RowsPerStrip, 2u32).unwrap();
+    directory.write_tag(Tag::StripByteCounts, 8u32).unwrap();
+    directory.finish().unwrap();
+    data.set_position(0);
+
+    let geotiff = GeoTiff::read(data).expect("Could not read half-precision data");
+    assert_eq!(geotiff.data_type(), RasterDataType::F16);
+    assert_eq!(
+        geotiff.values::<f32>().unwrap(),
+        vec![1.5, -2.0, 0.25, 65504.0]
+    );
+
+    let mut written = Cursor::new(Vec::new());
+    geotiff
+        .write(&mut written)
+        .expect("Could not write GeoTIFF");
+    written.set_position(0);
+    let geotiff = GeoTiff::read(written).expect("Could not read written GeoTIFF");
+    assert_eq!(geotiff.data_type(), RasterDataType::F16);
+    assert_eq!(
+        geotiff.values::<f64>().unwrap(),
+        vec![1.5, -2.0, 0.25, 65504.0]
+    );
+}
+
+#[test]
+fn test_signed_byte() {
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut directory = encoder.image_directory().unwrap();
+    let offset = directory.write_data(&[0xffu8, 0x80, 0x7f, 0][..]).unwrap() as u32;
+    directory.write_tag(Tag::ImageWidth, 2u32).unwrap();
+    directory.write_tag(Tag::ImageLength, 2u32).unwrap();
+    directory.write_tag(Tag::BitsPerSample, 8u16).unwrap();
+    directory.write_tag(Tag::Compression, 1u16).unwrap();
+    directory
+        .write_tag(Tag::PhotometricInterpretation, 1u16)
+        .unwrap();
+    directory.write_tag(Tag::StripOffsets, offset).unwrap();
+    directory.write_tag(Tag::RowsPerStrip, 2u32).unwrap();
+    directory.write_tag(Tag::StripByteCounts, 4u32).unwrap();
+    directory
+        .write_tag(
+            Tag::Unknown(42112),
+            "<GDALMetadata>\n  <Item name=\"PIXELTYPE\" sample=\"0\" domain=\"IMAGE_STRUCTURE\">SIGNEDBYTE</Item>\n</GDALMetadata>",
+        )
+        .unwrap();
+    directory.finish().unwrap();
+
+    data.set_position(0);
+    let mut reader = GeoTiffReader::open(data.clone()).expect("Could not open signed bytes");
+    assert_eq!(
+        reader.band_formats(),
+        &[BandFormat {
+            sample_format: SampleFormat::Int,
+            bits_per_sample: 8
+        }]
+    );
+    assert_eq!(reader.data_type(), RasterDataType::I8);
+    assert_eq!(
+        reader
+            .get_value_at::<i8>(&Coord { x: 0.5, y: 1.5 }, 0)
+            .unwrap(),
+        Some(127)
+    );
+
+    let geotiff = GeoTiff::read(data).expect("Could not read signed bytes");
+    assert_eq!(geotiff.data_type(), RasterDataType::I8);
+    assert_eq!(geotiff.values::<i8>().unwrap(), vec![-1, -128, 127, 0]);
+
+    // The data is written with a signed sample format
+    let mut written = Cursor::new(Vec::new());
+    geotiff
+        .write(&mut written)
+        .expect("Could not write GeoTIFF");
+    written.set_position(0);
+    let geotiff = GeoTiff::read(written).expect("Could not read written GeoTIFF");
+    assert_eq!(
+        geotiff.band_formats(),
+        &[BandFormat {
+            sample_format: SampleFormat::Int,
+            bits_per_sample: 8
+        }]
+    );
+    assert_eq!(geotiff.values::<i8>().unwrap(), vec![-1, -128, 127, 0]);
+}
+
+#[test]
+fn test_write_blocks() {
+    let profile = Profile {
+        geotransform: Some([1000.0, 10.0, 0.0, 5000.0, 0.0, -10.0]),
+        geo_key_directory: GeoKeyDirectoryBuilder::from_epsg(3857).unwrap().build(),
+        no_data: Some(0.0),
+        compression: Compression::Deflate,
+        tile_size: Some((16, 16)),
+        ..Profile::new(40, 30, 1, RasterDataType::U16)
+    };
+    let values: Vec<u16> = (0..40 * 30).map(|value| value as u16).collect();
+    let expected = GeoTiff::from_profile(&profile, RasterData::U16(values.clone())).unwrap();
+    let read_block = |window: &Window| {
+        let mut block = Vec::with_capacity(window.width * window.height);
+        for y in window.y..window.y + window.height {
+            let start = y * 40 + window.x;
+            block.extend_from_slice(&values[start..start + window.width]);
+        }
+        Ok(RasterData::U16(block))
+    };
+
+    // Each block is requested once, even when written as one plane per sample
+    for (options, num_blocks) in [
+        (profile.write_options(), 6),
+        (profile.write_options().with_interleave(Interleave::Band), 6),
+        (
+            WriteOptions::default().with_predictor(Predictor::Horizontal),
+            1,
+        ),
+    ] {
+        let mut windows = Vec::new();
+        let mut data = Cursor::new(Vec::new());
+        GeoTiff::write_blocks(&mut data, &profile, &options, |window| {
+            windows.push(*window);
+            read_block(window)
+        })
+        .unwrap();
+        data.set_position(0);
+        let geotiff = GeoTiff::read(data).unwrap();
+
+        assert_eq!(
+            geotiff.values::<u16>().unwrap(),
+            expected.values::<u16>().unwrap()
+        );
+        assert_eq!(geotiff.geotransform(), expected.geotransform());
+        assert_eq!(geotiff.geo_key_directory.projected_type, Some(3857));
+        assert_eq!(geotiff.no_data, Some(0.0));
+        assert_eq!(windows.len(), num_blocks);
+    }
+
+    let mut data = Cursor::new(Vec::new());
+    assert!(matches!(
+        GeoTiff::write_blocks(&mut data, &profile, &profile.write_options(), |window| {
+            Ok(RasterData::U8(vec![0; window.width * window.height]))
+        }),
+        Err(GeoTiffError::Format(_))
+    ));
+    assert!(matches!(
+        GeoTiff::write_blocks(&mut data, &profile, &profile.write_options(), |_| {
+            Ok(RasterData::U16(vec![0; 1]))
+        }),
+        Err(GeoTiffError::Format(_))
+    ));
+}
+
+#[test]
+fn test_warp() {
+    let profile = Profile {
+        geotransform: Some([1000.0, 10.0, 0.0, 5000.0, 0.0, -10.0]),
+        geo_key_directory: GeoKeyDirectoryBuilder::from_epsg(3857).unwrap().build(),
+        no_data: Some(-1.0),
+        tile_size: Some((16, 16)),
+        ..Profile::new(40, 30, 1, RasterDataType::F32)
+    };
+    let values = (0..40 * 30).map(|value| value as f32).collect();
+    let geotiff = GeoTiff::from_profile(&profile, RasterData::F32(values)).unwrap();
+    let open = |geotiff: &GeoTiff| {
+        let mut data = Cursor::new(Vec::new());
+        geotiff.write(&mut data).unwrap();
+        data.set_position(0);
+        GeoTiff::open(data).unwrap()
+    };
+    let warp = |sources: &mut [GeoTiffReader<Cursor<Vec<u8>>>], profile: &Profile| {
+        let mut data = Cursor::new(Vec::new());
+        GeoTiffReader::mosaic(sources, &mut data, profile, Interpolation::Nearest).unwrap();
+        data.set_position(0);
+        GeoTiff::read(data).unwrap()
+    };
+
+    // Warping into the grid of the raster reproduces it
+    let mut reader = open(&geotiff);
+    let mut data = Cursor::new(Vec::new());
+    reader
+        .warp(&mut data, &profile, Interpolation::Nearest)
+        .unwrap();
+    data.set_position(0);
+    let warped = GeoTiff::read(data).unwrap();
+    assert_eq!(
+        warped.values::<f32>().unwrap(),
+        geotiff.values::<f32>().unwrap()
+    );
+
+    // A coarser grid extending beyond the raster samples the pixel centers
+    let coarse = Profile {
+        geotransform: Some([900.0, 20.0, 0.0, 5100.0, 0.0, -20.0]),
+        ..Profile::new(25, 20, 1, RasterDataType::F32)
+    };
+    let coarse = Profile {
+        geo_key_directory: profile.geo_key_directory.clone(),
+        no_data: Some(-1.0),
+        tile_size: Some((16, 16)),
+        ..coarse
+    };
+    let warped = warp(std::slice::from_mut(&mut reader), &coarse);
+    assert_eq!((warped.raster_width, warped.raster_height), (25, 20));
+    for row in 0..20 {
+        for col in 0..25 {
+            let center = Coord {
+                x: 900.0 + (col as f64 + 0.5) * 20.0,
+                y: 5100.0 - (row as f64 + 0.5) * 20.0,
+            };
+            let expected = geotiff
+                .sample(&center, 0, Interpolation::Nearest)
+                .unwrap()
+                .unwrap_or(-1.0);
+            assert_eq!(
+                warped.values::<f32>().unwrap()[row * 25 + col] as f64,
+                expected
+            );
+        }
+    }
+
+    // Mosaicking the halves of the raster reproduces it
+    let left = geotiff
+        .crop(&Rect::new(
+            Coord {
+                x: 1000.0,
+                y: 4700.0,
+            },
+            Coord {
+                x: 1200.0,
+                y: 5000.0,
+            },
+        ))
+        .unwrap()
+        .unwrap();
+    let right = geotiff
+        .crop(&Rect::new(
+            Coord {
+                x: 1200.0,
+                y: 4700.0,
+            },
+            Coord {
+                x: 1400.0,
+                y: 5000.0,
+            },
+        ))
+        .unwrap()
+        .unwrap();
+    assert_eq!((left.raster_width, left.raster_height), (20, 30));
+    let mosaic = warp(&mut [open(&left), open(&right)], &profile);
+    assert_eq!(
+        mosaic.values::<f32>().unwrap(),
+        geotiff.values::<f32>().unwrap()
+    );
+
+    let mut data = Cursor::new(Vec::new());
+    assert!(matches!(
+        reader.warp(
+            &mut data,
+            &Profile::new(40, 30, 1, RasterDataType::F32),
+            Interpolation::Nearest
+        ),
+        Err(GeoTiffError::Format(_))
+    ));
+    assert!(matches!(
+        reader.warp(
+            &mut data,
+            &Profile {
+                num_samples: 2,
+                ..profile.clone()
+            },
+            Interpolation::Nearest
+        ),
+        Err(GeoTiffError::Format(_))
+    ));
+}
+
+#[test]
+fn test_gdal_metadata() {
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut image = encoder.new_image::<colortype::Gray8>(1, 1).unwrap();
+    image
+        .encoder()
+        .write_tag(
+            Tag::Unknown(42112),
+            r#"<GDALMetadata>
+  <Item name="AREA_OR_POINT">Point</Item>
+  <Item name="DESCRIPTION" sample="0" role="description">Elevation &amp; depth</Item>
+  <Item name="SCALE" sample="0" role="scale">0.5</Item>
+  <Item name="OFFSET" sample="0" role="offset">-10</Item>
+  <Item name="UNITTYPE" sample="0" role="unittype">m</Item>
+  <Item name="STATISTICS_MINIMUM" sample="0">1</Item>
+  <Item name="STATISTICS_MAXIMUM" sample="0">254</Item>
+  <Item name="COMPRESSION" domain="IMAGE_STRUCTURE">NONE</Item>
+</GDALMetadata>"#,
+        )
+        .unwrap();
+    image.write_data(&[0]).unwrap();
+
+    data.set_position(0);
+    let mut geotiff = GeoTiff::read(data).expect("File I/O error");
+    let gdal_metadata = geotiff.gdal_metadata.as_ref().unwrap();
+    assert_eq!(gdal_metadata.items.len(), 8);
+    assert_eq!(gdal_metadata.get("area_or_point", None), Some("Point"));
+    assert_eq!(gdal_metadata.get("COMPRESSION", None), None);
+    assert_eq!(gdal_metadata.description(0), Some("Elevation & depth"));
+    assert_eq!(gdal_metadata.scale(0), Some(0.5));
+    assert_eq!(gdal_metadata.offset(0), Some(-10.0));
+    assert_eq!(gdal_metadata.unit_type(0), Some("m"));
+    assert_eq!(
+        gdal_metadata.statistics(0),
+        Some(BandStatistics {
+            minimum: Some(1.0),
+            maximum: Some(254.0),
+            mean: None,
+            std_dev: None,
+        })
+    );
+    assert_eq!(gdal_metadata.description(1), None);
+    assert_eq!(gdal_metadata.statistics(1), None);
+
+    geotiff.gdal_metadata.as_mut().unwrap().items[1].value = "Höhe <m>".into();
+    let mut data = Cursor::new(Vec::new());
+    geotiff.write(&mut data).expect("File I/O error");
+    data.set_position(0);
+    let written = GeoTiff::read(data).expect("File I/O error");
+    assert_eq!(written.gdal_metadata, geotiff.gdal_metadata);
+}
+
+#[test]
+fn test_content_fingerprint() {
+    let geotiff = read_geotiff("resources/marbles.tif");
+    let fingerprint = geotiff.content_fingerprint().expect("File I/O error");
+    assert_ne!(
+        fingerprint,
+        read_geotiff("resources/zh_dem_25.tif")
+            .content_fingerprint()
+            .expect("File I/O error")
+    );
+
+    // Storing the data differently does not change the fingerprint
+    let mut data = Cursor::new(Vec::new());
+    geotiff
+        .into_layout(Interleave::Band, ArrayOrder::ColumnMajor)
+        .expect("File I/O error")
+        .write(&mut data)
+        .expect("File I/O error");
+    data.set_position(0);
+    let mut written = GeoTiff::read(data).expect("File I/O error");
+    assert_eq!(
+        written.content_fingerprint().expect("File I/O error"),
+        fingerprint
+    );
+
+    written.no_data = Some(0.0);
+    assert_eq!(
+        written.content_fingerprint().expect("File I/O error"),
+        fingerprint
+    );
+    written.geo_key_directory.citation = Some("Marbles".into());
+    assert_ne!(
+        written.content_fingerprint().expect("File I/O error"),
+        fingerprint
+    );
+}
+
+#[cfg(feature = "fuzzing")]
+#[test]
+fn test_fuzzing_inputs() {
+    use geotiff::fuzzing::{CoordinateTransformInput, GeoKeyDirectoryInput};
+
+    let directory = GeoKeyDirectoryInput {
+        directory_data: vec![1, 1, 0, 2, 1024, 0, 1, 2, 2049, 34737, 7, 0],
+        double_params_data: vec![],
+        ascii_params_data: "WGS 84|".into(),
+    }
+    .parse()
+    .unwrap();
+    assert_eq!(directory.model_type, Some(2));
+    assert_eq!(directory.geog_citation.as_deref(), Some("WGS 84"));
+
+    // Malformed data is rejected instead of panicking
+    for directory_data in [
+        vec![],
+        vec![1, 1, 0, 2, 1024],
+        vec![1, 1, 0, 1, 2049, 34737, 7, 9],
+    ] {
+        assert!(GeoKeyDirectoryInput {
+            directory_data,
+            double_params_data: vec![],
+            ascii_params_data: "WGS 84|".into(),
+        }
+        .parse()
+        .is_err());
+    }
+
+    let (model, raster) = CoordinateTransformInput {
+        pixel_scale_data: Some(vec![2.0, 2.0, 0.0]),
+        tie_points_data: Some(vec![0.0, 0.0, 0.0, 100.0, 200.0, 0.0]),
+        model_transformation_data: None,
+        coord: (1.0, 1.0),
+    }
+    .parse()
+    .unwrap();
+    assert_eq!(model, Coord { x: 102.0, y: 198.0 });
+    assert_eq!(raster, Coord { x: 1.0, y: 1.0 });
+
+    assert!(CoordinateTransformInput {
+        pixel_scale_data: Some(vec![2.0]),
+        tie_points_data: None,
+        model_transformation_data: None,
+        coord: (0.0, 0.0),
+    }
+    .parse()
+    .is_err());
+
+    // Tie points on a line cannot be triangulated
+    assert!(matches!(
+        CoordinateTransformInput {
+            pixel_scale_data: None,
+            tie_points_data: Some(vec![
+                0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 0.0, 0.0, 2.0, 1.0, 0.0, 2.0, 0.0, 0.0, 3.0,
+                1.0, 0.0
+            ]),
+            model_transformation_data: None,
+            coord: (0.5, 0.5),
+        }
+        .parse(),
+        Err(GeoTiffError::Transform(_))
+    ));
+}
+
+#[cfg(feature = "epsg")]
+#[test]
+fn test_resolve_epsg() {
+    let directory = GeoKeyDirectoryBuilder::from_epsg(32633)
+        .unwrap()
+        .build()
+        .resolve_epsg();
+    assert_eq!(directory.geographic_type, Some(4326));
+    assert_eq!(directory.geog_geodetic_datum, Some(6326));
+    assert_eq!(directory.geog_ellipsoid, Some(7030));
+    assert_eq!(directory.geog_semi_major_axis, Some(6378137.0));
+    assert_eq!(directory.geog_inv_flattening, Some(298.257223563));
+    assert_eq!(directory.geog_prime_meridian, Some(8901));
+    assert_eq!(directory.projection, Some(16033));
+    assert_eq!(directory.proj_coord_trans, Some(1));
+    assert_eq!(directory.proj_nat_origin_long, Some(15.0));
+    assert_eq!(directory.proj_scale_at_nat_origin, Some(0.9996));
+    assert_eq!(directory.proj_false_easting, Some(500000.0));
+    assert_eq!(directory.proj_false_northing, Some(0.0));
+    assert_eq!(directory.proj_linear_units, Some(9001));
+
+    let directory = GeoKeyDirectoryBuilder::from_epsg(2154)
+        .unwrap()
+        .build()
+        .resolve_epsg();
+    assert_eq!(directory.geog_ellipsoid, Some(7019));
+    assert_eq!(directory.proj_coord_trans, Some(8));
+    assert_eq!(directory.proj_std_parallel1, Some(49.0));
+    assert_eq!(directory.proj_std_parallel2, Some(44.0));
+    assert_eq!(directory.proj_false_origin_northing, Some(6600000.0));
+
+    // Keys present in the file are kept
+    let mut directory = GeoKeyDirectoryBuilder::from_epsg(4267).unwrap().build();
+    directory.geog_ellipsoid = Some(32767);
+    let resolved = directory.resolve_epsg();
+    assert_eq!(resolved.geog_geodetic_datum, Some(6267));
+    assert_eq!(resolved.geog_ellipsoid, Some(32767));
+    assert_eq!(resolved.projection, None);
+
+    // Unknown codes leave the directory unchanged
+    let directory = GeoKeyDirectory {
+        projected_type: Some(1234),
+        ..GeoKeyDirectory::default()
+    };
+    assert_eq!(directory.resolve_epsg(), directory);
+}
+
+#[test]
+fn test_geo_key_directory_builder() {
+    let directory = GeoKeyDirectory::builder()
+        .projected_type(3857)
+        .raster_type(RasterType::RasterPixelIsArea)
+        .citation("WGS 84 / Pseudo-Mercator")
+        .proj_linear_units(9001)
+        .try_build()
+        .expect("Valid GeoKeys");
+    assert_eq!(directory.model_type, Some(1));
+    assert_eq!(directory.projected_type, Some(3857));
+    assert_eq!(
+        directory.citation.as_deref(),
+        Some("WGS 84 / Pseudo-Mercator")
+    );
+
+    // Map projection methods are set and read by their codes
+    let directory = GeoKeyDirectory::builder()
+        .coordinate_transformation_method(CoordinateTransformationMethod::LambertConformalConic2Sp)
+        .build();
+    assert_eq!(directory.proj_coord_trans, Some(8));
+    assert_eq!(
+        directory.coordinate_transformation_method(),
+        Some(CoordinateTransformationMethod::LambertConformalConic2Sp)
+    );
+    let directory = GeoKeyDirectory::builder().proj_coord_trans(99).build();
+    assert_eq!(directory.coordinate_transformation_method(), None);
+
+    let (directory_data, double_params_data, ascii_params_data) = GeoKeyDirectory::builder()
+        .geographic_type(32767)
+        .geog_semi_major_axis(6378137.0)
+        .geog_inv_flattening(298.257223563)
+        .geog_citation("Custom")
+        .to_tag_data()
+        .expect("Valid GeoKeys");
+    assert_eq!(
+        directory_data,
+        vec![
+            1, 1, 1, 5, 1024, 0, 1, 2, 2048, 0, 1, 32767, 2049, 34737, 7, 0, 2057, 34736, 1, 0,
+            2059, 34736, 1, 1
+        ]
+    );
+    assert_eq!(double_params_data, vec![6378137.0, 298.257223563]);
+    assert_eq!(ascii_params_data, "Custom|");
+
+    for builder in [
+        GeoKeyDirectory::builder()
+            .model_type(2)
+            .projected_type(32633),
+        GeoKeyDirectory::builder().model_type(1),
+        GeoKeyDirectory::builder().projected_type(32767),
+        GeoKeyDirectory::builder().geographic_type(32767),
+        GeoKeyDirectory::builder()
+            .projected_type(32633)
+            .proj_linear_units(32767),
+        GeoKeyDirectory::builder()
+            .geographic_type(4326)
+            .geog_angular_units(9102)
+            .geog_angular_unit_size(0.0174),
+    ] {
+        assert!(matches!(
+            builder.clone().try_build(),
+            Err(GeoTiffError::Crs(_))
+        ));
+        assert!(builder.to_tag_data().is_err());
+    }
+}
+
+#[test]
+fn test_metadata_formatting() {
+    let directory = GeoKeyDirectoryBuilder::from_epsg(4326)
+        .unwrap()
+        .raster_type(RasterType::RasterPixelIsArea)
+        .build();
+    assert_eq!(
+        directory.to_string(),
+        "GeoKeyDirectory 1.1.1\n\
+         ModelType (1024): 2\n\
+         RasterType (1025): 1\n\
+         GeographicType (2048): 4326\n\
+         GeogCitation (2049): \"WGS 84\"\n\
+         GeogAngularUnits (2054): 9102"
+    );
+    assert_eq!(
+        format!("{directory:?}"),
+        "GeoKeyDirectory { key_directory_version: 1, key_revision: 1, minor_revision: 1, \
+         ModelType: Short(2), RasterType: Short(1), GeographicType: Short(4326), \
+         GeogCitation: Ascii(\"WGS 84\"), GeogAngularUnits: Short(9102) }"
+    );
+    assert_eq!(
+        directory.keys().collect::<Vec<_>>(),
+        [
+            (1024, GeoKeyValue::Short(2)),
+            (1025, GeoKeyValue::Short(1)),
+            (2048, GeoKeyValue::Short(4326)),
+            (2049, GeoKeyValue::Ascii("WGS 84".into())),
+            (2054, GeoKeyValue::Short(9102)),
+        ]
+    );
+
+    // The raster data is summarized
+    let geotiff = read_geotiff("resources/merc.tif");
+    let debug = format!("{geotiff:?}");
+    assert!(debug.contains("raster_data: RasterData { type: u8, len: 40000 }"));
+    assert!(debug.contains("corrupt_chunks: 0, masked_pixels: None"));
+    assert!(debug.len() < 2000);
+}
+
+#[test]
+fn test_to_wkt() {
+    // A user-defined Mercator projection on NAD27 given by its keys
+    let geotiff = read_geotiff("resources/merc.tif");
+    let wkt = geotiff.geo_key_directory.to_wkt().unwrap();
+    assert!(wkt.starts_with("PROJCRS[\"Mercator North American 1927\",BASEGEOGCRS[\"unknown\","));
+    assert!(wkt.contains(
+        "DATUM[\"North American Datum 1927\",\
+         ELLIPSOID[\"Clarke 1866\",6378206.4,294.978698213898,LENGTHUNIT[\"metre\",1],\
+         ID[\"EPSG\",7008]],ID[\"EPSG\",6267]]"
+    ));
+    assert!(wkt.contains("METHOD[\"Mercator (variant A)\",ID[\"EPSG\",9804]]"));
+    assert!(wkt.contains(
+        "PARAMETER[\"Longitude of natural origin\",-90,\
+         ANGLEUNIT[\"degree\",0.017453292519943295],ID[\"EPSG\",8802]]"
+    ));
+    assert!(wkt.contains(
+        "PARAMETER[\"Scale factor at natural origin\",0.829916312080482,\
+         SCALEUNIT[\"unity\",1],ID[\"EPSG\",8805]]"
+    ));
+    assert!(wkt.ends_with("LENGTHUNIT[\"metre\",1]]"));
+
+    let directory = GeoKeyDirectory {
+        model_type: Some(2),
+        geog_citation: Some("Sphere".into()),
+        geog_semi_major_axis: Some(6371000.0),
+        geog_semi_minor_axis: Some(6371000.0),
+        ..GeoKeyDirectory::default()
+    };
+    assert_eq!(
+        directory.to_wkt().unwrap(),
+        "GEOGCRS[\"Sphere\",DATUM[\"unknown\",\
+         ELLIPSOID[\"unknown\",6371000,0,LENGTHUNIT[\"metre\",1]]],\
+         PRIMEM[\"Greenwich\",0,ANGLEUNIT[\"degree\",0.017453292519943295]],\
+         CS[ellipsoidal,2],AXIS[\"geodetic latitude (Lat)\",north,ORDER[1]],\
+         AXIS[\"geodetic longitude (Lon)\",east,ORDER[2]],\
+         ANGLEUNIT[\"degree\",0.017453292519943295]]"
+    );
+
+    // The ellipsoid is missing
+    let directory = GeoKeyDirectory {
+        model_type: Some(2),
+        ..GeoKeyDirectory::default()
+    };
+    assert_eq!(directory.to_wkt(), None);
+}
+
+#[test]
+fn test_citations() {
+    let directory = GeoKeyDirectory {
+        model_type: Some(2),
+        geographic_type: Some(32767),
+        geog_citation: Some(
+            "GCS Name = Custom|Datum = Custom_Datum|Ellipsoid = Custom \"Sphere\"|\
+             Primem = Greenwich|AUnits = Degree||"
+                .into(),
+        ),
+        geog_geodetic_datum: Some(32767),
+        geog_semi_major_axis: Some(6371000.0),
+        geog_inv_flattening: Some(0.0),
+        ..GeoKeyDirectory::default()
+    };
+    assert_eq!(
+        directory.citations(),
+        Citations {
+            gcs_name: Some("Custom".into()),
+            datum: Some("Custom_Datum".into()),
+            ellipsoid: Some("Custom \"Sphere\"".into()),
+            prime_meridian: Some("Greenwich".into()),
+            angular_units: Some("Degree".into()),
+            ..Citations::default()
+        }
+    );
+    // The names replace unknown ones in WKT
+    let wkt = directory.to_wkt().unwrap();
+    assert!(wkt.starts_with(
+        "GEOGCRS[\"Custom\",DATUM[\"Custom_Datum\",ELLIPSOID[\"Custom \"\"Sphere\"\"\",6371000,0,"
+    ));
+
+    // Plain citations name the CRS, more specific keys first
+    let directory = GeoKeyDirectory {
+        model_type: Some(1),
+        citation: Some("UTM 33N".into()),
+        geog_citation: Some("WGS 84".into()),
+        proj_citation: Some("PCS Name = WGS 84 / UTM zone 33N|LUnits = metre|".into()),
+        ..GeoKeyDirectory::default()
+    };
+    assert_eq!(
+        directory.citations(),
+        Citations {
+            pcs_name: Some("WGS 84 / UTM zone 33N".into()),
+            gcs_name: Some("WGS 84".into()),
+            linear_units: Some("metre".into()),
+            ..Citations::default()
+        }
+    );
+
+    // IMAGINE separates the pairs by line breaks
+    let directory = GeoKeyDirectory {
+        citation: Some("IMAGINE GeoTIFF Support\nProjection Name = UTM\nUnits = meters".into()),
+        ..GeoKeyDirectory::default()
+    };
+    assert_eq!(
+        directory.citations(),
+        Citations {
+            projection_name: Some("UTM".into()),
+            ..Citations::default()
+        }
+    );
+    assert_eq!(GeoKeyDirectory::default().citations(), Citations::default());
+}
+
+#[cfg(feature = "epsg")]
+#[test]
+fn test_to_wkt_from_epsg() {
+    let wkt = GeoKeyDirectoryBuilder::from_epsg(32633)
+        .unwrap()
+        .build()
+        .to_wkt()
+        .unwrap();
+    assert!(wkt.starts_with("PROJCRS[\"WGS 84 / UTM zone 33N\",BASEGEOGCRS[\"WGS 84\","));
+    assert!(wkt.contains("CONVERSION[\"UTM zone 33N\",METHOD[\"Transverse Mercator\""));
+    assert!(wkt.contains("PARAMETER[\"False easting\",500000,LENGTHUNIT[\"metre\",1]"));
+    assert!(wkt.contains("ID[\"EPSG\",16033]]"));
+    assert!(wkt.ends_with("ID[\"EPSG\",32633]]"));
+
+    let wkt = GeoKeyDirectoryBuilder::from_epsg(4326)
+        .unwrap()
+        .build()
+        .to_wkt()
+        .unwrap();
+    assert!(wkt.starts_with("GEOGCRS[\"WGS 84\",DATUM[\"World Geodetic System 1984\","));
+    assert!(wkt.ends_with("ID[\"EPSG\",4326]]"));
+}
+
+#[test]
+fn test_georeference_report() {
+    let geotiff = read_geotiff("resources/merc.tif");
+    let report = geotiff.georeference_report();
+    assert_eq!(report.points.len(), 5);
+    assert_eq!(report.points[0].location, ReferenceLocation::UpperLeft);
+    assert_eq!(
+        report.points[0].model,
+        Coord {
+            x: 1871032.9538880002,
+            y: 693358.6681440001
+        }
+    );
+    let (pixel_width, pixel_height) = report.pixel_size.unwrap();
+    assert!((pixel_width - 154.75).abs() < 1e-3);
+    assert!((pixel_height - 154.75).abs() < 1e-3);
+    assert_eq!(
+        geotiff.ground_sample_distance(),
+        Some(Coord {
+            x: pixel_width,
+            y: pixel_height
+        })
+    );
+    #[cfg(not(feature = "proj"))]
+    assert!(report.is_plausible());
+
+    // A geographic raster with pixels of half a degree
+    let write = |lat: f64| {
+        let mut data = Cursor::new(Vec::new());
+        let mut encoder = TiffEncoder::new(&mut data).unwrap();
+        let mut image = encoder.new_image::<colortype::Gray8>(4, 2).unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::ModelPixelScaleTag, &[0.5, 0.5, 0.0][..])
+            .unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::ModelTiepointTag, &[0.0, 0.0, 0.0, 10.0, lat, 0.0][..])
+            .unwrap();
+        image.write_data(&[0; 8]).unwrap();
+        data.set_position(0);
+
+        let mut geotiff = GeoTiff::read(data).expect("File I/O error");
+        geotiff.geo_key_directory = GeoKeyDirectoryBuilder::from_epsg(4326).unwrap().build();
+        geotiff
+    };
+
+    let geotiff = write(60.0);
+    let report = geotiff.georeference_report();
+    let (pixel_width, pixel_height) = report.pixel_size.unwrap();
+    assert!((pixel_width - 28218.3).abs() < 1.0);
+    assert!((pixel_height - 55597.5).abs() < 1.0);
+    assert_eq!(geotiff.resolution(), Some(Coord { x: 0.5, y: 0.5 }));
+    let gsd = geotiff.ground_sample_distance().unwrap();
+    assert!((gsd.x - 28218.3).abs() < 1.0);
+    assert!((gsd.y - 55597.5).abs() < 1.0);
+    #[cfg(not(feature = "proj"))]
+    {
+        assert_eq!(report.points[0].lon_lat, Some(Coord { x: 10.0, y: 60.0 }));
+        assert!(report.is_plausible());
+    }
+
+    let report = write(91.0).georeference_report();
+    assert!(report.issues.contains(&GeoreferenceIssue::OutsideCrsBounds(
+        ReferenceLocation::UpperLeft
+    )));
+    assert!(
+        !report.issues.contains(&GeoreferenceIssue::OutsideCrsBounds(
+            ReferenceLocation::LowerLeft
+        ))
+    );
+
+    let geotiff = GeoTiff::read(write_image_with_tags(&[])).expect("File I/O error");
+    let report = geotiff.georeference_report();
+    assert_eq!(
+        report.issues,
+        [
+            GeoreferenceIssue::MissingTransform,
+            GeoreferenceIssue::MissingCrs
+        ]
+    );
+    assert_eq!(report.pixel_size, None);
+    assert_eq!(geotiff.ground_sample_distance(), None);
+}
+
+#[test]
+fn test_geo_keys_from_epsg() {
+    let directory = GeoKeyDirectoryBuilder::from_epsg(3857)
+        .unwrap()
+        .raster_type(RasterType::RasterPixelIsArea)
+        .build();
+    assert_eq!(directory.model_type, Some(1));
+    assert_eq!(directory.projected_type, Some(3857));
+    assert_eq!(
+        directory.proj_citation.as_deref(),
+        Some("WGS 84 / Pseudo-Mercator")
+    );
+    assert_eq!(directory.proj_linear_units, Some(9001));
+    assert_eq!(directory.geog_citation.as_deref(), Some("WGS 84"));
+
+    let directory = GeoKeyDirectoryBuilder::from_epsg(32733).unwrap().build();
+    assert_eq!(directory.citation.as_deref(), Some("WGS 84 / UTM zone 33S"));
+    assert!(GeoKeyDirectoryBuilder::from_epsg(1234).is_none());
+
+    // Replacing the coordinate reference system keeps the other keys
+    let mut geotiff = read_geotiff("resources/merc.tif");
+    geotiff.geo_key_directory =
+        GeoKeyDirectoryBuilder::from_directory(geotiff.geo_key_directory.clone())
+            .epsg(4326)
+            .unwrap()
+            .build();
+    assert_eq!(
+        geotiff.geo_key_directory.raster_type,
+        Some(RasterType::RasterPixelIsArea)
+    );
+    assert_eq!(geotiff.geo_key_directory.model_type, Some(2));
+    assert_eq!(geotiff.geo_key_directory.geographic_type, Some(4326));
+    assert_eq!(geotiff.geo_key_directory.projected_type, None);
+    assert_eq!(geotiff.geo_key_directory.proj_nat_origin_long, None);
+
+    let mut data = Cursor::new(Vec::new());
+    geotiff.write(&mut data).expect("File I/O error");
+    data.set_position(0);
+    let written = GeoTiff::read(data).expect("File I/O error");
+    assert_eq!(written.geo_key_directory, geotiff.geo_key_directory);
+}
+
+#[test]
+fn test_read_ascii_geo_keys() {
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut image = encoder.new_image::<colortype::Gray8>(1, 1).unwrap();
+    image
+        .encoder()
+        .write_tag(
+            Tag::GeoKeyDirectoryTag,
+            &[
+                1u16, 1, 1, 3, //
+                1026, 34737, 8, 0, // count excluding the pipe
+                2049, 34737, 9, 9, // count including the pipe
+                3073, 34737, 7, 18, // terminated by NUL instead of a pipe
+            ][..],
+        )
+        .unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::GeoAsciiParamsTag, "Citation|Geodetic|WGS 84")
+        .unwrap();
+    image.write_data(&[0]).unwrap();
+
+    data.set_position(0);
+    let geotiff = GeoTiff::read(data).expect("File I/O error");
+    assert_eq!(
+        geotiff.geo_key_directory.citation.as_deref(),
+        Some("Citation")
+    );
+    assert_eq!(
+        geotiff.geo_key_directory.geog_citation.as_deref(),
+        Some("Geodetic")
+    );
+    assert_eq!(
+        geotiff.geo_key_directory.proj_citation.as_deref(),
+        Some("WGS 84")
+    );
+}
+
+#[test]
+fn test_read_options() {
+    let strict = ReadOptions::default().with_strict(true);
+    for path in [
+        "resources/zh_dem_25.tif",
+        "resources/austrian_capitals_model_transformation_pixel_is_area.tif",
+    ] {
+        GeoTiff::read_with_options(File::open(path).unwrap(), &strict).unwrap();
+    }
+    // The GeoKeys of the file are not sorted by ID
+    assert!(matches!(
+        GeoTiff::read_with_options(File::open("resources/merc.tif").unwrap(), &strict),
+        Err(GeoTiffError::Format(_))
+    ));
+
+    let write = |directory: &[u16], ascii_params: &str, transform_tags: bool| {
+        let mut data = Cursor::new(Vec::new());
+        let mut encoder = TiffEncoder::new(&mut data).unwrap();
+        let mut image = encoder.new_image::<colortype::Gray8>(1, 1).unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::GeoKeyDirectoryTag, directory)
+            .unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::GeoAsciiParamsTag, ascii_params)
+            .unwrap();
+        if transform_tags {
+            let mut transformation = [0.0; 16];
+            transformation[..8].copy_from_slice(&[2.0, 0.0, 0.0, 10.0, 0.0, -2.0, 0.0, 20.0]);
+            transformation[15] = 1.0;
+            image
+                .encoder()
+                .write_tag(Tag::ModelTransformationTag, &transformation[..])
+                .unwrap();
+            image
+                .encoder()
+                .write_tag(Tag::ModelPixelScaleTag, &[1.0, 1.0, 0.0][..])
+                .unwrap();
+            image
+                .encoder()
+                .write_tag(
+                    Tag::ModelTiepointTag,
+                    &[0.0, 0.0, 0.0, 100.0, 200.0, 0.0][..],
+                )
+                .unwrap();
+        }
+        image.write_data(&[0]).unwrap();
+        data.into_inner()
+    };
+
+    for (data, citation) in [
+        // A GeoKey directory of version 1.0
+        (write(&[1, 0, 0, 1, 1024, 0, 1, 2], "", false), None),
+        // Fewer keys than announced
+        (write(&[1, 1, 0, 2, 1024, 0, 1, 2], "", false), None),
+        // Keys not sorted by ID
+        (
+            write(&[1, 1, 0, 2, 1025, 0, 1, 1, 1024, 0, 1, 2], "", false),
+            None,
+        ),
+        // An ASCII value terminated by NUL
+        (
+            write(
+                &[1, 1, 0, 2, 1024, 0, 1, 2, 1026, 34737, 7, 0],
+                "WGS 84",
+                false,
+            ),
+            Some("WGS 84"),
+        ),
+        // Tie point, pixel scale and transformation matrix
+        (write(&[1, 1, 0, 1, 1024, 0, 1, 2], "", true), None),
+    ] {
+        let geotiff = GeoTiff::read(Cursor::new(&data)).unwrap();
+        assert_eq!(geotiff.geo_key_directory.model_type, Some(2));
+        assert_eq!(geotiff.geo_key_directory.citation.as_deref(), citation);
+        assert!(matches!(
+            GeoTiff::read_with_options(Cursor::new(&data), &strict),
+            Err(GeoTiffError::Format(_))
+        ));
+    }
+
+    // The tie point and pixel scale take precedence
+    let data = write(&[1, 1, 0, 1, 1024, 0, 1, 2], "", true);
+    assert_eq!(
+        GeoTiff::read(Cursor::new(data)).unwrap().origin(),
+        Some(Coord { x: 100.0, y: 200.0 })
+    );
+}
+
+#[test]
+fn test_read_limits() {
+    // The raster takes 2160 bytes and each of its tiles 1536 bytes
+    let mut limits = Limits::default();
+    limits.decoding_buffer_size = 2000;
+    let options = ReadOptions::default().with_limits(limits);
+    assert!(matches!(
+        GeoTiff::read_with_options(write_tiled_image(false), &options),
+        Err(GeoTiffError::Tiff(TiffError::LimitsExceeded))
+    ));
+    let mut reader = GeoTiffReader::open_with_options(write_tiled_image(false), &options).unwrap();
+    reader.read_region(&Window::new(14, 15, 4, 2)).unwrap();
+
+    // The tile offsets of the image exceed the buffer
+    let mut limits = Limits::default();
+    limits.decoding_buffer_size = 16;
+    assert!(matches!(
+        GeoTiffReader::open_with_options(
+            write_tiled_image(false),
+            &ReadOptions::default().with_limits(limits)
+        ),
+        Err(GeoTiffError::Tiff(TiffError::LimitsExceeded))
+    ));
+
+    // A corrupt byte count of a strip read on its own is not allocated
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut directory = encoder.image_directory().unwrap();
+    let offset = directory.write_data(&[0u8; 3][..]).unwrap() as u32;
+    directory.write_tag(Tag::ImageWidth, 2u32).unwrap();
+    directory.write_tag(Tag::ImageLength, 1u32).unwrap();
+    directory.write_tag(Tag::BitsPerSample, 12u16).unwrap();
+    directory.write_tag(Tag::Compression, 1u16).unwrap();
+    directory
+        .write_tag(Tag::PhotometricInterpretation, 1u16)
+        .unwrap();
+    directory.write_tag(Tag::StripOffsets, offset).unwrap();
+    directory.write_tag(Tag::RowsPerStrip, 1u32).unwrap();
+    directory.write_tag(Tag::StripByteCounts, u32::MAX).unwrap();
+    directory.finish().unwrap();
+    data.set_position(0);
+    assert!(matches!(
+        GeoTiff::read(data),
+        Err(GeoTiffError::Tiff(TiffError::LimitsExceeded))
+    ));
+
+    // Nor is the one of a tile read for decoding tiles concurrently
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut directory = encoder.image_directory().unwrap();
+    let offset = directory.write_data(&[0u8; 256][..]).unwrap() as u32;
+    directory.write_tag(Tag::ImageWidth, 16u32).unwrap();
+    directory.write_tag(Tag::ImageLength, 16u32).unwrap();
+    directory.write_tag(Tag::BitsPerSample, 8u16).unwrap();
+    directory.write_tag(Tag::Compression, 1u16).unwrap();
+    directory
+        .write_tag(Tag::PhotometricInterpretation, 1u16)
+        .unwrap();
+    directory.write_tag(Tag::TileWidth, 16u32).unwrap();
+    directory.write_tag(Tag::TileLength, 16u32).unwrap();
+    directory.write_tag(Tag::TileOffsets, offset).unwrap();
+    directory.write_tag(Tag::TileByteCounts, u32::MAX).unwrap();
+    directory.finish().unwrap();
+    data.set_position(0);
+    assert!(matches!(
+        GeoTiff::read(data),
+        Err(GeoTiffError::Tiff(TiffError::LimitsExceeded))
+    ));
+}
+
+#[test]
+fn test_decoder_mut() {
+    // A custom tag not covered by the API
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut image = encoder.new_image::<colortype::Gray8>(2, 1).unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::Unknown(65000), "custom")
+        .unwrap();
+    image.write_data(&[1, 2]).unwrap();
+    data.set_position(0);
+
+    let mut reader = GeoTiffReader::open(data).expect("File I/O error");
+    assert_eq!(
+        reader
+            .decoder_mut()
+            .get_tag_ascii_string(Tag::Unknown(65000))
+            .unwrap(),
+        "custom"
+    );
+    assert_eq!(
+        reader
+            .read()
+            .expect("File I/O error")
+            .values::<u8>()
+            .unwrap(),
+        vec![1, 2]
+    );
+}
+
+#[test]
+fn test_unknown_geo_keys() {
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut image = encoder.new_image::<colortype::Gray8>(1, 1).unwrap();
+    image
+        .encoder()
+        .write_tag(
+            Tag::GeoKeyDirectoryTag,
+            &[
+                1u16, 1, 1, 4, //
+                1024, 0, 1, 2, //
+                5000, 0, 1, 7, //
+                50000, 34736, 1, 0, //
+                50001, 34737, 7, 0, //
+            ][..],
+        )
+        .unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::GeoDoubleParamsTag, &[0.5][..])
+        .unwrap();
+    image
+        .encoder()
+        .write_tag(Tag::GeoAsciiParamsTag, "Vendor|")
+        .unwrap();
+    image.write_data(&[0]).unwrap();
+
+    data.set_position(0);
+    let geotiff = GeoTiff::read(data).expect("File I/O error");
+    let directory = &geotiff.geo_key_directory;
+    assert_eq!(directory.model_type, Some(2));
+    assert_eq!(
+        directory.unknown_keys,
+        [
+            (5000, GeoKeyValue::Short(7)),
+            (50000, GeoKeyValue::Double(0.5)),
+            (50001, GeoKeyValue::Ascii("Vendor".into())),
+        ]
+        .into()
+    );
+    assert_eq!(
+        directory.to_string(),
+        "GeoKeyDirectory 1.1.1\n\
+         ModelType (1024): 2\n\
+         GeoKey5000 (5000): 7\n\
+         GeoKey50000 (50000): 0.5\n\
+         GeoKey50001 (50001): \"Vendor\""
+    );
+
+    // The unknown keys are written back
+    let mut written = Cursor::new(Vec::new());
+    geotiff.write(&mut written).expect("File I/O error");
+    written.set_position(0);
+    let written = GeoTiff::read(written).expect("File I/O error");
+    assert_eq!(written.geo_key_directory, geotiff.geo_key_directory);
+}
+
+#[cfg(feature = "ndarray")]
+#[test]
+fn test_to_array() {
+    let window = Window::new(700, 550, 30, 20);
+    let open = || {
+        GeoTiffReader::open(File::open("resources/marbles.tif").expect("File I/O error"))
+            .expect("File I/O error")
+    };
+    let band = open()
+        .with_interleave(Interleave::Band)
+        .read_region(&window)
+        .expect("File I/O error");
+    let array = open()
+        .with_order(ArrayOrder::ColumnMajor)
+        .read_region(&window)
+        .expect("File I/O error")
+        .to_array3::<u8>();
+    assert_eq!(array.shape(), &[3, 20, 30]);
+    assert_eq!(
+        array.iter().copied().collect::<Vec<_>>(),
+        band.values::<u8>().unwrap()
+    );
+    assert!(band.to_array2::<u8>().is_none());
+
+    let geotiff = read_geotiff("resources/zh_dem_25.tif");
+    let array = geotiff.to_array2::<f64>().unwrap();
+    assert_eq!(array.shape(), &[366, 399]);
+    assert_eq!(
+        array[[199, 161]],
+        geotiff.values::<f64>().unwrap()[199 * 399 + 161]
+    );
+}
+
+#[cfg(feature = "arrow")]
+#[test]
+fn test_to_arrow() {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Float64Type, Int16Type, UInt8Type};
+    use arrow_array::Array;
+
+    let geotiff = read_geotiff("resources/zh_dem_25.tif");
+    let values = geotiff.values::<i16>().unwrap();
+    let array = geotiff.to_arrow_array(0).unwrap().unwrap();
+    let array = array.as_primitive::<Int16Type>();
+    assert_eq!(array.len(), 399 * 366);
+    assert!(array
+        .iter()
+        .zip(&values)
+        .all(|(value, expected)| value == Some(*expected)));
+
+    // No-data values are null
+    let mut masked = read_geotiff("resources/zh_dem_25.tif");
+    masked.no_data = Some(551.0);
+    let array = masked.to_arrow_array(0).unwrap().unwrap();
+    assert!(array.is_null(0));
+    assert_eq!(
+        array.null_count(),
+        values.iter().filter(|value| **value == 551).count()
+    );
+    assert!(matches!(
+        geotiff.to_arrow_array(1),
+        Err(GeoTiffError::SampleOutOfBounds { sample: 1, .. })
+    ));
+
+    let batch = geotiff
+        .to_arrow_record_batch(&Window::new(0, 0, 2, 2))
+        .expect("Arrow export failed");
+    assert_eq!(batch.num_rows(), 4);
+    let schema = batch.schema();
+    let names = schema
+        .fields()
+        .iter()
+        .map(|field| field.name().as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["x", "y", "sample_0"]);
+    assert_eq!(
+        batch.column(0).as_primitive::<Float64Type>().value(0),
+        677575.0
+    );
+    assert_eq!(
+        batch.column(1).as_primitive::<Float64Type>().value(0),
+        253000.0
+    );
+    assert_eq!(batch.column(2).as_primitive::<Int16Type>().value(0), 551);
+
+    let marbles = read_geotiff("resources/marbles.tif");
+    let batch = marbles
+        .to_arrow_record_batch(&Window::new(761, 599, 1, 1))
+        .expect("Arrow export failed");
+    let samples = (2..5)
+        .map(|column| batch.column(column).as_primitive::<UInt8Type>().value(0))
+        .collect::<Vec<_>>();
+    assert_eq!(samples, [147, 128, 165]);
+
+    assert!(geotiff
+        .to_arrow_record_batch(&Window::new(398, 0, 2, 1))
+        .is_err());
+}
+
+#[cfg(feature = "gdal")]
+#[test]
+fn test_gdal_conversions() {
+    use gdal::raster::Buffer;
+    use gdal::Dataset;
+
+    let geotiff = read_geotiff("resources/zh_dem_25.tif");
+    let dataset = Dataset::try_from(&geotiff).expect("GDAL error");
+    assert_eq!(dataset.raster_size(), (399, 366));
+    assert_eq!(dataset.raster_count(), 1);
+    assert_eq!(
+        dataset.geo_transform().expect("GDAL error"),
+        geotiff.geotransform().unwrap()
+    );
+    let band = dataset.rasterband(1).expect("GDAL error");
+    assert_eq!(band.no_data_value(), Some(-9999.0));
+    assert_eq!(
+        band.read_band_as::<i16>().expect("GDAL error").data(),
+        geotiff.values::<i16>().unwrap()
+    );
+
+    let converted = GeoTiff::try_from(&dataset).expect("GDAL error");
+    assert_eq!(converted.data_type(), RasterDataType::I16);
+    assert_eq!(converted.geotransform(), geotiff.geotransform());
+    assert_eq!(converted.no_data, Some(-9999.0));
+    assert_eq!(
+        converted.values::<i16>().unwrap(),
+        geotiff.values::<i16>().unwrap()
+    );
+
+    // Multi-band rasters are interleaved by pixel
+    let marbles = read_geotiff("resources/marbles.tif");
+    let dataset = Dataset::try_from(&marbles).expect("GDAL error");
+    assert_eq!(dataset.raster_count(), 3);
+    let converted = GeoTiff::try_from(&dataset).expect("GDAL error");
+    assert_eq!(
+        converted.values::<u8>().unwrap(),
+        marbles.values::<u8>().unwrap()
+    );
+
+    let buffer = Buffer::<f64>::from(marbles.band(2).unwrap());
+    assert_eq!(
+        buffer.shape(),
+        (marbles.raster_width, marbles.raster_height)
+    );
+    assert_eq!(
+        buffer.data(),
+        marbles.band(2).unwrap().iter::<f64>().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_interleave() {
+    let open = || {
+        GeoTiffReader::open(File::open("resources/marbles.tif").expect("File I/O error"))
+            .expect("File I/O error")
+    };
+    let window = Window::new(700, 550, 30, 20);
+    let pixel = open().read_region(&window).expect("File I/O error");
+    let band = open()
+        .with_interleave(Interleave::Band)
+        .read_region(&window)
+        .expect("File I/O error");
+    assert_eq!(pixel.interleave(), Interleave::Pixel);
+    assert_eq!(band.interleave(), Interleave::Band);
+
+    let pixel_values = pixel.values::<u8>().unwrap();
+    let band_values = band.values::<u8>().unwrap();
+    for sample in 0..3 {
+        for index in 0..window.width * window.height {
+            assert_eq!(
+                band_values[sample * window.width * window.height + index],
+                pixel_values[index * 3 + sample]
+            );
+        }
+    }
+
+    let coord = Coord { x: 61.0, y: 49.0 };
+    for sample in 0..3 {
+        assert_eq!(
+            band.get_value_at::<u8>(&coord, sample),
+            pixel.get_value_at::<u8>(&coord, sample)
+        );
+    }
+
+    // Band sequential data is written as a planar image, which can be read in either layout
+    let mut data = Cursor::new(Vec::new());
+    band.write(&mut data).expect("File I/O error");
+    data.set_position(0);
+    let planar = GeoTiffReader::open(data.clone()).expect("File I/O error");
+    assert_eq!(
+        planar
+            .read()
+            .expect("File I/O error")
+            .values::<u8>()
+            .unwrap(),
+        pixel_values
+    );
+    let planar = GeoTiffReader::open(data)
+        .expect("File I/O error")
+        .with_interleave(Interleave::Band);
+    assert_eq!(
+        planar
+            .read()
+            .expect("File I/O error")
+            .values::<u8>()
+            .unwrap(),
+        band_values
+    );
+}
+
+#[test]
+fn test_array_order() {
+    let open = || {
+        GeoTiffReader::open(File::open("resources/zh_dem_25.tif").expect("File I/O error"))
+            .expect("File I/O error")
+    };
+    let window = Window::new(120, 15, 30, 20);
+    let row_major = open().read_region(&window).expect("File I/O error");
+    let column_major = open()
+        .with_order(ArrayOrder::ColumnMajor)
+        .read_region(&window)
+        .expect("File I/O error");
+    assert_eq!(column_major.order(), ArrayOrder::ColumnMajor);
+
+    let row_major_values = row_major.values::<i16>().unwrap();
+    let column_major_values = column_major.values::<i16>().unwrap();
+    for y in 0..window.height {
+        for x in 0..window.width {
+            assert_eq!(
+                column_major_values[x * window.height + y],
+                row_major_values[y * window.width + x]
+            );
+        }
+    }
+
+    let extent = row_major.model_extent();
+    assert_eq!(
+        column_major.get_value_at::<i16>(&extent.center(), 0),
+        row_major.get_value_at::<i16>(&extent.center(), 0)
+    );
+
+    let transposed = row_major
+        .into_layout(Interleave::Pixel, ArrayOrder::ColumnMajor)
+        .expect("Layout error");
+    assert_eq!(transposed.values::<i16>().unwrap(), column_major_values);
+
+    let mut data = Cursor::new(Vec::new());
+    column_major.write(&mut data).expect("File I/O error");
+    data.set_position(0);
+    assert_eq!(
+        GeoTiff::read(data)
+            .expect("File I/O error")
+            .values::<i16>()
+            .unwrap(),
+        row_major_values
+    );
+
+    let marbles = GeoTiffReader::open(File::open("resources/marbles.tif").expect("File I/O error"))
+        .expect("File I/O error")
+        .read_region(&Window::new(700, 550, 3, 3))
+        .expect("File I/O error");
+    let values = marbles.values::<u8>().unwrap();
+    let marbles = marbles
+        .into_layout(Interleave::Band, ArrayOrder::ColumnMajor)
+        .expect("Layout error");
+    let band_values = marbles.values::<u8>().unwrap();
+    for sample in 0..3 {
+        for y in 0..3 {
+            for x in 0..3 {
+                assert_eq!(
+                    band_values[(sample * 3 + x) * 3 + y],
+                    values[(y * 3 + x) * 3 + sample]
+                );
+            }
+        }
+    }
+}
@@ -213,3 +213,59 @@ fn test_transform<P: AsRef<Path>>(path: P, raster_type: RasterType) {
     assert_eq!(geotiff.get_value_at::<u8>(&sankt_poelten, 0), Some(BLACK));
     assert_eq!(geotiff.get_value_at::<u8>(&vienna, 0), Some(BLACK));
 }
+
+#[cfg(feature = "proj")]
+#[test]
+fn test_transform_get_value_at_lon_lat() {
+    let geotiff = read_geotiff(
+        "resources/austrian_capitals_model_tie_point_and_pixel_scale_pixel_is_area.tif",
+    );
+
+    for capital in [
+        BREGENZ,
+        EISENSTADT,
+        GRAZ,
+        INNSBRUCK,
+        KLAGENFURT,
+        LINZ,
+        SALZBURG,
+        SANKT_POELTEN,
+        VIENNA,
+    ] {
+        assert_eq!(
+            geotiff.get_value_at_lon_lat::<u8>(&capital, 0).unwrap(),
+            Some(BLACK)
+        );
+    }
+
+    // The same location in Web Mercator
+    let proj = Proj::new_known_crs("EPSG:4326", "EPSG:3857", None).unwrap();
+    assert_eq!(
+        geotiff
+            .get_value_at_crs::<u8>(&proj.convert(VIENNA).unwrap(), 3857, 0)
+            .unwrap(),
+        Some(BLACK)
+    );
+    assert_eq!(
+        geotiff
+            .get_value_at_lon_lat::<u8>(&Coord { x: 0.0, y: 0.0 }, 0)
+            .unwrap(),
+        None
+    );
+}
+
+#[cfg(feature = "proj")]
+#[test]
+fn test_transform_georeference_report() {
+    let geotiff = read_geotiff(
+        "resources/austrian_capitals_model_tie_point_and_pixel_scale_pixel_is_area.tif",
+    );
+    let report = geotiff.georeference_report();
+    assert!(report.is_plausible());
+
+    // The center of the raster lies in Austria
+    let center = report.points[4].lon_lat.unwrap();
+    assert!((9.5..17.2).contains(&center.x));
+    assert!((46.3..49.1).contains(&center.y));
+    assert_eq!(report.pixel_size, Some((1000.0, 1000.0)));
+}